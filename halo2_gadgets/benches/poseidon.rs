@@ -210,7 +210,9 @@ fn bench_poseidon<S, const WIDTH: usize, const RATE: usize, const L: usize>(
         &mut transcript,
     )
     .expect("proof generation should not fail");
-    let proof = transcript.finalize();
+    let proof = transcript
+        .finalize()
+        .expect("finalizing the transcript should not fail");
 
     c.bench_function(&verifier_name, |b| {
         b.iter(|| {
@@ -1,12 +1,45 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use ff::Field;
-use halo2_gadgets::poseidon::primitives::{self as poseidon, ConstantLength, P128Pow5T3};
+use halo2_gadgets::poseidon::primitives::{
+    self as poseidon, check_mds, generate_constants, ConstantLength, Mds, P128Pow5T3, Spec,
+};
 
 use halo2curves::pasta::pallas;
 #[cfg(unix)]
 use pprof::criterion::{Output, PProfProfiler};
 use rand::rngs::OsRng;
 
+/// A wide specification, whose constants are generated on demand. The MDS matrix is
+/// not checked for security, so this must only be used for benchmarking.
+#[derive(Debug)]
+struct WideSpec;
+
+impl Spec<pallas::Base, 9, 8> for WideSpec {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        60
+    }
+
+    fn sbox(val: pallas::Base) -> pallas::Base {
+        val.pow_vartime([5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (
+        Vec<[pallas::Base; 9]>,
+        Mds<pallas::Base, 9>,
+        Mds<pallas::Base, 9>,
+    ) {
+        generate_constants::<_, Self, 9, 8>()
+    }
+}
+
 fn bench_primitives(c: &mut Criterion) {
     {
         let rng = OsRng;
@@ -20,6 +53,14 @@ fn bench_primitives(c: &mut Criterion) {
             })
         });
     }
+
+    // Compare with RAYON_NUM_THREADS=1 to measure the effect of parallelism.
+    c.bench_function("Poseidon constants width 9", |b| {
+        b.iter(<WideSpec as Spec<pallas::Base, 9, 8>>::constants)
+    });
+
+    let (_, mds, _) = WideSpec::constants();
+    c.bench_function("Poseidon check_mds width 9", |b| b.iter(|| check_mds(&mds)));
 }
 
 #[cfg(unix)]
@@ -125,7 +125,7 @@ fn bench(name: &str, k: u32, c: &mut Criterion) {
     //         let mut transcript = Blake2bWrite::init(Fq::one());
     //         create_proof(&params, &pk, &circuit, &[], &mut transcript)
     //             .expect("proof generation should not fail");
-    //         let proof: Vec<u8> = transcript.finalize();
+    //         let proof: Vec<u8> = transcript.finalize().unwrap();
     //     });
     // });
 
@@ -142,7 +142,9 @@ fn bench(name: &str, k: u32, c: &mut Criterion) {
             &mut transcript,
         )
         .expect("proof generation should not fail");
-        let proof: Vec<u8> = transcript.finalize();
+        let proof: Vec<u8> = transcript
+            .finalize()
+            .expect("finalizing the transcript should not fail");
         let mut file = File::create(proof_path).expect("Failed to create sha256_proof");
         file.write_all(&proof[..]).expect("Failed to write proof");
     }
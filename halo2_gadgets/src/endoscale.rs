@@ -0,0 +1,20 @@
+//! Endoscaling: applying a challenge to a point using the curve endomorphism.
+//!
+//! A 128-bit [`Challenge128`] is mapped to a scalar by
+//! [`Challenge128::get_scalar`], one pair of bits at a time. The same bits can be
+//! applied directly to a point $P$: starting from $[2](\phi(P) + P)$, each pair of bits
+//! selects one of $\pm P, \pm \phi(P)$ to be added in a double-and-add step, where
+//! $\phi(x, y) = (\zeta x, y)$ is the endomorphism of the curve. The resulting point is
+//! $[s]P$, where $s$ is the scalar given by the challenge.
+//!
+//! [`primitive`] implements both mappings out of circuit, and [`chip`] implements them
+//! in circuit, so that a recursive verifier can compute commitments to challenge
+//! bitstrings exactly as the native verifier does.
+//!
+//! [`Challenge128`]: halo2_proofs::transcript::Challenge128
+//! [`Challenge128::get_scalar`]: halo2_proofs::transcript::EncodedChallenge::get_scalar
+
+pub mod chip;
+pub mod primitive;
+
+pub use chip::{EndoscaleConfig, EndoscaleScalarConfig};
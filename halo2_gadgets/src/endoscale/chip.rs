@@ -0,0 +1,612 @@
+//! Chips for endoscaling in circuit.
+//!
+//! Both chips take the challenge as a field element, witness its bits two at a time
+//! from the most significant end, and constrain a running sum of the bits to equal the
+//! challenge, so the result is bound to the same challenge as in the
+//! [`primitive`](super::primitive) functions.
+
+use std::marker::PhantomData;
+
+use ff::{Field, PrimeFieldBits, WithSmallOrderMulGroup};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::utilities::bool_check;
+
+/// Returns the windows `(b_{2i}, b_{2i+1})` of the low `num_bits` bits of
+/// `challenge`, most significant window first.
+fn windows<F: PrimeFieldBits>(challenge: Value<&F>, num_bits: usize) -> Vec<Value<(bool, bool)>> {
+    assert_eq!(num_bits % 2, 0);
+    let num_windows = num_bits / 2;
+    challenge
+        .map(|challenge| {
+            let bits: Vec<bool> = challenge
+                .to_le_bits()
+                .iter()
+                .by_vals()
+                .take(num_bits)
+                .collect();
+            bits.chunks(2)
+                .rev()
+                .map(|pair| (pair[0], pair[1]))
+                .collect::<Vec<_>>()
+        })
+        .transpose_vec(num_windows)
+}
+
+/// Checks that `z_next = 4 z_cur + 2 b_neg + b_endo`, and that both bits are boolean.
+fn running_sum_checks<F: PrimeFieldBits>(
+    z_cur: Expression<F>,
+    z_next: Expression<F>,
+    b_endo: Expression<F>,
+    b_neg: Expression<F>,
+) -> [(&'static str, Expression<F>); 3] {
+    let z_check = z_next - (z_cur * F::from(4) + b_neg.clone() * F::from(2) + b_endo.clone());
+    [
+        ("b_endo is bool", bool_check(b_endo)),
+        ("b_neg is bool", bool_check(b_neg)),
+        ("running sum", z_check),
+    ]
+}
+
+/// Configuration for endoscaling a point of `C` in a circuit over `C::Base`, following
+/// [`endoscale_point`](super::primitive::endoscale_point).
+///
+/// For a challenge of $n$ bits, the region has $n/2 + 2$ rows:
+///
+/// | row       | q_init | q_step | b_endo     | b_neg        | z     | x_a   | y_a   | lambda_1      | lambda_2    | x_p | y_p |
+/// |-----------|--------|--------|------------|--------------|-------|-------|-------|---------------|-------------|-----|-----|
+/// | 0         | 1      | 0      |            |              |       |       |       | $\lambda$     |             | x_p | y_p |
+/// | 1         | 0      | 1      | $b_{n-2}$  | $b_{n-1}$    | 0     | $x_A$ | $y_A$ | $\lambda_1$   | $\lambda_2$ | x_p | y_p |
+/// | ...       | ...    | ...    | ...        | ...          | ...   | ...   | ...   | ...           | ...         | ... | ... |
+/// | n/2       | 0      | 1      | $b_0$      | $b_1$        | $z$   | $x_A$ | $y_A$ | $\lambda_1$   | $\lambda_2$ | x_p | y_p |
+/// | n/2 + 1   | 0      | 0      |            |              | c     | $x$   | $y$   |               |             |     |     |
+///
+/// The first row computes the initial accumulator $[2](\phi(P) + P)$, and each
+/// following row computes $A' = (A + S) + A$ for $S \in \{\pm P, \pm \phi(P)\}$ using
+/// incomplete addition. The final running sum is constrained to equal the challenge
+/// $c$.
+#[derive(Clone, Debug)]
+pub struct EndoscaleConfig<C: CurveAffine> {
+    q_init: Selector,
+    q_step: Selector,
+    b_endo: Column<Advice>,
+    b_neg: Column<Advice>,
+    z: Column<Advice>,
+    x_a: Column<Advice>,
+    y_a: Column<Advice>,
+    lambda_1: Column<Advice>,
+    lambda_2: Column<Advice>,
+    x_p: Column<Advice>,
+    y_p: Column<Advice>,
+    _marker: PhantomData<C>,
+}
+
+impl<C: CurveAffine> EndoscaleConfig<C>
+where
+    C::Base: WithSmallOrderMulGroup<3> + PrimeFieldBits,
+{
+    /// Configures the chip.
+    ///
+    /// The columns `z`, `x_a`, `y_a`, `x_p` and `y_p` (`advices[2]`, `advices[3]`,
+    /// `advices[4]`, `advices[7]` and `advices[8]`) will be equality-enabled.
+    pub fn configure(meta: &mut ConstraintSystem<C::Base>, advices: [Column<Advice>; 9]) -> Self {
+        let config = Self {
+            q_init: meta.selector(),
+            q_step: meta.selector(),
+            b_endo: advices[0],
+            b_neg: advices[1],
+            z: advices[2],
+            x_a: advices[3],
+            y_a: advices[4],
+            lambda_1: advices[5],
+            lambda_2: advices[6],
+            x_p: advices[7],
+            y_p: advices[8],
+            _marker: PhantomData,
+        };
+        for column in [config.z, config.x_a, config.y_a, config.x_p, config.y_p] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("endoscale point init", |meta| {
+            let q_init = meta.query_selector(config.q_init);
+            let x_p = meta.query_advice(config.x_p, Rotation::cur());
+            let y_p = meta.query_advice(config.y_p, Rotation::cur());
+            let lambda = meta.query_advice(config.lambda_1, Rotation::cur());
+            let x_a = meta.query_advice(config.x_a, Rotation::next());
+            let y_a = meta.query_advice(config.y_a, Rotation::next());
+            let z = meta.query_advice(config.z, Rotation::next());
+
+            // φ(P) and P have the same y-coordinate, so
+            // T = φ(P) + P = (-(ζ + 1) x_p, -y_p) = (ζ^2 x_p, -y_p).
+            let x_t = x_p * C::Base::ZETA.square();
+            let y_t = -y_p;
+
+            // (x_a, y_a) = [2] T
+            let lambda_check = lambda.clone() * y_t.clone() * C::Base::from(2)
+                - x_t.clone().square() * C::Base::from(3);
+            let x_check = x_a.clone() - (lambda.clone().square() - x_t.clone() * C::Base::from(2));
+            let y_check = y_a - (lambda * (x_t - x_a) - y_t);
+
+            Constraints::with_selector(
+                q_init,
+                [
+                    ("lambda", lambda_check),
+                    ("x_a", x_check),
+                    ("y_a", y_check),
+                    ("z = 0", z),
+                ],
+            )
+        });
+
+        meta.create_gate("endoscale point step", |meta| {
+            let q_step = meta.query_selector(config.q_step);
+            let b_endo = meta.query_advice(config.b_endo, Rotation::cur());
+            let b_neg = meta.query_advice(config.b_neg, Rotation::cur());
+            let z_cur = meta.query_advice(config.z, Rotation::cur());
+            let z_next = meta.query_advice(config.z, Rotation::next());
+            let x_a = meta.query_advice(config.x_a, Rotation::cur());
+            let y_a = meta.query_advice(config.y_a, Rotation::cur());
+            let x_a_next = meta.query_advice(config.x_a, Rotation::next());
+            let y_a_next = meta.query_advice(config.y_a, Rotation::next());
+            let lambda_1 = meta.query_advice(config.lambda_1, Rotation::cur());
+            let lambda_2 = meta.query_advice(config.lambda_2, Rotation::cur());
+            let x_p = meta.query_advice(config.x_p, Rotation::cur());
+            let y_p = meta.query_advice(config.y_p, Rotation::cur());
+
+            let one = Expression::Constant(C::Base::ONE);
+            // S = (ζ x_p, y_p) if b_endo else (x_p, y_p), negated if b_neg.
+            let x_s = x_p * (one.clone() + b_endo.clone() * (C::Base::ZETA - C::Base::ONE));
+            let y_s = y_p * (one - b_neg.clone() * C::Base::from(2));
+
+            // R = A + S
+            let lambda_1_check =
+                lambda_1.clone() * (x_a.clone() - x_s.clone()) - (y_a.clone() - y_s);
+            let x_r = lambda_1.clone().square() - x_a.clone() - x_s;
+
+            // A' = R + A
+            let lambda_2_check = (lambda_1 + lambda_2.clone()) * (x_a.clone() - x_r.clone())
+                - y_a.clone() * C::Base::from(2);
+            let x_check = lambda_2.clone().square() - x_a_next.clone() - x_r - x_a.clone();
+            let y_check = lambda_2 * (x_a - x_a_next) - y_a - y_a_next;
+
+            Constraints::with_selector(
+                q_step,
+                running_sum_checks(z_cur, z_next, b_endo, b_neg)
+                    .into_iter()
+                    .chain([
+                        ("lambda_1", lambda_1_check),
+                        ("lambda_2", lambda_2_check),
+                        ("x_a", x_check),
+                        ("y_a", y_check),
+                    ]),
+            )
+        });
+
+        config
+    }
+
+    /// Applies the low `num_bits` bits of `challenge` to the point `base` using the
+    /// endomorphism, and returns the coordinates of the result.
+    ///
+    /// `challenge` is constrained to be less than $2^{num\_bits}$. `base` must not be
+    /// the identity, and is not checked to be on the curve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bits` is odd.
+    #[allow(clippy::type_complexity)]
+    pub fn endoscale_point(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        challenge: &AssignedCell<C::Base, C::Base>,
+        num_bits: usize,
+        base: (
+            &AssignedCell<C::Base, C::Base>,
+            &AssignedCell<C::Base, C::Base>,
+        ),
+    ) -> Result<
+        (
+            AssignedCell<C::Base, C::Base>,
+            AssignedCell<C::Base, C::Base>,
+        ),
+        Error,
+    > {
+        let windows = windows(challenge.value(), num_bits);
+
+        layouter.assign_region(
+            || "endoscale point",
+            |mut region| {
+                self.q_init.enable(&mut region, 0)?;
+                let x_p = base.0.copy_advice(|| "x_p", &mut region, self.x_p, 0)?;
+                let y_p = base.1.copy_advice(|| "y_p", &mut region, self.y_p, 0)?;
+                let (x_p, y_p) = (x_p.value().copied(), y_p.value().copied());
+
+                // [2](φ(P) + P)
+                let zeta = C::Base::ZETA;
+                let init = x_p.zip(y_p).map(|(x_p, y_p)| {
+                    let x_t = zeta.square() * x_p;
+                    let y_t = -y_p;
+                    let lambda = x_t.square()
+                        * C::Base::from(3)
+                        * y_t.double().invert().unwrap_or(C::Base::ZERO);
+                    let x_a = lambda.square() - x_t.double();
+                    let y_a = lambda * (x_t - x_a) - y_t;
+                    (lambda, x_a, y_a)
+                });
+                region.assign_advice(
+                    || "lambda",
+                    self.lambda_1,
+                    0,
+                    || init.map(|(lambda, _, _)| lambda),
+                )?;
+
+                let mut z = Value::known(C::Base::ZERO);
+                let mut acc = init.map(|(_, x_a, y_a)| (x_a, y_a));
+                for (i, window) in windows.iter().enumerate() {
+                    let row = i + 1;
+                    self.q_step.enable(&mut region, row)?;
+                    base.0.copy_advice(|| "x_p", &mut region, self.x_p, row)?;
+                    base.1.copy_advice(|| "y_p", &mut region, self.y_p, row)?;
+
+                    let b_endo = window.map(|(b_endo, _)| C::Base::from(b_endo as u64));
+                    let b_neg = window.map(|(_, b_neg)| C::Base::from(b_neg as u64));
+                    region.assign_advice(|| "b_endo", self.b_endo, row, || b_endo)?;
+                    region.assign_advice(|| "b_neg", self.b_neg, row, || b_neg)?;
+                    region.assign_advice(|| "z", self.z, row, || z)?;
+                    region.assign_advice(|| "x_a", self.x_a, row, || acc.map(|(x_a, _)| x_a))?;
+                    region.assign_advice(|| "y_a", self.y_a, row, || acc.map(|(_, y_a)| y_a))?;
+
+                    let step = acc.zip(x_p.zip(y_p)).zip(*window).map(
+                        |(((x_a, y_a), (x_p, y_p)), (b_endo, b_neg))| {
+                            let x_s = if b_endo { zeta * x_p } else { x_p };
+                            let y_s = if b_neg { -y_p } else { y_p };
+                            let lambda_1 =
+                                (y_a - y_s) * (x_a - x_s).invert().unwrap_or(C::Base::ZERO);
+                            let x_r = lambda_1.square() - x_a - x_s;
+                            let lambda_2 = y_a.double()
+                                * (x_a - x_r).invert().unwrap_or(C::Base::ZERO)
+                                - lambda_1;
+                            let x_a_next = lambda_2.square() - x_r - x_a;
+                            let y_a_next = lambda_2 * (x_a - x_a_next) - y_a;
+                            (lambda_1, lambda_2, (x_a_next, y_a_next))
+                        },
+                    );
+                    region.assign_advice(
+                        || "lambda_1",
+                        self.lambda_1,
+                        row,
+                        || step.map(|(lambda_1, _, _)| lambda_1),
+                    )?;
+                    region.assign_advice(
+                        || "lambda_2",
+                        self.lambda_2,
+                        row,
+                        || step.map(|(_, lambda_2, _)| lambda_2),
+                    )?;
+
+                    z = z * Value::known(C::Base::from(4))
+                        + b_neg * Value::known(C::Base::from(2))
+                        + b_endo;
+                    acc = step.map(|(_, _, acc)| acc);
+                }
+
+                let row = windows.len() + 1;
+                let z = region.assign_advice(|| "z", self.z, row, || z)?;
+                region.constrain_equal(z.cell(), challenge.cell())?;
+                let x = region.assign_advice(|| "x", self.x_a, row, || acc.map(|(x, _)| x))?;
+                let y = region.assign_advice(|| "y", self.y_a, row, || acc.map(|(_, y)| y))?;
+
+                Ok((x, y))
+            },
+        )
+    }
+}
+
+/// Configuration for mapping a challenge to a scalar in a circuit over the scalar
+/// field `F`, following [`endoscale_scalar`](super::primitive::endoscale_scalar).
+///
+/// For a challenge of $n$ bits, the region has $n/2 + 1$ rows:
+///
+/// | row   | q_init | q_step | b_endo    | b_neg     | z   | acc               |
+/// |-------|--------|--------|-----------|-----------|-----|-------------------|
+/// | 0     | 1      | 1      | $b_{n-2}$ | $b_{n-1}$ | 0   | $2(\zeta + 1)$    |
+/// | ...   | 0      | 1      | ...       | ...       | ... | ...               |
+/// | n/2-1 | 0      | 1      | $b_0$     | $b_1$     | $z$ | $acc$             |
+/// | n/2   | 0      | 0      |           |           | c   | $s$               |
+///
+/// where each step computes $acc' = 2 acc + q$ for $q \in \{\pm 1, \pm \zeta\}$, and the
+/// final running sum is constrained to equal the challenge $c$.
+#[derive(Clone, Debug)]
+pub struct EndoscaleScalarConfig<F: Field> {
+    q_init: Selector,
+    q_step: Selector,
+    b_endo: Column<Advice>,
+    b_neg: Column<Advice>,
+    z: Column<Advice>,
+    acc: Column<Advice>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: WithSmallOrderMulGroup<3> + PrimeFieldBits> EndoscaleScalarConfig<F> {
+    /// Configures the chip.
+    ///
+    /// The columns `z` and `acc` (`advices[2]` and `advices[3]`) will be
+    /// equality-enabled.
+    pub fn configure(meta: &mut ConstraintSystem<F>, advices: [Column<Advice>; 4]) -> Self {
+        let config = Self {
+            q_init: meta.selector(),
+            q_step: meta.selector(),
+            b_endo: advices[0],
+            b_neg: advices[1],
+            z: advices[2],
+            acc: advices[3],
+            _marker: PhantomData,
+        };
+        meta.enable_equality(config.z);
+        meta.enable_equality(config.acc);
+
+        meta.create_gate("endoscale scalar init", |meta| {
+            let q_init = meta.query_selector(config.q_init);
+            let z = meta.query_advice(config.z, Rotation::cur());
+            let acc = meta.query_advice(config.acc, Rotation::cur());
+
+            let init = Expression::Constant((F::ZETA + F::ONE).double());
+            Constraints::with_selector(q_init, [("acc", acc - init), ("z = 0", z)])
+        });
+
+        meta.create_gate("endoscale scalar step", |meta| {
+            let q_step = meta.query_selector(config.q_step);
+            let b_endo = meta.query_advice(config.b_endo, Rotation::cur());
+            let b_neg = meta.query_advice(config.b_neg, Rotation::cur());
+            let z_cur = meta.query_advice(config.z, Rotation::cur());
+            let z_next = meta.query_advice(config.z, Rotation::next());
+            let acc = meta.query_advice(config.acc, Rotation::cur());
+            let acc_next = meta.query_advice(config.acc, Rotation::next());
+
+            let one = Expression::Constant(F::ONE);
+            // q = ζ if b_endo else 1, negated if b_neg.
+            let q = (one.clone() + b_endo.clone() * (F::ZETA - F::ONE))
+                * (one - b_neg.clone() * F::from(2));
+            let acc_check = acc_next - (acc * F::from(2) + q);
+
+            Constraints::with_selector(
+                q_step,
+                running_sum_checks(z_cur, z_next, b_endo, b_neg)
+                    .into_iter()
+                    .chain(Some(("acc", acc_check))),
+            )
+        });
+
+        config
+    }
+
+    /// Maps the low `num_bits` bits of `challenge` to a scalar.
+    ///
+    /// `challenge` is constrained to be less than $2^{num\_bits}$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bits` is odd.
+    pub fn endoscale_scalar(
+        &self,
+        mut layouter: impl Layouter<F>,
+        challenge: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let windows = windows(challenge.value(), num_bits);
+
+        layouter.assign_region(
+            || "endoscale scalar",
+            |mut region| {
+                self.q_init.enable(&mut region, 0)?;
+
+                let mut z = Value::known(F::ZERO);
+                let mut acc = Value::known((F::ZETA + F::ONE).double());
+                for (row, window) in windows.iter().enumerate() {
+                    self.q_step.enable(&mut region, row)?;
+
+                    let b_endo = window.map(|(b_endo, _)| F::from(b_endo as u64));
+                    let b_neg = window.map(|(_, b_neg)| F::from(b_neg as u64));
+                    region.assign_advice(|| "b_endo", self.b_endo, row, || b_endo)?;
+                    region.assign_advice(|| "b_neg", self.b_neg, row, || b_neg)?;
+                    region.assign_advice(|| "z", self.z, row, || z)?;
+                    region.assign_advice(|| "acc", self.acc, row, || acc)?;
+
+                    let q = window.map(|(b_endo, b_neg)| {
+                        let q = if b_neg { -F::ONE } else { F::ONE };
+                        if b_endo {
+                            q * F::ZETA
+                        } else {
+                            q
+                        }
+                    });
+                    z = z * Value::known(F::from(4)) + b_neg * Value::known(F::from(2)) + b_endo;
+                    acc = acc * Value::known(F::from(2)) + q;
+                }
+
+                let row = windows.len();
+                let z = region.assign_advice(|| "z", self.z, row, || z)?;
+                region.constrain_equal(z.cell(), challenge.cell())?;
+                region.assign_advice(|| "acc", self.acc, row, || acc)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endoscale::primitive::{challenge_bits, endoscale_point};
+    use ff::PrimeField;
+    use group::{Curve, Group};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::Circuit,
+        transcript::{Challenge128, EncodedChallenge},
+    };
+    use halo2curves::pasta::{pallas, Fp, Fq};
+    use rand::{rngs::OsRng, RngCore};
+
+    fn random_challenge() -> u128 {
+        (u128::from(OsRng.next_u64()) << 64) | u128::from(OsRng.next_u64())
+    }
+
+    #[derive(Default)]
+    struct PointCircuit {
+        challenge: Value<Fp>,
+        base: Value<(Fp, Fp)>,
+        expected: Value<(Fp, Fp)>,
+    }
+
+    impl Circuit<Fp> for PointCircuit {
+        type Config = EndoscaleConfig<pallas::Affine>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advices = [(); 9].map(|_| meta.advice_column());
+            EndoscaleConfig::configure(meta, advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (challenge, x, y) = layouter.assign_region(
+                || "load inputs",
+                |mut region| {
+                    let challenge =
+                        region.assign_advice(|| "challenge", config.z, 0, || self.challenge)?;
+                    let x = region.assign_advice(
+                        || "x",
+                        config.x_p,
+                        0,
+                        || self.base.map(|(x, _)| x),
+                    )?;
+                    let y = region.assign_advice(
+                        || "y",
+                        config.y_p,
+                        0,
+                        || self.base.map(|(_, y)| y),
+                    )?;
+                    Ok((challenge, x, y))
+                },
+            )?;
+
+            let (x, y) = config.endoscale_point(
+                layouter.namespace(|| "endoscale"),
+                &challenge,
+                128,
+                (&x, &y),
+            )?;
+            x.value().zip(y.value()).zip(self.expected).assert_if_known(
+                |((x, y), (x_expected, y_expected))| *x == x_expected && *y == y_expected,
+            );
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn endoscale_point_chip() {
+        let challenge = random_challenge();
+        let base = pallas::Point::random(OsRng).to_affine();
+        let coordinates = base.coordinates().unwrap();
+        let expected = endoscale_point(&challenge_bits(challenge), base).to_affine();
+        let expected_coordinates = expected.coordinates().unwrap();
+
+        let circuit = PointCircuit {
+            challenge: Value::known(Fp::from_u128(challenge)),
+            base: Value::known((*coordinates.x(), *coordinates.y())),
+            expected: Value::known((*expected_coordinates.x(), *expected_coordinates.y())),
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A challenge that does not fit in 128 bits is rejected.
+        let circuit = PointCircuit {
+            challenge: Value::known(Fp::from_u128(challenge) + Fp::from_u128(1 << 64).square()),
+            base: circuit.base,
+            expected: Value::unknown(),
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[derive(Default)]
+    struct ScalarCircuit {
+        challenge: Value<Fq>,
+        expected: Value<Fq>,
+    }
+
+    impl Circuit<Fq> for ScalarCircuit {
+        type Config = EndoscaleScalarConfig<Fq>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+            let advices = [(); 4].map(|_| meta.advice_column());
+            EndoscaleScalarConfig::configure(meta, advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fq>,
+        ) -> Result<(), Error> {
+            let challenge = layouter.assign_region(
+                || "load challenge",
+                |mut region| region.assign_advice(|| "challenge", config.z, 0, || self.challenge),
+            )?;
+
+            let scalar =
+                config.endoscale_scalar(layouter.namespace(|| "endoscale"), &challenge, 128)?;
+            scalar
+                .value()
+                .zip(self.expected)
+                .assert_if_known(|(scalar, expected)| **scalar == *expected);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn endoscale_scalar_chip() {
+        let challenge = random_challenge();
+        // The scalar matches the one computed by the native verifier.
+        let expected = Challenge128::<pallas::Affine>::new(&Fp::from_u128(challenge)).get_scalar();
+
+        let circuit = ScalarCircuit {
+            challenge: Value::known(Fq::from_u128(challenge)),
+            expected: Value::known(expected),
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A different challenge is rejected.
+        let circuit = ScalarCircuit {
+            challenge: Value::known(Fq::from_u128(challenge) + Fq::from_u128(1 << 64).square()),
+            expected: Value::unknown(),
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
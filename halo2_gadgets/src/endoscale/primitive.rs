@@ -0,0 +1,115 @@
+//! Endoscaling out of circuit.
+//!
+//! Bitstrings are little-endian and of even length. They are consumed in pairs from
+//! the most significant end: in the pair $(b_{2i}, b_{2i+1})$, $b_{2i}$ selects the
+//! endomorphism and $b_{2i+1}$ selects negation, as in
+//! [`Challenge128::get_scalar`](halo2_proofs::transcript::EncodedChallenge::get_scalar).
+
+use ff::WithSmallOrderMulGroup;
+use group::prime::PrimeCurveAffine;
+use halo2_proofs::arithmetic::CurveAffine;
+
+/// Returns the bits of the 128-bit challenge `challenge`, in little-endian order.
+pub fn challenge_bits(challenge: u128) -> [bool; 128] {
+    let mut bits = [false; 128];
+    for (i, bit) in bits.iter_mut().enumerate() {
+        *bit = (challenge >> i) & 1 == 1;
+    }
+    bits
+}
+
+/// Maps the bitstring `bits` to a scalar, such that
+/// `endoscale_point(bits, base) == base * endoscale_scalar(bits)`.
+///
+/// This relies on the endomorphism constants of the base and scalar fields being
+/// paired, i.e. $(\zeta_{base} x, y) = [\zeta_{scalar}] (x, y)$, as they are for the
+/// Pasta curves.
+///
+/// # Panics
+///
+/// Panics if `bits` has odd length.
+pub fn endoscale_scalar<F: WithSmallOrderMulGroup<3>>(bits: &[bool]) -> F {
+    assert_eq!(bits.len() % 2, 0);
+
+    let mut acc = (F::ZETA + F::ONE).double();
+    for pair in bits.chunks(2).rev() {
+        let (endo, negate) = (pair[0], pair[1]);
+        let q = if negate { -F::ONE } else { F::ONE };
+        let q = if endo { q * F::ZETA } else { q };
+        acc = acc.double() + q;
+    }
+    acc
+}
+
+/// Applies the bitstring `bits` to `base` using the endomorphism of the curve.
+///
+/// # Panics
+///
+/// Panics if `bits` has odd length, or if `base` is the identity.
+pub fn endoscale_point<C: CurveAffine>(bits: &[bool], base: C) -> C::Curve
+where
+    C::Base: WithSmallOrderMulGroup<3>,
+{
+    assert_eq!(bits.len() % 2, 0);
+    let coordinates = Option::from(base.coordinates()).expect("base is not the identity");
+    let (x, y) = (*coordinates.x(), *coordinates.y());
+    let endo_base = C::from_xy(x * C::Base::ZETA, y).unwrap();
+
+    let mut acc = (endo_base.to_curve() + base).double();
+    for pair in bits.chunks(2).rev() {
+        let (endo, negate) = (pair[0], pair[1]);
+        let s = if endo { endo_base } else { base };
+        let s = if negate { -s } else { s };
+        acc = acc + s + acc;
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::PrimeField;
+    use group::{Curve, Group};
+    use halo2_proofs::transcript::{Challenge128, EncodedChallenge};
+    use halo2curves::pasta::{pallas, vesta};
+    use rand::{rngs::OsRng, RngCore};
+
+    fn random_challenge() -> u128 {
+        (u128::from(OsRng.next_u64()) << 64) | u128::from(OsRng.next_u64())
+    }
+
+    fn consistency<C: CurveAffine>()
+    where
+        C::Base: WithSmallOrderMulGroup<3>,
+        C::Scalar: WithSmallOrderMulGroup<3>,
+    {
+        for challenge in [0, u128::MAX, random_challenge()] {
+            let bits = challenge_bits(challenge);
+
+            // The scalar is the one used by the native verifier.
+            let scalar = endoscale_scalar::<C::Scalar>(&bits);
+            assert_eq!(
+                scalar,
+                Challenge128::<C>::new(&C::Base::from_u128(challenge)).get_scalar()
+            );
+
+            // Endoscaling a point is consistent with scalar multiplication.
+            let base = C::Curve::random(OsRng).to_affine();
+            assert_eq!(endoscale_point(&bits, base), base * scalar);
+        }
+
+        // Shorter bitstrings are supported.
+        let bits = [true, false, false, true];
+        let base = C::generator();
+        assert_eq!(
+            endoscale_point(&bits, base),
+            base * endoscale_scalar::<C::Scalar>(&bits)
+        );
+    }
+
+    #[test]
+    fn endoscale_consistency() {
+        consistency::<pallas::Affine>();
+        consistency::<vesta::Affine>();
+    }
+}
@@ -0,0 +1,31 @@
+//! The instruction traits that chips implement to be used with the gadgets in this
+//! crate.
+//!
+//! This module gathers, in one place, every trait a chip implements and every type that
+//! appears in those traits' signatures. A chip written against these items in another
+//! crate works with the corresponding gadgets here, without depending on any of the
+//! chips this crate provides.
+//!
+//! # Stability
+//!
+//! The items re-exported here are the interface between gadgets and chips, and are
+//! treated as more stable than the rest of the crate: they only change in releases that
+//! are breaking under semver, and such changes are listed in the changelog. Adding a
+//! provided method to a trait is not considered breaking. Items that are not re-exported
+//! here, such as the chips and their configurations, carry no such promise.
+//!
+//! The traits are expressed in terms of the circuit API of `halo2_proofs` ([`Chip`],
+//! [`Layouter`], [`AssignedCell`]), which chips must use the same version of as this
+//! crate.
+//!
+//! [`Chip`]: halo2_proofs::circuit::Chip
+//! [`Layouter`]: halo2_proofs::circuit::Layouter
+//! [`AssignedCell`]: halo2_proofs::circuit::AssignedCell
+
+pub use crate::poseidon::{
+    primitives::{Absorbing, Domain, Spec, SpongeMode, SpongeRate, Squeezing, State},
+    PaddedWord, PoseidonInstructions, PoseidonSpongeInstructions,
+};
+pub use crate::utilities::{
+    cond_swap::CondSwapInstructions, FieldValue, UtilitiesInstructions, Var,
+};
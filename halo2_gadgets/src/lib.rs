@@ -23,5 +23,7 @@
 #![deny(missing_docs)]
 #![deny(unsafe_code)]
 
+pub mod endoscale;
+pub mod interfaces;
 pub mod poseidon;
 pub mod utilities;
@@ -11,11 +11,22 @@ use halo2_proofs::{
     plonk::Error,
 };
 
+mod instance;
+pub use instance::{expose_hashed_instance, hashed_instance};
+
+mod padding;
+pub use padding::PaddingConfig;
+
 mod pow5;
 pub use pow5::{Pow5Chip, Pow5Config, StateWord};
 
 pub mod primitives;
-use primitives::{Absorbing, ConstantLength, Domain, Spec, SpongeMode, Squeezing, State};
+pub use primitives::{validate_spec, SecurityReport};
+
+pub mod transcript;
+use primitives::{
+    Absorbing, BoundedLength, ConstantLength, Domain, Spec, SpongeMode, Squeezing, State,
+};
 
 /// A word from the padded input to a Poseidon sponge.
 #[derive(Clone, Debug)]
@@ -268,12 +279,13 @@ impl<
 
 impl<
         F: PrimeField,
-        PoseidonChip: PoseidonSpongeInstructions<F, S, ConstantLength<L>, T, RATE>,
+        PoseidonChip: PoseidonSpongeInstructions<F, S, ConstantLength<L, LABEL>, T, RATE>,
         S: Spec<F, T, RATE>,
         const T: usize,
         const RATE: usize,
         const L: usize,
-    > Hash<F, PoseidonChip, S, ConstantLength<L>, T, RATE>
+        const LABEL: u64,
+    > Hash<F, PoseidonChip, S, ConstantLength<L, LABEL>, T, RATE>
 {
     /// Hashes the given input.
     pub fn hash(
@@ -284,7 +296,9 @@ impl<
         for (i, value) in message
             .into_iter()
             .map(PaddedWord::Message)
-            .chain(<ConstantLength<L> as Domain<F, RATE>>::padding(L).map(PaddedWord::Padding))
+            .chain(
+                <ConstantLength<L, LABEL> as Domain<F, RATE>>::padding(L).map(PaddedWord::Padding),
+            )
             .enumerate()
         {
             self.sponge
@@ -295,3 +309,38 @@ impl<
             .squeeze(layouter.namespace(|| "squeeze"))
     }
 }
+
+impl<
+        F: PrimeField,
+        PoseidonChip: PoseidonSpongeInstructions<F, S, BoundedLength<MAX>, T, RATE>,
+        S: Spec<F, T, RATE>,
+        const T: usize,
+        const RATE: usize,
+        const MAX: usize,
+    > Hash<F, PoseidonChip, S, BoundedLength<MAX>, T, RATE>
+{
+    /// Hashes the first `len` words of `message`, where `len` is a witness of at most
+    /// `MAX`. The input is padded in-circuit with `padding`, so the circuit does not
+    /// depend on `len`.
+    pub fn hash(
+        mut self,
+        mut layouter: impl Layouter<F>,
+        padding: &PaddingConfig<F>,
+        message: [AssignedCell<F, F>; MAX],
+        len: AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let padded = padding.pad(
+            layouter.namespace(|| "pad"),
+            &message,
+            &len,
+            BoundedLength::<MAX>::padded_len(RATE),
+        )?;
+        for (i, value) in padded.into_iter().map(PaddedWord::Message).enumerate() {
+            self.sponge
+                .absorb(layouter.namespace(|| format!("absorb_{i}")), value)?;
+        }
+        self.sponge
+            .finish_absorbing(layouter.namespace(|| "finish absorbing"))?
+            .squeeze(layouter.namespace(|| "squeeze"))
+    }
+}
@@ -0,0 +1,168 @@
+//! Exposing many public values through a single instance value.
+
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Column, Error, Instance},
+};
+
+use super::{
+    primitives::{self, ConstantLength, Spec},
+    Hash, PoseidonSpongeInstructions,
+};
+
+/// Returns the instance value that [`expose_hashed_instance`] constrains to the public
+/// `values`.
+///
+/// The verifier computes this from the public values and passes it in place of them.
+pub fn hashed_instance<
+    F: PrimeField,
+    S: Spec<F, T, RATE>,
+    const T: usize,
+    const RATE: usize,
+    const L: usize,
+>(
+    values: [F; L],
+) -> F {
+    primitives::Hash::<F, S, ConstantLength<L>, T, RATE>::init().hash(values)
+}
+
+/// Constrains row `row` of the `instance` column to the Poseidon hash of `values`, and
+/// returns the cell of the hash.
+///
+/// This exposes `values` to the verifier as a single instance value, which it computes
+/// with [`hashed_instance`]. The work of the verifier on the instance columns grows
+/// with the number of instance values, so for a long vector of public values this
+/// replaces that work by a native hash, at the cost of hashing the values in the
+/// circuit. For a handful of values, exposing each of them directly is cheaper.
+///
+/// `values` are usually advice cells that the rest of the circuit constrains; the hash
+/// binds them to the values hashed by the verifier. The `instance` column must be
+/// equality-enabled.
+pub fn expose_hashed_instance<
+    F,
+    PoseidonChip,
+    S,
+    const T: usize,
+    const RATE: usize,
+    const L: usize,
+>(
+    chip: PoseidonChip,
+    mut layouter: impl Layouter<F>,
+    values: [AssignedCell<F, F>; L],
+    instance: Column<Instance>,
+    row: usize,
+) -> Result<AssignedCell<F, F>, Error>
+where
+    F: PrimeField,
+    PoseidonChip: PoseidonSpongeInstructions<F, S, ConstantLength<L>, T, RATE>,
+    S: Spec<F, T, RATE>,
+{
+    let hasher =
+        Hash::<_, _, S, ConstantLength<L>, T, RATE>::init(chip, layouter.namespace(|| "init"))?;
+    let digest = hasher.hash(layouter.namespace(|| "hash"), values)?;
+    layouter.constrain_instance(digest.cell(), instance, row)?;
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use group::ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use halo2curves::pasta::Fp;
+    use rand::rngs::OsRng;
+
+    use super::{expose_hashed_instance, hashed_instance};
+    use crate::poseidon::{primitives::P128Pow5T3, Pow5Chip, Pow5Config};
+
+    const L: usize = 6;
+
+    struct MyCircuit {
+        values: Value<[Fp; L]>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (Pow5Config<Fp, 3, 2>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit {
+                values: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+            let rc_a = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            meta.enable_constant(rc_b[0]);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let config = Pow5Chip::configure::<P128Pow5T3>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let values = layouter.assign_region(
+                || "load values",
+                |mut region| {
+                    let values = (0..L)
+                        .map(|i| {
+                            region.assign_advice(
+                                || format!("value {i}"),
+                                config.state[i % 3],
+                                i / 3,
+                                || self.values.map(|values| values[i]),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(values.try_into().unwrap())
+                },
+            )?;
+
+            expose_hashed_instance::<_, _, P128Pow5T3, 3, 2, L>(
+                Pow5Chip::construct(config),
+                layouter.namespace(|| "expose"),
+                values,
+                instance,
+                0,
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn hashed_instance_is_constrained() {
+        let values = [(); L].map(|_| Fp::random(OsRng));
+        let digest = hashed_instance::<_, P128Pow5T3, 3, 2, L>(values);
+        let circuit = MyCircuit {
+            values: Value::known(values),
+        };
+
+        let prover = MockProver::run(8, &circuit, vec![vec![digest]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(8, &circuit, vec![vec![digest + Fp::ONE]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,347 @@
+use std::marker::PhantomData;
+
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::utilities::bool_check;
+
+/// Configuration for padding [`BoundedLength`] inputs in-circuit.
+///
+/// The gate walks over the padded input one word per row, keeping a flag that is set
+/// while the row is within the message:
+///
+/// | message | in_message   | len            | word                |
+/// |---------|--------------|----------------|---------------------|
+/// |         | 1            | 0              |                     |
+/// | $m_0$   | $b_0$        | $b_0$          | $w_0$               |
+/// | $m_1$   | $b_1$        | $b_0 + b_1$    | $w_1$               |
+/// | ...     | ...          | ...            | ...                 |
+///
+/// The flags are boolean and non-increasing, so they are ones for exactly the first
+/// `len` rows. Each padded word is then $w_i = b_i \cdot m_i + (b_{i-1} - b_i)$, which
+/// is the message word within the message, one right after it, and zero elsewhere.
+/// Rows past the maximum length have their flag fixed to zero, which bounds `len`.
+///
+/// [`BoundedLength`]: super::primitives::BoundedLength
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaddingConfig<F: PrimeField> {
+    q_pad: Selector,
+    message: Column<Advice>,
+    in_message: Column<Advice>,
+    len: Column<Advice>,
+    word: Column<Advice>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> PaddingConfig<F> {
+    /// Configures the padding gate.
+    ///
+    /// All columns are equality-enabled. Padding assigns constants, so a fixed column
+    /// must be enabled with [`ConstraintSystem::enable_constant`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        message: Column<Advice>,
+        in_message: Column<Advice>,
+        len: Column<Advice>,
+        word: Column<Advice>,
+    ) -> Self {
+        for column in [message, in_message, len, word] {
+            meta.enable_equality(column);
+        }
+
+        let q_pad = meta.selector();
+
+        meta.create_gate("bounded-length padding", |meta| {
+            let q_pad = meta.query_selector(q_pad);
+            let m = meta.query_advice(message, Rotation::cur());
+            let b_prev = meta.query_advice(in_message, Rotation::prev());
+            let b = meta.query_advice(in_message, Rotation::cur());
+            let len_prev = meta.query_advice(len, Rotation::prev());
+            let len = meta.query_advice(len, Rotation::cur());
+            let word = meta.query_advice(word, Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+
+            Constraints::with_selector(
+                q_pad,
+                [
+                    ("bool_check in_message", bool_check(b.clone())),
+                    (
+                        "in_message is non-increasing",
+                        b.clone() * (one - b_prev.clone()),
+                    ),
+                    ("len = len_prev + in_message", len - len_prev - b.clone()),
+                    ("word", word - (b.clone() * m + b_prev - b)),
+                ],
+            )
+        });
+
+        PaddingConfig {
+            q_pad,
+            message,
+            in_message,
+            len,
+            word,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Pads the first `len` words of `message` to `padded_len` words.
+    ///
+    /// Words of `message` past `len` are ignored. The circuit is unsatisfiable if `len`
+    /// is greater than `message.len()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `padded_len` is not greater than `message.len()`.
+    pub fn pad(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: &[AssignedCell<F, F>],
+        len: &AssignedCell<F, F>,
+        padded_len: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let max_len = message.len();
+        assert!(padded_len > max_len);
+
+        // Out-of-range lengths are witnessed as the maximum, and fail the final check.
+        let len_usize = len.value().map(|len| {
+            (0..=max_len)
+                .find(|l| F::from(*l as u64) == *len)
+                .unwrap_or(max_len)
+        });
+
+        layouter.assign_region(
+            || "bounded-length padding",
+            |mut region| {
+                let mut b_prev = region.assign_advice_from_constant(
+                    || "in_message",
+                    self.in_message,
+                    0,
+                    F::ONE,
+                )?;
+                let mut len_acc =
+                    region.assign_advice_from_constant(|| "len", self.len, 0, F::ZERO)?;
+
+                let mut words = Vec::with_capacity(padded_len);
+                for i in 0..padded_len {
+                    let row = i + 1;
+                    self.q_pad.enable(&mut region, row)?;
+
+                    let (m, b) = if i < max_len {
+                        let m = message[i].copy_advice(
+                            || format!("message_{i}"),
+                            &mut region,
+                            self.message,
+                            row,
+                        )?;
+                        let b = region.assign_advice(
+                            || format!("in_message_{i}"),
+                            self.in_message,
+                            row,
+                            || len_usize.map(|len| F::from((i < len) as u64)),
+                        )?;
+                        (m, b)
+                    } else {
+                        let m = region.assign_advice(
+                            || format!("message_{i}"),
+                            self.message,
+                            row,
+                            || Value::known(F::ZERO),
+                        )?;
+                        let b = region.assign_advice_from_constant(
+                            || format!("in_message_{i}"),
+                            self.in_message,
+                            row,
+                            F::ZERO,
+                        )?;
+                        (m, b)
+                    };
+
+                    len_acc = region.assign_advice(
+                        || format!("len_{i}"),
+                        self.len,
+                        row,
+                        || len_acc.value().copied() + b.value(),
+                    )?;
+
+                    let word = b.value().copied() * m.value() + b_prev.value() - b.value();
+                    words.push(region.assign_advice(
+                        || format!("word_{i}"),
+                        self.word,
+                        row,
+                        || word,
+                    )?);
+
+                    b_prev = b;
+                }
+
+                region.constrain_equal(len_acc.cell(), len.cell())?;
+
+                Ok(words)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::Fp;
+    use rand::rngs::OsRng;
+
+    use super::PaddingConfig;
+    use crate::poseidon::{
+        primitives::{self as poseidon, BoundedLength, P128Pow5T3 as OrchardNullifier},
+        Hash, Pow5Chip, Pow5Config,
+    };
+    use std::convert::TryInto;
+
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const MAX: usize = 5;
+
+    #[derive(Default)]
+    struct HashCircuit {
+        message: Value<[Fp; MAX]>,
+        len: Value<Fp>,
+        // For the purpose of this test, witness the result.
+        output: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for HashCircuit {
+        type Config = (
+            Pow5Config<Fp, WIDTH, RATE>,
+            PaddingConfig<Fp>,
+            Column<Advice>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+
+            let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+            meta.enable_constant(rc_b[0]);
+
+            let poseidon_config = Pow5Chip::configure::<OrchardNullifier>(
+                meta,
+                state.clone().try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+            let padding_config =
+                PaddingConfig::configure(meta, state[0], state[1], state[2], partial_sbox);
+
+            (poseidon_config, padding_config, state[0])
+        }
+
+        fn synthesize(
+            &self,
+            (poseidon_config, padding_config, advice): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Pow5Chip::construct(poseidon_config);
+
+            let (message, len) = layouter.assign_region(
+                || "load message",
+                |mut region| {
+                    let message = (0..MAX)
+                        .map(|i| {
+                            region.assign_advice(
+                                || format!("load message_{i}"),
+                                advice,
+                                i,
+                                || self.message.map(|message| message[i]),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let len = region.assign_advice(|| "load len", advice, MAX, || self.len)?;
+                    Ok((message.try_into().unwrap(), len))
+                },
+            )?;
+
+            let hasher = Hash::<_, _, OrchardNullifier, BoundedLength<MAX>, WIDTH, RATE>::init(
+                chip,
+                layouter.namespace(|| "init"),
+            )?;
+            let output =
+                hasher.hash(layouter.namespace(|| "hash"), &padding_config, message, len)?;
+
+            layouter.assign_region(
+                || "constrain output",
+                |mut region| {
+                    let expected_var =
+                        region.assign_advice(|| "load output", advice, 0, || self.output)?;
+                    region.constrain_equal(output.cell(), expected_var.cell())
+                },
+            )
+        }
+    }
+
+    fn circuit(message: [Fp; MAX], len: u64, output: Fp) -> HashCircuit {
+        HashCircuit {
+            message: Value::known(message),
+            len: Value::known(Fp::from(len)),
+            output: Value::known(output),
+        }
+    }
+
+    #[test]
+    fn bounded_length_hash() {
+        let rng = OsRng;
+        let k = 8;
+
+        let message = [(); MAX].map(|_| Fp::random(rng));
+        for len in 0..=MAX {
+            let output =
+                poseidon::Hash::<_, OrchardNullifier, BoundedLength<MAX>, WIDTH, RATE>::init()
+                    .hash(&message[..len]);
+
+            let prover = MockProver::run(k, &circuit(message, len as u64, output), vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // Words past the length do not affect the output.
+            let mut other_message = message;
+            for word in other_message[len..].iter_mut() {
+                *word = Fp::random(rng);
+            }
+            let prover =
+                MockProver::run(k, &circuit(other_message, len as u64, output), vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The length is bound to the output.
+            if len > 0 {
+                let prover =
+                    MockProver::run(k, &circuit(message, len as u64 - 1, output), vec![]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+        }
+
+        // The length is bounded by MAX.
+        let output = poseidon::Hash::<_, OrchardNullifier, BoundedLength<MAX>, WIDTH, RATE>::init()
+            .hash(&message);
+        let prover = MockProver::run(k, &circuit(message, MAX as u64 + 1, output), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+        let prover = MockProver::run(k, &circuit(message, u64::MAX, output), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -13,20 +13,26 @@ pub(crate) mod fp;
 pub(crate) mod fq;
 pub(crate) mod grain;
 pub(crate) mod mds;
+pub use mds::{check_mds, MdsError};
 
 #[cfg(test)]
 pub(crate) mod test_vectors;
+#[cfg(feature = "test-vectors")]
+pub mod vectors;
 
 mod p128pow5t3;
 pub use p128pow5t3::P128Pow5T3;
 
+mod security;
+pub use security::{validate_spec, Attack, AttackMargin, SecurityReport, SpecError};
+
 use grain::SboxType;
 
 /// The type used to hold permutation state.
-pub(crate) type State<F, const T: usize> = [F; T];
+pub type State<F, const T: usize> = [F; T];
 
 /// The type used to hold sponge rate.
-pub(crate) type SpongeRate<F, const RATE: usize> = [Option<F>; RATE];
+pub type SpongeRate<F, const RATE: usize> = [Option<F>; RATE];
 
 /// The type used to hold the MDS matrix and its inverse.
 pub type Mds<F, const T: usize> = [[F; T]; T];
@@ -47,15 +53,25 @@ pub trait Spec<F: Field, const T: usize, const RATE: usize>: fmt::Debug {
     /// Side-loaded index of the first correct and secure MDS that will be generated by
     /// the reference implementation.
     ///
-    /// This is used by the default implementation of [`Spec::constants`]. If you are
-    /// hard-coding the constants, you may leave this unimplemented.
+    /// This is used by [`generate_constants`]. Specifications that hard-code their
+    /// constants in [`Spec::constants`] should return the index their constants were
+    /// generated with.
     fn secure_mds() -> usize;
 
     /// Generates `(round_constants, mds, mds^-1)` corresponding to this specification.
+    ///
+    /// This may either call [`generate_constants`], which derives the constants with
+    /// the Grain LFSR as in the reference implementation, or return hard-coded
+    /// constants (as [`P128Pow5T3`] does), in which case [`check_mds`] can be used to
+    /// check the MDS matrix.
     fn constants() -> (Vec<[F; T]>, Mds<F, T>, Mds<F, T>);
 }
 
 /// Generates `(round_constants, mds, mds^-1)` corresponding to this specification.
+///
+/// The round constants and the MDS candidates are consecutive outputs of a single
+/// Grain LFSR, so they are generated sequentially. Only the inversion of the chosen MDS
+/// matrix is computed in parallel.
 pub fn generate_constants<
     F: FromUniformBytes<64> + Ord,
     S: Spec<F, T, RATE>,
@@ -186,9 +202,20 @@ impl<F: fmt::Debug, const RATE: usize> Absorbing<F, RATE> {
                 .unwrap(),
         )
     }
+
+    /// Returns the words absorbed so far, with `None` for the unused rate positions.
+    pub fn expose_inner(&self) -> &SpongeRate<F, RATE> {
+        &self.0
+    }
+}
+
+impl<F: fmt::Debug, const RATE: usize> Squeezing<F, RATE> {
+    /// Constructs the squeezing state holding the given sponge outputs.
+    pub fn init_full(words: [F; RATE]) -> Self {
+        Self(words.map(Some))
+    }
 }
 
-#[derive(Clone)]
 /// A Poseidon sponge.
 pub(crate) struct Sponge<
     F: Field,
@@ -204,6 +231,21 @@ pub(crate) struct Sponge<
     _marker: PhantomData<S>,
 }
 
+// Implemented by hand, as deriving `Clone` would require `S: Clone`.
+impl<F: Field, S: Spec<F, T, RATE>, M: SpongeMode + Clone, const T: usize, const RATE: usize> Clone
+    for Sponge<F, S, M, T, RATE>
+{
+    fn clone(&self) -> Self {
+        Sponge {
+            mode: self.mode.clone(),
+            state: self.state,
+            mds_matrix: self.mds_matrix,
+            round_constants: self.round_constants.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
 impl<F: Field, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>
     Sponge<F, S, Absorbing<F, RATE>, T, RATE>
 {
@@ -303,20 +345,34 @@ pub trait Domain<F: Field, const RATE: usize> {
 /// A Poseidon hash function used with constant input length.
 ///
 /// Domain specified in [ePrint 2019/458 section 4.2](https://eprint.iacr.org/2019/458.pdf).
+///
+/// `LABEL` separates the hashes of applications that use the same input length: it is
+/// encoded into the capacity element alongside the length, so inputs hashed under
+/// different labels never share a permutation. The default label of zero leaves the
+/// capacity element as specified in the paper.
 #[derive(Clone, Copy, Debug)]
-pub struct ConstantLength<const L: usize>;
+pub struct ConstantLength<const L: usize, const LABEL: u64 = 0>;
 
-impl<F: PrimeField, const RATE: usize, const L: usize> Domain<F, RATE> for ConstantLength<L> {
+impl<F: PrimeField, const RATE: usize, const L: usize, const LABEL: u64> Domain<F, RATE>
+    for ConstantLength<L, LABEL>
+{
     type Padding = iter::Take<iter::Repeat<F>>;
 
     fn name() -> String {
-        format!("ConstantLength<{L}>")
+        if LABEL == 0 {
+            format!("ConstantLength<{L}>")
+        } else {
+            format!("ConstantLength<{L}, {LABEL}>")
+        }
     }
 
     fn initial_capacity_element() -> F {
-        // Capacity value is $length \cdot 2^64 + (o-1)$ where o is the output length.
-        // We hard-code an output length of 1.
-        F::from_u128((L as u128) << 64)
+        // Capacity value is $label \cdot 2^128 + length \cdot 2^64 + (o-1)$ where o is
+        // the output length. We hard-code an output length of 1. The label occupies
+        // bits 128 to 191, which keeps bit 127 clear to separate this domain from
+        // `BoundedLength`.
+        let label = F::from_u128(u128::from(LABEL)) * F::from_u128(1 << 64).square();
+        label + F::from_u128((L as u128) << 64)
     }
 
     fn padding(input_len: usize) -> Self::Padding {
@@ -330,6 +386,45 @@ impl<F: PrimeField, const RATE: usize, const L: usize> Domain<F, RATE> for Const
     }
 }
 
+/// A Poseidon hash function used with inputs of any length up to `MAX`.
+///
+/// The input is padded with a single one followed by zeroes, up to the smallest multiple
+/// of RATE that is greater than `MAX`. Every input therefore takes the same number of
+/// permutations, so the length can be a witness in a circuit; see
+/// [`Hash::hash`](crate::poseidon::Hash) for the gadget.
+#[derive(Clone, Copy, Debug)]
+pub struct BoundedLength<const MAX: usize>;
+
+impl<const MAX: usize> BoundedLength<MAX> {
+    /// Returns the length of a padded input.
+    pub(crate) fn padded_len(rate: usize) -> usize {
+        (MAX / rate + 1) * rate
+    }
+}
+
+impl<F: PrimeField, const RATE: usize, const MAX: usize> Domain<F, RATE> for BoundedLength<MAX> {
+    type Padding = iter::Chain<iter::Once<F>, iter::Take<iter::Repeat<F>>>;
+
+    fn name() -> String {
+        format!("BoundedLength<{MAX}>")
+    }
+
+    fn initial_capacity_element() -> F {
+        // Capacity value is $2^{127} + MAX \cdot 2^64 + (o-1)$ where o is the output
+        // length, which we hard-code to 1. The top bit separates this domain from
+        // `ConstantLength`.
+        F::from_u128((1 << 127) | ((MAX as u128) << 64))
+    }
+
+    fn padding(input_len: usize) -> Self::Padding {
+        assert!(input_len <= MAX);
+        // The padding is injective on its own, since the last non-zero word of a
+        // padded input marks the end of the message.
+        let zeroes = Self::padded_len(RATE) - input_len - 1;
+        iter::once(F::ONE).chain(iter::repeat(F::ZERO).take(zeroes))
+    }
+}
+
 #[derive(Clone)]
 /// A Poseidon hash function, built around a sponge.
 pub struct Hash<
@@ -369,14 +464,43 @@ impl<F: Field, S: Spec<F, T, RATE>, D: Domain<F, RATE>, const T: usize, const RA
     }
 }
 
-impl<F: PrimeField, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, const L: usize>
-    Hash<F, S, ConstantLength<L>, T, RATE>
+impl<
+        F: PrimeField,
+        S: Spec<F, T, RATE>,
+        const T: usize,
+        const RATE: usize,
+        const L: usize,
+        const LABEL: u64,
+    > Hash<F, S, ConstantLength<L, LABEL>, T, RATE>
 {
     /// Hashes the given input.
     pub fn hash(mut self, message: [F; L]) -> F {
         for value in message
             .into_iter()
-            .chain(<ConstantLength<L> as Domain<F, RATE>>::padding(L))
+            .chain(<ConstantLength<L, LABEL> as Domain<F, RATE>>::padding(L))
+        {
+            self.sponge.absorb(value);
+        }
+        self.sponge.finish_absorbing().squeeze()
+    }
+}
+
+impl<F: PrimeField, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, const MAX: usize>
+    Hash<F, S, BoundedLength<MAX>, T, RATE>
+{
+    /// Hashes the given input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `message` is longer than `MAX`.
+    pub fn hash(mut self, message: &[F]) -> F {
+        for value in
+            message
+                .iter()
+                .copied()
+                .chain(<BoundedLength<MAX> as Domain<F, RATE>>::padding(
+                    message.len(),
+                ))
         {
             self.sponge.absorb(value);
         }
@@ -386,8 +510,10 @@ impl<F: PrimeField, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, cons
 
 #[cfg(test)]
 mod tests {
-    use super::{permute, ConstantLength, Hash, P128Pow5T3 as OrchardNullifier, Spec};
-    use ff::PrimeField;
+    use super::{
+        permute, BoundedLength, ConstantLength, Hash, P128Pow5T3 as OrchardNullifier, Spec,
+    };
+    use ff::{Field, PrimeField};
     use halo2curves::pasta::pallas;
 
     #[test]
@@ -405,4 +531,61 @@ mod tests {
         permute::<_, OrchardNullifier, 3, 2>(&mut state, &mds, &round_constants);
         assert_eq!(state[0], result);
     }
+
+    #[test]
+    fn bounded_length_padding() {
+        assert_eq!(BoundedLength::<3>::padded_len(2), 4);
+        assert_eq!(BoundedLength::<4>::padded_len(2), 6);
+
+        let hash = |message: &[pallas::Base]| {
+            Hash::<_, OrchardNullifier, BoundedLength<3>, 3, 2>::init().hash(message)
+        };
+
+        // Trailing zeroes are part of the message.
+        let message = [
+            pallas::Base::from(6),
+            pallas::Base::ZERO,
+            pallas::Base::ZERO,
+        ];
+        assert_ne!(hash(&message[..1]), hash(&message[..2]));
+        assert_ne!(hash(&message[..2]), hash(&message));
+
+        // The domain is separated from constant-length hashing of the padded input.
+        let padded = [
+            message[0],
+            pallas::Base::ONE,
+            pallas::Base::ZERO,
+            pallas::Base::ZERO,
+        ];
+        assert_ne!(
+            hash(&message[..1]),
+            Hash::<_, OrchardNullifier, ConstantLength<4>, 3, 2>::init().hash(padded)
+        );
+    }
+
+    #[test]
+    fn constant_length_labels() {
+        let message = [pallas::Base::from(6), pallas::Base::from(42)];
+        let hash_0 = Hash::<_, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash(message);
+        let hash_1 = Hash::<_, OrchardNullifier, ConstantLength<2, 1>, 3, 2>::init().hash(message);
+        let hash_2 = Hash::<_, OrchardNullifier, ConstantLength<2, 2>, 3, 2>::init().hash(message);
+
+        // The default label is the unlabelled domain.
+        assert_eq!(
+            Hash::<_, OrchardNullifier, ConstantLength<2, 0>, 3, 2>::init().hash(message),
+            hash_0
+        );
+        assert_ne!(hash_0, hash_1);
+        assert_ne!(hash_1, hash_2);
+
+        // The label is encoded above the length in the capacity element.
+        let (round_constants, mds, _) = OrchardNullifier::constants();
+        let mut state = [
+            message[0],
+            message[1],
+            pallas::Base::from_u128(1 << 64).square() + pallas::Base::from_u128(2 << 64),
+        ];
+        permute::<_, OrchardNullifier, 3, 2>(&mut state, &mds, &round_constants);
+        assert_eq!(state[0], hash_1);
+    }
 }
@@ -1,4 +1,7 @@
-use ff::FromUniformBytes;
+use std::fmt;
+
+use ff::{BatchInvert, Field, FromUniformBytes, PrimeField, PrimeFieldBits};
+use halo2_proofs::arithmetic::{batch_invert, parallelize};
 
 use super::{grain::Grain, Mds};
 
@@ -55,9 +58,10 @@ pub(super) fn generate_mds<F: FromUniformBytes<64> + Ord, const T: usize>(
                 let sum = xs[i] + ys[j];
                 // We leverage the secure MDS selection counter to also check this.
                 assert!(!sum.is_zero_vartime());
-                mds[i][j] = sum.invert().unwrap();
+                mds[i][j] = sum;
             }
         }
+        mds.iter_mut().flat_map(|row| row.iter_mut()).batch_invert();
 
         break (xs, ys, mds);
     };
@@ -74,34 +78,291 @@ pub(super) fn generate_mds<F: FromUniformBytes<64> + Ord, const T: usize>(
     // where A_i(x) and B_i(x) are the Lagrange polynomials for xs and ys respectively.
     //
     // We adapt this to the positive Cauchy formulation by negating ys.
+    //
+    // The denominators of the Lagrange polynomials do not depend on the point at which
+    // they are evaluated, so we invert them once up front.
+    let neg_ys: Vec<_> = ys.iter().map(|y| -*y).collect();
+    let lagrange_denominators = |xs: &[F]| {
+        let mut denominators: Vec<F> = (0..T)
+            .map(|j| {
+                xs.iter()
+                    .enumerate()
+                    .filter(|(m, _)| *m != j)
+                    .fold(F::ONE, |acc, (_, x_m)| acc * (xs[j] - x_m))
+            })
+            .collect();
+        // By construction, the elements of xs are distinct, so none of these are zero.
+        batch_invert(&mut denominators);
+        denominators
+    };
+    let xs_denominators = lagrange_denominators(&xs);
+    let ys_denominators = lagrange_denominators(&neg_ys);
+    let l = |xs: &[F], denominators: &[F], j, x: F| {
+        xs.iter()
+            .enumerate()
+            .filter(|(m, _)| *m != j)
+            .fold(denominators[j], |acc, (_, x_m)| acc * (x - x_m))
+    };
+
+    // Each row of the inverse is independent of the others.
     let mut mds_inv = [[F::ZERO; T]; T];
-    let l = |xs: &[F], j, x: F| {
-        let x_j = xs[j];
-        xs.iter().enumerate().fold(F::ONE, |acc, (m, x_m)| {
-            if m == j {
-                acc
-            } else {
-                // We can invert freely; by construction, the elements of xs are distinct.
-                let diff: F = x_j - *x_m;
-                acc * (x - x_m) * diff.invert().unwrap()
+    parallelize(&mut mds_inv, |rows, start| {
+        for (i, row) in rows.iter_mut().enumerate() {
+            let i = start + i;
+            for (j, b_ij) in row.iter_mut().enumerate() {
+                *b_ij = (xs[j] - neg_ys[i])
+                    * l(&xs, &xs_denominators, j, neg_ys[i])
+                    * l(&neg_ys, &ys_denominators, i, xs[j]);
             }
+        }
+    });
+
+    (mds, mds_inv)
+}
+
+/// A reason for which [`check_mds`] rejects a matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MdsError {
+    /// The matrix has a singular square submatrix, so it is not MDS.
+    NotMds,
+    /// The characteristic polynomial of the given power of the matrix is reducible, so
+    /// the matrix could not be shown to resist invariant subspace trails.
+    ReduciblePower(usize),
+}
+
+impl fmt::Display for MdsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MdsError::NotMds => write!(f, "matrix has a singular square submatrix"),
+            MdsError::ReduciblePower(power) => {
+                write!(f, "characteristic polynomial of M^{} is reducible", power)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MdsError {}
+
+/// Checks that `mds` is an MDS matrix that resists invariant subspace trails.
+///
+/// The matrix must be MDS, i.e. every square submatrix of it must be non-singular.
+/// It must also have no invariant subspace trails over up to $2T$ rounds, which (as
+/// shown by [Grassi, Rechberger and Schofnegger](https://eprint.iacr.org/2020/500))
+/// rules out infinitely long subspace trails through the partial rounds. This is
+/// checked with the sufficient condition that the characteristic polynomials of
+/// $M, M^2, \ldots, M^{2T}$ are irreducible.
+///
+/// The reference implementation additionally searches for the invariant subspaces of
+/// matrices that fail this condition, so a matrix it accepts may be rejected here
+/// with [`MdsError::ReduciblePower`]. The matrices used by [`P128Pow5T3`] satisfy
+/// the condition.
+///
+/// The number of square submatrices grows as $\binom{2T}{T}$, so this is slow for
+/// very wide states. The submatrices and the powers of the matrix are checked in
+/// parallel. Each check runs to completion, so the result does not depend on which
+/// check finishes first: a matrix that is not MDS is always rejected with
+/// [`MdsError::NotMds`], and otherwise the smallest reducible power is reported.
+///
+/// [`P128Pow5T3`]: super::P128Pow5T3
+pub fn check_mds<F: PrimeFieldBits, const T: usize>(mds: &Mds<F, T>) -> Result<(), MdsError> {
+    assert!(T < 32, "check_mds supports widths below 32");
+    let rows: Vec<Vec<F>> = mds.iter().map(|row| row.to_vec()).collect();
+
+    // Every square submatrix, indexed by the bitmasks of its rows and columns. The
+    // submatrices with the rows of `row_mask` are checked by one thread.
+    let mut singular = vec![false; (1 << T) - 1];
+    parallelize(&mut singular, |singular, start| {
+        for (row_mask, singular) in (start as u32 + 1..).zip(singular.iter_mut()) {
+            *singular = (1u32..(1 << T))
+                .filter(|col_mask| row_mask.count_ones() == col_mask.count_ones())
+                .any(|col_mask| {
+                    let submatrix = (0..T)
+                        .filter(|i| row_mask & (1 << i) != 0)
+                        .map(|i| {
+                            (0..T)
+                                .filter(|j| col_mask & (1 << j) != 0)
+                                .map(|j| rows[i][j])
+                                .collect()
+                        })
+                        .collect();
+                    is_singular(submatrix)
+                });
+        }
+    });
+    if singular.contains(&true) {
+        return Err(MdsError::NotMds);
+    }
+
+    // The powers are cheap to compute in turn, and the irreducibility tests of their
+    // characteristic polynomials run in parallel.
+    let mut powers = vec![rows.clone()];
+    for _ in 1..2 * T {
+        powers.push(matrix_mul(powers.last().unwrap(), &rows));
+    }
+    let mut reducible = vec![false; 2 * T];
+    parallelize(&mut reducible, |reducible, start| {
+        for (power, reducible) in powers[start..].iter().zip(reducible.iter_mut()) {
+            *reducible = !is_irreducible(&characteristic_polynomial(power));
+        }
+    });
+    match reducible.iter().position(|reducible| *reducible) {
+        Some(i) => Err(MdsError::ReduciblePower(i + 1)),
+        None => Ok(()),
+    }
+}
+
+fn matrix_mul<F: Field>(a: &[Vec<F>], b: &[Vec<F>]) -> Vec<Vec<F>> {
+    let n = a.len();
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| (0..n).fold(F::ZERO, |acc, k| acc + a[i][k] * b[k][j]))
+                .collect()
         })
+        .collect()
+}
+
+/// Returns `true` if the square matrix `m` has a zero determinant.
+fn is_singular<F: Field>(mut m: Vec<Vec<F>>) -> bool {
+    let n = m.len();
+    for col in 0..n {
+        let pivot = match (col..n).find(|row| !bool::from(m[*row][col].is_zero())) {
+            Some(pivot) => pivot,
+            None => return true,
+        };
+        m.swap(col, pivot);
+        let inv = m[col][col].invert().unwrap();
+        for row in (col + 1)..n {
+            let factor = m[row][col] * inv;
+            for k in col..n {
+                let sub = factor * m[col][k];
+                m[row][k] -= sub;
+            }
+        }
+    }
+    false
+}
+
+/// Returns the coefficients of the characteristic polynomial of `m`, from the
+/// constant term up, computed with the Faddeev-LeVerrier algorithm.
+fn characteristic_polynomial<F: PrimeField>(m: &[Vec<F>]) -> Vec<F> {
+    let n = m.len();
+    let mut coeffs = vec![F::ZERO; n + 1];
+    coeffs[n] = F::ONE;
+    let mut m_k = vec![vec![F::ZERO; n]; n];
+    for k in 1..=n {
+        // M_k = M M_{k-1} + c_{n-k+1} I
+        m_k = matrix_mul(m, &m_k);
+        for (i, row) in m_k.iter_mut().enumerate() {
+            row[i] += coeffs[n - k + 1];
+        }
+        // c_{n-k} = -tr(M M_k) / k
+        let trace = (0..n).fold(F::ZERO, |acc, i| {
+            acc + (0..n).fold(F::ZERO, |acc, j| acc + m[i][j] * m_k[j][i])
+        });
+        coeffs[n - k] = -trace * F::from(k as u64).invert().unwrap();
+    }
+    coeffs
+}
+
+/// Rabin's irreducibility test for the monic polynomial `f` over the prime field.
+fn is_irreducible<F: PrimeFieldBits>(f: &[F]) -> bool {
+    let n = f.len() - 1;
+    let x = vec![F::ZERO, F::ONE];
+
+    // x^(p^k) mod f for k = 1..=n. The first is computed by exponentiation, and the
+    // others by substituting x^p into the previous one, since g(x)^p = g(x^p).
+    let mut x_p = vec![F::ONE];
+    for bit in F::char_le_bits().iter().by_vals().rev() {
+        x_p = poly_mul_mod(&x_p, &x_p, f);
+        if bit {
+            x_p = poly_mul_mod(&x_p, &x, f);
+        }
+    }
+    let mut frobenius = vec![x_p.clone()];
+    for _ in 1..n {
+        let prev = frobenius.last().unwrap();
+        let next = prev.iter().rev().fold(vec![], |acc, coeff| {
+            let mut acc = poly_mul_mod(&acc, &x_p, f);
+            if acc.is_empty() {
+                acc.push(F::ZERO);
+            }
+            acc[0] += coeff;
+            trim(acc)
+        });
+        frobenius.push(next);
+    }
+
+    let minus_x = |g: &[F]| {
+        let mut g = g.to_vec();
+        g.resize(std::cmp::max(g.len(), 2), F::ZERO);
+        g[1] -= F::ONE;
+        trim(g)
     };
-    let neg_ys: Vec<_> = ys.iter().map(|y| -*y).collect();
-    for i in 0..T {
-        for j in 0..T {
-            mds_inv[i][j] = (xs[j] - neg_ys[i]) * l(&xs, j, neg_ys[i]) * l(&neg_ys, i, xs[j]);
+
+    // f divides x^(p^n) - x, and shares no factor with x^(p^(n/q)) - x for any prime
+    // q dividing n.
+    minus_x(&frobenius[n - 1]).is_empty()
+        && (2..=n)
+            .filter(|q| n % q == 0 && (2..*q).all(|r| q % r != 0))
+            .all(|q| poly_gcd(f.to_vec(), minus_x(&frobenius[n / q - 1])).len() == 1)
+}
+
+/// Removes the zero leading coefficients of `a`.
+fn trim<F: Field>(mut a: Vec<F>) -> Vec<F> {
+    while a.last().map_or(false, |c| bool::from(c.is_zero())) {
+        a.pop();
+    }
+    a
+}
+
+/// Returns `a` modulo the nonzero polynomial `f`.
+fn poly_mod<F: Field>(mut a: Vec<F>, f: &[F]) -> Vec<F> {
+    let f = trim(f.to_vec());
+    let lead_inv = f.last().unwrap().invert().unwrap();
+    while a.len() >= f.len() {
+        let factor = *a.last().unwrap() * lead_inv;
+        let shift = a.len() - f.len();
+        for (i, coeff) in f.iter().enumerate() {
+            a[shift + i] -= factor * coeff;
         }
+        a.pop();
     }
+    trim(a)
+}
 
-    (mds, mds_inv)
+fn poly_mul_mod<F: Field>(a: &[F], b: &[F], f: &[F]) -> Vec<F> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut product = vec![F::ZERO; a.len() + b.len() - 1];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            product[i + j] += *a * b;
+        }
+    }
+    poly_mod(product, f)
+}
+
+/// Returns a greatest common divisor of `a` and `b`, which is a nonzero constant if
+/// they are coprime.
+fn poly_gcd<F: Field>(a: Vec<F>, b: Vec<F>) -> Vec<F> {
+    let (mut a, mut b) = (trim(a), trim(b));
+    while !b.is_empty() {
+        let r = poly_mod(a, &b);
+        a = b;
+        b = r;
+    }
+    a
 }
 
 #[cfg(test)]
 mod tests {
+    use ff::Field;
     use halo2curves::pasta::Fp;
 
-    use super::{generate_mds, Grain};
+    use super::{check_mds, generate_mds, Grain, MdsError};
+    use crate::poseidon::primitives::{fp, fq};
 
     #[test]
     fn poseidon_mds() {
@@ -121,4 +382,23 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn mds_security_checks() {
+        // The standard matrices pass, as do freshly generated ones.
+        assert_eq!(check_mds(&fp::MDS), Ok(()));
+        assert_eq!(check_mds(&fq::MDS), Ok(()));
+        let mut grain = Grain::new(super::super::grain::SboxType::Pow, 3, 8, 56);
+        let (mds, _) = generate_mds::<Fp, 3>(&mut grain, 0);
+        assert_eq!(check_mds(&mds), Ok(()));
+
+        // A zero entry is a singular 1x1 submatrix.
+        let mut not_mds = fp::MDS;
+        not_mds[1][2] = Fp::ZERO;
+        assert_eq!(check_mds(&not_mds), Err(MdsError::NotMds));
+
+        // This Cauchy matrix is MDS, but its characteristic polynomial is reducible.
+        let cauchy = [1, 2, 3].map(|x| [4, 5, 6].map(|y| Fp::from(x + y).invert().unwrap()));
+        assert_eq!(check_mds(&cauchy), Err(MdsError::ReduciblePower(1)));
+    }
 }
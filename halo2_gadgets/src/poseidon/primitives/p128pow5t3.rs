@@ -25,8 +25,9 @@ impl Spec<Fp, 3, 2> for P128Pow5T3 {
         val.pow_vartime([5])
     }
 
+    // The hard-coded constants are the ones generated with the first MDS matrix.
     fn secure_mds() -> usize {
-        unimplemented!()
+        0
     }
 
     fn constants() -> (Vec<[Fp; 3]>, Mds<Fp, 3>, Mds<Fp, 3>) {
@@ -51,8 +52,9 @@ impl Spec<Fq, 3, 2> for P128Pow5T3 {
         val.pow_vartime([5])
     }
 
+    // The hard-coded constants are the ones generated with the first MDS matrix.
     fn secure_mds() -> usize {
-        unimplemented!()
+        0
     }
 
     fn constants() -> (Vec<[Fq; 3]>, Mds<Fq, 3>, Mds<Fq, 3>) {
@@ -0,0 +1,367 @@
+//! Round-number security checks for Poseidon specifications.
+//!
+//! The formulas follow Section 5.5 and Appendix C of the
+//! [Poseidon paper](https://eprint.iacr.org/2019/458), together with the additional
+//! Gröbner basis bound from [eprint 2023/537](https://eprint.iacr.org/2023/537), as
+//! implemented by the reference `calc_round_numbers.py` script.
+
+use std::fmt;
+
+use ff::{Field, PrimeFieldBits};
+
+use super::Spec;
+
+/// The S-box exponents that [`validate_spec`] recognises.
+const SBOX_EXPONENTS: [u64; 8] = [3, 5, 7, 11, 13, 17, 19, 23];
+
+/// The number of full rounds the paper recommends adding as a security margin.
+const FULL_ROUNDS_MARGIN: usize = 2;
+
+/// The factor by which the paper recommends increasing the partial rounds as a
+/// security margin.
+const PARTIAL_ROUNDS_MARGIN: f64 = 1.075;
+
+/// An attack considered when choosing the number of rounds of a Poseidon permutation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attack {
+    /// Statistical (differential and linear) attacks.
+    Statistical,
+    /// Interpolation attacks.
+    Interpolation,
+    /// Gröbner basis attacks on the whole permutation.
+    Groebner1,
+    /// Gröbner basis attacks that skip the first full rounds.
+    Groebner2,
+    /// Gröbner basis attacks that additionally skip the last full rounds.
+    Groebner3,
+    /// The binomial Gröbner basis attack of eprint 2023/537.
+    Binomial,
+}
+
+/// The margin by which a specification resists a single [`Attack`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttackMargin {
+    /// The attack.
+    pub attack: Attack,
+    /// The number of full rounds beyond those required to resist the attack, or for
+    /// [`Attack::Binomial`], the number of bits by which the attack cost exceeds the
+    /// security level. A negative margin means the specification is insecure.
+    pub margin: i64,
+}
+
+/// The result of [`validate_spec`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecurityReport {
+    /// The exponent of the $x^\alpha$ S-box.
+    pub alpha: u64,
+    /// The targeted security level in bits.
+    pub security_bits: u32,
+    /// The number of full rounds of the specification.
+    pub full_rounds: usize,
+    /// The number of partial rounds of the specification.
+    pub partial_rounds: usize,
+    /// The number of full rounds left once the recommended security margin is removed.
+    pub effective_full_rounds: usize,
+    /// The number of partial rounds left once the recommended security margin is
+    /// removed.
+    pub effective_partial_rounds: usize,
+    /// The margin against each attack, computed from the effective round numbers.
+    pub attacks: Vec<AttackMargin>,
+}
+
+impl SecurityReport {
+    /// Returns `true` if the specification resists every attack with the
+    /// recommended security margin.
+    pub fn is_secure(&self) -> bool {
+        self.attacks.iter().all(|attack| attack.margin >= 0)
+    }
+
+    /// Returns the attacks that the specification does not resist.
+    pub fn failures(&self) -> impl Iterator<Item = &AttackMargin> {
+        self.attacks.iter().filter(|attack| attack.margin < 0)
+    }
+}
+
+/// An error returned by [`validate_spec`] for specifications it cannot analyse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecError {
+    /// The S-box is not $x^\alpha$ for a supported exponent $\alpha$.
+    UnsupportedSbox,
+    /// The S-box $x^\alpha$ is not a permutation of the field, because $\alpha$ is
+    /// not coprime to $p - 1$.
+    SboxNotInvertible(u64),
+    /// The number of full rounds is odd.
+    OddFullRounds(usize),
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::UnsupportedSbox => write!(f, "S-box is not x^alpha for a supported alpha"),
+            SpecError::SboxNotInvertible(alpha) => {
+                write!(f, "x^{} is not a permutation of the field", alpha)
+            }
+            SpecError::OddFullRounds(r_f) => {
+                write!(f, "number of full rounds must be even, got {}", r_f)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// Checks that the round numbers of `S` provide `security_bits` bits of security,
+/// with the security margin recommended by the Poseidon paper ($+2$ full rounds and
+/// $+7.5\%$ partial rounds).
+///
+/// The S-box is detected by evaluating [`Spec::sbox`], and must be $x^\alpha$ for a
+/// small exponent $\alpha$.
+///
+/// This only checks the number of rounds: the MDS matrix and round constants are
+/// assumed to be generated as in [`generate_constants`](super::generate_constants).
+pub fn validate_spec<F, S, const T: usize, const RATE: usize>(
+    security_bits: u32,
+) -> Result<SecurityReport, SpecError>
+where
+    F: PrimeFieldBits,
+    S: Spec<F, T, RATE>,
+{
+    let alpha = sbox_exponent::<F, S, T, RATE>()?;
+
+    let full_rounds = S::full_rounds();
+    let partial_rounds = S::partial_rounds();
+    if full_rounds % 2 != 0 {
+        return Err(SpecError::OddFullRounds(full_rounds));
+    }
+
+    // Remove the security margin, keeping the largest number of partial rounds from
+    // which the specification's partial rounds could have been derived.
+    let effective_full_rounds = full_rounds.saturating_sub(FULL_ROUNDS_MARGIN);
+    let effective_partial_rounds = (0..=partial_rounds)
+        .rev()
+        .find(|r_p| (*r_p as f64 * PARTIAL_ROUNDS_MARGIN).ceil() as usize <= partial_rounds)
+        .unwrap_or(0);
+
+    let params = RoundParams {
+        log2_p: log2_modulus::<F>(),
+        field_bits: F::NUM_BITS as f64,
+        t: T as f64,
+        alpha: alpha as f64,
+        m: security_bits as f64,
+    };
+    let r_f = effective_full_rounds as f64;
+    let r_p = effective_partial_rounds as f64;
+
+    let rounds_margin = |attack, required: f64| AttackMargin {
+        attack,
+        margin: effective_full_rounds as i64 - required.ceil() as i64,
+    };
+    let attacks = vec![
+        rounds_margin(Attack::Statistical, params.statistical()),
+        rounds_margin(Attack::Interpolation, params.interpolation(r_p)),
+        rounds_margin(Attack::Groebner1, params.groebner_1(r_p)),
+        rounds_margin(Attack::Groebner2, params.groebner_2(r_p)),
+        rounds_margin(Attack::Groebner3, params.groebner_3(r_p)),
+        AttackMargin {
+            attack: Attack::Binomial,
+            margin: params.binomial_cost(r_f, r_p) as i64 - security_bits as i64,
+        },
+    ];
+
+    Ok(SecurityReport {
+        alpha,
+        security_bits,
+        full_rounds,
+        partial_rounds,
+        effective_full_rounds,
+        effective_partial_rounds,
+        attacks,
+    })
+}
+
+/// Finds the exponent $\alpha$ such that `S::sbox` is $x^\alpha$.
+fn sbox_exponent<F: PrimeFieldBits, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>(
+) -> Result<u64, SpecError> {
+    let x = F::from(2);
+    let alpha = SBOX_EXPONENTS
+        .into_iter()
+        .find(|alpha| S::sbox(x) == x.pow_vartime([*alpha]))
+        .ok_or(SpecError::UnsupportedSbox)?;
+
+    // x^alpha is a permutation iff no non-trivial alpha-th root of unity exists. As
+    // alpha is prime, it suffices to check that (p - 1) is not a multiple of alpha.
+    let p_mod_alpha = F::char_le_bits()
+        .iter()
+        .by_vals()
+        .rev()
+        .fold(0, |acc, bit| (2 * acc + bit as u64) % alpha);
+    if p_mod_alpha == 1 {
+        return Err(SpecError::SboxNotInvertible(alpha));
+    }
+
+    Ok(alpha)
+}
+
+/// Approximates $\log_2(p)$ from the most significant bits of the characteristic.
+fn log2_modulus<F: PrimeFieldBits>() -> f64 {
+    let bits: Vec<bool> = F::char_le_bits().iter().by_vals().collect();
+    let top = bits.iter().rposition(|bit| *bit).expect("p > 0");
+    let shift = top.saturating_sub(52);
+    let mantissa = bits[shift..=top]
+        .iter()
+        .rev()
+        .fold(0u64, |acc, bit| (acc << 1) | *bit as u64);
+    (mantissa as f64).log2() + shift as f64
+}
+
+/// The parameters of the round-number formulas.
+struct RoundParams {
+    log2_p: f64,
+    field_bits: f64,
+    t: f64,
+    alpha: f64,
+    m: f64,
+}
+
+impl RoundParams {
+    /// $\log_\alpha(x)$.
+    fn log_alpha(&self, x: f64) -> f64 {
+        x.ln() / self.alpha.ln()
+    }
+
+    /// The full rounds required to resist statistical attacks.
+    fn statistical(&self) -> f64 {
+        if self.m <= (self.log2_p - (self.alpha - 1.0) / 2.0).floor() * (self.t + 1.0) {
+            6.0
+        } else {
+            10.0
+        }
+    }
+
+    /// The full rounds required to resist interpolation attacks.
+    fn interpolation(&self, r_p: f64) -> f64 {
+        1.0 + (self.log_alpha(2.0) * self.m.min(self.field_bits)).ceil()
+            + self.log_alpha(self.t).ceil()
+            - r_p
+    }
+
+    /// The full rounds required to resist the first Gröbner basis attack.
+    fn groebner_1(&self, r_p: f64) -> f64 {
+        self.log_alpha(2.0) * self.m.min(self.log2_p) - r_p
+    }
+
+    /// The full rounds required to resist the second Gröbner basis attack.
+    fn groebner_2(&self, r_p: f64) -> f64 {
+        self.t - 1.0 + self.log_alpha(2.0) * (self.m / (self.t + 1.0)).min(self.log2_p / 2.0) - r_p
+    }
+
+    /// The full rounds required to resist the third Gröbner basis attack.
+    fn groebner_3(&self, r_p: f64) -> f64 {
+        (self.t - 2.0 + self.m / (2.0 * self.alpha.log2()) - r_p) / (self.t - 1.0)
+    }
+
+    /// The cost in bits of the binomial Gröbner basis attack.
+    fn binomial_cost(&self, r_f: f64, r_p: f64) -> f64 {
+        let r_temp = (self.t / 3.0).floor();
+        let over = (r_f - 1.0) * self.t + r_p + r_temp + r_temp * (r_f / 2.0) + r_p + self.alpha;
+        let under = r_temp * (r_f / 2.0) + r_p + self.alpha;
+        // Like the reference script, use 2 rather than the linear algebra constant
+        // 2.3727 to be conservative.
+        (2.0 * log2_binomial(over as u64, under as u64)).ceil()
+    }
+}
+
+/// $\log_2 \binom{n}{k}$.
+fn log2_binomial(n: u64, k: u64) -> f64 {
+    let k = k.min(n - k);
+    (1..=k)
+        .map(|i| ((n - k + i) as f64).log2() - (i as f64).log2())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2curves::pasta::pallas::Base as Fp;
+
+    use super::{validate_spec, Attack, SpecError};
+    use crate::poseidon::primitives::{Mds, P128Pow5T3, Spec};
+
+    /// A spec with configurable round numbers and S-box exponent.
+    #[derive(Debug)]
+    struct CustomSpec<const R_F: usize, const R_P: usize, const ALPHA: u64>;
+
+    impl<const R_F: usize, const R_P: usize, const ALPHA: u64> Spec<Fp, 3, 2>
+        for CustomSpec<R_F, R_P, ALPHA>
+    {
+        fn full_rounds() -> usize {
+            R_F
+        }
+
+        fn partial_rounds() -> usize {
+            R_P
+        }
+
+        fn sbox(val: Fp) -> Fp {
+            val.pow_vartime([ALPHA])
+        }
+
+        fn secure_mds() -> usize {
+            unimplemented!()
+        }
+
+        fn constants() -> (Vec<[Fp; 3]>, Mds<Fp, 3>, Mds<Fp, 3>) {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn p128pow5t3_is_secure() {
+        let report = validate_spec::<Fp, P128Pow5T3, 3, 2>(128).unwrap();
+        assert_eq!(report.alpha, 5);
+        assert_eq!(report.effective_full_rounds, 6);
+        assert_eq!(report.effective_partial_rounds, 52);
+        assert!(report.is_secure(), "{:?}", report);
+
+        // The standard parameters have no slack in the interpolation bound.
+        let interpolation = report
+            .attacks
+            .iter()
+            .find(|attack| attack.attack == Attack::Interpolation)
+            .unwrap();
+        assert_eq!(interpolation.margin, 0);
+    }
+
+    #[test]
+    fn too_few_rounds() {
+        // Secure without the security margin, but not with it.
+        let report = validate_spec::<Fp, CustomSpec<6, 56, 5>, 3, 2>(128).unwrap();
+        assert!(!report.is_secure());
+        assert!(report
+            .failures()
+            .any(|attack| attack.attack == Attack::Statistical));
+
+        let report = validate_spec::<Fp, CustomSpec<8, 40, 5>, 3, 2>(128).unwrap();
+        assert!(!report.is_secure());
+        assert!(report
+            .failures()
+            .any(|attack| attack.attack == Attack::Interpolation));
+    }
+
+    #[test]
+    fn invalid_specs() {
+        // x^3 is not a permutation of the Pallas base field.
+        assert_eq!(
+            validate_spec::<Fp, CustomSpec<8, 56, 3>, 3, 2>(128),
+            Err(SpecError::SboxNotInvertible(3))
+        );
+        assert_eq!(
+            validate_spec::<Fp, CustomSpec<8, 56, 4>, 3, 2>(128),
+            Err(SpecError::UnsupportedSbox)
+        );
+        assert_eq!(
+            validate_spec::<Fp, CustomSpec<7, 56, 5>, 3, 2>(128),
+            Err(SpecError::OddFullRounds(7))
+        );
+    }
+}
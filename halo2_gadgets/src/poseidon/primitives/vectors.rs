@@ -0,0 +1,456 @@
+//! Test vectors for Poseidon, in a JSON format that other implementations can consume.
+//!
+//! A file of test vectors covers one specification, field and domain:
+//!
+//! ```json
+//! {
+//!   "primitive": "poseidon",
+//!   "spec": "P128Pow5T3",
+//!   "field": "pallas::Base",
+//!   "width": 3,
+//!   "rate": 2,
+//!   "full_rounds": 8,
+//!   "partial_rounds": 56,
+//!   "params_digest": "<64 hex digits>",
+//!   "domain": "ConstantLength<2>",
+//!   "permute": [{ "input": ["<hex>", "<hex>", "<hex>"], "output": ["<hex>", "<hex>", "<hex>"] }],
+//!   "hash": [{ "input": ["<hex>", "<hex>"], "output": "<hex>" }]
+//! }
+//! ```
+//!
+//! Field elements are the hex encodings of their canonical byte representations, as
+//! returned by [`PrimeField::to_repr`]; for the Pasta fields these are little-endian.
+//!
+//! `params_digest` is the hex encoding of the 32-byte BLAKE2b hash, personalized with
+//! `Poseidon_Params_`, of the following, in order:
+//! - `width`, `rate`, `full_rounds` and `partial_rounds`, each as 8 little-endian bytes;
+//! - the round constants, round by round;
+//! - the MDS matrix, row by row.
+//!
+//! It lets an implementation check that it uses the same parameters as the vectors
+//! before checking any outputs.
+//!
+//! The vectors for [`P128Pow5T3`] over the Pasta base fields, generated from this
+//! crate's test vectors, are in the crate's `test-vectors` directory.
+//!
+//! `spec` and `field` name the specification and field for readers, and are not
+//! checked. Permutation vectors apply the bare permutation to a full state. Hash vectors
+//! absorb the input followed by the padding of `domain`, starting from the capacity
+//! element of `domain`, and squeeze a single element.
+//!
+//! [`P128Pow5T3`]: super::P128Pow5T3
+
+use std::fmt;
+
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+use super::{permute, Absorbing, Domain, Spec, Sponge};
+
+/// A set of Poseidon test vectors for one specification, field and domain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVectors {
+    /// The primitive the vectors are for, which is always `poseidon`.
+    pub primitive: String,
+    /// The name of the specification.
+    pub spec: String,
+    /// The name of the field.
+    pub field: String,
+    /// The width of the permutation.
+    pub width: usize,
+    /// The rate of the sponge.
+    pub rate: usize,
+    /// The number of full rounds.
+    pub full_rounds: usize,
+    /// The number of partial rounds.
+    pub partial_rounds: usize,
+    /// The digest of the round constants and MDS matrix.
+    pub params_digest: String,
+    /// The name of the domain used by the hash vectors.
+    pub domain: String,
+    /// Vectors for the permutation.
+    pub permute: Vec<PermuteVector>,
+    /// Vectors for the hash function.
+    pub hash: Vec<HashVector>,
+}
+
+/// A test vector for the Poseidon permutation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermuteVector {
+    /// The initial state.
+    pub input: Vec<String>,
+    /// The state after the permutation.
+    pub output: Vec<String>,
+}
+
+/// A test vector for the Poseidon hash function.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashVector {
+    /// The message.
+    pub input: Vec<String>,
+    /// The digest.
+    pub output: String,
+}
+
+/// An error that occurred while loading or checking test vectors.
+#[derive(Debug)]
+pub enum VectorError {
+    /// The test vectors are not valid JSON for the format.
+    Json(serde_json::Error),
+    /// A field element is not the hex encoding of a canonical representation.
+    InvalidElement(String),
+    /// The vectors are for different parameters or a different domain.
+    Params(&'static str),
+    /// The permutation vector with this index does not match.
+    Permute(usize),
+    /// The hash vector with this index does not match.
+    Hash(usize),
+}
+
+impl fmt::Display for VectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VectorError::Json(e) => write!(f, "invalid test vectors: {}", e),
+            VectorError::InvalidElement(s) => write!(f, "invalid field element: {}", s),
+            VectorError::Params(field) => write!(f, "test vectors have a different {}", field),
+            VectorError::Permute(i) => write!(f, "permutation vector {} does not match", i),
+            VectorError::Hash(i) => write!(f, "hash vector {} does not match", i),
+        }
+    }
+}
+
+impl std::error::Error for VectorError {}
+
+impl From<serde_json::Error> for VectorError {
+    fn from(e: serde_json::Error) -> Self {
+        VectorError::Json(e)
+    }
+}
+
+impl TestVectors {
+    /// Generates test vectors for the specification `S` over `F`, permuting each of
+    /// `permute_inputs` and hashing each of `hash_inputs` in the domain `D`.
+    ///
+    /// `spec` and `field` are the names recorded in the vectors.
+    pub fn generate<
+        F: PrimeField,
+        S: Spec<F, T, RATE>,
+        D: Domain<F, RATE>,
+        const T: usize,
+        const RATE: usize,
+    >(
+        spec: &str,
+        field: &str,
+        permute_inputs: &[[F; T]],
+        hash_inputs: &[Vec<F>],
+    ) -> Self {
+        let (round_constants, mds, _) = S::constants();
+
+        TestVectors {
+            primitive: "poseidon".to_string(),
+            spec: spec.to_string(),
+            field: field.to_string(),
+            width: T,
+            rate: RATE,
+            full_rounds: S::full_rounds(),
+            partial_rounds: S::partial_rounds(),
+            params_digest: params_digest::<F, S, T, RATE>(),
+            domain: D::name(),
+            permute: permute_inputs
+                .iter()
+                .map(|input| {
+                    let mut state = *input;
+                    permute::<F, S, T, RATE>(&mut state, &mds, &round_constants);
+                    PermuteVector {
+                        input: input.iter().map(encode).collect(),
+                        output: state.iter().map(encode).collect(),
+                    }
+                })
+                .collect(),
+            hash: hash_inputs
+                .iter()
+                .map(|input| HashVector {
+                    input: input.iter().map(encode).collect(),
+                    output: encode(&hash::<F, S, D, T, RATE>(input)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Serializes the test vectors as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("test vectors can be serialized")
+    }
+
+    /// Parses test vectors from JSON.
+    pub fn from_json(json: &str) -> Result<Self, VectorError> {
+        let vectors: Self = serde_json::from_str(json)?;
+        if vectors.primitive != "poseidon" {
+            return Err(VectorError::Params("primitive"));
+        }
+        Ok(vectors)
+    }
+
+    /// Checks the test vectors against the specification `S` over `F`, hashing in the
+    /// domain `D`.
+    pub fn check<
+        F: PrimeField,
+        S: Spec<F, T, RATE>,
+        D: Domain<F, RATE>,
+        const T: usize,
+        const RATE: usize,
+    >(
+        &self,
+    ) -> Result<(), VectorError> {
+        if self.width != T {
+            return Err(VectorError::Params("width"));
+        }
+        if self.rate != RATE {
+            return Err(VectorError::Params("rate"));
+        }
+        if self.full_rounds != S::full_rounds() || self.partial_rounds != S::partial_rounds() {
+            return Err(VectorError::Params("number of rounds"));
+        }
+        if self.params_digest != params_digest::<F, S, T, RATE>() {
+            return Err(VectorError::Params("params_digest"));
+        }
+        if self.domain != D::name() {
+            return Err(VectorError::Params("domain"));
+        }
+
+        let (round_constants, mds, _) = S::constants();
+        for (i, vector) in self.permute.iter().enumerate() {
+            let mut state: [F; T] = decode_all(&vector.input)?
+                .try_into()
+                .map_err(|_| VectorError::Permute(i))?;
+            permute::<F, S, T, RATE>(&mut state, &mds, &round_constants);
+            if decode_all::<F>(&vector.output)? != state {
+                return Err(VectorError::Permute(i));
+            }
+        }
+
+        for (i, vector) in self.hash.iter().enumerate() {
+            let input = decode_all::<F>(&vector.input)?;
+            if decode::<F>(&vector.output)? != hash::<F, S, D, T, RATE>(&input) {
+                return Err(VectorError::Hash(i));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn hash<
+    F: PrimeField,
+    S: Spec<F, T, RATE>,
+    D: Domain<F, RATE>,
+    const T: usize,
+    const RATE: usize,
+>(
+    message: &[F],
+) -> F {
+    let mut sponge =
+        Sponge::<F, S, Absorbing<F, RATE>, T, RATE>::new(D::initial_capacity_element());
+    for value in message.iter().copied().chain(D::padding(message.len())) {
+        sponge.absorb(value);
+    }
+    sponge.finish_absorbing().squeeze()
+}
+
+fn params_digest<F: PrimeField, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>() -> String
+{
+    let (round_constants, mds, _) = S::constants();
+
+    let mut hasher = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"Poseidon_Params_")
+        .to_state();
+    for value in [T, RATE, S::full_rounds(), S::partial_rounds()] {
+        hasher.update(&(value as u64).to_le_bytes());
+    }
+    for value in round_constants.iter().chain(mds.iter()).flatten() {
+        hasher.update(value.to_repr().as_ref());
+    }
+    hex(hasher.finalize().as_bytes())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn encode<F: PrimeField>(value: &F) -> String {
+    hex(value.to_repr().as_ref())
+}
+
+fn decode<F: PrimeField>(s: &str) -> Result<F, VectorError> {
+    let invalid = || VectorError::InvalidElement(s.to_string());
+
+    let mut repr = F::Repr::default();
+    if !s.is_ascii() || s.len() != 2 * repr.as_ref().len() {
+        return Err(invalid());
+    }
+    for (byte, digits) in repr.as_mut().iter_mut().zip(s.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(digits, 16).map_err(|_| invalid())?;
+    }
+    Option::from(F::from_repr(repr)).ok_or_else(invalid)
+}
+
+fn decode_all<F: PrimeField>(values: &[String]) -> Result<Vec<F>, VectorError> {
+    values.iter().map(|s| decode(s)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::PrimeField;
+    use halo2curves::pasta::{Fp, Fq};
+
+    use super::{TestVectors, VectorError};
+    use crate::poseidon::primitives::{
+        test_vectors::{self, HashTestVector, PermuteTestVector},
+        BoundedLength, ConstantLength, P128Pow5T3,
+    };
+
+    fn generate<F: PrimeField<Repr = [u8; 32]>>(
+        field: &str,
+        permute: &[PermuteTestVector],
+        hash: &[HashTestVector],
+    ) -> TestVectors {
+        let permute_inputs = permute
+            .iter()
+            .map(|tv| tv.initial_state.map(|x| F::from_repr(x).unwrap()))
+            .collect::<Vec<_>>();
+        let hash_inputs = hash
+            .iter()
+            .map(|tv| tv.input.iter().map(|x| F::from_repr(*x).unwrap()).collect())
+            .collect::<Vec<_>>();
+        let vectors = TestVectors::generate::<F, P128Pow5T3, ConstantLength<2>, 3, 2>(
+            "P128Pow5T3",
+            field,
+            &permute_inputs,
+            &hash_inputs,
+        );
+
+        // The generated vectors agree with the reference ones.
+        for (vector, tv) in vectors.permute.iter().zip(permute) {
+            let expected = tv.final_state.map(|x| super::hex(&x));
+            assert_eq!(vector.output, expected);
+        }
+        for (vector, tv) in vectors.hash.iter().zip(hash) {
+            assert_eq!(vector.output, super::hex(&tv.output));
+        }
+
+        vectors
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let vectors = [
+            generate::<Fp>(
+                "pallas::Base",
+                &test_vectors::fp::permute(),
+                &test_vectors::fp::hash(),
+            ),
+            generate::<Fq>(
+                "vesta::Base",
+                &test_vectors::fq::permute(),
+                &test_vectors::fq::hash(),
+            ),
+        ];
+
+        for vectors in vectors.iter() {
+            let loaded = TestVectors::from_json(&vectors.to_json()).unwrap();
+            assert_eq!(&loaded, vectors);
+        }
+
+        assert!(vectors[0]
+            .check::<Fp, P128Pow5T3, ConstantLength<2>, 3, 2>()
+            .is_ok());
+        assert!(vectors[1]
+            .check::<Fq, P128Pow5T3, ConstantLength<2>, 3, 2>()
+            .is_ok());
+
+        // The parameters of the two fields differ.
+        assert_ne!(vectors[0].params_digest, vectors[1].params_digest);
+        assert!(matches!(
+            vectors[0].check::<Fq, P128Pow5T3, ConstantLength<2>, 3, 2>(),
+            Err(VectorError::Params("params_digest"))
+        ));
+        assert!(matches!(
+            vectors[0].check::<Fp, P128Pow5T3, BoundedLength<2>, 3, 2>(),
+            Err(VectorError::Params("domain"))
+        ));
+    }
+
+    #[test]
+    fn committed_vectors() {
+        let pallas = TestVectors::from_json(include_str!(
+            "../../../test-vectors/poseidon_p128pow5t3_pallas.json"
+        ))
+        .unwrap();
+        let vesta = TestVectors::from_json(include_str!(
+            "../../../test-vectors/poseidon_p128pow5t3_vesta.json"
+        ))
+        .unwrap();
+
+        assert!(pallas
+            .check::<Fp, P128Pow5T3, ConstantLength<2>, 3, 2>()
+            .is_ok());
+        assert!(vesta
+            .check::<Fq, P128Pow5T3, ConstantLength<2>, 3, 2>()
+            .is_ok());
+
+        // The committed files are the ones this crate generates.
+        assert_eq!(
+            pallas,
+            generate::<Fp>(
+                "pallas::Base",
+                &test_vectors::fp::permute(),
+                &test_vectors::fp::hash(),
+            )
+        );
+        assert_eq!(
+            vesta,
+            generate::<Fq>(
+                "vesta::Base",
+                &test_vectors::fq::permute(),
+                &test_vectors::fq::hash(),
+            )
+        );
+    }
+
+    #[test]
+    fn check_mismatches() {
+        let vectors = generate::<Fp>(
+            "pallas::Base",
+            &test_vectors::fp::permute(),
+            &test_vectors::fp::hash(),
+        );
+
+        let mut tampered = vectors.clone();
+        tampered.hash[1].output = tampered.hash[0].output.clone();
+        assert!(matches!(
+            tampered.check::<Fp, P128Pow5T3, ConstantLength<2>, 3, 2>(),
+            Err(VectorError::Hash(1))
+        ));
+
+        let mut tampered = vectors.clone();
+        tampered.permute[0].output.swap(0, 1);
+        assert!(matches!(
+            tampered.check::<Fp, P128Pow5T3, ConstantLength<2>, 3, 2>(),
+            Err(VectorError::Permute(0))
+        ));
+
+        let mut tampered = vectors;
+        tampered.hash[0].input[0] = "zz".to_string();
+        assert!(matches!(
+            tampered.check::<Fp, P128Pow5T3, ConstantLength<2>, 3, 2>(),
+            Err(VectorError::InvalidElement(_))
+        ));
+
+        assert!(matches!(
+            TestVectors::from_json("{}"),
+            Err(VectorError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,280 @@
+//! A Fiat-Shamir transcript built from the Poseidon sponge.
+//!
+//! Points are absorbed by their affine coordinates in the base field of the curve
+//! (see [`crate::utilities::point`] for the encoding of the identity),
+//! and challenges are squeezed as base field elements and encoded as
+//! [`Challenge128`]s. Every operation is therefore native to the base field, which
+//! is the scalar field of the other curve in a 2-cycle such as Pallas/Vesta, so the
+//! transcript can be checked cheaply by a recursive verifier circuit on that curve.
+
+use std::io::{self, Read, Write};
+
+use ff::{PrimeField, WithSmallOrderMulGroup};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    transcript::{
+        Challenge128, EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer,
+        TranscriptWrite, TranscriptWriterBuffer,
+    },
+};
+
+use super::primitives::{Absorbing, Spec, Sponge};
+use crate::utilities::point::to_coordinates;
+
+/// The width of the sponge used by the transcript.
+const WIDTH: usize = 3;
+
+/// The rate of the sponge used by the transcript.
+const RATE: usize = 2;
+
+/// The sponge underlying the transcripts, shared between the prover and verifier.
+struct TranscriptSponge<C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> {
+    sponge: Sponge<C::Base, S, Absorbing<C::Base, RATE>, WIDTH, RATE>,
+}
+
+impl<C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> TranscriptSponge<C, S>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    fn new() -> Self {
+        // Domain-separate the transcript from other uses of the sponge.
+        let personal = u128::from_le_bytes(*b"Halo2-Transcript");
+        TranscriptSponge {
+            sponge: Sponge::new(C::Base::from_u128(personal)),
+        }
+    }
+
+    fn squeeze_challenge(&mut self) -> Challenge128<C> {
+        let challenge = self.sponge.clone().finish_absorbing().squeeze();
+        // Bind later challenges to this one.
+        self.sponge.absorb(challenge);
+        Challenge128::new(&challenge)
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        let (x, y) = to_coordinates(point);
+        self.sponge.absorb(x);
+        self.sponge.absorb(y);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        // The scalar field may be larger than the base field, so the scalar is
+        // absorbed as two 128-bit limbs.
+        let repr = scalar.to_repr();
+        for limb in repr.as_ref().chunks(16) {
+            let mut bytes = [0u8; 16];
+            bytes[..limb.len()].copy_from_slice(limb);
+            self.sponge
+                .absorb(C::Base::from_u128(u128::from_le_bytes(bytes)));
+        }
+        Ok(())
+    }
+}
+
+/// A transcript reader that hashes with the Poseidon specification `S`.
+pub struct PoseidonRead<R: Read, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> {
+    sponge: TranscriptSponge<C, S>,
+    reader: R,
+}
+
+impl<R: Read, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> std::fmt::Debug
+    for PoseidonRead<R, C, S>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PoseidonRead").finish_non_exhaustive()
+    }
+}
+
+impl<R: Read, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>>
+    TranscriptReadBuffer<R, C, Challenge128<C>> for PoseidonRead<R, C, S>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    fn init(reader: R) -> Self {
+        PoseidonRead {
+            sponge: TranscriptSponge::new(),
+            reader,
+        }
+    }
+}
+
+impl<R: Read, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> TranscriptRead<C, Challenge128<C>>
+    for PoseidonRead<R, C, S>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut data = <C::Scalar as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+
+        Ok(scalar)
+    }
+}
+
+impl<R: Read, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> Transcript<C, Challenge128<C>>
+    for PoseidonRead<R, C, S>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    fn squeeze_challenge(&mut self) -> Challenge128<C> {
+        self.sponge.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.sponge.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.sponge.common_scalar(scalar)
+    }
+}
+
+/// A transcript writer that hashes with the Poseidon specification `S`.
+pub struct PoseidonWrite<W: Write, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> {
+    sponge: TranscriptSponge<C, S>,
+    writer: W,
+}
+
+impl<W: Write, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> std::fmt::Debug
+    for PoseidonWrite<W, C, S>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PoseidonWrite").finish_non_exhaustive()
+    }
+}
+
+impl<W: Write, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>>
+    TranscriptWriterBuffer<W, C, Challenge128<C>> for PoseidonWrite<W, C, S>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    fn init(writer: W) -> Self {
+        PoseidonWrite {
+            sponge: TranscriptSponge::new(),
+            writer,
+        }
+    }
+
+    fn finalize(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> TranscriptWrite<C, Challenge128<C>>
+    for PoseidonWrite<W, C, S>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let compressed = point.to_bytes();
+        self.writer.write_all(compressed.as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        let data = scalar.to_repr();
+        self.writer.write_all(data.as_ref())
+    }
+}
+
+impl<W: Write, C: CurveAffine, S: Spec<C::Base, WIDTH, RATE>> Transcript<C, Challenge128<C>>
+    for PoseidonWrite<W, C, S>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    fn squeeze_challenge(&mut self) -> Challenge128<C> {
+        self.sponge.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.sponge.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.sponge.common_scalar(scalar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use group::{prime::PrimeCurveAffine, Curve, Group};
+    use halo2_proofs::{
+        arithmetic::CurveAffine,
+        transcript::{
+            EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+            TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::pasta::{pallas, vesta};
+    use rand::rngs::OsRng;
+
+    use super::{PoseidonRead, PoseidonWrite};
+    use crate::poseidon::primitives::{P128Pow5T3, Spec};
+
+    fn round_trip<C: CurveAffine>()
+    where
+        P128Pow5T3: Spec<C::Base, 3, 2>,
+    {
+        let points = [C::identity(), C::Curve::random(OsRng).to_affine()];
+        let scalars = [C::Scalar::ZERO, -C::Scalar::ONE, C::Scalar::random(OsRng)];
+
+        let mut writer = PoseidonWrite::<_, C, P128Pow5T3>::init(vec![]);
+        let mut challenges = vec![];
+        for point in points {
+            writer.write_point(point).unwrap();
+            challenges.push(writer.squeeze_challenge().get_scalar());
+        }
+        for scalar in scalars {
+            writer.write_scalar(scalar).unwrap();
+            challenges.push(writer.squeeze_challenge().get_scalar());
+        }
+        // Consecutive challenges differ.
+        challenges.push(writer.squeeze_challenge().get_scalar());
+        assert_ne!(
+            challenges[challenges.len() - 1],
+            challenges[challenges.len() - 2]
+        );
+        let proof = writer.finalize().unwrap();
+
+        let mut reader = PoseidonRead::<_, C, P128Pow5T3>::init(&proof[..]);
+        let mut read_challenges = vec![];
+        for point in points {
+            assert_eq!(reader.read_point().unwrap(), point);
+            read_challenges.push(reader.squeeze_challenge().get_scalar());
+        }
+        for scalar in scalars {
+            assert_eq!(reader.read_scalar().unwrap(), scalar);
+            read_challenges.push(reader.squeeze_challenge().get_scalar());
+        }
+        read_challenges.push(reader.squeeze_challenge().get_scalar());
+        assert_eq!(challenges, read_challenges);
+    }
+
+    #[test]
+    fn round_trip_over_cycle() {
+        round_trip::<pallas::Affine>();
+        round_trip::<vesta::Affine>();
+    }
+}
@@ -3,11 +3,17 @@
 use ff::{Field, PrimeField, PrimeFieldBits};
 use halo2_proofs::{
     circuit::{AssignedCell, Cell, Layouter, Value},
-    plonk::{Advice, Column, Error, Expression},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, TableColumn},
 };
 use std::marker::PhantomData;
 use std::ops::Range;
 
+pub mod cond_swap;
+pub mod decompose_bytes;
+pub mod decompose_running_sum;
+pub mod lookup_range_check;
+pub mod point;
+
 /// A type that has a value at either keygen or proving time.
 pub trait FieldValue<F: Field> {
     /// Returns the value of this type.
@@ -160,6 +166,18 @@ pub fn bitrange_subset<F: PrimeFieldBits>(field_elem: &F, bitrange: Range<usize>
         })
 }
 
+/// Returns the table column holding the values $[0, 2^{num\_bits})$ that the
+/// range-check chips of this crate share, and whether the caller is the chip that
+/// should load it.
+///
+/// See [`ConstraintSystem::shared_lookup_table_column`].
+pub fn range_table_column<F: Field>(
+    meta: &mut ConstraintSystem<F>,
+    num_bits: usize,
+) -> (TableColumn, bool) {
+    meta.shared_lookup_table_column(&format!("{}-bit range table", num_bits))
+}
+
 /// Check that an expression is in the small range [0..range),
 /// i.e. 0 ≤ word < range.
 pub fn range_check<F: PrimeField>(word: Expression<F>, range: usize) -> Expression<F> {
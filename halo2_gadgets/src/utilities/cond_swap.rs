@@ -0,0 +1,497 @@
+//! Gadget and chip for a conditional swap utility, with multiplexing and zero-testing
+//! helpers built on the same columns.
+
+use super::{bool_check, ternary, UtilitiesInstructions};
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// Instructions for a conditional swap gadget.
+pub trait CondSwapInstructions<F: Field>: UtilitiesInstructions<F> {
+    #[allow(clippy::type_complexity)]
+    /// Given an input pair (a,b) and a `swap` boolean flag, returns
+    /// (b,a) if `swap` is set, else (a,b) if `swap` is not set.
+    ///
+    /// The second element of the pair is required to be a witnessed
+    /// value, not a variable that already exists in the circuit.
+    fn swap(
+        &self,
+        layouter: impl Layouter<F>,
+        pair: (Self::Var, Value<F>),
+        swap: Value<bool>,
+    ) -> Result<(Self::Var, Self::Var), Error>;
+
+    /// Given a boolean `choice` and two variables `left` and `right`, returns
+    /// `right` if `choice` is set, else `left`.
+    ///
+    /// `choice` is constrained to be boolean.
+    fn mux(
+        &self,
+        layouter: impl Layouter<F>,
+        choice: &Self::Var,
+        left: &Self::Var,
+        right: &Self::Var,
+    ) -> Result<Self::Var, Error>;
+
+    /// Returns a boolean variable that is set if and only if `value` is zero.
+    fn is_zero(&self, layouter: impl Layouter<F>, value: &Self::Var) -> Result<Self::Var, Error>;
+}
+
+/// A chip implementing a conditional swap.
+#[derive(Clone, Debug)]
+pub struct CondSwapChip<F> {
+    config: CondSwapConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for CondSwapChip<F> {
+    type Config = CondSwapConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// Configuration for the [`CondSwapChip`].
+///
+/// Both gates use a single row:
+///
+/// | q_swap | a | b | a_swapped | b_swapped | swap   |
+/// |--------|---|---|-----------|-----------|--------|
+/// |   1    | a | b |   a'      |   b'      | bit    |
+///
+/// | q_is_zero | a     | b          | swap    |
+/// |-----------|-------|------------|---------|
+/// |     1     | value | value^{-1} | is_zero |
+///
+/// where `value^{-1}` is zero if `value` is zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CondSwapConfig {
+    q_swap: Selector,
+    q_is_zero: Selector,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    a_swapped: Column<Advice>,
+    b_swapped: Column<Advice>,
+    swap: Column<Advice>,
+}
+
+#[cfg(test)]
+impl CondSwapConfig {
+    pub(crate) fn a(&self) -> Column<Advice> {
+        self.a
+    }
+}
+
+impl<F: Field> UtilitiesInstructions<F> for CondSwapChip<F> {
+    type Var = AssignedCell<F, F>;
+}
+
+impl<F: PrimeField> CondSwapInstructions<F> for CondSwapChip<F> {
+    fn swap(
+        &self,
+        mut layouter: impl Layouter<F>,
+        pair: (Self::Var, Value<F>),
+        swap: Value<bool>,
+    ) -> Result<(Self::Var, Self::Var), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "swap",
+            |mut region| {
+                // Enable `q_swap` selector
+                config.q_swap.enable(&mut region, 0)?;
+
+                // Copy in `a` value
+                let a = pair.0.copy_advice(|| "copy a", &mut region, config.a, 0)?;
+
+                // Witness `b` value
+                let b = region.assign_advice(|| "witness b", config.b, 0, || pair.1)?;
+
+                // Witness `swap` value
+                let swap_val = swap.map(|swap| F::from(swap as u64));
+                region.assign_advice(|| "swap", config.swap, 0, || swap_val)?;
+
+                config.assign_swapped(&mut region, &a, &b, swap)
+            },
+        )
+    }
+
+    fn mux(
+        &self,
+        mut layouter: impl Layouter<F>,
+        choice: &Self::Var,
+        left: &Self::Var,
+        right: &Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "mux",
+            |mut region| {
+                // Enable `q_swap` selector
+                config.q_swap.enable(&mut region, 0)?;
+
+                // Copy in the inputs
+                let a = left.copy_advice(|| "copy left", &mut region, config.a, 0)?;
+                let b = right.copy_advice(|| "copy right", &mut region, config.b, 0)?;
+                let choice = choice.copy_advice(|| "copy choice", &mut region, config.swap, 0)?;
+
+                // The gate constrains `choice` to be boolean, so a non-boolean value
+                // only needs to produce some witness here.
+                let swap = choice.value().map(|choice| *choice == F::ONE);
+                let (out, _) = config.assign_swapped(&mut region, &a, &b, swap)?;
+                Ok(out)
+            },
+        )
+    }
+
+    fn is_zero(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "is_zero",
+            |mut region| {
+                // Enable `q_is_zero` selector
+                config.q_is_zero.enable(&mut region, 0)?;
+
+                // Copy in `value`
+                let value = value.copy_advice(|| "copy value", &mut region, config.a, 0)?;
+
+                // Witness the inverse of `value`, or zero if `value` is zero
+                let inv = value.value().map(|value| value.invert().unwrap_or(F::ZERO));
+                region.assign_advice(|| "value inverse", config.b, 0, || inv)?;
+
+                let is_zero = value
+                    .value()
+                    .map(|value| F::from(bool::from(value.is_zero()) as u64));
+                region.assign_advice(|| "is_zero", config.swap, 0, || is_zero)
+            },
+        )
+    }
+}
+
+impl CondSwapConfig {
+    /// Assigns the swapped outputs of the swap gate at offset 0 of `region`.
+    fn assign_swapped<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        swap: Value<bool>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let a_swapped = a
+            .value()
+            .zip(b.value())
+            .zip(swap)
+            .map(|((a, b), swap)| if swap { b } else { a })
+            .cloned();
+        let b_swapped = a
+            .value()
+            .zip(b.value())
+            .zip(swap)
+            .map(|((a, b), swap)| if swap { a } else { b })
+            .cloned();
+
+        let a_swapped = region.assign_advice(|| "a_swapped", self.a_swapped, 0, || a_swapped)?;
+        let b_swapped = region.assign_advice(|| "b_swapped", self.b_swapped, 0, || b_swapped)?;
+
+        Ok((a_swapped, b_swapped))
+    }
+}
+
+impl<F: PrimeField> CondSwapChip<F> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// # Side-effects
+    ///
+    /// All of `advices` will be equality-enabled, as the inputs of each helper are
+    /// copied in and its outputs may be copied elsewhere.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 5],
+    ) -> CondSwapConfig {
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+
+        let q_swap = meta.selector();
+        let q_is_zero = meta.selector();
+
+        let config = CondSwapConfig {
+            q_swap,
+            q_is_zero,
+            a: advices[0],
+            b: advices[1],
+            a_swapped: advices[2],
+            b_swapped: advices[3],
+            swap: advices[4],
+        };
+
+        meta.create_gate("a' = b ⋅ swap + a ⋅ (1-swap)", |meta| {
+            let q_swap = meta.query_selector(q_swap);
+
+            let a = meta.query_advice(config.a, Rotation::cur());
+            let b = meta.query_advice(config.b, Rotation::cur());
+            let a_swapped = meta.query_advice(config.a_swapped, Rotation::cur());
+            let b_swapped = meta.query_advice(config.b_swapped, Rotation::cur());
+            let swap = meta.query_advice(config.swap, Rotation::cur());
+
+            // This checks that `a_swapped` is equal to `b` when `swap` is set,
+            // but remains as `a` when `swap` is not set.
+            let a_check = a_swapped - ternary(swap.clone(), b.clone(), a.clone());
+
+            // This checks that `b_swapped` is equal to `a` when `swap` is set,
+            // but remains as `b` when `swap` is not set.
+            let b_check = b_swapped - ternary(swap.clone(), a, b);
+
+            // Check `swap` is boolean.
+            let bool_check = bool_check(swap);
+
+            Constraints::with_selector(
+                q_swap,
+                [
+                    ("a check", a_check),
+                    ("b check", b_check),
+                    ("swap is bool", bool_check),
+                ],
+            )
+        });
+
+        meta.create_gate("is_zero", |meta| {
+            let q_is_zero = meta.query_selector(q_is_zero);
+
+            let value = meta.query_advice(config.a, Rotation::cur());
+            let inv = meta.query_advice(config.b, Rotation::cur());
+            let is_zero = meta.query_advice(config.swap, Rotation::cur());
+
+            // If `value` is nonzero, `is_zero` must be 0 (and `inv` its inverse).
+            // If `value` is zero, the first constraint forces `is_zero` to be 1.
+            let one = Expression::Constant(F::ONE);
+            Constraints::with_selector(
+                q_is_zero,
+                [
+                    (
+                        "value ⋅ inv = 1 - is_zero",
+                        value.clone() * inv - (one - is_zero.clone()),
+                    ),
+                    ("value ⋅ is_zero = 0", value * is_zero),
+                ],
+            )
+        });
+
+        config
+    }
+
+    /// Constructs a [`CondSwapChip`] given a [`CondSwapConfig`].
+    pub fn construct(config: CondSwapConfig) -> Self {
+        CondSwapChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::UtilitiesInstructions;
+    use super::{CondSwapChip, CondSwapConfig, CondSwapInstructions};
+    use ff::{Field, PrimeField};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::pallas::Base;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn cond_swap() {
+        #[derive(Default)]
+        struct MyCircuit<F: Field> {
+            a: Value<F>,
+            b: Value<F>,
+            swap: Value<bool>,
+        }
+
+        impl<F: PrimeField> Circuit<F> for MyCircuit<F> {
+            type Config = CondSwapConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                Self::default()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+                let advices = [
+                    meta.advice_column(),
+                    meta.advice_column(),
+                    meta.advice_column(),
+                    meta.advice_column(),
+                    meta.advice_column(),
+                ];
+
+                CondSwapChip::<F>::configure(meta, advices)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<F>,
+            ) -> Result<(), Error> {
+                let chip = CondSwapChip::<F>::construct(config.clone());
+
+                // Load the pair and the swap flag into the circuit.
+                let a = chip.load_private(layouter.namespace(|| "a"), config.a(), self.a)?;
+                // Return the swapped pair.
+                let swapped_pair = chip.swap(
+                    layouter.namespace(|| "swap"),
+                    (a.clone(), self.b),
+                    self.swap,
+                )?;
+
+                self.swap
+                    .zip(a.value().zip(self.b.as_ref()))
+                    .zip(swapped_pair.0.value().zip(swapped_pair.1.value()))
+                    .assert_if_known(|((swap, (a, b)), (a_swapped, b_swapped))| {
+                        if *swap {
+                            // Check that `a` and `b` have been swapped
+                            (a_swapped == b) && (b_swapped == a)
+                        } else {
+                            // Check that `a` and `b` have not been swapped
+                            (a_swapped == a) && (b_swapped == b)
+                        }
+                    });
+
+                Ok(())
+            }
+        }
+
+        let rng = OsRng;
+
+        // Test swap case
+        {
+            let circuit: MyCircuit<Base> = MyCircuit {
+                a: Value::known(Base::random(rng)),
+                b: Value::known(Base::random(rng)),
+                swap: Value::known(true),
+            };
+            let prover = MockProver::<Base>::run(3, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        // Test non-swap case
+        {
+            let circuit: MyCircuit<Base> = MyCircuit {
+                a: Value::known(Base::random(rng)),
+                b: Value::known(Base::random(rng)),
+                swap: Value::known(false),
+            };
+            let prover = MockProver::<Base>::run(3, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn mux_and_is_zero() {
+        #[derive(Default)]
+        struct MyCircuit {
+            choice: Value<Base>,
+            left: Value<Base>,
+            right: Value<Base>,
+        }
+
+        impl Circuit<Base> for MyCircuit {
+            type Config = CondSwapConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                Self::default()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Base>) -> Self::Config {
+                let advices = [(); 5].map(|_| meta.advice_column());
+                CondSwapChip::<Base>::configure(meta, advices)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Base>,
+            ) -> Result<(), Error> {
+                let chip = CondSwapChip::<Base>::construct(config.clone());
+
+                let choice =
+                    chip.load_private(layouter.namespace(|| "choice"), config.a(), self.choice)?;
+                let left =
+                    chip.load_private(layouter.namespace(|| "left"), config.a(), self.left)?;
+                let right =
+                    chip.load_private(layouter.namespace(|| "right"), config.a(), self.right)?;
+
+                let out = chip.mux(layouter.namespace(|| "mux"), &choice, &left, &right)?;
+                choice
+                    .value()
+                    .zip(left.value().zip(right.value()))
+                    .zip(out.value())
+                    .assert_if_known(|((choice, (left, right)), out)| {
+                        if **choice == Base::ONE {
+                            out == right
+                        } else {
+                            out == left
+                        }
+                    });
+
+                let is_zero = chip.is_zero(layouter.namespace(|| "is_zero"), &left)?;
+                left.value()
+                    .zip(is_zero.value())
+                    .assert_if_known(|(left, is_zero)| {
+                        bool::from(left.is_zero()) == (**is_zero == Base::ONE)
+                    });
+
+                Ok(())
+            }
+        }
+
+        let left = Base::random(OsRng);
+        let right = Base::random(OsRng);
+        for (choice, left) in [
+            (Base::ZERO, left),
+            (Base::ONE, left),
+            (Base::ONE, Base::ZERO),
+        ] {
+            let circuit = MyCircuit {
+                choice: Value::known(choice),
+                left: Value::known(left),
+                right: Value::known(right),
+            };
+            let prover = MockProver::<Base>::run(4, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        // A non-boolean choice is rejected.
+        let circuit = MyCircuit {
+            choice: Value::known(Base::from(2)),
+            left: Value::known(left),
+            right: Value::known(right),
+        };
+        let prover = MockProver::<Base>::run(4, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Gadget proving that a field element is the reduction of a 32-byte string.
+//!
+//! This is the in-circuit counterpart of
+//! [`halo2_proofs::arithmetic::from_bytes_reduced`]: the byte string is interpreted as
+//! a little-endian 256-bit integer, and the witnessed field element is constrained to
+//! equal that integer modulo the field characteristic.
+
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+use super::range_table_column;
+
+/// The number of bytes decomposed by [`DecomposeBytesConfig`].
+pub const NUM_BYTES: usize = 32;
+
+/// Configuration for the byte decomposition gadget.
+///
+/// The gadget uses a single region of [`NUM_BYTES`] rows. Bytes are assigned from the
+/// most significant to the least significant, and the `acc` column holds a running
+/// sum such that
+///
+/// ```text
+/// acc_0 = b_31
+/// acc_i = 256 * acc_{i-1} + b_{31-i}
+/// ```
+///
+/// so that the last row of `acc` holds the reduced field element. Each byte is range
+/// checked against an 8-bit lookup table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecomposeBytesConfig<F: PrimeField> {
+    q_start: Selector,
+    q_running: Selector,
+    q_range: Selector,
+    byte: Column<Advice>,
+    acc: Column<Advice>,
+    table: TableColumn,
+    load_table: bool,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> DecomposeBytesConfig<F> {
+    /// Configures the byte decomposition gadget.
+    ///
+    /// `byte` and `acc` have equality enabled, so that the assigned bytes and the
+    /// resulting field element can be constrained to cells elsewhere in the circuit.
+    /// `table` is loaded with the values `0..256` by [`DecomposeBytesConfig::load`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        byte: Column<Advice>,
+        acc: Column<Advice>,
+        table: TableColumn,
+    ) -> Self {
+        meta.enable_equality(byte);
+        meta.enable_equality(acc);
+
+        let config = Self {
+            q_start: meta.selector(),
+            q_running: meta.selector(),
+            q_range: meta.complex_selector(),
+            byte,
+            acc,
+            table,
+            load_table: true,
+            _marker: PhantomData,
+        };
+
+        meta.create_gate("decompose bytes", |meta| {
+            let q_start = meta.query_selector(config.q_start);
+            let q_running = meta.query_selector(config.q_running);
+            let byte = meta.query_advice(config.byte, Rotation::cur());
+            let acc_prev = meta.query_advice(config.acc, Rotation::prev());
+            let acc = meta.query_advice(config.acc, Rotation::cur());
+
+            let two_pow_8 = F::from(1 << 8);
+            vec![
+                q_start * (acc.clone() - byte.clone()),
+                q_running * (acc - (acc_prev * two_pow_8 + byte)),
+            ]
+        });
+
+        meta.lookup("byte range check", |meta| {
+            let q_range = meta.query_selector(config.q_range);
+            let byte = meta.query_advice(config.byte, Rotation::cur());
+            vec![(q_range * byte, config.table)]
+        });
+
+        config
+    }
+
+    /// Configures the byte decomposition gadget like [`DecomposeBytesConfig::configure`],
+    /// with the 8-bit lookup table shared with the other chips of the circuit that
+    /// request it through [`range_table_column`](super::range_table_column).
+    pub fn configure_with_shared_table(
+        meta: &mut ConstraintSystem<F>,
+        byte: Column<Advice>,
+        acc: Column<Advice>,
+    ) -> Self {
+        let (table, load_table) = range_table_column(meta, 8);
+        Self {
+            load_table,
+            ..Self::configure(meta, byte, acc, table)
+        }
+    }
+
+    /// Loads the 8-bit lookup table used to range check the bytes.
+    ///
+    /// If the table is shared through [`DecomposeBytesConfig::configure_with_shared_table`],
+    /// this does nothing unless this chip is the one responsible for loading it.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        if !self.load_table {
+            return Ok(());
+        }
+
+        layouter.assign_table(
+            || "byte table",
+            |mut table| {
+                for value in 0..(1 << 8) {
+                    table.assign_cell(
+                        || "byte",
+                        self.table,
+                        value,
+                        || Value::known(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Witnesses `bytes` and the field element
+    /// [`from_bytes_reduced(bytes)`](halo2_proofs::arithmetic::from_bytes_reduced),
+    /// constraining the latter to be the reduction of the former.
+    ///
+    /// Returns the assigned bytes in little-endian order, together with the assigned
+    /// field element.
+    #[allow(clippy::type_complexity)]
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: Value<[u8; NUM_BYTES]>,
+    ) -> Result<(Vec<AssignedCell<F, F>>, AssignedCell<F, F>), Error> {
+        layouter.assign_region(
+            || "decompose bytes",
+            |mut region| {
+                let mut assigned = Vec::with_capacity(NUM_BYTES);
+                let mut acc_value = Value::known(F::ZERO);
+                let mut acc = None;
+
+                for offset in 0..NUM_BYTES {
+                    // Bytes are assigned from the most significant.
+                    let index = NUM_BYTES - 1 - offset;
+
+                    if offset == 0 {
+                        self.q_start.enable(&mut region, offset)?;
+                    } else {
+                        self.q_running.enable(&mut region, offset)?;
+                    }
+                    self.q_range.enable(&mut region, offset)?;
+
+                    let byte_value = bytes.map(|bytes| F::from(bytes[index] as u64));
+                    assigned.push(region.assign_advice(
+                        || format!("byte {}", index),
+                        self.byte,
+                        offset,
+                        || byte_value,
+                    )?);
+
+                    acc_value = acc_value * Value::known(F::from(1 << 8)) + byte_value;
+                    acc = Some(region.assign_advice(
+                        || format!("acc {}", offset),
+                        self.acc,
+                        offset,
+                        || acc_value,
+                    )?);
+                }
+
+                assigned.reverse();
+                Ok((assigned, acc.expect("NUM_BYTES > 0")))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+    use halo2_proofs::{
+        arithmetic::from_bytes_reduced,
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::{Circuit, Instance},
+    };
+    use halo2curves::pasta::pallas;
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        bytes: Value<[u8; NUM_BYTES]>,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = (DecomposeBytesConfig<pallas::Base>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let byte = meta.advice_column();
+            let acc = meta.advice_column();
+            let table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            (
+                DecomposeBytesConfig::configure(meta, byte, acc, table),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let (_, element) = config.assign(layouter.namespace(|| "decompose"), self.bytes)?;
+            layouter.constrain_instance(element.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn decompose_bytes() {
+        let canonical = pallas::Base::random(OsRng).to_repr();
+        // Exceeds the modulus, so is reduced.
+        let non_canonical = [0xff; NUM_BYTES];
+
+        for bytes in [canonical, non_canonical] {
+            let circuit = MyCircuit {
+                bytes: Value::known(bytes),
+            };
+            let expected = from_bytes_reduced::<pallas::Base>(&bytes);
+
+            let prover = MockProver::run(9, &circuit, vec![vec![expected]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The element must match the claimed bytes.
+            let prover =
+                MockProver::run(9, &circuit, vec![vec![expected + pallas::Base::ONE]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
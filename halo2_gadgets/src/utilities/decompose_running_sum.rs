@@ -0,0 +1,414 @@
+//! Decomposes an $n$-bit field element $\alpha$ into $W$ windows, each window
+//! being a $K$-bit word, using a running sum $z$.
+//!     $$\alpha = k_0 + (2^K) k_1 + (2^{2K}) k_2 + ... + (2^{(W-1)K}) k_{W-1}$$
+//!
+//! $z_0$ is initialized as $\alpha$. Each successive $z_{i+1}$ is computed as
+//!                $$z_{i+1} = (z_{i} - k_i) / (2^K).$$
+//! $z_W$ is returned.
+//!
+//! The window $k_i = z_i - 2^K z_{i+1}$ is checked to lie in $[0, 2^K)$ either with a
+//! polynomial constraint of degree $2^K$ (see [`RunningSumConfig::configure`]), which
+//! is only practical for small $K$, or with a lookup into a $K$-bit table (see
+//! [`RunningSumConfig::configure_with_table`]).
+//!
+//! In strict mode, $z_W$ is constrained to be zero, which proves that $\alpha$ fits in
+//! $W K$ bits. Otherwise $z_W$ is left unconstrained, and is returned so that the
+//! caller can constrain the high bits of $\alpha$ by other means.
+
+use ff::PrimeFieldBits;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use super::{bitrange_subset, range_check};
+use std::marker::PhantomData;
+
+/// The running sum $[z_0, ..., z_W]$. If created in strict mode, $z_W = 0$.
+#[derive(Clone, Debug)]
+pub struct RunningSum<F: PrimeFieldBits>(pub(super) Vec<AssignedCell<F, F>>);
+
+impl<F: PrimeFieldBits> std::ops::Deref for RunningSum<F> {
+    type Target = Vec<AssignedCell<F, F>>;
+
+    fn deref(&self) -> &Vec<AssignedCell<F, F>> {
+        &self.0
+    }
+}
+
+/// Configuration that provides methods for running sum decomposition.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RunningSumConfig<F: PrimeFieldBits, const WINDOW_NUM_BITS: usize> {
+    q_range_check: Selector,
+    z: Column<Advice>,
+    table: Option<TableColumn>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeFieldBits, const WINDOW_NUM_BITS: usize> RunningSumConfig<F, WINDOW_NUM_BITS> {
+    /// Returns the q_range_check selector of this [`RunningSumConfig`].
+    pub fn q_range_check(&self) -> Selector {
+        self.q_range_check
+    }
+
+    /// Configures the running sum decomposition, checking each window with a
+    /// polynomial constraint of degree $2^K$.
+    ///
+    /// `z` has equality enabled, so that $z_0$ can be copied in and the running sum
+    /// can be used elsewhere in the circuit. Strict decompositions additionally
+    /// require a fixed column enabled with
+    /// [`ConstraintSystem::enable_constant`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if WINDOW_NUM_BITS > 3.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_range_check: Selector,
+        z: Column<Advice>,
+    ) -> Self {
+        assert!(WINDOW_NUM_BITS <= 3);
+
+        meta.enable_equality(z);
+
+        let config = Self {
+            q_range_check,
+            z,
+            table: None,
+            _marker: PhantomData,
+        };
+
+        meta.create_gate("range check", |meta| {
+            let q_range_check = meta.query_selector(config.q_range_check);
+            let z_cur = meta.query_advice(config.z, Rotation::cur());
+            let z_next = meta.query_advice(config.z, Rotation::next());
+            //    z_i = 2^{K}⋅z_{i + 1} + k_i
+            // => k_i = z_i - 2^{K}⋅z_{i + 1}
+            let word = z_cur - z_next * F::from(1 << WINDOW_NUM_BITS);
+
+            Constraints::with_selector(q_range_check, Some(range_check(word, 1 << WINDOW_NUM_BITS)))
+        });
+
+        config
+    }
+
+    /// Configures the running sum decomposition, checking each window with a lookup
+    /// into `table`, which is loaded with the values $[0, 2^K)$ by
+    /// [`RunningSumConfig::load_table`].
+    ///
+    /// `q_range_check` must be a complex selector, as it is used in a lookup. The
+    /// table needs $2^K$ rows, which makes this suitable for windows of up to around
+    /// 10 bits.
+    pub fn configure_with_table(
+        meta: &mut ConstraintSystem<F>,
+        q_range_check: Selector,
+        z: Column<Advice>,
+        table: TableColumn,
+    ) -> Self {
+        meta.enable_equality(z);
+
+        let config = Self {
+            q_range_check,
+            z,
+            table: Some(table),
+            _marker: PhantomData,
+        };
+
+        meta.lookup("running sum window", |meta| {
+            let q_range_check = meta.query_selector(config.q_range_check);
+            let z_cur = meta.query_advice(config.z, Rotation::cur());
+            let z_next = meta.query_advice(config.z, Rotation::next());
+            let word = z_cur - z_next * F::from(1 << WINDOW_NUM_BITS);
+
+            vec![(q_range_check * word, table)]
+        });
+
+        config
+    }
+
+    /// Loads the lookup table of a configuration created with
+    /// [`RunningSumConfig::configure_with_table`]. Does nothing otherwise.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let table = match self.table {
+            Some(table) => table,
+            None => return Ok(()),
+        };
+
+        layouter.assign_table(
+            || format!("{}-bit table", WINDOW_NUM_BITS),
+            |mut table_region| {
+                for value in 0..(1 << WINDOW_NUM_BITS) {
+                    table_region.assign_cell(
+                        || "window",
+                        table,
+                        value,
+                        || Value::known(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Decompose a field element alpha that is witnessed in this helper.
+    ///
+    /// `strict` = true constrains the final running sum to be zero, i.e.
+    /// constrains alpha to be within WINDOW_NUM_BITS * num_windows bits.
+    pub fn witness_decompose(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        alpha: Value<F>,
+        strict: bool,
+        word_num_bits: usize,
+        num_windows: usize,
+    ) -> Result<RunningSum<F>, Error> {
+        let z_0 = region.assign_advice(|| "z_0 = alpha", self.z, offset, || alpha)?;
+        self.decompose(region, offset, z_0, strict, word_num_bits, num_windows)
+    }
+
+    /// Decompose an existing variable alpha that is copied into this helper.
+    ///
+    /// `strict` = true constrains the final running sum to be zero, i.e.
+    /// constrains alpha to be within WINDOW_NUM_BITS * num_windows bits.
+    pub fn copy_decompose(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        alpha: AssignedCell<F, F>,
+        strict: bool,
+        word_num_bits: usize,
+        num_windows: usize,
+    ) -> Result<RunningSum<F>, Error> {
+        let z_0 = alpha.copy_advice(|| "copy z_0 = alpha", region, self.z, offset)?;
+        self.decompose(region, offset, z_0, strict, word_num_bits, num_windows)
+    }
+
+    /// `z_0` must be the cell at `(self.z, offset)` in `region`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are too many windows for the given word size.
+    fn decompose(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        z_0: AssignedCell<F, F>,
+        strict: bool,
+        word_num_bits: usize,
+        num_windows: usize,
+    ) -> Result<RunningSum<F>, Error> {
+        // Make sure that we do not have more windows than required for the number
+        // of bits in the word. In other words, every window must contain at least
+        // one bit of the word (no empty windows).
+        //
+        // For example, let:
+        //      - word_num_bits = 64
+        //      - WINDOW_NUM_BITS = 3
+        // In this case, the maximum allowed num_windows is 22:
+        //                    3 * 22 < 64 + 3
+        //
+        assert!(WINDOW_NUM_BITS * num_windows < word_num_bits + WINDOW_NUM_BITS);
+        assert!(word_num_bits <= F::NUM_BITS as usize);
+
+        // Enable selectors
+        for idx in 0..num_windows {
+            self.q_range_check.enable(region, offset + idx)?;
+        }
+
+        // Decompose base field element into K-bit words.
+        let words: Vec<Value<F>> = (0..num_windows)
+            .map(|i| {
+                let start = i * WINDOW_NUM_BITS;
+                let end = std::cmp::min(start + WINDOW_NUM_BITS, word_num_bits);
+                z_0.value().map(|alpha| bitrange_subset(alpha, start..end))
+            })
+            .collect();
+
+        // Initialize empty vector to store running sum values [z_0, ..., z_W].
+        let mut zs: Vec<AssignedCell<F, F>> = vec![z_0.clone()];
+        let mut z = z_0;
+
+        // Assign running sum `z_{i+1}` = (z_i - k_i) / (2^K) for i = 0..=n-1.
+        // Outside of this helper, z_0 = alpha must have already been loaded into the
+        // `z` column at `offset`.
+        let two_pow_k_inv = Value::known(F::from(1 << WINDOW_NUM_BITS).invert().unwrap());
+        for (i, word) in words.into_iter().enumerate() {
+            // z_next = (z_cur - word) / (2^K)
+            let z_next = {
+                let z_cur_val = z.value().copied();
+                let z_next_val = (z_cur_val - word) * two_pow_k_inv;
+                region.assign_advice(
+                    || format!("z_{:?}", i + 1),
+                    self.z,
+                    offset + i + 1,
+                    || z_next_val,
+                )?
+            };
+
+            // Update `z`.
+            z = z_next;
+            zs.push(z.clone());
+        }
+        assert_eq!(zs.len(), num_windows + 1);
+
+        if strict {
+            // Constrain the final running sum output to be zero.
+            region.constrain_constant(zs.last().unwrap().cell(), F::ZERO)?;
+        }
+
+        Ok(RunningSum(zs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::{Field, PrimeField};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::pallas;
+    use rand::{rngs::OsRng, RngCore};
+
+    #[derive(Clone)]
+    struct MyCircuit<
+        const WINDOW_NUM_BITS: usize,
+        const WORD_NUM_BITS: usize,
+        const NUM_WINDOWS: usize,
+        const LOOKUP: bool,
+    > {
+        alpha: Value<pallas::Base>,
+        strict: bool,
+    }
+
+    impl<
+            const WINDOW_NUM_BITS: usize,
+            const WORD_NUM_BITS: usize,
+            const NUM_WINDOWS: usize,
+            const LOOKUP: bool,
+        > Circuit<pallas::Base> for MyCircuit<WINDOW_NUM_BITS, WORD_NUM_BITS, NUM_WINDOWS, LOOKUP>
+    {
+        type Config = RunningSumConfig<pallas::Base, WINDOW_NUM_BITS>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                alpha: Value::unknown(),
+                strict: self.strict,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let z = meta.advice_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            if LOOKUP {
+                let q_range_check = meta.complex_selector();
+                let table = meta.lookup_table_column();
+                RunningSumConfig::configure_with_table(meta, q_range_check, z, table)
+            } else {
+                let q_range_check = meta.selector();
+                RunningSumConfig::configure(meta, q_range_check, z)
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            config.load_table(&mut layouter)?;
+            layouter.assign_region(
+                || "decompose",
+                |mut region| {
+                    let offset = 0;
+                    let zs = config.witness_decompose(
+                        &mut region,
+                        offset,
+                        self.alpha,
+                        self.strict,
+                        WORD_NUM_BITS,
+                        NUM_WINDOWS,
+                    )?;
+                    let alpha = zs[0].clone();
+
+                    let offset = offset + NUM_WINDOWS + 1;
+
+                    config.copy_decompose(
+                        &mut region,
+                        offset,
+                        alpha,
+                        self.strict,
+                        WORD_NUM_BITS,
+                        NUM_WINDOWS,
+                    )?;
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    const FIELD_NUM_BITS: usize = pallas::Base::NUM_BITS as usize;
+
+    #[test]
+    fn test_running_sum() {
+        let alpha = pallas::Base::random(OsRng);
+
+        // Strict full decomposition with 3-bit windows.
+        {
+            let circuit: MyCircuit<3, FIELD_NUM_BITS, 85, false> = MyCircuit {
+                alpha: Value::known(alpha),
+                strict: true,
+            };
+            let prover = MockProver::<pallas::Base>::run(8, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        // Strict full decomposition with 8-bit windows checked by lookups.
+        {
+            let circuit: MyCircuit<8, FIELD_NUM_BITS, 32, true> = MyCircuit {
+                alpha: Value::known(alpha),
+                strict: true,
+            };
+            let prover = MockProver::<pallas::Base>::run(9, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        // A 64-bit word.
+        {
+            let word = pallas::Base::from(OsRng.next_u64());
+            let circuit: MyCircuit<3, 64, 22, false> = MyCircuit {
+                alpha: Value::known(word),
+                strict: true,
+            };
+            let prover = MockProver::<pallas::Base>::run(8, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        // A word that does not fit in 66 bits passes only in non-strict mode.
+        {
+            let word = pallas::Base::from(2).pow([66]);
+            let circuit: MyCircuit<3, 64, 22, false> = MyCircuit {
+                alpha: Value::known(word),
+                strict: false,
+            };
+            let prover = MockProver::<pallas::Base>::run(8, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            let circuit: MyCircuit<3, 64, 22, false> = MyCircuit {
+                alpha: Value::known(word),
+                strict: true,
+            };
+            let prover = MockProver::<pallas::Base>::run(8, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,675 @@
+//! Range checks with a lookup into a $K$-bit table.
+//!
+//! A single lookup argument into a table of the values $[0, 2^K)$ serves two kinds of
+//! checks:
+//!
+//! - Full-word checks decompose a field element into $W$ words of $K$ bits with a
+//!   running sum, as in [`decompose_running_sum`](super::decompose_running_sum), and
+//!   look each word up. In strict mode, this proves that the element fits in $W K$
+//!   bits.
+//! - Short checks prove that an element fits in $s < K$ bits, by looking up both the
+//!   element and the element shifted left by $K - s$ bits.
+//!
+//! The table column is provided by the caller, so that it can be shared with another
+//! table of the circuit whose first column holds $[0, 2^K)$, such as the generator
+//! table of a Sinsemilla chip. Alternatively,
+//! [`LookupRangeCheckConfig::configure_with_shared_table`] obtains it from the registry
+//! of shared tables in the [`ConstraintSystem`].
+
+use ff::PrimeFieldBits;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector, TableColumn,
+    },
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use super::{
+    bitrange_subset, decompose_running_sum::RunningSum, lebs2ip, range_table_column,
+    RangeConstrained,
+};
+
+impl<F: PrimeFieldBits> RangeConstrained<F, AssignedCell<F, F>> {
+    /// Witnesses the bits of `value` in `bitrange`, and constrains them to be the
+    /// correct number of bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bitrange.len() > K`.
+    pub fn witness_short<const K: usize>(
+        lookup_config: &LookupRangeCheckConfig<F, K>,
+        layouter: impl Layouter<F>,
+        value: Value<&F>,
+        bitrange: Range<usize>,
+    ) -> Result<Self, Error> {
+        let num_bits = bitrange.len();
+
+        lookup_config
+            .witness_short_check(
+                layouter,
+                value.map(|value| bitrange_subset(value, bitrange)),
+                num_bits,
+            )
+            .map(|inner| Self {
+                inner,
+                num_bits,
+                _phantom: PhantomData,
+            })
+    }
+}
+
+/// Configuration that provides methods for lookup range checks.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LookupRangeCheckConfig<F: PrimeFieldBits, const K: usize> {
+    q_lookup: Selector,
+    q_running: Selector,
+    q_bitshift: Selector,
+    running_sum: Column<Advice>,
+    table_idx: TableColumn,
+    load_table: bool,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeFieldBits, const K: usize> LookupRangeCheckConfig<F, K> {
+    /// Configures the range checks.
+    ///
+    /// The `running_sum` advice column holds the element being checked and its running
+    /// sum or shifted form. The `table_idx` column must contain the values $[0, 2^K)$,
+    /// either loaded with [`LookupRangeCheckConfig::load`] or by another chip that
+    /// shares it.
+    ///
+    /// `running_sum` has equality enabled. Strict and short checks additionally require
+    /// a fixed column enabled with [`ConstraintSystem::enable_constant`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` is 0 or greater than 32.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        running_sum: Column<Advice>,
+        table_idx: TableColumn,
+    ) -> Self {
+        assert!(K > 0 && K <= 32);
+
+        meta.enable_equality(running_sum);
+
+        let config = LookupRangeCheckConfig {
+            q_lookup: meta.complex_selector(),
+            q_running: meta.complex_selector(),
+            q_bitshift: meta.selector(),
+            running_sum,
+            table_idx,
+            load_table: true,
+            _marker: PhantomData,
+        };
+
+        meta.lookup("lookup range check", |meta| {
+            let q_lookup = meta.query_selector(config.q_lookup);
+            let q_running = meta.query_selector(config.q_running);
+            let z_cur = meta.query_advice(config.running_sum, Rotation::cur());
+
+            // In a full-word check, the word is recovered from the running sum:
+            //    z_i = 2^{K}⋅z_{i + 1} + k_i
+            // => k_i = z_i - 2^{K}⋅z_{i + 1}
+            let running_sum_word = {
+                let z_next = meta.query_advice(config.running_sum, Rotation::next());
+                z_cur.clone() - z_next * F::from(1 << K)
+            };
+
+            // In a short check, the word is witnessed directly.
+            let q_short = Expression::Constant(F::ONE) - q_running.clone();
+
+            vec![(
+                q_lookup * (q_running * running_sum_word + q_short * z_cur),
+                config.table_idx,
+            )]
+        });
+
+        meta.create_gate("short lookup bitshift", |meta| {
+            let q_bitshift = meta.query_selector(config.q_bitshift);
+            let word = meta.query_advice(config.running_sum, Rotation::prev());
+            let shifted_word = meta.query_advice(config.running_sum, Rotation::cur());
+            let inv_two_pow_s = meta.query_advice(config.running_sum, Rotation::next());
+
+            // shifted_word = word * 2^{K-s}
+            //              = word * 2^K * inv_two_pow_s
+            Constraints::with_selector(
+                q_bitshift,
+                Some(word * F::from(1 << K) * inv_two_pow_s - shifted_word),
+            )
+        });
+
+        config
+    }
+
+    /// Configures the range checks like [`LookupRangeCheckConfig::configure`], with
+    /// the table of $[0, 2^K)$ shared with the other chips of the circuit that request
+    /// it through [`range_table_column`](super::range_table_column).
+    pub fn configure_with_shared_table(
+        meta: &mut ConstraintSystem<F>,
+        running_sum: Column<Advice>,
+    ) -> Self {
+        let (table_idx, load_table) = range_table_column(meta, K);
+        Self {
+            load_table,
+            ..Self::configure(meta, running_sum, table_idx)
+        }
+    }
+
+    /// Loads the values $[0, 2^K)$ into the table column.
+    ///
+    /// This is not needed if the column is loaded by another chip that shares it. If
+    /// the table is shared through [`LookupRangeCheckConfig::configure_with_shared_table`],
+    /// this does nothing unless this chip is the one responsible for loading it.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        if !self.load_table {
+            return Ok(());
+        }
+
+        layouter.assign_table(
+            || format!("{}-bit table", K),
+            |mut table| {
+                for index in 0..(1 << K) {
+                    table.assign_cell(
+                        || "table_idx",
+                        self.table_idx,
+                        index,
+                        || Value::known(F::from(index as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Decomposes an existing cell, copied into this helper, into `num_words` words of
+    /// `K` bits.
+    ///
+    /// `strict` = true constrains the final running sum to be zero, i.e. constrains
+    /// the element to be within `num_words * K` bits.
+    pub fn copy_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        element: AssignedCell<F, F>,
+        num_words: usize,
+        strict: bool,
+    ) -> Result<RunningSum<F>, Error> {
+        layouter.assign_region(
+            || format!("{:?} words range check", num_words),
+            |mut region| {
+                let z_0 = element.copy_advice(|| "z_0", &mut region, self.running_sum, 0)?;
+                self.range_check(&mut region, z_0, num_words, strict)
+            },
+        )
+    }
+
+    /// Decomposes a value, witnessed in this helper, into `num_words` words of `K`
+    /// bits.
+    ///
+    /// `strict` = true constrains the final running sum to be zero, i.e. constrains
+    /// the value to be within `num_words * K` bits.
+    pub fn witness_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_words: usize,
+        strict: bool,
+    ) -> Result<RunningSum<F>, Error> {
+        layouter.assign_region(
+            || "witness element",
+            |mut region| {
+                let z_0 = region.assign_advice(|| "z_0", self.running_sum, 0, || value)?;
+                self.range_check(&mut region, z_0, num_words, strict)
+            },
+        )
+    }
+
+    /// `element` must be the cell at `(self.running_sum, 0)` in `region`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_words * K` exceeds the capacity of the field.
+    fn range_check(
+        &self,
+        region: &mut Region<'_, F>,
+        element: AssignedCell<F, F>,
+        num_words: usize,
+        strict: bool,
+    ) -> Result<RunningSum<F>, Error> {
+        assert!(num_words * K <= F::CAPACITY as usize);
+        let num_bits = num_words * K;
+
+        // Chunk the first num_bits bits of `element` into K-bit words.
+        let words = element
+            .value()
+            .map(|element| {
+                element
+                    .to_le_bits()
+                    .into_iter()
+                    .take(num_bits)
+                    .collect::<Vec<_>>()
+                    .chunks_exact(K)
+                    .map(|word| F::from(lebs2ip::<K>(word.try_into().unwrap())))
+                    .collect::<Vec<_>>()
+            })
+            .transpose_vec(num_words);
+
+        // Assign the running sum z_{i + 1} = (z_i - k_i) / 2^K, starting from
+        // z_0 = element.
+        let mut zs = vec![element.clone()];
+        let mut z = element;
+        let inv_two_pow_k = F::from(1u64 << K).invert().unwrap();
+        for (idx, word) in words.into_iter().enumerate() {
+            self.q_lookup.enable(region, idx)?;
+            self.q_running.enable(region, idx)?;
+
+            let z_next = z
+                .value()
+                .zip(word)
+                .map(|(z, word)| (*z - word) * inv_two_pow_k);
+            z = region.assign_advice(
+                || format!("z_{:?}", idx + 1),
+                self.running_sum,
+                idx + 1,
+                || z_next,
+            )?;
+            zs.push(z.clone());
+        }
+
+        if strict {
+            // Constrain the final running sum to be zero.
+            region.constrain_constant(zs.last().unwrap().cell(), F::ZERO)?;
+        }
+
+        Ok(RunningSum(zs))
+    }
+
+    /// Checks that an existing cell, copied into this helper, fits in `num_bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bits > K`.
+    pub fn copy_short_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        element: AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<(), Error> {
+        assert!(num_bits <= K);
+        layouter.assign_region(
+            || format!("range check {:?} bits", num_bits),
+            |mut region| {
+                let element =
+                    element.copy_advice(|| "element", &mut region, self.running_sum, 0)?;
+                self.short_range_check(&mut region, element, num_bits)
+            },
+        )
+    }
+
+    /// Checks that a value, witnessed in this helper, fits in `num_bits` bits, and
+    /// returns the witnessed cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bits > K`.
+    pub fn witness_short_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        element: Value<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(num_bits <= K);
+        layouter.assign_region(
+            || format!("range check {:?} bits", num_bits),
+            |mut region| {
+                let element =
+                    region.assign_advice(|| "element", self.running_sum, 0, || element)?;
+                self.short_range_check(&mut region, element.clone(), num_bits)?;
+                Ok(element)
+            },
+        )
+    }
+
+    /// `element` must be the cell at `(self.running_sum, 0)` in `region`.
+    fn short_range_check(
+        &self,
+        region: &mut Region<'_, F>,
+        element: AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<(), Error> {
+        // Look up the element, which shows it fits in K bits, and the element shifted
+        // by K - num_bits bits, which then shows it fits in num_bits bits.
+        self.q_lookup.enable(region, 0)?;
+        self.q_lookup.enable(region, 1)?;
+        self.q_bitshift.enable(region, 1)?;
+
+        let shifted = element
+            .value()
+            .map(|element| *element * F::from(1 << (K - num_bits)));
+        region.assign_advice(
+            || format!("element * 2^({}-{})", K, num_bits),
+            self.running_sum,
+            1,
+            || shifted,
+        )?;
+
+        // The shift is checked against 2^{-num_bits}, taken from a fixed column.
+        let inv_two_pow_s = F::from(1 << num_bits).invert().unwrap();
+        region.assign_advice_from_constant(
+            || format!("2^(-{})", num_bits),
+            self.running_sum,
+            2,
+            inv_two_pow_s,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::decompose_bytes::{DecomposeBytesConfig, NUM_BYTES};
+    use ff::{Field, PrimeField};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::pallas;
+    use rand::rngs::OsRng;
+
+    const K: usize = 10;
+
+    fn configure(
+        meta: &mut ConstraintSystem<pallas::Base>,
+    ) -> LookupRangeCheckConfig<pallas::Base, K> {
+        let running_sum = meta.advice_column();
+        let table_idx = meta.lookup_table_column();
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        LookupRangeCheckConfig::configure(meta, running_sum, table_idx)
+    }
+
+    #[derive(Clone)]
+    struct FullCircuit {
+        element: Value<pallas::Base>,
+        num_words: usize,
+        strict: bool,
+    }
+
+    impl Circuit<pallas::Base> for FullCircuit {
+        type Config = LookupRangeCheckConfig<pallas::Base, K>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                element: Value::unknown(),
+                ..self.clone()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+
+            // Check the element both when witnessed and when copied in.
+            let zs = config.witness_check(
+                layouter.namespace(|| "witness"),
+                self.element,
+                self.num_words,
+                self.strict,
+            )?;
+            let zs_copy = config.copy_check(
+                layouter.namespace(|| "copy"),
+                zs[0].clone(),
+                self.num_words,
+                self.strict,
+            )?;
+            assert_eq!(zs.len(), self.num_words + 1);
+            assert_eq!(zs_copy.len(), self.num_words + 1);
+
+            // The running sum matches its definition.
+            let expected = self.element.map(|element| {
+                let mut z = element;
+                let mut zs = vec![z];
+                for _ in 0..self.num_words {
+                    let word = bitrange_subset(&z, 0..K);
+                    z = (z - word) * pallas::Base::from(1 << K).invert().unwrap();
+                    zs.push(z);
+                }
+                zs
+            });
+            for (i, z) in zs.iter().enumerate() {
+                z.value()
+                    .zip(expected.as_ref())
+                    .assert_if_known(|(z, expected)| **z == expected[i]);
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lookup_range_check() {
+        let run = |element: pallas::Base, num_words: usize, strict: bool| {
+            let circuit = FullCircuit {
+                element: Value::known(element),
+                num_words,
+                strict,
+            };
+            MockProver::<pallas::Base>::run(11, &circuit, vec![])
+                .unwrap()
+                .verify()
+        };
+
+        // Elements that fit in num_words * K bits pass in strict mode.
+        let element = pallas::Base::from((1 << (6 * K)) - 1);
+        assert_eq!(run(element, 6, true), Ok(()));
+        assert_eq!(run(pallas::Base::ZERO, 6, true), Ok(()));
+
+        // Larger elements only pass in non-strict mode.
+        let element = pallas::Base::from(1 << (6 * K));
+        assert!(run(element, 6, true).is_err());
+        assert_eq!(run(element, 6, false), Ok(()));
+        let element = pallas::Base::random(OsRng);
+        assert_eq!(run(element, 6, false), Ok(()));
+    }
+
+    #[derive(Clone)]
+    struct ShortCircuit {
+        element: Value<pallas::Base>,
+        num_bits: usize,
+    }
+
+    impl Circuit<pallas::Base> for ShortCircuit {
+        type Config = LookupRangeCheckConfig<pallas::Base, K>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                element: Value::unknown(),
+                num_bits: self.num_bits,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let element = config.witness_short_check(
+                layouter.namespace(|| "witness"),
+                self.element,
+                self.num_bits,
+            )?;
+            config.copy_short_check(layouter.namespace(|| "copy"), element, self.num_bits)
+        }
+    }
+
+    #[test]
+    fn short_range_check() {
+        let run = |element: u64, num_bits: usize| {
+            let circuit = ShortCircuit {
+                element: Value::known(pallas::Base::from(element)),
+                num_bits,
+            };
+            MockProver::<pallas::Base>::run(11, &circuit, vec![])
+                .unwrap()
+                .verify()
+        };
+
+        // Edge cases: zero bits and K bits.
+        assert_eq!(run(0, 0), Ok(()));
+        assert_eq!(run((1 << K) - 1, K), Ok(()));
+
+        // An element within num_bits.
+        assert_eq!(run((1 << 6) - 1, 6), Ok(()));
+
+        // An element larger than num_bits, within and beyond K bits.
+        assert!(run(1 << 6, 6).is_err());
+        assert!(run(1 << K, 6).is_err());
+        assert!(run(1 << K, K).is_err());
+    }
+
+    #[test]
+    fn witness_short() {
+        #[derive(Clone)]
+        struct MyCircuit(Value<pallas::Base>);
+
+        impl Circuit<pallas::Base> for MyCircuit {
+            type Config = LookupRangeCheckConfig<pallas::Base, K>;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit(Value::unknown())
+            }
+
+            fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+                configure(meta)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<pallas::Base>,
+            ) -> Result<(), Error> {
+                config.load(&mut layouter)?;
+                let bits = RangeConstrained::witness_short(
+                    &config,
+                    layouter.namespace(|| "bits 3..10"),
+                    self.0.as_ref(),
+                    3..10,
+                )?;
+                assert_eq!(bits.num_bits(), 7);
+                bits.inner()
+                    .value()
+                    .zip(self.0)
+                    .assert_if_known(|(bits, value)| **bits == bitrange_subset(value, 3..10));
+                Ok(())
+            }
+        }
+
+        let value = pallas::Base::from_u128(0b1011_0110_1101);
+        let prover =
+            MockProver::<pallas::Base>::run(11, &MyCircuit(Value::known(value)), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[derive(Clone)]
+    struct SharedTableCircuit {
+        element: Value<pallas::Base>,
+        bytes: Value<[u8; NUM_BYTES]>,
+    }
+
+    impl Circuit<pallas::Base> for SharedTableCircuit {
+        type Config = (
+            [LookupRangeCheckConfig<pallas::Base, 8>; 2],
+            DecomposeBytesConfig<pallas::Base>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                element: Value::unknown(),
+                bytes: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let num_fixed_columns = meta.num_fixed_columns();
+
+            let range_checks = [(); 2].map(|_| {
+                let running_sum = meta.advice_column();
+                LookupRangeCheckConfig::configure_with_shared_table(meta, running_sum)
+            });
+            let byte = meta.advice_column();
+            let acc = meta.advice_column();
+            let bytes = DecomposeBytesConfig::configure_with_shared_table(meta, byte, acc);
+
+            // The three chips share a single 8-bit table.
+            assert_eq!(meta.num_fixed_columns(), num_fixed_columns + 1);
+            (range_checks, bytes)
+        }
+
+        fn synthesize(
+            &self,
+            (range_checks, bytes): Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            // Only the chip that allocated the table loads it.
+            for config in &range_checks {
+                config.load(&mut layouter)?;
+            }
+            bytes.load(&mut layouter)?;
+
+            for config in &range_checks {
+                config.witness_short_check(layouter.namespace(|| "short"), self.element, 5)?;
+            }
+            bytes.assign(layouter.namespace(|| "bytes"), self.bytes)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn shared_table() {
+        let run = |element: u64| {
+            let circuit = SharedTableCircuit {
+                element: Value::known(pallas::Base::from(element)),
+                bytes: Value::known(pallas::Base::random(OsRng).to_repr()),
+            };
+            MockProver::<pallas::Base>::run(10, &circuit, vec![])
+                .unwrap()
+                .verify()
+        };
+
+        assert_eq!(run((1 << 5) - 1), Ok(()));
+        assert!(run(1 << 5).is_err());
+    }
+}
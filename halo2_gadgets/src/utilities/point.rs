@@ -0,0 +1,67 @@
+//! The encoding of curve points as pairs of base field elements.
+//!
+//! Gadgets and transcripts in this crate represent a point by its affine coordinates
+//! `(x, y)`. The identity has no affine coordinates, and is represented by `(0, 0)`
+//! instead. This is unambiguous on the short Weierstrass curves `y^2 = x^3 + b`
+//! supported by this crate, since `(0, 0)` satisfies the curve equation only if
+//! `b = 0`.
+//!
+//! Circuits that witness points in this encoding must either rule out `(0, 0)` or
+//! handle it explicitly as the identity.
+
+use ff::Field;
+use group::prime::PrimeCurveAffine;
+use halo2_proofs::arithmetic::CurveAffine;
+
+/// Returns the encoding of `point` as `(x, y)`, with the identity encoded as
+/// `(0, 0)`.
+pub fn to_coordinates<C: CurveAffine>(point: C) -> (C::Base, C::Base) {
+    Option::from(point.coordinates())
+        .map(|coordinates| (*coordinates.x(), *coordinates.y()))
+        .unwrap_or((C::Base::ZERO, C::Base::ZERO))
+}
+
+/// Returns the point encoded by `(x, y)`, treating `(0, 0)` as the identity, or
+/// `None` if `(x, y)` is not on the curve.
+pub fn from_coordinates<C: CurveAffine>(x: C::Base, y: C::Base) -> Option<C> {
+    if x.is_zero_vartime() && y.is_zero_vartime() {
+        Some(C::identity())
+    } else {
+        C::from_xy(x, y).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::{Curve, Group};
+    use halo2curves::pasta::pallas;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn identity_encoding() {
+        let identity = pallas::Affine::identity();
+        assert_eq!(
+            to_coordinates(identity),
+            (pallas::Base::ZERO, pallas::Base::ZERO)
+        );
+        assert_eq!(
+            from_coordinates::<pallas::Affine>(pallas::Base::ZERO, pallas::Base::ZERO),
+            Some(identity)
+        );
+
+        let point = pallas::Point::random(OsRng).to_affine();
+        let (x, y) = to_coordinates(point);
+        assert_eq!(from_coordinates(x, y), Some(point));
+
+        // Points off the curve are rejected.
+        assert_eq!(
+            from_coordinates::<pallas::Affine>(x, y + pallas::Base::ONE),
+            None
+        );
+        assert_eq!(
+            from_coordinates::<pallas::Affine>(pallas::Base::ZERO, pallas::Base::ONE),
+            None
+        );
+    }
+}
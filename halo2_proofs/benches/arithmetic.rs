@@ -3,12 +3,12 @@ extern crate criterion;
 
 use crate::arithmetic::best_multiexp_cpu;
 use crate::halo2curves::pasta::{EqAffine, Fp};
-use group::ff::Field;
+use group::ff::{BatchInvert, Field};
 use halo2_proofs::*;
 
 use halo2_proofs::poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA};
 
-use criterion::{black_box, Criterion};
+use criterion::{black_box, BatchSize, Criterion};
 use rand_core::OsRng;
 
 fn criterion_benchmark(c: &mut Criterion) {
@@ -32,6 +32,30 @@ fn criterion_benchmark(c: &mut Criterion) {
             })
         });
     }
+
+    // batch inversion
+    {
+        let mut group = c.benchmark_group("batch-invert");
+        for k in [10, 14, 18] {
+            let values: Vec<Fp> = (0..(1 << k)).map(|_| Fp::random(rng)).collect();
+
+            group.bench_function(format!("serial-k{}", k), |b| {
+                b.iter_batched(
+                    || values.clone(),
+                    |mut values| values.iter_mut().batch_invert(),
+                    BatchSize::LargeInput,
+                )
+            });
+            group.bench_function(format!("parallel-k{}", k), |b| {
+                b.iter_batched(
+                    || values.clone(),
+                    |mut values| arithmetic::batch_invert(&mut values),
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+        group.finish();
+    }
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -179,7 +179,9 @@ fn criterion_benchmark(c: &mut Criterion) {
             &mut transcript,
         )
         .expect("proof generation should not fail");
-        transcript.finalize()
+        transcript
+            .finalize()
+            .expect("finalizing the transcript should not fail")
     }
 
     fn verifier(params: &ParamsKZG<Bn256>, vk: &VerifyingKey<G1Affine>, proof: &[u8]) {
@@ -292,7 +292,9 @@ fn criterion_benchmark(c: &mut Criterion) {
             &mut transcript,
         )
         .expect("proof generation should not fail");
-        transcript.finalize()
+        transcript
+            .finalize()
+            .expect("finalizing the transcript should not fail")
     }
 
     fn verifier(params: &ParamsIPA<EqAffine>, vk: &VerifyingKey<EqAffine>, proof: &[u8]) {
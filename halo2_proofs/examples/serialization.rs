@@ -172,7 +172,9 @@ fn main() {
         &mut transcript,
     )
     .expect("prover should not fail");
-    let proof = transcript.finalize();
+    let proof = transcript
+        .finalize()
+        .expect("finalizing the transcript should not fail");
 
     let strategy = SingleStrategy::new(&params);
     let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
@@ -293,7 +293,9 @@ fn test_prover<C: CurveAffine, const W: usize, const H: usize>(
         )
         .expect("proof generation should not fail");
 
-        transcript.finalize()
+        transcript
+            .finalize()
+            .expect("finalizing the transcript should not fail")
     };
 
     let accepted = {
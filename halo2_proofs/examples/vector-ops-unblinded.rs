@@ -492,7 +492,9 @@ where
         )
         .expect("proof generation should not fail");
 
-        transcript.finalize()
+        transcript
+            .finalize()
+            .expect("finalizing the transcript should not fail")
     };
 
     let accepted = {
@@ -6,16 +6,19 @@ use super::icicle;
 use super::multicore;
 pub use ff::Field;
 use group::{
-    ff::{BatchInvert, PrimeField},
+    ff::{BatchInvert, FromUniformBytes, PrimeField},
     prime::PrimeCurveAffine,
     Curve, GroupOpsOwned, ScalarMulOwned,
 };
-#[cfg(feature = "icicle_gpu")]
-use rustacuda::prelude::DeviceBuffer;
 
 use halo2curves::msm::msm_best;
 pub use halo2curves::{CurveAffine, CurveExt};
 
+pub mod ct;
+pub(crate) mod limbs;
+pub mod msm;
+pub use msm::{best_multiexp, Bases};
+
 /// This represents an element of a group with basic operations that can be
 /// performed. This allows an FFT implementation (for example) to operate
 /// generically over either a field or elliptic curve group.
@@ -38,16 +41,22 @@ where
 ///
 /// This will use multithreading if beneficial.
 pub fn best_multiexp_cpu<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
+    ct::vartime("best_multiexp_cpu");
     msm_best(coeffs, bases)
 }
 
 #[cfg(feature = "icicle_gpu")]
-/// Performs a multi-exponentiation operation on GPU using Icicle library
-pub fn best_multiexp_gpu<C: CurveAffine>(coeffs: &[C::Scalar], is_lagrange: bool) -> C::Curve {
-    let scalars_ptr: DeviceBuffer<::icicle::curves::bn254::ScalarField_BN254> =
-        icicle::copy_scalars_to_device::<C>(coeffs);
-
-    return icicle::multiexp_on_device::<C>(scalars_ptr, is_lagrange);
+/// Performs a multi-exponentiation operation on GPU using Icicle library, over the
+/// bases loaded for the parameters with the given fingerprint.
+///
+/// Returns `None` if those bases are not loaded on the GPU.
+pub fn best_multiexp_gpu<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    fingerprint: [u8; 32],
+    is_lagrange: bool,
+) -> Option<C::Curve> {
+    ct::vartime("best_multiexp_gpu");
+    icicle::multiexp_on_device::<C>(fingerprint, coeffs, is_lagrange)
 }
 
 /// Dispatcher
@@ -61,6 +70,16 @@ pub fn best_fft<Scalar: Field, G: FftGroup<Scalar>>(
     fft::fft(a, omega, log_n, data, inverse);
 }
 
+/// Converts `points` to affine form.
+///
+/// This shares a single field inversion between all of the points, so should be
+/// preferred to calling [`Curve::to_affine`] on each of them.
+pub fn batch_normalize<C: PrimeCurveAffine>(points: &[C::Curve]) -> Vec<C> {
+    let mut affine = vec![C::identity(); points.len()];
+    C::Curve::batch_normalize(points, &mut affine);
+    affine
+}
+
 /// Convert coefficient bases group elements to lagrange basis by inverse FFT.
 pub fn g_to_lagrange<C: PrimeCurveAffine>(g_projective: Vec<C::Curve>, k: u32) -> Vec<C> {
     let n_inv = C::Scalar::TWO_INV.pow_vartime([k as u64, 0, 0, 0]);
@@ -209,6 +228,17 @@ pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mu
     });
 }
 
+/// Inverts every nonzero element of `values` in place, leaving zeros unchanged, and
+/// returns the inverse of the product of the nonzero elements.
+///
+/// This splits `values` into one chunk per thread and applies Montgomery's trick
+/// to each chunk, so it costs one field inversion per thread in addition to
+/// roughly three multiplications per element. It runs in constant time; see
+/// [`ct::batch_invert`].
+pub fn batch_invert<F: Field>(values: &mut [F]) -> F {
+    ct::batch_invert(values)
+}
+
 ///
 pub fn log2_floor(num: usize) -> u32 {
     assert!(num > 0);
@@ -314,6 +344,22 @@ pub fn bitreverse(mut n: usize, l: usize) -> usize {
     r
 }
 
+/// Maps a 32-byte string, such as the output of SHA-256, into a field element.
+///
+/// `bytes` is interpreted as a little-endian 256-bit unsigned integer, which is
+/// reduced modulo the field characteristic. For fields smaller than 256 bits this
+/// mapping is not injective; callers that need a canonical encoding should use
+/// [`PrimeField::from_repr`] instead. Big-endian digests must be reversed before
+/// being passed in.
+///
+/// This is the reduction enforced in-circuit by the byte decomposition gadget in
+/// `halo2_gadgets::utilities::decompose_bytes`.
+pub fn from_bytes_reduced<F: FromUniformBytes<64>>(bytes: &[u8; 32]) -> F {
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(bytes);
+    F::from_uniform_bytes(&wide)
+}
+
 #[cfg(test)]
 use rand_core::OsRng;
 
@@ -341,3 +387,52 @@ fn test_lagrange_interpolate() {
         }
     }
 }
+
+#[test]
+fn test_from_bytes_reduced() {
+    // Canonical encodings are mapped to themselves.
+    let a = Fp::random(OsRng);
+    assert_eq!(from_bytes_reduced::<Fp>(&a.to_repr()), a);
+
+    // Non-canonical encodings are reduced modulo p.
+    let bytes = [0xff; 32];
+    let expected = bytes.iter().rev().fold(Fp::ZERO, |acc, byte| {
+        acc * Fp::from(256) + Fp::from(*byte as u64)
+    });
+    assert_eq!(from_bytes_reduced::<Fp>(&bytes), expected);
+}
+
+#[test]
+fn test_from_bytes_reduced_endianness() {
+    // The byte order must not depend on the target's native endianness.
+    let mut bytes = [0u8; 32];
+    bytes[0] = 1;
+    assert_eq!(from_bytes_reduced::<Fp>(&bytes), Fp::ONE);
+
+    let mut bytes = [0u8; 32];
+    bytes[1] = 1;
+    assert_eq!(from_bytes_reduced::<Fp>(&bytes), Fp::from(256));
+
+    let mut bytes = [0u8; 32];
+    bytes[31] = 1;
+    assert_eq!(
+        from_bytes_reduced::<Fp>(&bytes),
+        Fp::from(2).pow_vartime([248])
+    );
+
+    let repr = Fp::from(0x0102).to_repr();
+    assert_eq!(&repr[..3], &[2, 1, 0]);
+}
+
+#[test]
+fn test_batch_normalize() {
+    use crate::halo2curves::pasta::{Eq, EqAffine};
+    use group::Group;
+
+    let points = vec![Eq::random(OsRng), Eq::identity(), Eq::random(OsRng)];
+    let affine = batch_normalize::<EqAffine>(&points);
+    assert_eq!(
+        affine,
+        points.iter().map(|p| p.to_affine()).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,232 @@
+//! Constant-time arithmetic for secret data.
+//!
+//! The functions in this module take the same time whatever the values they are given,
+//! as long as the field and group operations they use are constant-time themselves.
+//! Their running time may depend on the lengths of their inputs.
+//!
+//! Other parts of the crate, such as multi-scalar multiplication, run in variable time.
+//! With the `ct-audit` feature, these report themselves through [`vartime`], which
+//! panics if it is called within [`secret`]. This catches variable-time operations
+//! creeping into code that handles secret data.
+//!
+//! Code within [`secret`] can run a variable-time operation as an audited exception
+//! through [`vartime_exception`], which states why it is acceptable. The prover
+//! handles the witness inside [`secret`], and commits to it this way because
+//! multi-scalar multiplication is variable-time.
+//!
+//! The audit only sees the thread that entered [`secret`]. Work that such code hands to
+//! rayon workers is not checked, so variable-time operations must call [`vartime`]
+//! before they split their work across threads.
+
+use ff::Field;
+pub use subtle::{Choice, ConditionallySelectable};
+
+use super::multicore;
+
+#[cfg(feature = "ct-audit")]
+thread_local! {
+    // The number of nested `secret` scopes on this thread. Threads spawned within a
+    // scope, including rayon workers, start at zero.
+    static SECRET_DEPTH: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+/// Inverts every nonzero element of `values` in place, leaving zeros unchanged, and
+/// returns the inverse of the product of the nonzero elements.
+///
+/// This uses Montgomery's trick on one chunk of `values` per thread. Zeros are skipped
+/// with [`ConditionallySelectable::conditional_select`] rather than by branching, so
+/// the running time does not reveal which elements are zero.
+pub fn batch_invert<F: Field>(values: &mut [F]) -> F {
+    let num_threads = multicore::current_num_threads();
+    let chunk_size = std::cmp::max((values.len() + num_threads - 1) / num_threads, 1);
+    let mut products = vec![F::ONE; (values.len() + chunk_size - 1) / chunk_size];
+
+    multicore::scope(|scope| {
+        for (chunk, product) in values.chunks_mut(chunk_size).zip(products.iter_mut()) {
+            scope.spawn(move |_| {
+                *product = batch_invert_chunk(chunk);
+            });
+        }
+    });
+
+    products.into_iter().product()
+}
+
+fn batch_invert_chunk<F: Field>(values: &mut [F]) -> F {
+    // prefixes[i] is the product of the nonzero elements before values[i].
+    let mut prefixes = Vec::with_capacity(values.len());
+    let mut acc = F::ONE;
+    for value in values.iter() {
+        prefixes.push(acc);
+        acc = F::conditional_select(&(acc * value), &acc, value.is_zero());
+    }
+
+    // The product of nonzero elements is never zero, so this inversion succeeds.
+    let inverse = acc.invert().unwrap();
+
+    let mut acc = inverse;
+    for (value, prefix) in values.iter_mut().zip(prefixes).rev() {
+        let is_zero = value.is_zero();
+        let value_inv = acc * prefix;
+        acc = F::conditional_select(&(acc * *value), &acc, is_zero);
+        *value = F::conditional_select(&value_inv, &*value, is_zero);
+    }
+
+    inverse
+}
+
+/// Returns `a` if `choice` is 0, or `b` if `choice` is 1, without branching on
+/// `choice`.
+///
+/// This works for field elements as well as curve points. There is no need for it
+/// when `choice` is public.
+pub fn conditional_select<T: ConditionallySelectable>(a: &T, b: &T, choice: Choice) -> T {
+    T::conditional_select(a, b, choice)
+}
+
+/// Runs `f`, which handles secret data.
+///
+/// With the `ct-audit` feature, any call to [`vartime`] on this thread while `f` runs
+/// panics. Calls on other threads, including the rayon workers that `f` uses, are not
+/// checked. Otherwise this just calls `f`.
+pub fn secret<R>(f: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "ct-audit")]
+    {
+        struct Guard;
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                SECRET_DEPTH.with(|depth| depth.set(depth.get() - 1));
+            }
+        }
+
+        SECRET_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        let _guard = Guard;
+        f()
+    }
+
+    #[cfg(not(feature = "ct-audit"))]
+    f()
+}
+
+/// Runs `f`, which runs variable-time operations on secret data, as an audited
+/// exception to [`secret`], for the given `reason`.
+///
+/// With the `ct-audit` feature, calls to [`vartime`] within `f` do not panic, and the
+/// audit resumes once `f` returns. Otherwise this just calls `f`.
+pub fn vartime_exception<R>(reason: &'static str, f: impl FnOnce() -> R) -> R {
+    let _ = reason;
+
+    #[cfg(feature = "ct-audit")]
+    {
+        struct Guard(usize);
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                SECRET_DEPTH.with(|depth| depth.set(self.0));
+            }
+        }
+
+        let _guard = Guard(SECRET_DEPTH.with(|depth| depth.replace(0)));
+        f()
+    }
+
+    #[cfg(not(feature = "ct-audit"))]
+    f()
+}
+
+/// Reports that the variable-time operation `op` is about to run.
+///
+/// # Panics
+///
+/// With the `ct-audit` feature, panics if called within [`secret`] on the same thread.
+#[inline]
+pub fn vartime(op: &'static str) {
+    #[cfg(feature = "ct-audit")]
+    SECRET_DEPTH.with(|depth| {
+        assert_eq!(
+            depth.get(),
+            0,
+            "variable-time operation `{}` ran on secret data",
+            op
+        )
+    });
+
+    #[cfg(not(feature = "ct-audit"))]
+    let _ = op;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::halo2curves::pasta::{Ep, Fp};
+    use group::Group;
+    use rand_core::OsRng;
+
+    #[test]
+    fn batch_invert_skips_zeros() {
+        for len in [0, 1, 2, 7, 100] {
+            let mut values = (0..len).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+            for value in values.iter_mut().step_by(3) {
+                *value = Fp::ZERO;
+            }
+            let expected = values
+                .iter()
+                .map(|value| value.invert().unwrap_or(Fp::ZERO))
+                .collect::<Vec<_>>();
+            let product = values
+                .iter()
+                .filter(|value| !bool::from(value.is_zero()))
+                .product::<Fp>();
+
+            let inverse = batch_invert(&mut values);
+            assert_eq!(values, expected);
+            assert_eq!(inverse * product, Fp::ONE);
+        }
+    }
+
+    #[test]
+    fn conditional_select_points() {
+        let a = Ep::random(OsRng);
+        let b = Ep::random(OsRng);
+        assert_eq!(conditional_select(&a, &b, Choice::from(0)), a);
+        assert_eq!(conditional_select(&a, &b, Choice::from(1)), b);
+    }
+
+    #[test]
+    fn vartime_outside_secret() {
+        vartime("test");
+        secret(|| ());
+        vartime("test");
+    }
+
+    #[cfg(feature = "ct-audit")]
+    #[test]
+    #[should_panic(expected = "variable-time operation `test` ran on secret data")]
+    fn vartime_within_secret() {
+        secret(|| secret(|| ()));
+        secret(|| vartime("test"));
+    }
+
+    #[test]
+    fn vartime_in_exception() {
+        secret(|| vartime_exception("test", || secret(|| ())));
+        secret(|| vartime_exception("test", || vartime("test")));
+    }
+
+    #[cfg(feature = "ct-audit")]
+    #[test]
+    #[should_panic(expected = "variable-time operation `test` ran on secret data")]
+    fn vartime_after_exception() {
+        secret(|| {
+            vartime_exception("test", || ());
+            vartime("test");
+        });
+    }
+
+    #[test]
+    fn vartime_on_other_thread() {
+        // The audit is per thread, so this is not caught.
+        secret(|| std::thread::spawn(|| vartime("test")).join().unwrap());
+    }
+}
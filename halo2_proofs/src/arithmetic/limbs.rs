@@ -0,0 +1,89 @@
+//! Conversions between 32-byte field representations and `u32` limbs.
+//!
+//! Both are little-endian: byte `i` of a representation is byte `i % 4` of limb
+//! `i / 4`, and limb 0 is the least significant. The conversions go through
+//! `from_le_bytes` and `to_le_bytes` rather than reinterpreting memory, so they give
+//! the same limbs on big-endian targets.
+
+// Only the GPU glue converts to limbs.
+#![cfg_attr(not(feature = "icicle_gpu"), allow(dead_code))]
+
+use group::ff::PrimeField;
+
+/// Converts a little-endian byte representation into little-endian `u32` limbs.
+pub(crate) fn u32_from_u8(u8_arr: &[u8; 32]) -> [u32; 8] {
+    let mut t = [0u32; 8];
+    for (limb, bytes) in t.iter_mut().zip(u8_arr.chunks_exact(4)) {
+        *limb = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    t
+}
+
+/// Converts little-endian `u32` limbs into a little-endian byte representation.
+pub(crate) fn u8_from_u32(u32_arr: &[u32; 8]) -> [u8; 32] {
+    let mut t = [0u8; 32];
+    for (bytes, limb) in t.chunks_exact_mut(4).zip(u32_arr.iter()) {
+        bytes.copy_from_slice(&limb.to_le_bytes());
+    }
+    t
+}
+
+/// Converts a field element into `u32` limbs, assuming a 32-byte little-endian
+/// representation.
+pub(crate) fn u32_from_field<F: PrimeField>(value: &F) -> [u32; 8] {
+    let repr = value.to_repr();
+    u32_from_u8(
+        repr.as_ref()
+            .try_into()
+            .expect("field representation should be 32 bytes"),
+    )
+}
+
+/// Converts `u32` limbs into a field element, assuming a 32-byte little-endian
+/// representation. Returns `None` if the limbs do not encode a canonical element.
+pub(crate) fn field_from_u32<F: PrimeField>(u32_arr: &[u32; 8]) -> Option<F> {
+    let mut repr = F::Repr::default();
+    repr.as_mut().copy_from_slice(&u8_from_u32(u32_arr));
+    F::from_repr(repr).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::ff::Field;
+    use halo2curves::pasta::{Fp, Fq};
+    use rand_core::OsRng;
+
+    #[test]
+    fn limbs_are_little_endian() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0x01;
+        bytes[1] = 0x02;
+        bytes[4] = 0x03;
+        bytes[31] = 0x04;
+
+        let limbs = u32_from_u8(&bytes);
+        assert_eq!(limbs, [0x0201, 0x03, 0, 0, 0, 0, 0, 0x0400_0000]);
+        assert_eq!(u8_from_u32(&limbs), bytes);
+
+        assert_eq!(
+            u32_from_field(&Fp::from(0x0102_0304_0506)),
+            [0x0304_0506, 0x0102, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn fields_round_trip() {
+        for _ in 0..16 {
+            let fp = Fp::random(OsRng);
+            assert_eq!(field_from_u32::<Fp>(&u32_from_field(&fp)), Some(fp));
+            let fq = Fq::random(OsRng);
+            assert_eq!(field_from_u32::<Fq>(&u32_from_field(&fq)), Some(fq));
+        }
+
+        // The modulus is not a canonical encoding.
+        let mut modulus = u32_from_field(&-Fp::ONE);
+        modulus[0] += 1;
+        assert_eq!(field_from_u32::<Fp>(&modulus), None);
+    }
+}
@@ -0,0 +1,414 @@
+//! Backends for multi-scalar multiplication.
+//!
+//! Commitments and the evaluation of [`MSM`]s go through [`best_multiexp`], which
+//! dispatches to the [`MsmBackend`] registered for the curve with [`set_backend`].
+//! Without a registered backend, [`Cpu`] is used, or [`Icicle`] when the `icicle_gpu`
+//! feature is enabled and the `ENABLE_ICICLE_GPU` environment variable is set.
+//!
+//! Multiplications over bases that are used many times, such as the bases of the
+//! commitment parameters, can instead use a [`FixedBaseTable`] of precomputed
+//! multiples of the bases.
+//!
+//! [`MSM`]: crate::poly::commitment::MSM
+
+use std::any::{Any, TypeId};
+use std::mem::size_of;
+use std::sync::{Arc, RwLock};
+
+use ff::PrimeField;
+use group::{prime::PrimeCurveAffine, Curve, Group};
+
+use super::{best_multiexp_cpu, ct, multicore, parallelize, CurveAffine};
+
+/// The bases of a multi-scalar multiplication.
+///
+/// Backends that keep the bases of the commitment parameters in their own memory,
+/// such as GPUs, can use this to avoid copying them for every multiplication. The
+/// bases of the parameters are identified by the
+/// [fingerprint](crate::poly::commitment::Params::fingerprint) of the parameters,
+/// when the parameters have loaded them into such a backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bases {
+    /// A prefix of the bases of the commitment parameters in monomial form.
+    G {
+        /// The fingerprint of the parameters, if they have loaded their bases.
+        fingerprint: Option<[u8; 32]>,
+    },
+    /// A prefix of the bases of the commitment parameters in Lagrange form.
+    GLagrange {
+        /// The fingerprint of the parameters, if they have loaded their bases.
+        fingerprint: Option<[u8; 32]>,
+    },
+    /// Any other bases.
+    Other,
+}
+
+/// A backend for multi-scalar multiplication over the curve `C`.
+pub trait MsmBackend<C: CurveAffine>: Send + Sync {
+    /// Returns the name of the backend.
+    fn name(&self) -> &'static str;
+
+    /// Returns $\sum_i \mathsf{coeffs}_i \cdot \mathsf{bases}_i$.
+    ///
+    /// `coeffs` and `bases` have the same length, and `kind` describes `bases`.
+    fn multiexp(&self, coeffs: &[C::Scalar], bases: &[C], kind: Bases) -> C::Curve;
+}
+
+/// Multi-scalar multiplication on the CPU, with Pippenger's algorithm.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Cpu;
+
+impl<C: CurveAffine> MsmBackend<C> for Cpu {
+    fn name(&self) -> &'static str {
+        "cpu-pippenger"
+    }
+
+    fn multiexp(&self, coeffs: &[C::Scalar], bases: &[C], _: Bases) -> C::Curve {
+        best_multiexp_cpu(coeffs, bases)
+    }
+}
+
+/// Multi-scalar multiplication on a CUDA GPU, with the Icicle library.
+///
+/// Only multiplications over the BN254 $\mathbb{G}_1$ bases of parameters loaded on
+/// the GPU, identified by their fingerprint, are supported; other curves and bases,
+/// and multiplications too small to benefit from the GPU, run on the CPU.
+#[cfg(feature = "icicle_gpu")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Icicle;
+
+#[cfg(feature = "icicle_gpu")]
+impl<C: CurveAffine> MsmBackend<C> for Icicle {
+    fn name(&self) -> &'static str {
+        "cuda-icicle"
+    }
+
+    fn multiexp(&self, coeffs: &[C::Scalar], bases: &[C], kind: Bases) -> C::Curve {
+        // The GPU kernels are specific to BN254.
+        if TypeId::of::<C>() != TypeId::of::<halo2curves::bn256::G1Affine>() {
+            return best_multiexp_cpu(coeffs, bases);
+        }
+        let (fingerprint, is_lagrange) = match kind {
+            Bases::G {
+                fingerprint: Some(fingerprint),
+            } => (fingerprint, false),
+            Bases::GLagrange {
+                fingerprint: Some(fingerprint),
+            } => (fingerprint, true),
+            _ => return best_multiexp_cpu(coeffs, bases),
+        };
+        if crate::icicle::should_use_cpu_msm(coeffs.len()) {
+            return best_multiexp_cpu(coeffs, bases);
+        }
+        super::best_multiexp_gpu::<C>(coeffs, fingerprint, is_lagrange)
+            .unwrap_or_else(|| best_multiexp_cpu(coeffs, bases))
+    }
+}
+
+// The registered backends, each an `Arc<dyn MsmBackend<C>>` keyed by the type of `C`.
+static BACKENDS: RwLock<Vec<(TypeId, Box<dyn Any + Send + Sync>)>> = RwLock::new(Vec::new());
+
+/// Registers `backend` for multi-scalar multiplications over `C`, replacing any
+/// backend registered before.
+pub fn set_backend<C: CurveAffine>(backend: Arc<dyn MsmBackend<C>>) {
+    let mut backends = BACKENDS.write().unwrap();
+    backends.retain(|(curve, _)| *curve != TypeId::of::<C>());
+    backends.push((TypeId::of::<C>(), Box::new(backend)));
+}
+
+/// Removes the backend registered for `C`, if any, so that the default is used.
+pub fn clear_backend<C: CurveAffine>() {
+    let mut backends = BACKENDS.write().unwrap();
+    backends.retain(|(curve, _)| *curve != TypeId::of::<C>());
+}
+
+/// Returns the backend used for multi-scalar multiplications over `C`.
+pub fn backend<C: CurveAffine>() -> Arc<dyn MsmBackend<C>> {
+    let registered = BACKENDS
+        .read()
+        .unwrap()
+        .iter()
+        .find(|(curve, _)| *curve == TypeId::of::<C>())
+        .and_then(|(_, backend)| backend.downcast_ref::<Arc<dyn MsmBackend<C>>>().cloned());
+
+    registered.unwrap_or_else(|| {
+        #[cfg(feature = "icicle_gpu")]
+        if std::env::var("ENABLE_ICICLE_GPU").is_ok() {
+            return Arc::new(Icicle);
+        }
+
+        Arc::new(Cpu)
+    })
+}
+
+/// Performs a multi-scalar multiplication with the backend registered for `C`.
+///
+/// This function will panic if coeffs and bases have a different length.
+pub fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], kind: Bases) -> C::Curve {
+    assert_eq!(coeffs.len(), bases.len());
+    backend::<C>().multiexp(coeffs, bases, kind)
+}
+
+/// The largest window size of a [`FixedBaseTable`]. Evaluating a table allocates
+/// $2^\mathsf{window}$ buckets per thread.
+pub const MAX_WINDOW_SIZE: usize = 20;
+
+/// The configuration of a [`FixedBaseTable`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PrecomputeConfig {
+    /// The number of bits of the scalars covered by each precomputed multiple of a
+    /// base, at most [`MAX_WINDOW_SIZE`]. If `None`, it is chosen from the number of
+    /// bases.
+    pub window_size: Option<usize>,
+    /// The largest size of the table in bytes, if any. The window size is increased
+    /// until the table fits.
+    pub memory_budget: Option<usize>,
+}
+
+/// Precomputed multiples of fixed bases, for multi-scalar multiplications over them.
+///
+/// For a window size $c$, the table holds $2^{cj} \cdot G_i$ for every base $G_i$ and
+/// every window $j$ of the scalars. A multiplication then adds each point to the
+/// bucket of its digit once, with no doublings, and sums the buckets once rather than
+/// once per window as Pippenger's algorithm does.
+#[derive(Clone, Debug)]
+pub struct FixedBaseTable<C: CurveAffine> {
+    window_size: usize,
+    num_windows: usize,
+    points: Vec<C>,
+}
+
+impl<C: CurveAffine> FixedBaseTable<C> {
+    /// Precomputes the table for `bases`.
+    ///
+    /// Returns `None` if the table does not fit in the memory budget of `config` with
+    /// any window size up to [`MAX_WINDOW_SIZE`].
+    pub fn new(bases: &[C], config: PrecomputeConfig) -> Option<Self> {
+        let window_size = config
+            .window_size
+            .unwrap_or_else(|| default_window_size(bases.len()))
+            .clamp(1, MAX_WINDOW_SIZE);
+        let window_size = (window_size..=MAX_WINDOW_SIZE).find(|&window_size| {
+            config.memory_budget.map_or(true, |budget| {
+                bases.len() * num_windows::<C>(window_size) * size_of::<C>() <= budget
+            })
+        })?;
+        let num_windows = num_windows::<C>(window_size);
+
+        let mut points = vec![C::identity(); bases.len() * num_windows];
+        parallelize(&mut points, |points, start| {
+            let mut projective = vec![C::Curve::identity(); points.len()];
+            let mut current = C::Curve::identity();
+            for (idx, point) in projective.iter_mut().enumerate() {
+                let (i, j) = ((start + idx) / num_windows, (start + idx) % num_windows);
+                if j == 0 {
+                    current = bases[i].to_curve();
+                } else if idx == 0 {
+                    // This chunk starts in the middle of the multiples of a base.
+                    current = bases[i].to_curve();
+                    for _ in 0..j * window_size {
+                        current = current.double();
+                    }
+                } else {
+                    for _ in 0..window_size {
+                        current = current.double();
+                    }
+                }
+                *point = current;
+            }
+            C::Curve::batch_normalize(&projective, points);
+        });
+
+        Some(FixedBaseTable {
+            window_size,
+            num_windows,
+            points,
+        })
+    }
+
+    /// Returns the number of bases in the table.
+    pub fn len(&self) -> usize {
+        self.points.len() / self.num_windows
+    }
+
+    /// Returns `true` if the table has no bases.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the window size of the table.
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Returns the size of the precomputed points in bytes.
+    pub fn memory_size(&self) -> usize {
+        self.points.len() * size_of::<C>()
+    }
+
+    /// Keeps the first `len` bases of the table and drops the others.
+    pub fn truncate(&mut self, len: usize) {
+        self.points.truncate(len * self.num_windows);
+    }
+
+    /// Returns $\sum_i \mathsf{coeffs}_i \cdot G_i$ over the first `coeffs.len()`
+    /// bases $G_i$ of the table.
+    ///
+    /// This function will panic if there are more coeffs than bases.
+    pub fn multiexp(&self, coeffs: &[C::Scalar]) -> C::Curve {
+        assert!(coeffs.len() <= self.len());
+        ct::vartime("fixed_base_multiexp");
+
+        let num_threads = multicore::current_num_threads();
+        let chunk_size = std::cmp::max((coeffs.len() + num_threads - 1) / num_threads, 1);
+        let mut results = vec![C::Curve::identity(); (coeffs.len() + chunk_size - 1) / chunk_size];
+
+        multicore::scope(|scope| {
+            for ((coeffs, points), result) in coeffs
+                .chunks(chunk_size)
+                .zip(self.points.chunks(chunk_size * self.num_windows))
+                .zip(results.iter_mut())
+            {
+                scope.spawn(move |_| {
+                    *result = self.multiexp_chunk(coeffs, points);
+                });
+            }
+        });
+
+        results.into_iter().sum()
+    }
+
+    fn multiexp_chunk(&self, coeffs: &[C::Scalar], points: &[C]) -> C::Curve {
+        // buckets[d - 1] is the sum of the points whose digit is d.
+        let mut buckets = vec![C::Curve::identity(); (1 << self.window_size) - 1];
+        for (coeff, points) in coeffs.iter().zip(points.chunks(self.num_windows)) {
+            let repr = coeff.to_repr();
+            for (j, point) in points.iter().enumerate() {
+                let digit = get_digit(repr.as_ref(), j * self.window_size, self.window_size);
+                if digit != 0 {
+                    buckets[digit - 1] += point;
+                }
+            }
+        }
+
+        // Computes sum_d d * buckets[d - 1] with a running sum.
+        let mut running_sum = C::Curve::identity();
+        let mut acc = C::Curve::identity();
+        for bucket in buckets.into_iter().rev() {
+            running_sum += bucket;
+            acc += running_sum;
+        }
+        acc
+    }
+}
+
+fn num_windows<C: CurveAffine>(window_size: usize) -> usize {
+    (C::Scalar::NUM_BITS as usize + window_size - 1) / window_size
+}
+
+fn default_window_size(num_bases: usize) -> usize {
+    let log_bases = usize::BITS - num_bases.leading_zeros();
+    (log_bases as usize).saturating_sub(2).clamp(4, 16)
+}
+
+// Returns the `len` bits of the little-endian `bytes` starting at bit `start`.
+fn get_digit(bytes: &[u8], start: usize, len: usize) -> usize {
+    (0..len)
+        .map(|i| start + i)
+        .take_while(|bit| bit / 8 < bytes.len())
+        .fold(0, |digit, bit| {
+            digit | (((bytes[bit / 8] >> (bit % 8)) & 1) as usize) << (bit - start)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::halo2curves::bn256::{Fr, G2Affine, G2};
+    use crate::halo2curves::pasta::{Ep, EpAffine, Fq};
+    use ff::Field;
+    use rand_core::OsRng;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Counts the multiplications it performs on the CPU. Registered for G2, which no
+    // other test multiplies over.
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    impl MsmBackend<G2Affine> for Counting {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn multiexp(&self, coeffs: &[Fr], bases: &[G2Affine], kind: Bases) -> G2 {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Cpu.multiexp(coeffs, bases, kind)
+        }
+    }
+
+    #[test]
+    fn registered_backend() {
+        let coeffs = (0..10).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+        let bases = (0..10)
+            .map(|_| G2::random(OsRng).to_affine())
+            .collect::<Vec<_>>();
+        let expected = coeffs
+            .iter()
+            .zip(bases.iter())
+            .fold(G2::identity(), |acc, (coeff, base)| acc + *base * *coeff);
+
+        let counting = Arc::new(Counting::default());
+        set_backend::<G2Affine>(counting.clone());
+        assert_eq!(backend::<G2Affine>().name(), "counting");
+        assert_eq!(best_multiexp(&coeffs, &bases, Bases::Other), expected);
+        assert_eq!(counting.0.load(Ordering::SeqCst), 1);
+
+        clear_backend::<G2Affine>();
+        assert_eq!(best_multiexp(&coeffs, &bases, Bases::Other), expected);
+        assert_eq!(counting.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn fixed_base_table() {
+        let bases = (0..50)
+            .map(|_| Ep::random(OsRng).to_affine())
+            .collect::<Vec<_>>();
+        let coeffs = (0..50).map(|_| Fq::random(OsRng)).collect::<Vec<_>>();
+
+        for window_size in [None, Some(1), Some(5), Some(8)] {
+            let config = PrecomputeConfig {
+                window_size,
+                memory_budget: None,
+            };
+            let table = FixedBaseTable::<EpAffine>::new(&bases, config).unwrap();
+            assert_eq!(table.len(), bases.len());
+            for len in [0, 1, 17, 50] {
+                assert_eq!(
+                    table.multiexp(&coeffs[..len]),
+                    best_multiexp_cpu(&coeffs[..len], &bases[..len])
+                );
+            }
+        }
+
+        // The window size grows until the table fits in the budget.
+        let config = PrecomputeConfig {
+            window_size: Some(4),
+            memory_budget: Some(50 * 26 * size_of::<EpAffine>()),
+        };
+        let mut table = FixedBaseTable::<EpAffine>::new(&bases, config).unwrap();
+        assert_eq!(table.window_size(), 10);
+        assert!(table.memory_size() <= config.memory_budget.unwrap());
+        table.truncate(20);
+        assert_eq!(table.len(), 20);
+        assert_eq!(
+            table.multiexp(&coeffs[..20]),
+            best_multiexp_cpu(&coeffs[..20], &bases[..20])
+        );
+
+        let config = PrecomputeConfig {
+            window_size: None,
+            memory_budget: Some(size_of::<EpAffine>()),
+        };
+        assert!(FixedBaseTable::<EpAffine>::new(&bases, config).is_none());
+    }
+}
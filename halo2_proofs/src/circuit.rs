@@ -435,6 +435,10 @@ pub trait Layouter<F: Field> {
     /// treat these assignments as a single "region" within the circuit. Outside this
     /// closure, the `Layouter` is allowed to optimise as it sees fit.
     ///
+    /// The floor planners in this crate place a region on the same rows as earlier
+    /// regions whenever their columns (including selectors) are disjoint, so chips that
+    /// use separate columns do not need to share a region to share rows.
+    ///
     /// ```ignore
     /// fn assign_region(&mut self, || "region name", |region| {
     ///     let config = chip.config();
@@ -470,6 +474,24 @@ pub trait Layouter<F: Field> {
         row: usize,
     ) -> Result<(), Error>;
 
+    /// Constrains a [`Cell`] to have a constant value.
+    ///
+    /// This is [`Region::constrain_constant`] for cells that have already been assigned
+    /// in another region. The constant value will be assigned to a cell within one of
+    /// the fixed columns configured via `ConstraintSystem::enable_constant`.
+    ///
+    /// Returns an error if the cell is in a column where equality has not been enabled.
+    fn constrain_constant<VR>(&mut self, cell: Cell, constant: VR) -> Result<(), Error>
+    where
+        VR: Into<Assigned<F>>,
+    {
+        let constant = constant.into();
+        self.assign_region(
+            || "constrain constant",
+            |mut region| region.constrain_constant(cell, constant),
+        )
+    }
+
     /// Queries the value of the given challenge.
     ///
     /// Returns `Value::unknown()` if the current synthesis phase is before the challenge can be queried.
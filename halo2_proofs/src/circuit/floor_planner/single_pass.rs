@@ -1,7 +1,6 @@
 use rustc_hash::FxHashMap as HashMap;
 use std::cmp;
 use std::fmt;
-use std::marker::PhantomData;
 
 use ff::Field;
 
@@ -32,7 +31,8 @@ impl FloorPlanner for SimpleFloorPlanner {
         config: C::Config,
         constants: Vec<Column<Fixed>>,
     ) -> Result<(), Error> {
-        let layouter = SingleChipLayouter::new(cs, constants)?;
+        let mut layouter = SingleChipLayouter::new(cs, constants)?;
+        layouter.deduplicate_constants = C::deduplicate_constants();
         circuit.synthesize(config, layouter)
     }
 }
@@ -47,7 +47,10 @@ pub struct SingleChipLayouter<'a, F: Field, CS: Assignment<F> + 'a> {
     columns: HashMap<RegionColumn, usize>,
     /// Stores the table fixed columns.
     table_columns: Vec<TableColumn>,
-    _marker: PhantomData<F>,
+    /// Whether constants with the same value share a fixed cell.
+    deduplicate_constants: bool,
+    /// Stores the constants assigned so far, and the rows they were assigned to.
+    assigned_constants: Vec<(F, usize)>,
 }
 
 impl<'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for SingleChipLayouter<'a, F, CS> {
@@ -68,7 +71,8 @@ impl<'a, F: Field, CS: Assignment<F>> SingleChipLayouter<'a, F, CS> {
             regions: vec![],
             columns: HashMap::default(),
             table_columns: vec![],
-            _marker: PhantomData,
+            deduplicate_constants: false,
+            assigned_constants: vec![],
         };
         Ok(ret)
     }
@@ -118,7 +122,8 @@ impl<'a, F: Field, CS: Assignment<F> + 'a + SyncDeps> Layouter<F>
         self.cs.exit_region();
 
         // Assign constants. For the simple floor planner, we assign constants in order in
-        // the first `constants` column.
+        // the first `constants` column. If the circuit deduplicates constants, we reuse
+        // the cell of any value assigned before.
         if self.constants.is_empty() {
             if !constants_to_assign.is_empty() {
                 return Err(Error::NotEnoughColumnsForConstants);
@@ -130,19 +135,35 @@ impl<'a, F: Field, CS: Assignment<F> + 'a + SyncDeps> Layouter<F>
                 .entry(Column::<Any>::from(constants_column).into())
                 .or_default();
             for (constant, advice) in constants_to_assign {
-                self.cs.assign_fixed(
-                    || format!("Constant({:?})", constant.evaluate()),
-                    constants_column,
-                    *next_constant_row,
-                    || Value::known(constant),
-                )?;
+                let value = constant.evaluate();
+                let assigned_row = self
+                    .assigned_constants
+                    .iter()
+                    .find(|(assigned, _)| *assigned == value)
+                    .map(|(_, row)| *row);
+                let constant_row = match assigned_row {
+                    Some(row) => row,
+                    None => {
+                        let row = *next_constant_row;
+                        self.cs.assign_fixed(
+                            || format!("Constant({:?})", value),
+                            constants_column,
+                            row,
+                            || Value::known(constant),
+                        )?;
+                        if self.deduplicate_constants {
+                            self.assigned_constants.push((value, row));
+                        }
+                        *next_constant_row += 1;
+                        row
+                    }
+                };
                 self.cs.copy(
                     constants_column.into(),
-                    *next_constant_row,
+                    constant_row,
                     advice.column,
                     *self.regions[*advice.region_index] + advice.row_offset,
                 )?;
-                *next_constant_row += 1;
             }
         }
 
@@ -382,8 +403,9 @@ mod tests {
 
     use super::SimpleFloorPlanner;
     use crate::{
-        dev::MockProver,
-        plonk::{Advice, Circuit, Column, Error},
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::{CellValue, MockProver},
+        plonk::{Advice, Circuit, Column, Error, FloorPlanner},
     };
 
     #[test]
@@ -431,4 +453,169 @@ mod tests {
             Error::NotEnoughColumnsForConstants,
         ));
     }
+
+    #[test]
+    fn constrain_constant_from_layouter() {
+        struct MyCircuit(u64);
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit(self.0)
+            }
+
+            fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                let constants = meta.fixed_column();
+                meta.enable_constant(constants);
+                let advice = meta.advice_column();
+                meta.enable_equality(advice);
+                advice
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), crate::plonk::Error> {
+                let cell = layouter.assign_region(
+                    || "assign value",
+                    |mut region| {
+                        region.assign_advice(
+                            || "value",
+                            config,
+                            0,
+                            || Value::known(vesta::Scalar::from(self.0)),
+                        )
+                    },
+                )?;
+                layouter.constrain_constant(cell.cell(), vesta::Scalar::one())
+            }
+        }
+
+        let prover = MockProver::run(3, &MyCircuit(1), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(3, &MyCircuit(2), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn constants_are_deduplicated() {
+        struct MyCircuit<P, const DEDUPLICATE: bool>(std::marker::PhantomData<P>);
+
+        impl<P: FloorPlanner, const DEDUPLICATE: bool> Circuit<vesta::Scalar>
+            for MyCircuit<P, DEDUPLICATE>
+        {
+            type Config = Column<Advice>;
+            type FloorPlanner = P;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit(std::marker::PhantomData)
+            }
+
+            fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                let constants = meta.fixed_column();
+                meta.enable_constant(constants);
+                let advice = meta.advice_column();
+                meta.enable_equality(advice);
+                advice
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), crate::plonk::Error> {
+                for (name, value) in [("a", 1), ("b", 2), ("c", 1)] {
+                    layouter.assign_region(
+                        || name,
+                        |mut region| {
+                            region.assign_advice_from_constant(
+                                || name,
+                                config,
+                                0,
+                                vesta::Scalar::from(value),
+                            )
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+
+            fn deduplicate_constants() -> bool {
+                DEDUPLICATE
+            }
+        }
+
+        fn assigned_constants<P: FloorPlanner, const DEDUPLICATE: bool>() -> usize {
+            let circuit = MyCircuit::<P, DEDUPLICATE>(std::marker::PhantomData);
+            let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+            prover.fixed()[0]
+                .iter()
+                .filter(|cell| matches!(cell, CellValue::Assigned(_)))
+                .count()
+        }
+
+        assert_eq!(assigned_constants::<SimpleFloorPlanner, true>(), 2);
+        assert_eq!(assigned_constants::<V1, true>(), 2);
+
+        // By default, every constrained cell gets its own constant.
+        assert_eq!(assigned_constants::<SimpleFloorPlanner, false>(), 3);
+        assert_eq!(assigned_constants::<V1, false>(), 3);
+    }
+
+    #[test]
+    fn disjoint_regions_share_rows() {
+        // Each region uses 6 rows, and only 10 rows are usable at k = 4.
+        struct MyCircuit;
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = [Column<Advice>; 2];
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit
+            }
+
+            fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                [meta.advice_column(), meta.advice_column()]
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), crate::plonk::Error> {
+                for column in config {
+                    layouter.assign_region(
+                        || "region",
+                        |mut region| {
+                            for offset in 0..6 {
+                                region.assign_advice(
+                                    || "value",
+                                    column,
+                                    offset,
+                                    || Value::known(vesta::Scalar::one()),
+                                )?;
+                            }
+                            Ok(())
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+        }
+
+        let prover = MockProver::run(4, &MyCircuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
 }
@@ -24,6 +24,10 @@ mod strategy;
 /// - Regions are measured as rectangles, bounded on the cells they assign.
 /// - Regions are laid out using a greedy first-fit strategy, after sorting regions by
 ///   their "advice area" (number of advice columns * rows).
+///
+/// Unlike [`SimpleFloorPlanner`](crate::circuit::SimpleFloorPlanner), which places each
+/// region after the previous one, regions that use disjoint columns can share rows. This
+/// can reduce the `k` required by circuits with many small regions.
 #[derive(Debug)]
 pub struct V1;
 
@@ -115,25 +119,43 @@ impl FloorPlanner for V1 {
             circuit.synthesize(config, V1Pass::assign(pass))?;
         }
 
-        // - Assign the constants.
-        if constant_positions().count() < plan.constants.len() {
+        // - Assign the constants, once per distinct value if the circuit deduplicates
+        //   them, and otherwise once per constrained cell.
+        let mut distinct_constants: Vec<(F, Assigned<F>, Vec<Cell>)> = vec![];
+        for (constant, advice) in plan.constants {
+            let value = constant.evaluate();
+            let assigned = if C::deduplicate_constants() {
+                distinct_constants
+                    .iter_mut()
+                    .find(|(assigned, _, _)| *assigned == value)
+            } else {
+                None
+            };
+            match assigned {
+                Some((_, _, cells)) => cells.push(advice),
+                None => distinct_constants.push((value, constant, vec![advice])),
+            }
+        }
+        if constant_positions().count() < distinct_constants.len() {
             return Err(Error::NotEnoughColumnsForConstants);
         }
-        for ((fixed_column, fixed_row), (value, advice)) in
-            constant_positions().zip(plan.constants.into_iter())
+        for ((fixed_column, fixed_row), (value, constant, cells)) in
+            constant_positions().zip(distinct_constants)
         {
             plan.cs.assign_fixed(
-                || format!("Constant({:?})", value.evaluate()),
+                || format!("Constant({:?})", value),
                 fixed_column,
                 fixed_row,
-                || Value::known(value),
-            )?;
-            plan.cs.copy(
-                fixed_column.into(),
-                fixed_row,
-                advice.column,
-                *plan.regions[*advice.region_index] + advice.row_offset,
+                || Value::known(constant),
             )?;
+            for advice in cells {
+                plan.cs.copy(
+                    fixed_column.into(),
+                    fixed_row,
+                    advice.column,
+                    *plan.regions[*advice.region_index] + advice.row_offset,
+                )?;
+            }
         }
 
         Ok(())
@@ -546,4 +568,85 @@ mod tests {
             Error::NotEnoughColumnsForConstants,
         ));
     }
+
+    #[test]
+    fn packs_regions_in_parallel() {
+        use std::marker::PhantomData;
+
+        use crate::{
+            circuit::{Layouter, SimpleFloorPlanner, Value},
+            plonk::{keygen_vk, ConstraintSystem, FloorPlanner, Selector},
+            poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA, Rotation},
+        };
+
+        const K: u32 = 4;
+        const NUM_REGIONS: usize = 4;
+        const REGION_ROWS: usize = 4;
+
+        // Regions that each use their own advice column and selector, so they can be
+        // laid out side by side.
+        struct MyCircuit<P>(PhantomData<P>);
+
+        impl<P: FloorPlanner> Circuit<vesta::Scalar> for MyCircuit<P> {
+            type Config = [(Column<Advice>, Selector); NUM_REGIONS];
+            type FloorPlanner = P;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit(PhantomData)
+            }
+
+            fn configure(meta: &mut ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                [(); NUM_REGIONS].map(|_| {
+                    let a = meta.advice_column();
+                    let q = meta.selector();
+                    meta.create_gate("a is zero", |meta| {
+                        let a = meta.query_advice(a, Rotation::cur());
+                        let q = meta.query_selector(q);
+                        vec![q * a]
+                    });
+                    (a, q)
+                })
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                for (a, q) in config {
+                    layouter.assign_region(
+                        || "region",
+                        |mut region| {
+                            for offset in 0..REGION_ROWS {
+                                q.enable(&mut region, offset)?;
+                                region.assign_advice(
+                                    || "a",
+                                    a,
+                                    offset,
+                                    || Value::known(vesta::Scalar::zero()),
+                                )?;
+                            }
+                            Ok(())
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+        }
+
+        let params: ParamsIPA<vesta::Affine> = ParamsIPA::new(K);
+
+        // The regions are stacked on top of each other, and do not fit.
+        assert!(matches!(
+            keygen_vk(&params, &MyCircuit::<SimpleFloorPlanner>(PhantomData)).unwrap_err(),
+            Error::NotEnoughRowsAvailable { current_k: K },
+        ));
+
+        // The regions are packed into the same rows.
+        assert!(keygen_vk(&params, &MyCircuit::<super::V1>(PhantomData)).is_ok());
+        let prover = MockProver::run(K, &MyCircuit::<super::V1>(PhantomData), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
 }
@@ -35,17 +35,33 @@ mod failure;
 pub use failure::{FailureLocation, VerifyFailure};
 
 pub mod cost;
-pub use cost::CircuitCost;
+pub use cost::{CircuitCost, NamespaceCost};
 
 #[cfg(feature = "cost-estimator")]
 pub mod cost_model;
 
+pub mod fuzz;
+pub use fuzz::UndetectedPerturbation;
+
 mod gates;
 pub use gates::CircuitGates;
 
+mod occupancy;
+pub use occupancy::{ColumnOccupancy, ColumnUsage, OccupancyColumn};
+
+mod snapshot;
+pub use snapshot::{LayoutDiff, LayoutSnapshot};
+
+mod stats;
+pub(crate) use stats::plonk_proof_stats;
+pub use stats::{proof_stats, ComponentStats, ProofStats};
+
 mod tfp;
 pub use tfp::TracingFloorPlanner;
 
+mod unused;
+pub use unused::UnconstrainedCell;
+
 #[cfg(feature = "dev-graph")]
 mod graph;
 
@@ -342,6 +358,18 @@ impl<F: Field> MockProver<F> {
     fn in_phase<P: Phase>(&self, phase: P) -> bool {
         self.current_phase == phase.to_sealed()
     }
+
+    fn unknown_value(&self, annotation: String, column: Column<Any>, row: usize) -> Error {
+        Error::UnknownValue {
+            region: self
+                .current_region
+                .as_ref()
+                .map(|region| region.name.clone()),
+            column,
+            annotation,
+            row,
+        }
+    }
 }
 
 impl<F: Field> Assignment<F> for MockProver<F> {
@@ -444,7 +472,7 @@ impl<F: Field> Assignment<F> for MockProver<F> {
 
     fn assign_advice<V, VR, A, AR>(
         &mut self,
-        _: A,
+        annotation: A,
         column: Column<Advice>,
         row: usize,
         to: V,
@@ -483,10 +511,10 @@ impl<F: Field> Assignment<F> for MockProver<F> {
                     .expect("bounds failure");
                 *value = CellValue::Assigned(to);
             }
-            Err(err) => {
+            Err(_) => {
                 // Propagate `assign` error if the column is in current phase.
                 if self.in_phase(column.column_type().phase) {
-                    return Err(err);
+                    return Err(self.unknown_value(annotation().into(), column.into(), row));
                 }
             }
         }
@@ -496,7 +524,7 @@ impl<F: Field> Assignment<F> for MockProver<F> {
 
     fn assign_fixed<V, VR, A, AR>(
         &mut self,
-        _: A,
+        annotation: A,
         column: Column<Fixed>,
         row: usize,
         to: V,
@@ -528,11 +556,16 @@ impl<F: Field> Assignment<F> for MockProver<F> {
                 .or_default();
         }
 
+        let value = to()
+            .into_field()
+            .evaluate()
+            .assign()
+            .map_err(|_| self.unknown_value(annotation().into(), column.into(), row))?;
         *self
             .fixed
             .get_mut(column.index())
             .and_then(|v| v.get_mut(row))
-            .expect("bounds failure") = CellValue::Assigned(to().into_field().evaluate().assign()?);
+            .expect("bounds failure") = CellValue::Assigned(value);
 
         Ok(())
     }
@@ -625,6 +658,7 @@ impl<F: FromUniformBytes<64> + Ord> MockProver<F> {
         #[cfg(feature = "mv-lookup")]
         let cs = cs.chunk_lookups();
         let cs = cs;
+        cs.warn_unsafe_rotations();
 
         assert!(
             n >= cs.minimum_rows(),
@@ -809,6 +843,22 @@ impl<F: FromUniformBytes<64> + Ord> MockProver<F> {
                                         let cell_row =
                                             ((gate_row + n + cell.rotation.0) % n) as usize;
 
+                                        // Rotations wrap around the domain, and the rows
+                                        // past the usable ones hold blinding factors.
+                                        let queried_row = gate_row + cell.rotation.0;
+                                        if queried_row < 0
+                                            || queried_row as usize >= self.usable_rows.end
+                                        {
+                                            return Some(VerifyFailure::QueryOutsideUsableRows {
+                                                gate: (gate_index, gate.name()).into(),
+                                                region: (r_i, r.name.clone()).into(),
+                                                gate_offset: *selector_row,
+                                                column: cell.column,
+                                                rotation: cell.rotation.0,
+                                                row: cell_row,
+                                            });
+                                        }
+
                                         match cell.column.column_type() {
                                             Any::Instance => {
                                                 // Handle instance cells, which are not in the region.
@@ -1437,8 +1487,8 @@ mod tests {
     use crate::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         plonk::{
-            sealed::SealedPhase, Advice, Any, Circuit, Column, ConstraintSystem, Error, Expression,
-            FirstPhase, Fixed, Instance, Selector, TableColumn,
+            sealed::SealedPhase, Advice, Any, Challenge, Circuit, Column, ConstraintSystem, Error,
+            Expression, FirstPhase, Fixed, Instance, SecondPhase, Selector, TableColumn,
         },
         poly::Rotation,
     };
@@ -1530,6 +1580,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn query_outside_usable_rows() {
+        const K: u32 = 4;
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = (Column<Advice>, Selector);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.selector();
+
+                meta.create_gate("Increment", |cells| {
+                    let prev = cells.query_advice(a, Rotation::prev());
+                    let cur = cells.query_advice(a, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    vec![q * (cur - prev - Expression::Constant(Fp::one()))]
+                });
+
+                (a, q)
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                (a, q): Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        // BUG: The gate is enabled on the first row of the circuit, so
+                        // its previous row is the last row, which holds blinding factors.
+                        q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", a, 0, || Value::known(Fp::one()))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(
+            prover.verify().unwrap_err()[0],
+            VerifyFailure::QueryOutsideUsableRows {
+                gate: (0, "Increment").into(),
+                region: (0, "Faulty synthesis".to_owned()).into(),
+                gate_offset: 0,
+                column: Column::new(
+                    0,
+                    Any::Advice(Advice {
+                        phase: FirstPhase.to_sealed()
+                    })
+                ),
+                rotation: -1,
+                row: (1 << K) - 1,
+            }
+        );
+    }
+
     #[test]
     fn bad_lookup_any() {
         const K: u32 = 4;
@@ -1834,6 +1952,360 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bad_shuffle() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct ShuffleCircuitConfig {
+            original: Column<Advice>,
+            shuffled: Column<Advice>,
+            q: Selector,
+        }
+
+        struct ShuffleCircuit {
+            shuffled: [u64; 4],
+        }
+
+        impl Circuit<Fp> for ShuffleCircuit {
+            type Config = ShuffleCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let original = meta.advice_column();
+                let shuffled = meta.advice_column();
+                let q = meta.complex_selector();
+
+                meta.shuffle("shuffle", |cells| {
+                    let q = cells.query_selector(q);
+                    let original = cells.query_advice(original, Rotation::cur());
+                    let shuffled = cells.query_advice(shuffled, Rotation::cur());
+
+                    vec![(q.clone() * original, q * shuffled)]
+                });
+
+                ShuffleCircuitConfig {
+                    original,
+                    shuffled,
+                    q,
+                }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    shuffled: self.shuffled,
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Shuffle",
+                    |mut region| {
+                        for (offset, shuffled) in self.shuffled.iter().enumerate() {
+                            config.q.enable(&mut region, offset)?;
+                            region.assign_advice(
+                                || "original",
+                                config.original,
+                                offset,
+                                || Value::known(Fp::from(offset as u64 + 1)),
+                            )?;
+                            region.assign_advice(
+                                || "shuffled",
+                                config.shuffled,
+                                offset,
+                                || Value::known(Fp::from(*shuffled)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(
+            K,
+            &ShuffleCircuit {
+                shuffled: [4, 2, 1, 3],
+            },
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // BUG: 5 is not one of the original values, and 4 is missing.
+        let prover = MockProver::run(
+            K,
+            &ShuffleCircuit {
+                shuffled: [5, 2, 1, 3],
+            },
+            vec![],
+        )
+        .unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert!(!failures.is_empty());
+        assert!(failures.iter().all(|failure| matches!(
+            failure,
+            VerifyFailure::Shuffle {
+                shuffle_index: 0,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    #[should_panic(expected = "expression containing simple selector supplied to shuffle argument")]
+    fn shuffle_rejects_simple_selector() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let q = meta.selector();
+
+        meta.shuffle("shuffle", |cells| {
+            let q = cells.query_selector(q);
+            let a = cells.query_advice(a, Rotation::cur());
+            let b = cells.query_advice(b, Rotation::cur());
+
+            vec![(q.clone() * a, q * b)]
+        });
+    }
+
+    #[test]
+    fn challenge_phases() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct RlcCircuitConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            rlc: Column<Advice>,
+            theta: Challenge,
+            q: Selector,
+        }
+
+        struct RlcCircuit {
+            // Added to the correct random linear combination.
+            error: u64,
+        }
+
+        impl Circuit<Fp> for RlcCircuit {
+            type Config = RlcCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let theta = meta.challenge_usable_after(FirstPhase);
+                let rlc = meta.advice_column_in(SecondPhase);
+                let q = meta.selector();
+
+                meta.create_gate("RLC", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let rlc = cells.query_advice(rlc, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    vec![q * (rlc - (a + theta.expr() * b))]
+                });
+
+                RlcCircuitConfig {
+                    a,
+                    b,
+                    rlc,
+                    theta,
+                    q,
+                }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { error: self.error }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let theta = layouter.get_challenge(config.theta);
+                layouter.assign_region(
+                    || "RLC",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        let a = Value::known(Fp::from(2));
+                        let b = Value::known(Fp::from(3));
+                        region.assign_advice(|| "a", config.a, 0, || a)?;
+                        region.assign_advice(|| "b", config.b, 0, || b)?;
+                        region.assign_advice(
+                            || "rlc",
+                            config.rlc,
+                            0,
+                            || a + theta * b + Value::known(Fp::from(self.error)),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &RlcCircuit { error: 0 }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(K, &RlcCircuit { error: 1 }, vec![]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            &failures[0],
+            VerifyFailure::ConstraintNotSatisfied { .. }
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "No Column<Advice> is used in phase")]
+    fn challenge_requires_advice_in_phase() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        meta.advice_column();
+        meta.challenge_usable_after(SecondPhase);
+    }
+
+    #[test]
+    fn unknown_value() {
+        const K: u32 = 4;
+
+        struct MissingWitnessCircuit;
+
+        impl Circuit<Fp> for MissingWitnessCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Missing witness",
+                    |mut region| {
+                        region.assign_advice(|| "a", config, 0, || Value::known(Fp::one()))?;
+                        // BUG: the witness should be known when proving.
+                        region.assign_advice(|| "b", config, 1, Value::<Fp>::unknown)?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let err = MockProver::run(K, &MissingWitnessCircuit, vec![]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnknownValue {
+                region: Some(region),
+                annotation,
+                row: 1,
+                ..
+            } if region == "Missing witness" && annotation == "b"
+        ));
+    }
+
+    #[test]
+    fn instance_query_in_gate() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct InstanceCircuitConfig {
+            a: Column<Advice>,
+            i: Column<Instance>,
+            q: Selector,
+        }
+
+        struct InstanceCircuit {
+            a: [u64; 2],
+        }
+
+        impl Circuit<Fp> for InstanceCircuit {
+            type Config = InstanceCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let i = meta.instance_column();
+                let q = meta.selector();
+
+                meta.create_gate("Instance sum", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let i_cur = cells.query_instance(i, Rotation::cur());
+                    let i_next = cells.query_instance(i, Rotation::next());
+                    let q = cells.query_selector(q);
+
+                    vec![q * (a - (i_cur + i_next))]
+                });
+
+                InstanceCircuitConfig { a, i, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { a: self.a }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Instance sum",
+                    |mut region| {
+                        for (offset, a) in self.a.iter().enumerate() {
+                            config.q.enable(&mut region, offset)?;
+                            region.assign_advice(
+                                || "a",
+                                config.a,
+                                offset,
+                                || Value::known(Fp::from(*a)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let instance = vec![vec![Fp::from(1), Fp::from(2), Fp::from(3)]];
+
+        let prover = MockProver::run(K, &InstanceCircuit { a: [3, 5] }, instance.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // BUG: the second row should be 2 + 3.
+        let prover = MockProver::run(K, &InstanceCircuit { a: [3, 6] }, instance).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            &failures[0],
+            VerifyFailure::ConstraintNotSatisfied {
+                location: FailureLocation::InRegion { offset: 1, .. },
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn contraint_unsatisfied() {
         const K: u32 = 4;
@@ -1,7 +1,9 @@
 //! Developer tools for investigating the cost of a circuit.
 
 use std::{
-    cmp, iter,
+    cmp,
+    collections::BTreeMap,
+    iter,
     marker::PhantomData,
     ops::{Add, Mul},
 };
@@ -39,10 +41,14 @@ pub struct CircuitCost<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> {
     lookups: usize,
     /// Number of columns in the global permutation.
     permutation_cols: usize,
+    /// Number of product polynomials in the global permutation.
+    permutation_chunks: usize,
     /// Number of distinct sets of points in the multiopening argument.
     point_sets: usize,
     /// Maximum rows used over all columns
     max_rows: usize,
+    /// The smallest `k` for which the rows used fit in the usable rows.
+    minimum_k: u32,
     /// Maximum rows used over all advice columns
     max_advice_rows: usize,
     /// Maximum rows used over all fixed columns
@@ -51,10 +57,33 @@ pub struct CircuitCost<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> {
     num_advice_columns: usize,
     num_instance_columns: usize,
     num_total_columns: usize,
+    /// Whether proofs hide the witness, which adds the random polynomial of the
+    /// vanishing argument.
+    hiding: bool,
+    /// Costs attributed to each namespace.
+    namespaces: BTreeMap<Vec<String>, NamespaceCost>,
 
     _marker: PhantomData<(G, ConcreteCircuit)>,
 }
 
+/// The cost of the regions created within a namespace of the circuit (see
+/// [`Layouter::namespace`](crate::circuit::Layouter::namespace)), including the
+/// regions created within its nested namespaces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NamespaceCost {
+    /// The number of regions.
+    pub regions: usize,
+    /// The total number of rows spanned by the regions.
+    pub rows: usize,
+    /// The number of cells assigned, including enabled selectors.
+    pub cells: usize,
+    /// The number of constraints enabled by selectors in the regions, i.e. the number
+    /// of gate polynomials evaluated at the rows where their selector is enabled.
+    /// Constraints of gates that do not query a selector are not attributed to any
+    /// namespace.
+    pub constraints: usize,
+}
+
 /// Region implementation used by Layout
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -69,6 +98,8 @@ pub(crate) struct LayoutRegion {
     pub(crate) rows: usize,
     /// The cells assigned in this region.
     pub(crate) cells: Vec<(RegionColumn, usize)>,
+    /// The namespace in which this region was created.
+    pub(crate) namespace: Vec<String>,
 }
 
 /// Cost and graphing layouter
@@ -91,6 +122,8 @@ pub(crate) struct Layout {
     pub(crate) equality: Vec<(Column<Any>, usize, Column<Any>, usize)>,
     /// Selector assignments used for optimization pass
     pub(crate) selectors: Vec<Vec<bool>>,
+    /// The current namespace.
+    namespace: Vec<String>,
 }
 
 impl Layout {
@@ -109,6 +142,7 @@ impl Layout {
             equality: vec![],
             // Selector assignments used for optimization pass
             selectors: vec![vec![false; n]; num_selectors],
+            namespace: vec![],
         }
     }
 
@@ -163,6 +197,7 @@ impl<F: Field> Assignment<F> for Layout {
             offset: None,
             rows: 0,
             cells: vec![],
+            namespace: self.namespace.clone(),
         })
     }
 
@@ -255,16 +290,16 @@ impl<F: Field> Assignment<F> for Layout {
         Value::unknown()
     }
 
-    fn push_namespace<NR, N>(&mut self, _: N)
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        // Do nothing; we don't care about namespaces in this context.
+        self.namespace.push(name_fn().into());
     }
 
     fn pop_namespace(&mut self, _: Option<String>) {
-        // Do nothing; we don't care about namespaces in this context.
+        self.namespace.pop();
     }
 }
 
@@ -284,6 +319,35 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             cs.constants.clone(),
         )
         .unwrap();
+
+        // Attribute each region to its namespace and all of the enclosing ones.
+        let mut constraints_per_selector = vec![0; cs.num_selectors];
+        for gate in &cs.gates {
+            for selector in gate.queried_selectors() {
+                constraints_per_selector[selector.index()] += gate.polynomials().len();
+            }
+        }
+        let mut namespaces: BTreeMap<Vec<String>, NamespaceCost> = BTreeMap::new();
+        for region in &layout.regions {
+            let constraints = region
+                .cells
+                .iter()
+                .map(|(column, _)| match column {
+                    RegionColumn::Selector(selector) => constraints_per_selector[selector.index()],
+                    RegionColumn::Column(_) => 0,
+                })
+                .sum::<usize>();
+            for depth in 0..=region.namespace.len() {
+                let cost = namespaces
+                    .entry(region.namespace[..depth].to_vec())
+                    .or_default();
+                cost.regions += 1;
+                cost.rows += region.rows;
+                cost.cells += region.cells.len();
+                cost.constraints += constraints;
+            }
+        }
+
         let (cs, _) = cs.compress_selectors(layout.selectors, false);
 
         assert!((1 << k) >= cs.minimum_rows());
@@ -324,7 +388,8 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
         point_sets.insert(vec![0, 1]); // permutation_product_poly
         let max_deg = cs.degree();
         let permutation_cols = cs.permutation.get_columns().len();
-        if permutation_cols > max_deg - 2 {
+        let permutation_chunks = cs.permutation.chunks(max_deg);
+        if permutation_chunks > 1 {
             // permutation_product_poly for chaining chunks.
             point_sets.insert(vec![-((cs.blinding_factors() + 1) as i32), 0, 1]);
         }
@@ -338,8 +403,10 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             fixed_queries: cs.fixed_queries.len(),
             lookups: cs.lookups.len(),
             permutation_cols,
+            permutation_chunks,
             point_sets: point_sets.len(),
             max_rows: layout.total_rows,
+            minimum_k: cs.minimum_k(layout.total_rows),
             max_advice_rows: layout.total_advice_rows,
             max_fixed_rows: layout.total_fixed_rows,
             num_advice_columns: cs.num_advice_columns,
@@ -348,18 +415,33 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             num_total_columns: cs.num_instance_columns
                 + cs.num_advice_columns
                 + cs.num_fixed_columns,
+            hiding: cs.is_hiding(),
+            namespaces,
             _marker: PhantomData,
         }
     }
 
-    fn permutation_chunks(&self) -> usize {
-        let chunk_size = self.max_deg - 2;
-        (self.permutation_cols + chunk_size - 1) / chunk_size
+    /// Returns the smallest `k` for which the circuit fits, accounting for the rows used
+    /// by its regions and lookup tables, and for the rows reserved for blinding factors.
+    ///
+    /// The circuit must have been measured with a `k` at least this large.
+    pub fn minimum_k(&self) -> u32 {
+        self.minimum_k
+    }
+
+    /// Returns the costs of the regions created within each namespace of the circuit,
+    /// keyed by the path of namespace names from the root. The entry for the empty
+    /// path covers every region of the circuit.
+    ///
+    /// Each entry includes the regions of the namespaces nested within it, so the
+    /// entries for the top-level namespaces show which gadget dominates the cost.
+    pub fn namespace_costs(&self) -> &BTreeMap<Vec<String>, NamespaceCost> {
+        &self.namespaces
     }
 
     /// Returns the marginal proof size per instance of this circuit.
     pub fn marginal_proof_size(&self) -> MarginalProofSize<G> {
-        let chunks = self.permutation_chunks();
+        let chunks = self.permutation_chunks;
 
         MarginalProofSize {
             // Cells:
@@ -409,9 +491,12 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
                 + ProofContribution::new(0, self.permutation_cols),
 
             // Vanishing argument:
-            // - 1 + (max_deg - 1) commitments
-            // - 1 random_poly eval
-            vanishing: ProofContribution::new(self.max_deg, 1),
+            // - (max_deg - 1) commitments
+            // - 1 random_poly commitment and eval, if proofs are hiding
+            vanishing: ProofContribution::new(
+                self.max_deg - 1 + usize::from(self.hiding),
+                usize::from(self.hiding),
+            ),
 
             // Multiopening argument:
             // - f_commitment
@@ -559,4 +644,171 @@ mod tests {
         }
         CircuitCost::<Eq, MyCircuit>::measure(K, &MyCircuit).proof_size(1);
     }
+
+    #[test]
+    fn namespace_costs() {
+        use crate::{
+            circuit::Layouter,
+            plonk::{Advice, Column, Selector},
+        };
+
+        const K: u32 = 5;
+
+        struct MyCircuit;
+        impl Circuit<Fp> for MyCircuit {
+            type Config = (Column<Advice>, Selector);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                Self
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.selector();
+                meta.create_gate("two constraints", |meta| {
+                    let q = meta.query_selector(q);
+                    let cur = meta.query_advice(a, Rotation::cur());
+                    let next = meta.query_advice(a, Rotation::next());
+                    vec![q.clone() * (cur.clone() - next), q * cur]
+                });
+                (a, q)
+            }
+
+            fn synthesize(
+                &self,
+                (a, q): Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let region = |n: usize| {
+                    move |mut region: crate::circuit::Region<'_, Fp>| {
+                        for row in 0..n {
+                            region.assign_advice(|| "a", a, row, || Value::known(Fp::ZERO))?;
+                        }
+                        q.enable(&mut region, 0)?;
+                        Ok(())
+                    }
+                };
+
+                let mut chip = layouter.namespace(|| "chip");
+                chip.assign_region(|| "outer", region(2))?;
+                chip.namespace(|| "inner")
+                    .assign_region(|| "inner", region(3))?;
+                drop(chip);
+                layouter.assign_region(|| "root", region(1))
+            }
+        }
+
+        let cost = CircuitCost::<Eq, MyCircuit>::measure(K, &MyCircuit);
+        let costs = cost.namespace_costs();
+        let path = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            costs[&path(&["chip", "inner"])],
+            NamespaceCost {
+                regions: 1,
+                rows: 3,
+                cells: 4,
+                constraints: 2,
+            }
+        );
+        assert_eq!(
+            costs[&path(&["chip"])],
+            NamespaceCost {
+                regions: 2,
+                rows: 5,
+                cells: 7,
+                constraints: 4,
+            }
+        );
+        assert_eq!(
+            costs[&path(&[])],
+            NamespaceCost {
+                regions: 3,
+                rows: 6,
+                cells: 9,
+                constraints: 6,
+            }
+        );
+    }
+
+    #[test]
+    fn minimum_k() {
+        use halo2curves::pasta::EqAffine;
+
+        use crate::{
+            circuit::Layouter,
+            plonk::{keygen_vk, Advice, Column, TableColumn},
+            poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA},
+        };
+
+        // A lookup into a table with `ROWS` rows, which is larger than the region.
+        struct MyCircuit<const ROWS: usize>;
+        impl<const ROWS: usize> Circuit<Fp> for MyCircuit<ROWS> {
+            type Config = (Column<Advice>, TableColumn);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                Self
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let table = meta.lookup_table_column();
+                meta.lookup("a in table", |meta| {
+                    vec![(meta.query_advice(a, Rotation::cur()), table)]
+                });
+                (a, table)
+            }
+
+            fn synthesize(
+                &self,
+                (a, table): Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "table",
+                    |mut t| {
+                        for row in 0..ROWS {
+                            t.assign_cell(
+                                || "table",
+                                table,
+                                row,
+                                || Value::known(Fp::from(row as u64)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+                layouter.assign_region(
+                    || "a",
+                    |mut region| {
+                        region.assign_advice(|| "a", a, 0, || Value::known(Fp::ONE))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        // With 5 blinding factors and l_last, 26 rows are usable for k = 5.
+        let cost = CircuitCost::<Eq, MyCircuit<26>>::measure(8, &MyCircuit);
+        assert_eq!(cost.minimum_k(), 5);
+        assert!(keygen_vk(&ParamsIPA::<EqAffine>::new(5), &MyCircuit::<26>).is_ok());
+
+        let cost = CircuitCost::<Eq, MyCircuit<27>>::measure(8, &MyCircuit);
+        assert_eq!(cost.minimum_k(), 6);
+        assert!(matches!(
+            keygen_vk(&ParamsIPA::<EqAffine>::new(5), &MyCircuit::<27>),
+            Err(Error::NotEnoughRowsAvailable { current_k: 5 })
+        ));
+        assert!(keygen_vk(&ParamsIPA::<EqAffine>::new(6), &MyCircuit::<27>).is_ok());
+    }
 }
@@ -155,6 +155,27 @@ pub enum VerifyFailure {
         /// The absolute row at which this cell should be assigned.
         row: usize,
     },
+    /// An active gate queried a cell outside the usable rows of the circuit.
+    ///
+    /// Rotations wrap around the domain, so a gate enabled on the first row that queries
+    /// `Rotation::prev()` reads the last row, and a gate enabled on the last usable row
+    /// that queries `Rotation::next()` reads the first blinding row. Neither can be
+    /// assigned, and the value read is not controlled by the prover.
+    QueryOutsideUsableRows {
+        /// The index of the active gate.
+        gate: metadata::Gate,
+        /// The region in which this gate was activated.
+        region: metadata::Region,
+        /// The offset (relative to the start of the region) at which the active gate
+        /// queries this cell.
+        gate_offset: usize,
+        /// The column of the queried cell.
+        column: Column<Any>,
+        /// The rotation at which the gate queries the cell.
+        rotation: i32,
+        /// The absolute row that the query reads, after wrapping around the domain.
+        row: usize,
+    },
     /// A constraint was not satisfied for a particular row.
     ConstraintNotSatisfied {
         /// The polynomial constraint that is not satisfied.
@@ -251,6 +272,19 @@ impl fmt::Display for VerifyFailure {
                     "{region} uses {gate} at offset {gate_offset}, which requires cell in instance column {column:?} at row {row} to be assigned.",
                 )
             }
+            Self::QueryOutsideUsableRows {
+                gate,
+                region,
+                gate_offset,
+                column,
+                rotation,
+                row,
+            } => {
+                write!(
+                    f,
+                    "{region} uses {gate} at offset {gate_offset}, which queries column {column:?} at rotation {rotation}, reading row {row} outside the usable rows.",
+                )
+            }
             Self::ConstraintNotSatisfied {
                 constraint,
                 location,
@@ -0,0 +1,183 @@
+//! Developer tools for finding missing constraints by perturbing a satisfying witness.
+
+use std::fmt;
+use std::iter;
+
+use ff::Field;
+use rand_core::RngCore;
+
+use super::{CellValue, FailureLocation, MockProver};
+use crate::plonk::{Advice, Any, Column};
+
+/// An assigned advice cell whose value was changed without [`MockProver::verify`]
+/// reporting a failure.
+///
+/// Unless the circuit is meant to accept several values for the cell, this points at a
+/// missing constraint, such as a range or canonicity check on a decomposition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UndetectedPerturbation {
+    column: Column<Any>,
+    row: usize,
+    location: FailureLocation,
+}
+
+impl UndetectedPerturbation {
+    /// Returns the column of the cell.
+    pub fn column(&self) -> Column<Any> {
+        self.column
+    }
+
+    /// Returns the absolute row of the cell.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Returns the region in which the cell was assigned, and its offset in it.
+    pub fn location(&self) -> &FailureLocation {
+        &self.location
+    }
+}
+
+impl fmt::Display for UndetectedPerturbation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cell in column {:?} {} can be changed without a constraint failing",
+            self.column, self.location
+        )
+    }
+}
+
+impl<F: Field> MockProver<F> {
+    /// Perturbs each assigned advice cell in turn, and returns the cells for which
+    /// [`MockProver::verify`] still succeeds.
+    ///
+    /// Each cell is set to its value plus one, and then to a random value drawn from
+    /// `rng`; a cell is reported if either change goes undetected. The witness is
+    /// restored after each check, so this prover is unchanged on return.
+    ///
+    /// This verifies the circuit twice per assigned advice cell, so it is only suited
+    /// to small circuits, such as the tests of a single chip.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the witness this prover was run with does not satisfy the circuit.
+    pub fn perturb_cells<R: RngCore>(&mut self, mut rng: R) -> Vec<UndetectedPerturbation> {
+        assert!(
+            self.verify().is_ok(),
+            "the circuit must be satisfied before its witness is perturbed"
+        );
+
+        let mut undetected = vec![];
+        for index in 0..self.advice.len() {
+            let column: Column<Any> = Column::new(
+                index,
+                Any::Advice(Advice {
+                    phase: self.cs.advice_column_phase[index],
+                }),
+            );
+            for row in self.usable_rows.clone() {
+                let original = match self.advice[index][row] {
+                    CellValue::Assigned(value) => value,
+                    _ => continue,
+                };
+
+                let perturbations = [original + F::ONE, F::random(&mut rng)];
+                let detected = perturbations
+                    .into_iter()
+                    .filter(|value| *value != original)
+                    .all(|value| {
+                        self.advice[index][row] = CellValue::Assigned(value);
+                        self.verify().is_err()
+                    });
+                self.advice[index][row] = CellValue::Assigned(original);
+
+                if !detected {
+                    undetected.push(UndetectedPerturbation {
+                        column,
+                        row,
+                        location: FailureLocation::find(
+                            &self.regions,
+                            row,
+                            iter::once(column).collect(),
+                        ),
+                    });
+                }
+            }
+        }
+        undetected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+    use rand_core::OsRng;
+
+    use super::super::MockProver;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[test]
+    fn perturb_cells() {
+        struct MyCircuit;
+
+        impl Circuit<Fp> for MyCircuit {
+            type Config = ([Column<Advice>; 3], Selector);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let [a, b, c] = [(); 3].map(|_| meta.advice_column());
+                let q = meta.selector();
+                meta.create_gate("c = a * b", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let b = meta.query_advice(b, Rotation::cur());
+                    let c = meta.query_advice(c, Rotation::cur());
+                    let q = meta.query_selector(q);
+                    vec![q * (a * b - c)]
+                });
+                ([a, b, c], q)
+            }
+
+            fn synthesize(
+                &self,
+                ([a, b, c], q): Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "region",
+                    |mut region| {
+                        q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", a, 0, || Value::known(Fp::from(2)))?;
+                        region.assign_advice(|| "b", b, 0, || Value::known(Fp::from(3)))?;
+                        region.assign_advice(|| "c", c, 0, || Value::known(Fp::from(6)))?;
+                        // Nothing constrains `b` times zero to be zero, so `b` is free.
+                        q.enable(&mut region, 1)?;
+                        region.assign_advice(|| "a", a, 1, || Value::known(Fp::zero()))?;
+                        region.assign_advice(|| "b", b, 1, || Value::known(Fp::from(5)))?;
+                        region.assign_advice(|| "c", c, 1, || Value::known(Fp::zero()))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let mut prover = MockProver::run(4, &MyCircuit, vec![]).unwrap();
+        let undetected: Vec<_> = prover
+            .perturb_cells(OsRng)
+            .iter()
+            .map(|cell| (cell.column(), cell.row()))
+            .collect();
+        assert_eq!(undetected, vec![(Column::new(1, Any::advice()), 1)]);
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
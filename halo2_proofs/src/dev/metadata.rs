@@ -113,7 +113,7 @@ impl<S: AsRef<str>> From<(S, Column, i32)> for VirtualCell {
 impl From<plonk::VirtualCell> for VirtualCell {
     fn from(c: plonk::VirtualCell) -> Self {
         VirtualCell {
-            name: "".to_string(),
+            name: c.name,
             column: c.column.into(),
             rotation: c.rotation.0,
         }
@@ -0,0 +1,329 @@
+//! Developer tools for measuring how densely a circuit occupies its columns.
+
+use std::fmt;
+use std::ops::Range;
+
+use ff::Field;
+
+use super::cost::Layout;
+use crate::{
+    circuit::layouter::RegionColumn,
+    plonk::{Any, Circuit, ConstraintSystem, FloorPlanner},
+};
+
+/// A column tracked by [`ColumnOccupancy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OccupancyColumn {
+    /// The advice column with the given index.
+    Advice(usize),
+    /// The fixed column with the given index.
+    Fixed(usize),
+    /// The selector with the given index.
+    Selector(usize),
+}
+
+impl fmt::Display for OccupancyColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OccupancyColumn::Advice(index) => write!(f, "advice {}", index),
+            OccupancyColumn::Fixed(index) => write!(f, "fixed {}", index),
+            OccupancyColumn::Selector(index) => write!(f, "selector {}", index),
+        }
+    }
+}
+
+/// The usable rows of a column that a circuit assigns, or enables for a selector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnUsage {
+    column: OccupancyColumn,
+    occupied: Vec<bool>,
+}
+
+impl ColumnUsage {
+    /// Returns the column.
+    pub fn column(&self) -> OccupancyColumn {
+        self.column
+    }
+
+    /// Returns, for each usable row of the column, whether it is occupied.
+    pub fn bitmap(&self) -> &[bool] {
+        &self.occupied
+    }
+
+    /// Returns whether `row` is occupied.
+    pub fn is_occupied(&self, row: usize) -> bool {
+        self.occupied.get(row).copied().unwrap_or(false)
+    }
+
+    /// Returns the number of occupied rows.
+    pub fn occupied_rows(&self) -> usize {
+        self.occupied.iter().filter(|occupied| **occupied).count()
+    }
+
+    /// Returns the percentage of the usable rows that are occupied.
+    pub fn utilization(&self) -> f64 {
+        percentage(self.occupied_rows(), self.occupied.len())
+    }
+
+    /// Returns the longest run of unoccupied rows, or the first of the longest runs
+    /// if there are several, or `None` if every row is occupied.
+    pub fn largest_gap(&self) -> Option<Range<usize>> {
+        let mut largest: Option<Range<usize>> = None;
+        let mut start = None;
+        for (row, occupied) in self.occupied.iter().chain(Some(&true)).enumerate() {
+            match (occupied, start) {
+                (false, None) => start = Some(row),
+                (true, Some(gap_start)) => {
+                    if largest
+                        .as_ref()
+                        .map_or(true, |gap| gap.len() < row - gap_start)
+                    {
+                        largest = Some(gap_start..row);
+                    }
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+        largest
+    }
+}
+
+/// Which usable rows of each advice, fixed and selector column a circuit occupies.
+///
+/// This measures how well a floor planner, or a manual placement of regions, packs a
+/// circuit: a column with a low utilization or a large gap has room for more cells.
+/// The [`fmt::Display`] form is a summary with one line per column.
+///
+/// # Examples
+///
+/// ```ignore
+/// use halo2_proofs::dev::ColumnOccupancy;
+///
+/// let occupancy = ColumnOccupancy::capture(K, &circuit);
+/// for column in occupancy.columns() {
+///     println!("{}: {:.1}%", column.column(), column.utilization());
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnOccupancy {
+    k: u32,
+    columns: Vec<ColumnUsage>,
+}
+
+impl ColumnOccupancy {
+    /// Synthesizes `circuit` with `k` rows and records the rows it occupies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the circuit cannot be synthesized with `k` rows.
+    pub fn capture<F: Field, ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+    ) -> Self {
+        let n = 1 << k;
+        let mut cs = ConstraintSystem::default();
+        #[cfg(feature = "circuit-params")]
+        let config = ConcreteCircuit::configure_with_params(&mut cs, circuit.params());
+        #[cfg(not(feature = "circuit-params"))]
+        let config = ConcreteCircuit::configure(&mut cs);
+        let mut layout = Layout::new(k, n, cs.num_selectors);
+        ConcreteCircuit::FloorPlanner::synthesize(
+            &mut layout,
+            circuit,
+            config,
+            cs.constants.clone(),
+        )
+        .unwrap();
+
+        let usable_rows = n - (cs.blinding_factors() + 1);
+        let mut columns: Vec<_> = (0..cs.num_advice_columns)
+            .map(OccupancyColumn::Advice)
+            .chain((0..cs.num_fixed_columns).map(OccupancyColumn::Fixed))
+            .chain((0..cs.num_selectors).map(OccupancyColumn::Selector))
+            .map(|column| ColumnUsage {
+                column,
+                occupied: vec![false; usable_rows],
+            })
+            .collect();
+
+        let cells = layout
+            .regions
+            .iter()
+            .flat_map(|region| region.cells.iter())
+            .chain(layout.loose_cells.iter());
+        for (column, row) in cells {
+            // The position of the column in `columns`.
+            let index = match column {
+                RegionColumn::Column(column) => match column.column_type() {
+                    Any::Advice(_) => column.index(),
+                    Any::Fixed => cs.num_advice_columns + column.index(),
+                    Any::Instance => continue,
+                },
+                RegionColumn::Selector(selector) => {
+                    cs.num_advice_columns + cs.num_fixed_columns + selector.index()
+                }
+            };
+            if let Some(occupied) = columns[index].occupied.get_mut(*row) {
+                *occupied = true;
+            }
+        }
+
+        ColumnOccupancy { k, columns }
+    }
+
+    /// Returns the `k` the circuit was synthesized with.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Returns the usage of every advice column, then every fixed column, then every
+    /// selector.
+    pub fn columns(&self) -> &[ColumnUsage] {
+        &self.columns
+    }
+
+    /// Returns the usage of `column`, if the circuit has it.
+    pub fn column(&self, column: OccupancyColumn) -> Option<&ColumnUsage> {
+        self.columns.iter().find(|usage| usage.column == column)
+    }
+
+    /// Returns the percentage of the usable cells of all columns that are occupied.
+    pub fn utilization(&self) -> f64 {
+        percentage(
+            self.columns.iter().map(ColumnUsage::occupied_rows).sum(),
+            self.columns.iter().map(|usage| usage.occupied.len()).sum(),
+        )
+    }
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / total as f64
+    }
+}
+
+impl fmt::Display for ColumnOccupancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:>10} {:>7}  largest gap",
+            "column", "occupied", "%"
+        )?;
+        for usage in &self.columns {
+            let gap = match usage.largest_gap() {
+                Some(gap) => format!("rows {}..{} ({})", gap.start, gap.end, gap.len()),
+                None => "none".to_string(),
+            };
+            writeln!(
+                f,
+                "{:<12} {:>10} {:>6.1}%  {}",
+                usage.column.to_string(),
+                usage.occupied_rows(),
+                usage.utilization(),
+                gap
+            )?;
+        }
+        writeln!(f, "total {:>28.1}%", self.utilization())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::{ColumnOccupancy, OccupancyColumn};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone, Default)]
+    struct MyCircuit;
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (Column<Advice>, Column<Fixed>, Column<Fixed>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let c = meta.fixed_column();
+            let unused = meta.fixed_column();
+            let q = meta.selector();
+            meta.create_gate("a is c", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let c = meta.query_fixed(c, Rotation::cur());
+                let q = meta.query_selector(q);
+                vec![q * (a - c)]
+            });
+            (a, c, unused, q)
+        }
+
+        fn synthesize(
+            &self,
+            (a, c, _, q): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "a is c",
+                |mut region| {
+                    for row in [0, 1, 5] {
+                        q.enable(&mut region, row)?;
+                        region.assign_advice(|| "a", a, row, || Value::known(Fp::from(1)))?;
+                        region.assign_fixed(|| "c", c, row, || Value::known(Fp::from(1)))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn column_occupancy() {
+        const K: u32 = 4;
+
+        let occupancy = ColumnOccupancy::capture(K, &MyCircuit);
+        assert_eq!(occupancy.columns().len(), 4);
+
+        let usable_rows = occupancy.columns()[0].bitmap().len();
+        assert!(usable_rows < 1 << K);
+
+        let a = occupancy.column(OccupancyColumn::Advice(0)).unwrap();
+        assert_eq!(a.occupied_rows(), 3);
+        assert!(a.is_occupied(5));
+        assert!(!a.is_occupied(2));
+        assert_eq!(a.largest_gap(), Some(6..usable_rows));
+        assert_eq!(a.utilization(), 300.0 / usable_rows as f64);
+        assert_eq!(
+            occupancy
+                .column(OccupancyColumn::Fixed(0))
+                .unwrap()
+                .bitmap(),
+            a.bitmap()
+        );
+        assert_eq!(
+            occupancy
+                .column(OccupancyColumn::Selector(0))
+                .unwrap()
+                .bitmap(),
+            a.bitmap()
+        );
+
+        let unused = occupancy.column(OccupancyColumn::Fixed(1)).unwrap();
+        assert_eq!(unused.occupied_rows(), 0);
+        assert_eq!(unused.utilization(), 0.0);
+        assert_eq!(unused.largest_gap(), Some(0..usable_rows));
+
+        assert_eq!(occupancy.utilization(), 900.0 / (4 * usable_rows) as f64);
+        assert!(occupancy.to_string().contains("fixed 1"));
+    }
+}
@@ -0,0 +1,300 @@
+//! Developer tools for detecting changes to the layout of a circuit.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use ff::Field;
+
+use super::{cost::Layout, metadata};
+use crate::{
+    circuit::layouter::RegionColumn,
+    plonk::{Circuit, ConstraintSystem, FloorPlanner},
+};
+
+/// A canonical description of the layout of a circuit: the positions of its regions,
+/// of the cells it assigns and enables, and of its copy constraints. Assigned values
+/// are not recorded.
+///
+/// Snapshots can be stored as text (via [`fmt::Display`]) and parsed back (via
+/// [`FromStr`]), so that a chip refactor can be checked against the layout before the
+/// refactor with [`LayoutSnapshot::diff`].
+///
+/// # Examples
+///
+/// ```ignore
+/// use halo2_proofs::dev::LayoutSnapshot;
+///
+/// let before: LayoutSnapshot = std::fs::read_to_string("layout.txt")?.parse()?;
+/// let after = LayoutSnapshot::capture(K, &circuit);
+/// let diff = before.diff(&after);
+/// assert!(diff.is_empty(), "layout changed:\n{}", diff);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutSnapshot {
+    /// Sorted, deduplicated lines of the canonical form.
+    lines: Vec<String>,
+}
+
+impl LayoutSnapshot {
+    /// Synthesizes `circuit` with `k` rows and captures its layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the circuit cannot be synthesized with `k` rows.
+    pub fn capture<F: Field, ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+    ) -> Self {
+        let n = 1 << k;
+        let mut cs = ConstraintSystem::default();
+        #[cfg(feature = "circuit-params")]
+        let config = ConcreteCircuit::configure_with_params(&mut cs, circuit.params());
+        #[cfg(not(feature = "circuit-params"))]
+        let config = ConcreteCircuit::configure(&mut cs);
+        let mut layout = Layout::new(k, n, cs.num_selectors);
+        ConcreteCircuit::FloorPlanner::synthesize(
+            &mut layout,
+            circuit,
+            config,
+            cs.constants.clone(),
+        )
+        .unwrap();
+
+        let mut lines = vec![];
+        for region in &layout.regions {
+            let offset = region.offset.unwrap_or(0);
+            lines.push(format!(
+                "region {:?} rows {}..{}",
+                region.name,
+                offset,
+                offset + region.rows
+            ));
+            lines.extend(
+                region
+                    .cells
+                    .iter()
+                    .map(|(column, row)| cell_line(Some(&region.name), column, *row)),
+            );
+        }
+        lines.extend(
+            layout
+                .loose_cells
+                .iter()
+                .map(|(column, row)| cell_line(None, column, *row)),
+        );
+        lines.extend(layout.equality.iter().map(|(l_col, l_row, r_col, r_row)| {
+            // Copy constraints are symmetric.
+            let mut cells = [
+                (metadata::Column::from(*l_col).to_string(), *l_row),
+                (metadata::Column::from(*r_col).to_string(), *r_row),
+            ];
+            cells.sort();
+            format!(
+                "copy {} row {} = {} row {}",
+                cells[0].0, cells[0].1, cells[1].0, cells[1].1
+            )
+        }));
+
+        lines.sort();
+        lines.dedup();
+        LayoutSnapshot { lines }
+    }
+
+    /// Returns the changes needed to go from `self` to `other`.
+    pub fn diff(&self, other: &LayoutSnapshot) -> LayoutDiff {
+        let mut counts: BTreeMap<&str, i32> = BTreeMap::new();
+        for line in &self.lines {
+            *counts.entry(line).or_default() -= 1;
+        }
+        for line in &other.lines {
+            *counts.entry(line).or_default() += 1;
+        }
+
+        let mut diff = LayoutDiff::default();
+        for (line, count) in counts {
+            match count {
+                c if c < 0 => diff.removed.push(line.to_string()),
+                c if c > 0 => diff.added.push(line.to_string()),
+                _ => (),
+            }
+        }
+        diff
+    }
+}
+
+fn cell_line(region: Option<&str>, column: &RegionColumn, row: usize) -> String {
+    let column = match column {
+        RegionColumn::Column(column) => metadata::Column::from(*column).to_string(),
+        RegionColumn::Selector(selector) => format!("Selector({})", selector.index()),
+    };
+    match region {
+        Some(region) => format!("cell {} row {} in {:?}", column, row, region),
+        None => format!("cell {} row {}", column, row),
+    }
+}
+
+impl fmt::Display for LayoutSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for LayoutSnapshot {
+    type Err = std::convert::Infallible;
+
+    /// Parses the text form of a snapshot, as produced by [`fmt::Display`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines: Vec<_> = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        lines.sort();
+        lines.dedup();
+        Ok(LayoutSnapshot { lines })
+    }
+}
+
+/// The difference between two [`LayoutSnapshot`]s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LayoutDiff {
+    /// Lines of the first snapshot that are not in the second.
+    pub removed: Vec<String>,
+    /// Lines of the second snapshot that are not in the first.
+    pub added: Vec<String>,
+}
+
+impl LayoutDiff {
+    /// Returns `true` if the two snapshots describe the same layout.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+}
+
+impl fmt::Display for LayoutDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.removed {
+            writeln!(f, "- {}", line)?;
+        }
+        for line in &self.added {
+            writeln!(f, "+ {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::LayoutSnapshot;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone, Default)]
+    struct MyCircuit {
+        value: Value<Fp>,
+        rows: usize,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit {
+                value: Value::unknown(),
+                rows: self.rows,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.selector();
+            meta.enable_equality(a);
+            meta.create_gate("a is constant", |meta| {
+                let cur = meta.query_advice(a, Rotation::cur());
+                let next = meta.query_advice(a, Rotation::next());
+                let q = meta.query_selector(q);
+                vec![q * (cur - next)]
+            });
+            (a, q)
+        }
+
+        fn synthesize(
+            &self,
+            (a, q): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "constant",
+                |mut region| {
+                    let first = region.assign_advice(|| "a", a, 0, || self.value)?;
+                    for row in 1..self.rows {
+                        q.enable(&mut region, row - 1)?;
+                        first.copy_advice(|| "a", &mut region, a, row)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn layout_snapshot_diff() {
+        const K: u32 = 4;
+
+        let before = LayoutSnapshot::capture(
+            K,
+            &MyCircuit {
+                value: Value::known(Fp::from(1)),
+                rows: 3,
+            },
+        );
+
+        // The snapshot survives a round trip through its text form.
+        assert_eq!(
+            before.to_string().parse::<LayoutSnapshot>().unwrap(),
+            before
+        );
+
+        // Values do not affect the layout.
+        let same = LayoutSnapshot::capture(
+            K,
+            &MyCircuit {
+                value: Value::known(Fp::from(2)),
+                rows: 3,
+            },
+        );
+        assert!(before.diff(&same).is_empty());
+
+        let after = LayoutSnapshot::capture(
+            K,
+            &MyCircuit {
+                value: Value::known(Fp::from(1)),
+                rows: 4,
+            },
+        );
+        let diff = before.diff(&after);
+        assert_eq!(diff.removed, vec!["region \"constant\" rows 0..3"]);
+        assert!(diff
+            .added
+            .contains(&"cell Column('Advice', 0) row 3 in \"constant\"".to_string()));
+        assert!(diff
+            .added
+            .contains(&"cell Selector(0) row 2 in \"constant\"".to_string()));
+        assert!(diff
+            .added
+            .contains(&"copy Column('Advice', 0) row 0 = Column('Advice', 0) row 3".to_string()));
+    }
+}
@@ -0,0 +1,254 @@
+//! Developer tools for attributing the size of a proof to the circuit features that
+//! produce it.
+
+use ff::PrimeField;
+use group::GroupEncoding;
+
+use crate::{
+    plonk::{ProvingMode, VerifyingKey},
+    poly::commitment::{CommitmentScheme, Verifier},
+};
+
+/// The number of commitments and evaluations a single component contributes to a
+/// proof, together with the number of bytes they occupy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComponentStats {
+    /// Number of curve points written to the proof.
+    pub commitments: usize,
+    /// Number of scalars written to the proof.
+    pub evaluations: usize,
+    /// Number of bytes occupied by the commitments and evaluations.
+    pub bytes: usize,
+}
+
+impl ComponentStats {
+    fn new(commitments: usize, evaluations: usize, point: usize, scalar: usize) -> Self {
+        ComponentStats {
+            commitments,
+            evaluations,
+            bytes: commitments * point + evaluations * scalar,
+        }
+    }
+}
+
+/// A breakdown of a serialized proof into the components that contribute to it.
+///
+/// Obtained from [`proof_stats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofStats {
+    /// Evaluations of the instance columns (only present for schemes that query
+    /// instance columns, such as IPA).
+    pub instance: ComponentStats,
+    /// Commitments to, and evaluations of, the advice columns.
+    pub advice: ComponentStats,
+    /// Evaluations of the fixed columns.
+    pub fixed: ComponentStats,
+    /// Permuted/multiplicity and product/grand-sum commitments of the lookup
+    /// arguments, together with their evaluations.
+    pub lookups: ComponentStats,
+    /// Permutation product commitments and their evaluations, together with the
+    /// evaluations of the permutation polynomials.
+    pub permutation: ComponentStats,
+    /// Product commitments of the shuffle arguments and their evaluations.
+    pub shuffles: ComponentStats,
+    /// The random polynomial commitment, the quotient polynomial pieces and the
+    /// random polynomial evaluation.
+    pub vanishing: ComponentStats,
+    /// Bytes of the multiopening argument. This is whatever remains of the proof after
+    /// the other components, so its commitment and evaluation counts are not known.
+    pub multiopen: ComponentStats,
+    /// Total length of the proof in bytes.
+    pub total_bytes: usize,
+}
+
+impl ProofStats {
+    /// Returns the number of bytes occupied by everything except the multiopening
+    /// argument.
+    pub fn plonk_bytes(&self) -> usize {
+        self.instance.bytes
+            + self.advice.bytes
+            + self.fixed.bytes
+            + self.lookups.bytes
+            + self.permutation.bytes
+            + self.shuffles.bytes
+            + self.vanishing.bytes
+    }
+}
+
+/// Summarizes the contents of `proof`, a proof for a single instance of the circuit
+/// described by `vk` that was created with the multiopen verifier `V`.
+///
+/// The breakdown of every component except the multiopening argument is derived from
+/// `vk` alone; the multiopening argument is reported as the remaining bytes of `proof`.
+///
+/// # Panics
+///
+/// Panics if `proof` is shorter than the components implied by `vk`.
+pub fn proof_stats<'params, Scheme: CommitmentScheme, V: Verifier<'params, Scheme>>(
+    vk: &VerifyingKey<Scheme::Curve>,
+    proof: &[u8],
+) -> ProofStats {
+    let mut stats = plonk_proof_stats::<Scheme, V>(vk);
+    stats.total_bytes = proof.len();
+
+    let plonk_bytes = stats.plonk_bytes();
+    assert!(
+        plonk_bytes <= proof.len(),
+        "proof of {} bytes is too short for the given verifying key ({} bytes expected before the multiopening argument)",
+        proof.len(),
+        plonk_bytes,
+    );
+    stats.multiopen.bytes = proof.len() - plonk_bytes;
+
+    stats
+}
+
+/// Derives the breakdown of every component except the multiopening argument from
+/// `vk`, leaving `multiopen` and `total_bytes` empty.
+pub(crate) fn plonk_proof_stats<'params, Scheme: CommitmentScheme, V: Verifier<'params, Scheme>>(
+    vk: &VerifyingKey<Scheme::Curve>,
+) -> ProofStats {
+    let point = <Scheme::Curve as GroupEncoding>::Repr::default()
+        .as_ref()
+        .len();
+    let scalar = <Scheme::Scalar as PrimeField>::Repr::default()
+        .as_ref()
+        .len();
+    let component =
+        |commitments, evaluations| ComponentStats::new(commitments, evaluations, point, scalar);
+
+    let cs = vk.cs();
+
+    let instance = if V::QUERY_INSTANCE {
+        component(0, cs.instance_queries().len())
+    } else {
+        component(0, 0)
+    };
+    let advice = component(cs.num_advice_columns(), cs.advice_queries().len());
+    let fixed = component(0, cs.fixed_queries().len());
+
+    // - permuted input, permuted table and product commitments, with 5 evals each
+    // - or, with `mv-lookup`, multiplicity and grand sum commitments, with 3 evals each
+    #[cfg(not(feature = "mv-lookup"))]
+    let lookups = component(3 * cs.lookups().len(), 5 * cs.lookups().len());
+    #[cfg(feature = "mv-lookup")]
+    let lookups = component(2 * cs.lookups().len(), 3 * cs.lookups().len());
+
+    // Each chunk of the permutation has a product commitment evaluated at x and
+    // \omega x, and every chunk except the last is also evaluated at l_last.
+    let permutation_columns = cs.permutation().get_columns().len();
+    let chunks = cs.permutation().chunks(cs.degree());
+    let product_evals = if chunks == 0 { 0 } else { 3 * chunks - 1 };
+    let permutation = component(chunks, product_evals + permutation_columns);
+
+    let shuffles = component(cs.shuffles().len(), 2 * cs.shuffles().len());
+
+    // The quotient chunk commitments and, if proofs are hiding, the random polynomial
+    // with its evaluation.
+    let random_poly = usize::from(cs.proving_mode() == ProvingMode::Hiding);
+    let vanishing = component(
+        random_poly + vk.get_domain().get_quotient_poly_degree(),
+        random_poly,
+    );
+
+    ProofStats {
+        instance,
+        advice,
+        fixed,
+        lookups,
+        permutation,
+        shuffles,
+        vanishing,
+        multiopen: ComponentStats::default(),
+        total_bytes: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error,
+        },
+        poly::kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        },
+        transcript::{Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+    };
+
+    #[derive(Clone, Default)]
+    struct MyCircuit(Value<Fr>);
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            meta.enable_equality(a);
+            a
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copy",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", config, 0, || self.0)?;
+                    a.copy_advice(|| "a copy", &mut region, config, 1)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn proof_stats_account_for_whole_proof() {
+        const K: u32 = 4;
+
+        let params: ParamsKZG<Bn256> = ParamsKZG::setup(K, OsRng);
+        let circuit = MyCircuit(Value::known(Fr::ONE));
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize().unwrap();
+
+        let stats = proof_stats::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<Bn256>>(&vk, &proof);
+
+        assert_eq!(stats.total_bytes, proof.len());
+        assert_eq!(stats.instance, ComponentStats::default());
+        assert_eq!(stats.advice.commitments, 1);
+        assert_eq!(stats.permutation.commitments, 1);
+        assert_eq!(
+            stats.plonk_bytes() + stats.multiopen.bytes,
+            stats.total_bytes
+        );
+        assert!(stats.multiopen.bytes > 0);
+    }
+}
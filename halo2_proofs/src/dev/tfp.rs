@@ -144,6 +144,10 @@ impl<'c, F: Field, C: Circuit<F>> Circuit<F> for TracingCircuit<'c, F, C> {
         self.inner_ref()
             .synthesize(config, TracingLayouter::new(layouter))
     }
+
+    fn deduplicate_constants() -> bool {
+        C::deduplicate_constants()
+    }
 }
 
 /// A helper type that augments a [`Layouter`] with [`tracing`] spans and events.
@@ -0,0 +1,308 @@
+//! Developer tools for finding cells and columns that no constraint refers to.
+
+use std::fmt;
+use std::iter;
+
+use ff::Field;
+use maybe_rayon::prelude::ParallelIterator;
+use rustc_hash::FxHashSet as HashSet;
+
+use super::{CellValue, FailureLocation, MockProver};
+use crate::plonk::{Advice, Any, Column, Expression, Fixed, Instance};
+
+/// An assigned advice cell that no gate, lookup, shuffle or copy constraint refers to.
+///
+/// The value of such a cell is not constrained at all, so it is either dead code in the
+/// circuit or, more worryingly, a value the circuit was meant to constrain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnconstrainedCell {
+    column: Column<Any>,
+    row: usize,
+    location: FailureLocation,
+}
+
+impl UnconstrainedCell {
+    /// Returns the column of the cell.
+    pub fn column(&self) -> Column<Any> {
+        self.column
+    }
+
+    /// Returns the absolute row of the cell.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Returns the region in which the cell was assigned, and its offset in it.
+    pub fn location(&self) -> &FailureLocation {
+        &self.location
+    }
+}
+
+impl fmt::Display for UnconstrainedCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cell in column {:?} {} is assigned but not constrained",
+            self.column, self.location
+        )
+    }
+}
+
+// Returns the advice columns queried by `expression`.
+fn advice_columns<F: Field>(expression: &Expression<F>) -> Vec<usize> {
+    expression.evaluate(
+        &|_| vec![],
+        &|_| vec![],
+        &|_| vec![],
+        &|query| vec![query.column_index()],
+        &|_| vec![],
+        &|_| vec![],
+        &|a| a,
+        &|mut a, b| {
+            a.extend(b);
+            a
+        },
+        &|mut a, b| {
+            a.extend(b);
+            a
+        },
+        &|a, _| a,
+    )
+}
+
+impl<F: Field> MockProver<F> {
+    /// Returns the advice, fixed and instance columns that no gate, lookup, shuffle or
+    /// copy constraint refers to.
+    ///
+    /// Anything assigned to such a column is unconstrained; the column is usually left
+    /// over from an earlier version of the circuit, or a query was forgotten.
+    pub fn unused_columns(&self) -> Vec<Column<Any>> {
+        let advice = (0..self.cs.num_advice_columns)
+            .filter(|index| {
+                !self
+                    .cs
+                    .advice_queries
+                    .iter()
+                    .any(|(column, _)| column.index() == *index)
+            })
+            .map(|index| {
+                let phase = self.cs.advice_column_phase[index];
+                Column::new(index, Any::Advice(Advice { phase }))
+            });
+        let fixed = (0..self.cs.num_fixed_columns)
+            .filter(|index| {
+                !self
+                    .cs
+                    .fixed_queries
+                    .iter()
+                    .any(|(column, _)| column.index() == *index)
+            })
+            .map(|index| Column::<Fixed>::new(index, Fixed).into());
+        let instance = (0..self.cs.num_instance_columns)
+            .filter(|index| {
+                !self
+                    .cs
+                    .instance_queries
+                    .iter()
+                    .any(|(column, _)| column.index() == *index)
+            })
+            .map(|index| Column::<Instance>::new(index, Instance).into());
+
+        advice.chain(fixed).chain(instance).collect()
+    }
+
+    /// Returns the assigned advice cells that no gate, lookup, shuffle or copy
+    /// constraint refers to.
+    ///
+    /// A gate refers to the cells it queries on the rows where one of its selectors is
+    /// enabled, or on every row if it has no selector. Lookups and shuffles are treated
+    /// as referring to every cell of the advice columns in their expressions, since
+    /// whether they are active on a row depends on the values in the circuit.
+    pub fn unconstrained_cells(&self) -> Vec<UnconstrainedCell> {
+        let n = self.n as i32;
+        let mut referenced: HashSet<(usize, usize)> = HashSet::default();
+
+        for gate in &self.cs.gates {
+            let rows: Vec<usize> = if gate.queried_selectors().is_empty() {
+                self.usable_rows.clone().collect()
+            } else {
+                self.usable_rows
+                    .clone()
+                    .filter(|row| {
+                        gate.queried_selectors()
+                            .iter()
+                            .any(|selector| self.selectors[selector.index()][*row])
+                    })
+                    .collect()
+            };
+            for cell in gate.queried_cells() {
+                if let Any::Advice(_) = cell.column.column_type() {
+                    for row in &rows {
+                        let row = (*row as i32 + cell.rotation.0).rem_euclid(n) as usize;
+                        referenced.insert((cell.column.index(), row));
+                    }
+                }
+            }
+        }
+
+        #[cfg(not(feature = "mv-lookup"))]
+        let lookup_expressions = self.cs.lookups.iter().flat_map(|lookup| {
+            lookup
+                .input_expressions
+                .iter()
+                .chain(lookup.table_expressions.iter())
+        });
+        #[cfg(feature = "mv-lookup")]
+        let lookup_expressions = self.cs.lookups.iter().flat_map(|lookup| {
+            lookup
+                .inputs_expressions
+                .iter()
+                .flatten()
+                .chain(lookup.table_expressions.iter())
+        });
+        let shuffle_expressions = self.cs.shuffles.iter().flat_map(|shuffle| {
+            shuffle
+                .input_expressions
+                .iter()
+                .chain(shuffle.shuffle_expressions.iter())
+        });
+        let argument_columns: HashSet<usize> = lookup_expressions
+            .chain(shuffle_expressions)
+            .flat_map(advice_columns)
+            .collect();
+
+        // A cell is copy-constrained if the permutation maps it to another cell.
+        let permutation_columns = self.cs.permutation.get_columns();
+        for (position, (column, mapping)) in permutation_columns
+            .iter()
+            .zip(self.permutation.mapping())
+            .enumerate()
+        {
+            if let Any::Advice(_) = column.column_type() {
+                let mapping: Vec<_> = mapping.collect();
+                for (row, permuted) in mapping.into_iter().enumerate() {
+                    if permuted != (position, row) {
+                        referenced.insert((column.index(), row));
+                    }
+                }
+            }
+        }
+
+        self.advice
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !argument_columns.contains(index))
+            .flat_map(|(index, cells)| {
+                let column: Column<Any> = Column::new(
+                    index,
+                    Any::Advice(Advice {
+                        phase: self.cs.advice_column_phase[index],
+                    }),
+                );
+                let referenced = &referenced;
+                self.usable_rows
+                    .clone()
+                    .filter(move |row| {
+                        matches!(cells[*row], CellValue::Assigned(_))
+                            && !referenced.contains(&(index, *row))
+                    })
+                    .map(move |row| UnconstrainedCell {
+                        column,
+                        row,
+                        location: FailureLocation::find(
+                            &self.regions,
+                            row,
+                            iter::once(column).collect(),
+                        ),
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::super::MockProver;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Fixed, Selector},
+        poly::Rotation,
+    };
+
+    #[test]
+    fn unused_columns_and_unconstrained_cells() {
+        struct MyCircuit;
+
+        impl Circuit<Fp> for MyCircuit {
+            type Config = ([Column<Advice>; 4], Selector);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let [a, b, c, d] = [(); 4].map(|_| meta.advice_column());
+                let _unused: Column<Fixed> = meta.fixed_column();
+                let q = meta.selector();
+                meta.enable_equality(d);
+                meta.create_gate("a is b", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let b = meta.query_advice(b, Rotation::cur());
+                    let q = meta.query_selector(q);
+                    vec![q * (a - b)]
+                });
+                ([a, b, c, d], q)
+            }
+
+            fn synthesize(
+                &self,
+                ([a, b, c, d], q): Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "region",
+                    |mut region| {
+                        let one = || Value::known(Fp::one());
+                        q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", a, 0, one)?;
+                        region.assign_advice(|| "b", b, 0, one)?;
+                        // The gate is not enabled on this row.
+                        region.assign_advice(|| "a", a, 1, one)?;
+                        // No constraint queries this column.
+                        region.assign_advice(|| "c", c, 0, one)?;
+                        let d0 = region.assign_advice(|| "d", d, 0, one)?;
+                        let d1 = region.assign_advice(|| "d", d, 1, one)?;
+                        region.constrain_equal(d0.cell(), d1.cell())?;
+                        // Equality is enabled on this column, but this cell is not copied.
+                        region.assign_advice(|| "d", d, 2, one)?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(4, &MyCircuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let advice = |index| Column::new(index, Any::advice());
+        assert_eq!(
+            prover.unused_columns(),
+            vec![advice(2), Column::new(0, Any::Fixed)]
+        );
+
+        let unconstrained: Vec<_> = prover
+            .unconstrained_cells()
+            .iter()
+            .map(|cell| (cell.column(), cell.row()))
+            .collect();
+        assert_eq!(
+            unconstrained,
+            vec![(advice(0), 1), (advice(2), 0), (advice(3), 2)]
+        );
+    }
+}
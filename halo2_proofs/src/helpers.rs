@@ -1,7 +1,7 @@
 use crate::poly::Polynomial;
 use ff::PrimeField;
 use halo2curves::{serde::SerdeObject, CurveAffine};
-use std::io;
+use std::{borrow::Borrow, io, sync::Arc};
 
 /// This enum specifies how various types are serialized and deserialized.
 #[derive(Clone, Copy, Debug)]
@@ -134,21 +134,39 @@ pub(crate) fn read_polynomial_vec<R: io::Read, F: SerdePrimeField, B>(
         .collect::<io::Result<Vec<_>>>()
 }
 
+/// Reads a vector of polynomials from buffer, each behind its own [`Arc`]
+pub(crate) fn read_shared_polynomial_vec<R: io::Read, F: SerdePrimeField, B>(
+    reader: &mut R,
+    format: SerdeFormat,
+) -> io::Result<Vec<Arc<Polynomial<F, B>>>> {
+    Ok(read_polynomial_vec(reader, format)?
+        .into_iter()
+        .map(Arc::new)
+        .collect())
+}
+
 /// Writes a slice of polynomials to buffer
-pub(crate) fn write_polynomial_slice<W: io::Write, F: SerdePrimeField, B>(
-    slice: &[Polynomial<F, B>],
+pub(crate) fn write_polynomial_slice<
+    W: io::Write,
+    F: SerdePrimeField,
+    B,
+    P: Borrow<Polynomial<F, B>>,
+>(
+    slice: &[P],
     writer: &mut W,
     format: SerdeFormat,
 ) -> io::Result<()> {
     writer.write_all(&(slice.len() as u32).to_be_bytes())?;
     for poly in slice.iter() {
-        poly.write(writer, format)?;
+        poly.borrow().write(writer, format)?;
     }
     Ok(())
 }
 
 /// Gets the total number of bytes of a slice of polynomials, assuming all polynomials are the same length
-pub(crate) fn polynomial_slice_byte_length<F: PrimeField, B>(slice: &[Polynomial<F, B>]) -> usize {
+pub(crate) fn polynomial_slice_byte_length<F: PrimeField, B, P: Borrow<Polynomial<F, B>>>(
+    slice: &[P],
+) -> usize {
     let field_len = F::default().to_repr().as_ref().len();
-    4 + slice.len() * (4 + field_len * slice.first().map(|poly| poly.len()).unwrap_or(0))
+    4 + slice.len() * (4 + field_len * slice.first().map(|poly| poly.borrow().len()).unwrap_or(0))
 }
@@ -1,54 +1,83 @@
-use group::ff::PrimeField;
+// The only unsafe code is the `Send` implementations below, each of which is allowed
+// individually with its justification.
+#![deny(unsafe_code)]
+
 use icicle::{
     curves::bn254::{Point_BN254, ScalarField_BN254},
     test_bn254::commit_bn254,
 };
-use std::sync::{Arc, Once};
+use std::sync::{Arc, Mutex};
 
 pub use icicle::curves::bn254::PointAffineNoInfinity_BN254;
+use rustacuda::context::CurrentContext;
 use rustacuda::memory::CopyDestination;
 use rustacuda::prelude::*;
 
+use crate::arithmetic::limbs::{field_from_u32, u32_from_field};
 pub use halo2curves::CurveAffine;
-use std::{env, mem};
+use std::env;
+
+struct GpuContext(Context);
+
+// SAFETY: `Context` is not `Send` because a CUDA context is current only on the threads
+// that make it current, which rustacuda does only for the thread that creates it. The
+// context is never dropped, and any thread that uses the GPU first makes it current
+// through `make_context_current` while holding the lock on `GPU_CONTEXT`.
+#[allow(unsafe_code)]
+unsafe impl Send for GpuContext {}
 
-static mut GPU_CONTEXT: Option<Context> = None;
-static mut GPU_G: Option<DeviceBuffer<PointAffineNoInfinity_BN254>> = None;
-static mut GPU_G_LAGRANGE: Option<DeviceBuffer<PointAffineNoInfinity_BN254>> = None;
-static GPU_INIT: Once = Once::new();
+/// The bases `g` and `g_lagrange` of one set of parameters, loaded on the GPU.
+struct GpuBases {
+    g: DeviceBuffer<PointAffineNoInfinity_BN254>,
+    g_lagrange: DeviceBuffer<PointAffineNoInfinity_BN254>,
+}
+
+// SAFETY: device memory belongs to the CUDA context rather than to the host thread that
+// allocated it, and the buffers are only accessed behind a `Mutex`, on threads that
+// have made that context current.
+#[allow(unsafe_code)]
+unsafe impl Send for GpuBases {}
+
+static GPU_CONTEXT: Mutex<Option<GpuContext>> = Mutex::new(None);
+/// The bases loaded on the GPU, keyed by the fingerprint of their parameters.
+///
+/// Bases stay loaded for the life of the process, and each entry is locked for the
+/// whole of an MSM over it, so parameters that load their bases while an MSM over
+/// other parameters is running neither free nor replace the bases it reads.
+#[allow(clippy::type_complexity)]
+static GPU_BASES: Mutex<Vec<([u8; 32], Arc<Mutex<GpuBases>>)>> = Mutex::new(Vec::new());
 
 pub fn should_use_cpu_msm(size: usize) -> bool {
     size <= (1
         << u8::from_str_radix(&env::var("ICICLE_SMALL_K").unwrap_or("8".to_string()), 10).unwrap())
 }
 
-pub fn init_gpu<C: CurveAffine>(g: &[C], g_lagrange: &[C]) {
-    unsafe {
-        GPU_INIT.call_once(|| {
-            GPU_CONTEXT = Some(rustacuda::quick_init().unwrap());
-            GPU_G = Some(copy_points_to_device(g));
-            GPU_G_LAGRANGE = Some(copy_points_to_device(g_lagrange));
-        });
+/// Loads the bases `g` and `g_lagrange` of the parameters with the given
+/// [fingerprint](crate::poly::commitment::Params::fingerprint) on the GPU, unless
+/// they are already loaded.
+pub fn init_gpu<C: CurveAffine>(fingerprint: [u8; 32], g: &[C], g_lagrange: &[C]) {
+    let mut loaded = GPU_BASES.lock().unwrap();
+    if loaded.iter().any(|(loaded, _)| *loaded == fingerprint) {
+        return;
     }
+
+    make_context_current();
+    let bases = GpuBases {
+        g: copy_points_to_device(g),
+        g_lagrange: copy_points_to_device(g_lagrange),
+    };
+    loaded.push((fingerprint, Arc::new(Mutex::new(bases))));
 }
 
-fn u32_from_u8(u8_arr: &[u8; 32]) -> [u32; 8] {
-    let mut t = [0u32; 8];
-    for i in 0..8 {
-        t[i] = u32::from_le_bytes([
-            u8_arr[4 * i],
-            u8_arr[4 * i + 1],
-            u8_arr[4 * i + 2],
-            u8_arr[4 * i + 3],
-        ]);
-    }
-    return t;
+/// Makes the CUDA context current on the calling thread, creating it on first use.
+fn make_context_current() {
+    let mut context = GPU_CONTEXT.lock().unwrap();
+    let context = context.get_or_insert_with(|| GpuContext(rustacuda::quick_init().unwrap()));
+    CurrentContext::set_current(&context.0).unwrap();
 }
 
 fn repr_from_u32<C: CurveAffine>(u32_arr: &[u32; 8]) -> <C as CurveAffine>::Base {
-    let t: &[<<C as CurveAffine>::Base as PrimeField>::Repr] =
-        unsafe { mem::transmute(&u32_arr[..]) };
-    return PrimeField::from_repr(t[0]).unwrap();
+    field_from_u32(u32_arr).unwrap()
 }
 
 fn is_infinity_point(point: Point_BN254) -> bool {
@@ -57,14 +86,9 @@ fn is_infinity_point(point: Point_BN254) -> bool {
 }
 
 fn icicle_scalars_from_c<C: CurveAffine>(coeffs: &[C::Scalar]) -> Vec<ScalarField_BN254> {
-    let _coeffs = [Arc::new(
-        coeffs.iter().map(|x| x.to_repr()).collect::<Vec<_>>(),
-    )];
-
-    let _coeffs: &Arc<Vec<[u32; 8]>> = unsafe { mem::transmute(&_coeffs) };
-    _coeffs
+    coeffs
         .iter()
-        .map(|x| ScalarField_BN254::from_limbs(x))
+        .map(|x| ScalarField_BN254::from_limbs(&u32_from_field(x)))
         .collect::<Vec<_>>()
 }
 
@@ -77,22 +101,12 @@ pub fn copy_scalars_to_device<C: CurveAffine>(
 }
 
 fn icicle_points_from_c<C: CurveAffine>(bases: &[C]) -> Vec<PointAffineNoInfinity_BN254> {
-    let _bases = [Arc::new(
-        bases
-            .iter()
-            .map(|p| {
-                let coordinates = p.coordinates().unwrap();
-                [coordinates.x().to_repr(), coordinates.y().to_repr()]
-            })
-            .collect::<Vec<_>>(),
-    )];
-
-    let _bases: &Arc<Vec<[[u8; 32]; 2]>> = unsafe { mem::transmute(&_bases) };
-    _bases
+    bases
         .iter()
-        .map(|x| {
-            let tx = u32_from_u8(&x[0]);
-            let ty = u32_from_u8(&x[1]);
+        .map(|p| {
+            let coordinates = p.coordinates().unwrap();
+            let tx = u32_from_field(coordinates.x());
+            let ty = u32_from_field(coordinates.y());
             PointAffineNoInfinity_BN254::from_limbs(&tx, &ty)
         })
         .collect::<Vec<_>>()
@@ -124,23 +138,66 @@ fn c_from_icicle_point<C: CurveAffine>(commit_res: Point_BN254) -> C::Curve {
     return affine.to_curve();
 }
 
+/// Returns $\sum_i \mathsf{coeffs}_i \cdot \mathsf{bases}_i$, where the bases are a
+/// prefix of `g_lagrange` if `is_lagrange` is set and of `g` otherwise, of the
+/// parameters with the given fingerprint.
+///
+/// Returns `None` if the bases of these parameters are not loaded on the GPU.
 pub fn multiexp_on_device<C: CurveAffine>(
-    mut coeffs: DeviceBuffer<ScalarField_BN254>,
+    fingerprint: [u8; 32],
+    coeffs: &[C::Scalar],
     is_lagrange: bool,
-) -> C::Curve {
-    let base_ptr: &mut DeviceBuffer<PointAffineNoInfinity_BN254>;
-    unsafe {
-        if is_lagrange {
-            base_ptr = GPU_G_LAGRANGE.as_mut().unwrap();
-        } else {
-            base_ptr = GPU_G.as_mut().unwrap();
-        };
-    }
-
+) -> Option<C::Curve> {
+    let bases = GPU_BASES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(loaded, _)| *loaded == fingerprint)
+        .map(|(_, bases)| bases.clone())?;
+
+    make_context_current();
+    let mut coeffs = copy_scalars_to_device::<C>(coeffs);
+    let mut bases = bases.lock().unwrap();
+    let base_ptr = if is_lagrange {
+        &mut bases.g_lagrange
+    } else {
+        &mut bases.g
+    };
     let d_commit_result = commit_bn254(base_ptr, &mut coeffs, 10);
 
     let mut h_commit_result = Point_BN254::zero();
     d_commit_result.copy_to(&mut h_commit_result).unwrap();
 
-    c_from_icicle_point::<C>(h_commit_result)
+    Some(c_from_icicle_point::<C>(h_commit_result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::{ff::Field, prime::PrimeCurveAffine, Curve};
+    use halo2curves::bn256::{Fq, Fr, G1Affine};
+    use rand_core::OsRng;
+
+    #[test]
+    fn scalars_round_trip() {
+        let scalars: Vec<Fr> = (0..16).map(|_| Fr::random(OsRng)).collect();
+        for (scalar, converted) in scalars
+            .iter()
+            .zip(icicle_scalars_from_c::<G1Affine>(&scalars))
+        {
+            assert_eq!(field_from_u32::<Fr>(&converted.s), Some(*scalar));
+        }
+    }
+
+    #[test]
+    fn points_round_trip() {
+        let points: Vec<G1Affine> = (0..16)
+            .map(|_| (G1Affine::generator() * Fr::random(OsRng)).to_affine())
+            .collect();
+        for (point, converted) in points.iter().zip(icicle_points_from_c(&points)) {
+            let x: Fq = repr_from_u32::<G1Affine>(&converted.x.s);
+            let y: Fq = repr_from_u32::<G1Affine>(&converted.y.s);
+            assert_eq!(G1Affine::from_xy(x, y).unwrap(), *point);
+        }
+    }
 }
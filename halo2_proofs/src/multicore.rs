@@ -1,3 +1,10 @@
+//! Parallelism helpers, backed by rayon when it is available.
+//!
+//! Code running in parallel must give the same result for any number of threads.
+//! Work that consumes randomness or reduces partial results must be split in a way
+//! that does not depend on [`current_num_threads`], so that, for example, a proof
+//! created from a given RNG is the same on every machine.
+
 pub use maybe_rayon::{
     current_num_threads,
     iter::{IntoParallelIterator, ParallelIterator},
@@ -33,3 +40,77 @@ where
             .try_reduce(&identity, |a, b| fold_op(a, Ok(b)))
     }
 }
+
+/// Runs `op` in a thread pool with `num_threads` threads.
+#[cfg(test)]
+pub(crate) fn with_num_threads<R: Send>(num_threads: usize, op: impl FnOnce() -> R + Send) -> R {
+    maybe_rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .unwrap()
+        .install(op)
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use group::{prime::PrimeCurveAffine, Curve};
+    use halo2curves::bn256::{Fr, G1Affine};
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::with_num_threads;
+    use crate::{
+        arithmetic::{best_fft, best_multiexp, Bases},
+        poly::EvaluationDomain,
+    };
+
+    /// The thread counts that results are compared across.
+    fn thread_counts() -> Vec<usize> {
+        vec![1, 2, 3, super::current_num_threads().max(4)]
+    }
+
+    #[test]
+    fn fft_is_independent_of_thread_count() {
+        let k = 10;
+        let domain = EvaluationDomain::<Fr>::new(1, k);
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let input: Vec<_> = (0..1 << k).map(|_| Fr::random(&mut rng)).collect();
+
+        let results: Vec<_> = thread_counts()
+            .into_iter()
+            .map(|num_threads| {
+                with_num_threads(num_threads, || {
+                    let mut a = input.clone();
+                    let l = a.len();
+                    best_fft(&mut a, domain.get_omega(), k, domain.get_fft_data(l), false);
+                    a
+                })
+            })
+            .collect();
+        for result in &results[1..] {
+            assert_eq!(result, &results[0]);
+        }
+    }
+
+    #[test]
+    fn multiexp_is_independent_of_thread_count() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let bases: Vec<_> = (0..1000)
+            .map(|_| (G1Affine::generator() * Fr::random(&mut rng)).to_affine())
+            .collect();
+        let coeffs: Vec<_> = (0..1000).map(|_| Fr::random(&mut rng)).collect();
+
+        let results: Vec<_> = thread_counts()
+            .into_iter()
+            .map(|num_threads| {
+                with_num_threads(num_threads, || {
+                    best_multiexp(&coeffs, &bases, Bases::Other).to_affine()
+                })
+            })
+            .collect();
+        for result in &results[1..] {
+            assert_eq!(result, &results[0]);
+        }
+    }
+}
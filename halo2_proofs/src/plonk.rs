@@ -10,8 +10,8 @@ use group::ff::{Field, FromUniformBytes, PrimeField};
 
 use crate::arithmetic::CurveAffine;
 use crate::helpers::{
-    polynomial_slice_byte_length, read_polynomial_vec, write_polynomial_slice, SerdeCurveAffine,
-    SerdePrimeField,
+    polynomial_slice_byte_length, read_shared_polynomial_vec, write_polynomial_slice,
+    SerdeCurveAffine, SerdePrimeField,
 };
 use crate::poly::{
     Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff, PinnedEvaluationDomain,
@@ -27,24 +27,41 @@ mod evaluation;
 mod keygen;
 #[cfg(not(feature = "mv-lookup"))]
 mod lookup;
+mod memory;
 #[cfg(feature = "mv-lookup")]
 mod mv_lookup;
 pub mod permutation;
+mod precommitted;
+mod progress;
 mod shuffle;
 mod vanishing;
 
+mod proof;
 mod prover;
+#[cfg(test)]
+mod test_utils;
 mod verifier;
 
 pub use assigned::*;
 pub use circuit::*;
 pub use error::*;
 pub use keygen::*;
+pub use memory::{MemoryEstimate, ProverScratch};
+pub use precommitted::PrecommittedAdvice;
+pub use progress::{ProgressSink, ProofStage};
+pub use proof::Proof;
 pub use prover::*;
 pub use verifier::*;
 
+#[cfg(not(feature = "mv-lookup"))]
+pub use lookup::Argument as LookupArgument;
+#[cfg(feature = "mv-lookup")]
+pub use mv_lookup::Argument as LookupArgument;
+pub use shuffle::Argument as ShuffleArgument;
+
 use evaluation::Evaluator;
 use std::io;
+use std::sync::Arc;
 
 /// This is a verifying key which allows for the verification of proofs for a
 /// particular circuit.
@@ -61,10 +78,16 @@ pub struct VerifyingKey<C: CurveAffine> {
     selectors: Vec<Vec<bool>>,
     /// Whether selector compression is turned on or not.
     compress_selectors: bool,
+    /// The version of the circuit; see [`Circuit::version`].
+    circuit_version: u32,
 }
 
-// Current version of the VK
-const VERSION: u8 = 0x03;
+// Current version of the VK, used when the circuit version is nonzero
+const VERSION: u8 = 0x04;
+
+// Version of the VK without the circuit version, which is read as 0. Keys for
+// unversioned circuits are still written in this format.
+const VERSION_UNVERSIONED_CIRCUIT: u8 = 0x03;
 
 impl<C: SerdeCurveAffine> VerifyingKey<C>
 where
@@ -81,12 +104,20 @@ where
     /// WITHOUT performing the expensive Montgomery reduction.
     pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
         // Version byte that will be checked on read.
-        writer.write_all(&[VERSION])?;
+        let versioned = self.circuit_version != 0;
+        writer.write_all(&[if versioned {
+            VERSION
+        } else {
+            VERSION_UNVERSIONED_CIRCUIT
+        }])?;
         let k = &self.domain.k();
         assert!(*k <= C::Scalar::S);
         // k value fits in 1 byte
         writer.write_all(&[*k as u8])?;
         writer.write_all(&[self.compress_selectors as u8])?;
+        if versioned {
+            writer.write_all(&self.circuit_version.to_le_bytes())?;
+        }
         writer.write_all(&(self.fixed_commitments.len() as u32).to_le_bytes())?;
         for commitment in &self.fixed_commitments {
             commitment.write(writer, format)?;
@@ -116,6 +147,10 @@ where
     /// Checks that field elements are less than modulus, and then checks that the point is on the curve.
     /// - `RawBytesUnchecked`: Reads an uncompressed curve element with coordinates in Montgomery form;
     /// does not perform any checks
+    ///
+    /// Fails if the key was written for a different [`Circuit::version`] of
+    /// `ConcreteCircuit`, with an [`io::Error`] that wraps
+    /// [`Error::CircuitVersionMismatch`].
     pub fn read<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
         reader: &mut R,
         format: SerdeFormat,
@@ -123,7 +158,7 @@ where
     ) -> io::Result<Self> {
         let mut version_byte = [0u8; 1];
         reader.read_exact(&mut version_byte)?;
-        if VERSION != version_byte[0] {
+        if VERSION != version_byte[0] && VERSION_UNVERSIONED_CIRCUIT != version_byte[0] {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 "unexpected version byte",
@@ -152,6 +187,22 @@ where
             ));
         }
         let compress_selectors = compress_selectors[0] == 1;
+        let circuit_version = if version_byte[0] == VERSION {
+            let mut circuit_version = [0u8; 4];
+            reader.read_exact(&mut circuit_version)?;
+            u32::from_le_bytes(circuit_version)
+        } else {
+            0
+        };
+        if circuit_version != ConcreteCircuit::version() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                Error::CircuitVersionMismatch {
+                    expected: ConcreteCircuit::version(),
+                    actual: circuit_version,
+                },
+            ));
+        }
         let (domain, cs, _) = keygen::create_domain::<C, ConcreteCircuit>(
             k as u32,
             #[cfg(feature = "circuit-params")]
@@ -196,6 +247,7 @@ where
             cs,
             selectors,
             compress_selectors,
+            circuit_version,
         ))
     }
 
@@ -226,7 +278,12 @@ impl<C: CurveAffine> VerifyingKey<C> {
     where
         C: SerdeCurveAffine,
     {
-        10 + (self.fixed_commitments.len() * C::byte_length(format))
+        // The version, k and selector compression bytes, the circuit version of
+        // versioned circuits and the number of fixed commitments.
+        let circuit_version = if self.circuit_version != 0 { 4 } else { 0 };
+        let header = 3 + circuit_version + 4;
+        header
+            + (self.fixed_commitments.len() * C::byte_length(format))
             + self.permutation.bytes_length(format)
             + self.selectors.len()
                 * (self
@@ -243,6 +300,7 @@ impl<C: CurveAffine> VerifyingKey<C> {
         cs: ConstraintSystem<C::Scalar>,
         selectors: Vec<Vec<bool>>,
         compress_selectors: bool,
+        circuit_version: u32,
     ) -> Self
     where
         C::ScalarExt: FromUniformBytes<64>,
@@ -260,6 +318,7 @@ impl<C: CurveAffine> VerifyingKey<C> {
             transcript_repr: C::Scalar::ZERO,
             selectors,
             compress_selectors,
+            circuit_version,
         };
 
         let mut hasher = Blake2bParams::new()
@@ -279,6 +338,14 @@ impl<C: CurveAffine> VerifyingKey<C> {
     }
 
     /// Hashes a verification key into a transcript.
+    ///
+    /// The key is represented by [`Self::transcript_repr`], a digest of its
+    /// [pinned](Self::pinned) form: the field moduli, the evaluation domain, the
+    /// constraint system, the fixed and permutation commitments, and the version of
+    /// the circuit if it is not 0. The prover and
+    /// verifier both hash it before any other element of the proof, so that a proof
+    /// is bound to the circuit it was created for and is rejected by the verifying
+    /// key of any other circuit.
     pub fn hash_into<E: EncodedChallenge<C>, T: Transcript<C, E>>(
         &self,
         transcript: &mut T,
@@ -298,9 +365,16 @@ impl<C: CurveAffine> VerifyingKey<C> {
             fixed_commitments: &self.fixed_commitments,
             permutation: &self.permutation,
             cs: self.cs.pinned(),
+            circuit_version: self.circuit_version,
         }
     }
 
+    /// Returns the version of the circuit this key was created for; see
+    /// [`Circuit::version`].
+    pub fn circuit_version(&self) -> u32 {
+        self.circuit_version
+    }
+
     /// Returns commitments of fixed polynomials
     pub fn fixed_commitments(&self) -> &Vec<C> {
         &self.fixed_commitments
@@ -311,7 +385,14 @@ impl<C: CurveAffine> VerifyingKey<C> {
         &self.permutation
     }
 
-    /// Returns `ConstraintSystem`
+    /// Returns the `ConstraintSystem` of the circuit this key was created for.
+    ///
+    /// This is the exact statement the key encodes, after selectors have been
+    /// turned into fixed columns: its [gates](ConstraintSystem::gates) as expression
+    /// trees, its [lookups](ConstraintSystem::lookups) and
+    /// [shuffles](ConstraintSystem::shuffles), and the columns of its
+    /// [permutation](ConstraintSystem::permutation). It can be inspected to audit
+    /// a key, or to generate a verifier for it, without the source of the circuit.
     pub fn cs(&self) -> &ConstraintSystem<C::Scalar> {
         &self.cs
     }
@@ -324,8 +405,6 @@ impl<C: CurveAffine> VerifyingKey<C> {
 
 /// Minimal representation of a verification key that can be used to identify
 /// its active contents.
-#[allow(dead_code)]
-#[derive(Debug)]
 pub struct PinnedVerificationKey<'a, C: CurveAffine> {
     base_modulus: &'static str,
     scalar_modulus: &'static str,
@@ -333,6 +412,26 @@ pub struct PinnedVerificationKey<'a, C: CurveAffine> {
     cs: PinnedConstraintSystem<'a, C::Scalar>,
     fixed_commitments: &'a Vec<C>,
     permutation: &'a permutation::VerifyingKey<C>,
+    circuit_version: u32,
+}
+
+impl<'a, C: CurveAffine> std::fmt::Debug for PinnedVerificationKey<'a, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("PinnedVerificationKey");
+        debug
+            .field("base_modulus", &self.base_modulus)
+            .field("scalar_modulus", &self.scalar_modulus)
+            .field("domain", &self.domain)
+            .field("cs", &self.cs)
+            .field("fixed_commitments", &self.fixed_commitments)
+            .field("permutation", &self.permutation);
+        // Version 0 is left out, so that the digest of circuits that do not set a
+        // version is the same as before versions were introduced.
+        if self.circuit_version != 0 {
+            debug.field("circuit_version", &self.circuit_version);
+        }
+        debug.finish()
+    }
 }
 /// This is a proving key which allows for the creation of proofs for a
 /// particular circuit.
@@ -342,9 +441,10 @@ pub struct ProvingKey<C: CurveAffine> {
     l0: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
     l_last: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
     l_active_row: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    fixed_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-    fixed_polys: Vec<Polynomial<C::Scalar, Coeff>>,
-    fixed_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    // Fixed columns may be shared with other proving keys; see [`FixedColumnPool`].
+    fixed_values: Vec<Arc<Polynomial<C::Scalar, LagrangeCoeff>>>,
+    fixed_polys: Vec<Arc<Polynomial<C::Scalar, Coeff>>>,
+    fixed_cosets: Vec<Arc<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>,
     permutation: permutation::ProvingKey<C>,
     ev: Evaluator<C>,
 }
@@ -425,9 +525,9 @@ where
         let l0 = Polynomial::read(reader, format)?;
         let l_last = Polynomial::read(reader, format)?;
         let l_active_row = Polynomial::read(reader, format)?;
-        let fixed_values = read_polynomial_vec(reader, format)?;
-        let fixed_polys = read_polynomial_vec(reader, format)?;
-        let fixed_cosets = read_polynomial_vec(reader, format)?;
+        let fixed_values = read_shared_polynomial_vec(reader, format)?;
+        let fixed_polys = read_shared_polynomial_vec(reader, format)?;
+        let fixed_cosets = read_shared_polynomial_vec(reader, format)?;
         let permutation = permutation::ProvingKey::read(reader, format)?;
         let ev = Evaluator::new(vk.cs());
         Ok(Self {
@@ -472,6 +572,8 @@ impl<C: CurveAffine> VerifyingKey<C> {
     }
 }
 
+// Each challenge of the protocol has its own type, so that one cannot be passed where
+// another is expected.
 #[derive(Clone, Copy, Debug)]
 struct Theta;
 type ChallengeTheta<F> = ChallengeScalar<F, Theta>;
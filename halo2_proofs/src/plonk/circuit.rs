@@ -8,6 +8,7 @@ use crate::{
 use core::cmp::max;
 use core::ops::{Add, Mul};
 use ff::Field;
+use rand_core::RngCore;
 use sealed::SealedPhase;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
@@ -797,6 +798,28 @@ pub trait Circuit<F: Field> {
     /// the caller will be different depending on the context, and they may or
     /// may not expect to have a witness present.
     fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Returns the version of the circuit, which is recorded in its verifying key and
+    /// in the [`Proof`](crate::plonk::Proof)s created for it.
+    ///
+    /// Applications can bump the version when they change the circuit, so that
+    /// verifying keys and proofs for older versions are rejected with
+    /// [`Error::CircuitVersionMismatch`] rather than by failing verification. The
+    /// default version is 0.
+    fn version() -> u32 {
+        0
+    }
+
+    /// Returns whether the floor planner should assign each distinct constant once.
+    ///
+    /// When this returns `true`, the built-in floor planners copy every cell that is
+    /// constrained to the same constant from a single fixed cell, rather than
+    /// assigning a fixed cell per constraint. This changes the fixed columns and
+    /// permutation of a circuit that uses a constant more than once, and therefore
+    /// its verifying key, so it is disabled by default.
+    fn deduplicate_constants() -> bool {
+        false
+    }
 }
 
 /// Low-degree expression representing an identity that must hold over the committed columns.
@@ -840,7 +863,7 @@ impl<F: Field> Expression<F> {
                         index: query.column_index,
                         column_type: Fixed,
                     };
-                    cells.queried_cells.push((col, query.rotation).into());
+                    cells.push_cell(col.into(), query.rotation);
                     query.index = Some(cells.meta.query_fixed_index(col, query.rotation));
                 }
             }
@@ -850,7 +873,7 @@ impl<F: Field> Expression<F> {
                         index: query.column_index,
                         column_type: Advice { phase: query.phase },
                     };
-                    cells.queried_cells.push((col, query.rotation).into());
+                    cells.push_cell(col.into(), query.rotation);
                     query.index = Some(cells.meta.query_advice_index(col, query.rotation));
                 }
             }
@@ -860,7 +883,7 @@ impl<F: Field> Expression<F> {
                         index: query.column_index,
                         column_type: Instance,
                     };
-                    cells.queried_cells.push((col, query.rotation).into());
+                    cells.push_cell(col.into(), query.rotation);
                     query.index = Some(cells.meta.query_instance_index(col, query.rotation));
                 }
             }
@@ -1378,13 +1401,33 @@ impl<F: Field> Product<Self> for Expression<F> {
 /// within a custom gate.
 #[derive(Clone, Debug)]
 pub struct VirtualCell {
+    pub(crate) name: String,
     pub(crate) column: Column<Any>,
     pub(crate) rotation: Rotation,
 }
 
+impl VirtualCell {
+    /// Returns the name given to this cell by [`VirtualCells::query_named`], or an
+    /// empty string if it was not named.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the queried column.
+    pub fn column(&self) -> Column<Any> {
+        self.column
+    }
+
+    /// Returns the rotation at which the column is queried.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+}
+
 impl<Col: Into<Column<Any>>> From<(Col, Rotation)> for VirtualCell {
     fn from((column, rotation): (Col, Rotation)) -> Self {
         VirtualCell {
+            name: "".to_string(),
             column: column.into(),
             rotation,
         }
@@ -1555,6 +1598,23 @@ pub struct LookupTracker<F: Field> {
     pub(crate) inputs: Vec<Vec<Expression<F>>>,
 }
 
+/// Whether the proofs of a circuit hide its witness; see
+/// [`ConstraintSystem::set_proving_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProvingMode {
+    /// Proofs are zero-knowledge: the prover blinds every witness polynomial and its
+    /// commitment, and masks the quotient polynomial with a random polynomial.
+    #[default]
+    Hiding,
+    /// Proofs are not zero-knowledge, and may reveal information about the witness.
+    ///
+    /// The prover neither blinds the witness polynomials and commitments nor sends a
+    /// random polynomial in the vanishing argument, and no rows are reserved for
+    /// blinding factors. This is only suitable for circuits whose witness is public,
+    /// but it makes proving faster and leaves more usable rows for a given `k`.
+    NonHiding,
+}
+
 /// This is a description of the circuit environment, such as the gate, column and
 /// permutation arrangements.
 #[derive(Debug, Clone)]
@@ -1604,11 +1664,16 @@ pub struct ConstraintSystem<F: Field> {
     // List of indexes of Fixed columns which are associated to a circuit-general Column tied to their annotation.
     pub(crate) general_column_annotations: HashMap<metadata::Column, String>,
 
+    // Lookup table columns shared between chips, keyed by the tag they were requested with.
+    pub(crate) shared_table_columns: HashMap<String, TableColumn>,
+
     // Vector of fixed columns, which can be used to store constant values
     // that are copied into advice columns.
     pub(crate) constants: Vec<Column<Fixed>>,
 
     pub(crate) minimum_degree: Option<usize>,
+
+    pub(crate) proving_mode: ProvingMode,
 }
 
 /// Represents the minimal parameters that determine a `ConstraintSystem`.
@@ -1631,6 +1696,7 @@ pub struct PinnedConstraintSystem<'a, F: Field> {
     shuffles: &'a Vec<shuffle::Argument<F>>,
     constants: &'a Vec<Column<Fixed>>,
     minimum_degree: &'a Option<usize>,
+    proving_mode: &'a ProvingMode,
 }
 
 impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
@@ -1664,6 +1730,10 @@ impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
         debug_struct
             .field("constants", self.constants)
             .field("minimum_degree", self.minimum_degree);
+        // Only show the proving mode if proofs are not hiding.
+        if *self.proving_mode != ProvingMode::Hiding {
+            debug_struct.field("proving_mode", self.proving_mode);
+        }
         debug_struct.finish()
     }
 }
@@ -1700,8 +1770,10 @@ impl<F: Field> Default for ConstraintSystem<F> {
             lookups: Vec::new(),
             shuffles: Vec::new(),
             general_column_annotations: HashMap::default(),
+            shared_table_columns: HashMap::default(),
             constants: vec![],
             minimum_degree: None,
+            proving_mode: ProvingMode::Hiding,
         }
     }
 }
@@ -1729,6 +1801,7 @@ impl<F: Field> ConstraintSystem<F> {
             shuffles: &self.shuffles,
             constants: &self.constants,
             minimum_degree: &self.minimum_degree,
+            proving_mode: &self.proving_mode,
         }
     }
 
@@ -1754,6 +1827,10 @@ impl<F: Field> ConstraintSystem<F> {
     ///
     /// `table_map` returns a map between input expressions and the table columns
     /// they need to match.
+    ///
+    /// The tuples may be of any width: the prover and verifier compress them into a
+    /// single column with powers of the `theta` challenge, so the degree of the
+    /// argument depends only on the degrees of the expressions.
     #[cfg(not(feature = "mv-lookup"))]
     pub fn lookup<S: AsRef<str>>(
         &mut self,
@@ -1785,6 +1862,10 @@ impl<F: Field> ConstraintSystem<F> {
     ///
     /// `table_map` returns a map between input expressions and the table columns
     /// they need to match.
+    ///
+    /// The tuples may be of any width: the prover and verifier compress them into a
+    /// single column with powers of the `theta` challenge, so the degree of the
+    /// argument depends only on the degrees of the expressions.
     #[cfg(feature = "mv-lookup")]
     pub fn lookup(
         &mut self,
@@ -1889,6 +1970,10 @@ impl<F: Field> ConstraintSystem<F> {
     ///
     /// `table_map` returns a map between input expressions and the table expressions
     /// they need to match.
+    ///
+    /// The tuples may be of any width: the prover and verifier compress them into a
+    /// single column with powers of the `theta` challenge, so the degree of the
+    /// argument depends only on the degrees of the expressions.
     #[cfg(not(feature = "mv-lookup"))]
     pub fn lookup_any<S: AsRef<str>>(
         &mut self,
@@ -1922,6 +2007,10 @@ impl<F: Field> ConstraintSystem<F> {
     ///
     /// `table_map` returns a map between input expressions and the table expressions
     /// they need to match.
+    ///
+    /// The tuples may be of any width: the prover and verifier compress them into a
+    /// single column with powers of the `theta` challenge, so the degree of the
+    /// argument depends only on the degrees of the expressions.
     #[cfg(feature = "mv-lookup")]
     pub fn lookup_any(
         &mut self,
@@ -1947,7 +2036,81 @@ impl<F: Field> ConstraintSystem<F> {
             });
     }
 
-    /// Add a shuffle argument for some input expressions and table expressions.
+    /// Add a lookup argument into a public table, whose contents are given by the
+    /// instance columns returned by `table_map`.
+    ///
+    /// Unlike the fixed tables of [`ConstraintSystem::lookup`], the contents of a public
+    /// table are not part of the verifying key: they are supplied as instance values
+    /// when creating and verifying each proof, so the table can be updated (for
+    /// example once per epoch) without running keygen again. The prover and verifier
+    /// must agree on the table, in the same way as for any other instance value.
+    ///
+    /// Rows of the instance columns beyond the supplied values are zero, so the
+    /// all-zero row is always part of the table. Input expressions should evaluate to
+    /// zero where the lookup is disabled, e.g. by multiplying them by a selector, and
+    /// a zero row should not be meaningful to the circuit otherwise. The table can have
+    /// at most as many rows as the instance columns, which is checked when creating
+    /// and verifying proofs.
+    #[cfg(not(feature = "mv-lookup"))]
+    pub fn lookup_public<S: AsRef<str>>(
+        &mut self,
+        name: S,
+        table_map: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<(Expression<F>, Column<Instance>)>,
+    ) -> usize {
+        self.lookup_any(name, |meta| {
+            let table_map = table_map(meta);
+            table_map
+                .into_iter()
+                .map(|(input, table)| (input, meta.query_instance(table, Rotation::cur())))
+                .collect()
+        })
+    }
+
+    /// Add a lookup argument into a public table, whose contents are given by the
+    /// instance columns returned by `table_map`.
+    ///
+    /// Unlike the fixed tables of [`ConstraintSystem::lookup`], the contents of a public
+    /// table are not part of the verifying key: they are supplied as instance values
+    /// when creating and verifying each proof, so the table can be updated (for
+    /// example once per epoch) without running keygen again. The prover and verifier
+    /// must agree on the table, in the same way as for any other instance value.
+    ///
+    /// Rows of the instance columns beyond the supplied values are zero, so the
+    /// all-zero row is always part of the table. Input expressions should evaluate to
+    /// zero where the lookup is disabled, e.g. by multiplying them by a selector, and
+    /// a zero row should not be meaningful to the circuit otherwise. The table can have
+    /// at most as many rows as the instance columns, which is checked when creating
+    /// and verifying proofs.
+    #[cfg(feature = "mv-lookup")]
+    pub fn lookup_public(
+        &mut self,
+        name: &'static str,
+        table_map: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<(Expression<F>, Column<Instance>)>,
+    ) {
+        self.lookup_any(name, |meta| {
+            let table_map = table_map(meta);
+            table_map
+                .into_iter()
+                .map(|(input, table)| (input, meta.query_instance(table, Rotation::cur())))
+                .collect()
+        })
+    }
+
+    /// Add a shuffle argument for some input expressions and shuffle expressions.
+    ///
+    /// `shuffle_map` returns a map between input expressions and the shuffle
+    /// expressions they need to match. The argument is satisfied if, over the usable
+    /// rows, the tuples of input values are a permutation of the tuples of shuffle
+    /// values. Unlike a lookup, no table is loaded and each value may be used exactly
+    /// once, which makes it suitable for sorting-based range checks and memory
+    /// consistency checks.
+    ///
+    /// Returns the index of the shuffle argument.
+    ///
+    /// # Panics
+    ///
+    /// Like lookup arguments, shuffle arguments cannot contain simple selectors; this
+    /// method panics if any of the returned expressions do.
     pub fn shuffle<S: AsRef<str>>(
         &mut self,
         name: S,
@@ -1957,6 +2120,9 @@ impl<F: Field> ConstraintSystem<F> {
         let shuffle_map = shuffle_map(&mut cells)
             .into_iter()
             .map(|(mut input, mut table)| {
+                if input.contains_simple_selector() || table.contains_simple_selector() {
+                    panic!("expression containing simple selector supplied to shuffle argument");
+                }
                 input.query_cells(&mut cells);
                 table.query_cells(&mut cells);
                 (input, table)
@@ -2079,8 +2245,55 @@ impl<F: Field> ConstraintSystem<F> {
         self.minimum_degree = Some(degree);
     }
 
+    /// Limits the number of equality-enabled columns covered by each product polynomial
+    /// of the permutation argument to `len`.
+    ///
+    /// By default each product polynomial covers as many columns as the circuit degree
+    /// allows, which minimises the number of product polynomials. A smaller chunk
+    /// length adds product polynomials, and with them commitments and evaluations to the
+    /// proof, but leaves the circuit degree unchanged. A chunk length larger than the
+    /// degree allows has no effect; use [`Self::set_minimum_degree`] to raise it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero.
+    pub fn set_permutation_chunk_len(&mut self, len: usize) {
+        self.permutation.set_max_chunk_len(len);
+    }
+
+    /// Sets whether the proofs of the circuit hide its witness. Proofs are
+    /// [`ProvingMode::Hiding`] unless this is called.
+    ///
+    /// The proving mode is part of the verifying key, so a proof created in one mode
+    /// can only be verified in that mode.
+    pub fn set_proving_mode(&mut self, mode: ProvingMode) {
+        self.proving_mode = mode;
+    }
+
     /// Creates a new gate.
     ///
+    /// Columns and selectors can be used in the constraints directly, through
+    /// [`Column::cur`], [`Column::next`], [`Column::prev`], [`Column::rot`] and
+    /// [`Selector::expr`], instead of being queried from the [`VirtualCells`]. Each cell is
+    /// queried once, however many times it appears in the constraints.
+    ///
+    /// ```
+    /// use halo2_proofs::plonk::ConstraintSystem;
+    /// use halo2curves::pasta::Fp;
+    ///
+    /// let mut meta = ConstraintSystem::<Fp>::default();
+    /// let [a, b, c] = [(); 3].map(|_| meta.advice_column());
+    /// let q = meta.selector();
+    ///
+    /// // c = a * b, and the next a is c.
+    /// meta.create_gate("mul", |_| {
+    ///     vec![
+    ///         q.expr() * (a.cur() * b.cur() - c.cur()),
+    ///         q.expr() * (a.next() - c.cur()),
+    ///     ]
+    /// });
+    /// ```
+    ///
     /// # Panics
     ///
     /// A gate is required to contain polynomial constraints. This method will panic if
@@ -2325,6 +2538,25 @@ impl<F: Field> ConstraintSystem<F> {
         }
     }
 
+    /// Returns the lookup table column registered under `tag`, allocating it if this is
+    /// the first request for `tag`.
+    ///
+    /// This lets chips that are configured independently share a table, such as a
+    /// table of the values $[0, 2^K)$, instead of each loading its own copy. The
+    /// returned flag is `true` for the request that allocated the column: that chip is
+    /// responsible for loading the table, and the other chips must not assign to it.
+    /// Chips sharing a tag must agree on the contents of the table.
+    pub fn shared_lookup_table_column(&mut self, tag: &str) -> (TableColumn, bool) {
+        if let Some(column) = self.shared_table_columns.get(tag) {
+            return (*column, false);
+        }
+
+        let column = self.lookup_table_column();
+        self.annotate_lookup_column(column, || tag);
+        self.shared_table_columns.insert(tag.to_string(), column);
+        (column, true)
+    }
+
     /// Annotate a Lookup column.
     pub fn annotate_lookup_column<A, AR>(&mut self, column: TableColumn, annotation: A)
     where
@@ -2397,6 +2629,11 @@ impl<F: Field> ConstraintSystem<F> {
 
     /// Allocate a new advice column in given phase
     ///
+    /// The prover commits to all advice columns of a phase before any challenge usable
+    /// after that phase is squeezed from the transcript. Columns in later phases can
+    /// therefore be assigned values that depend on such challenges, which are obtained
+    /// with [`Layouter::get_challenge`](crate::circuit::Layouter::get_challenge).
+    ///
     /// # Panics
     ///
     /// It panics if previous phase before the given one doesn't have advice column allocated.
@@ -2432,6 +2669,10 @@ impl<F: Field> ConstraintSystem<F> {
 
     /// Requests a challenge that is usable after the given phase.
     ///
+    /// The challenge is squeezed once the advice columns of `phase` have been committed
+    /// to. It can be used in gates via [`Challenge::expr`], and its value is available
+    /// to the assignment of advice columns in later phases.
+    ///
     /// # Panics
     ///
     /// It panics if the given phase doesn't have advice column allocated.
@@ -2530,6 +2771,10 @@ impl<F: Field> ConstraintSystem<F> {
     /// Compute the number of blinding factors necessary to perfectly blind
     /// each of the prover's witness polynomials.
     pub fn blinding_factors(&self) -> usize {
+        if self.proving_mode == ProvingMode::NonHiding {
+            return 0;
+        }
+
         // All of the prover's advice columns are evaluated at no more than
         let factors = *self.num_advice_queries.iter().max().unwrap_or(&1);
         // distinct points during gate checks.
@@ -2556,6 +2801,55 @@ impl<F: Field> ConstraintSystem<F> {
         factors + 1
     }
 
+    /// Returns the largest rotation, in either direction, at which a gate enabled on a
+    /// usable row cannot query another usable row by wrapping around the domain.
+    ///
+    /// Queries within this window that leave the usable rows land in the rows reserved
+    /// for blinding factors, which [`MockProver`](crate::dev::MockProver) reports.
+    /// Queries beyond it may silently read unrelated cells at the other end of the
+    /// circuit.
+    pub fn max_safe_rotation(&self) -> i32 {
+        (self.blinding_factors() + 1) as i32
+    }
+
+    /// Returns the cells queried by gates at rotations beyond
+    /// [`ConstraintSystem::max_safe_rotation`], along with the names of those gates.
+    ///
+    /// These are logged as warnings when the circuit is configured by keygen or the
+    /// [`MockProver`](crate::dev::MockProver).
+    pub fn unsafe_rotations(&self) -> Vec<(&str, &VirtualCell)> {
+        let max_rotation = self.max_safe_rotation();
+        self.gates
+            .iter()
+            .flat_map(|gate| {
+                gate.queried_cells
+                    .iter()
+                    .filter(move |cell| cell.rotation.0.abs() > max_rotation)
+                    .map(move |cell| (gate.name(), cell))
+            })
+            .collect()
+    }
+
+    /// Logs a warning for each of [`ConstraintSystem::unsafe_rotations`].
+    pub(crate) fn warn_unsafe_rotations(&self) {
+        let max_rotation = self.max_safe_rotation();
+        for (gate, cell) in self.unsafe_rotations() {
+            log::warn!(
+                "gate \"{}\" queries {:?} column {}{} at rotation {}, beyond the safe window of {} rows",
+                gate,
+                cell.column.column_type(),
+                cell.column.index(),
+                if cell.name.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (\"{}\")", cell.name)
+                },
+                cell.rotation.0,
+                max_rotation,
+            );
+        }
+    }
+
     /// Returns the minimum necessary rows that need to exist in order to
     /// account for e.g. blinding factors.
     pub fn minimum_rows(&self) -> usize {
@@ -2568,6 +2862,18 @@ impl<F: Field> ConstraintSystem<F> {
             + 1 // for at least one row
     }
 
+    /// Returns the smallest `k` for which a circuit that uses `rows` rows fits in the
+    /// usable rows of the domain, that is, alongside the rows reserved for blinding
+    /// factors and for `l_last`.
+    ///
+    /// `rows` must count every row used by the regions and the lookup tables of the
+    /// circuit; [`CircuitCost::minimum_k`](crate::dev::CircuitCost::minimum_k) measures
+    /// it.
+    pub fn minimum_k(&self, rows: usize) -> u32 {
+        let n = std::cmp::max(rows + self.blinding_factors() + 1, self.minimum_rows());
+        n.next_power_of_two().trailing_zeros()
+    }
+
     /// Returns number of fixed columns
     pub fn num_fixed_columns(&self) -> usize {
         self.num_fixed_columns
@@ -2650,6 +2956,26 @@ impl<F: Field> ConstraintSystem<F> {
     pub fn constants(&self) -> &Vec<Column<Fixed>> {
         &self.constants
     }
+
+    /// Returns whether proofs hide the witness
+    pub fn proving_mode(&self) -> ProvingMode {
+        self.proving_mode
+    }
+
+    /// Returns `true` if proofs hide the witness
+    pub(crate) fn is_hiding(&self) -> bool {
+        self.proving_mode == ProvingMode::Hiding
+    }
+
+    /// Samples a value to blind a witness polynomial or its commitment with, which is
+    /// zero if proofs do not hide the witness.
+    pub(crate) fn blinding_factor(&self, rng: impl RngCore) -> F {
+        if self.is_hiding() {
+            F::random(rng)
+        } else {
+            F::ZERO
+        }
+    }
 }
 
 /// Exposes the "virtual cells" that can be queried while creating a custom gate or lookup
@@ -2670,15 +2996,28 @@ impl<'a, F: Field> VirtualCells<'a, F> {
         }
     }
 
+    // Records a queried cell, once however many times the gate queries it.
+    fn push_cell(&mut self, column: Column<Any>, at: Rotation) {
+        if !self
+            .queried_cells
+            .iter()
+            .any(|cell| cell.column == column && cell.rotation == at)
+        {
+            self.queried_cells.push((column, at).into());
+        }
+    }
+
     /// Query a selector at the current position.
     pub fn query_selector(&mut self, selector: Selector) -> Expression<F> {
-        self.queried_selectors.push(selector);
+        if !self.queried_selectors.contains(&selector) {
+            self.queried_selectors.push(selector);
+        }
         Expression::Selector(selector)
     }
 
     /// Query a fixed column at a relative position
     pub fn query_fixed(&mut self, column: Column<Fixed>, at: Rotation) -> Expression<F> {
-        self.queried_cells.push((column, at).into());
+        self.push_cell(column.into(), at);
         Expression::Fixed(FixedQuery {
             index: Some(self.meta.query_fixed_index(column, at)),
             column_index: column.index,
@@ -2688,7 +3027,7 @@ impl<'a, F: Field> VirtualCells<'a, F> {
 
     /// Query an advice column at a relative position
     pub fn query_advice(&mut self, column: Column<Advice>, at: Rotation) -> Expression<F> {
-        self.queried_cells.push((column, at).into());
+        self.push_cell(column.into(), at);
         Expression::Advice(AdviceQuery {
             index: Some(self.meta.query_advice_index(column, at)),
             column_index: column.index,
@@ -2698,8 +3037,13 @@ impl<'a, F: Field> VirtualCells<'a, F> {
     }
 
     /// Query an instance column at a relative position
+    ///
+    /// Instance columns can be queried in gates, lookups and shuffles at any rotation,
+    /// just like advice and fixed columns. With commitment schemes that do not commit to
+    /// the instance columns (such as KZG), the verifier evaluates the queried instance
+    /// polynomials itself, so every rotation used here adds to the verifier's work.
     pub fn query_instance(&mut self, column: Column<Instance>, at: Rotation) -> Expression<F> {
-        self.queried_cells.push((column, at).into());
+        self.push_cell(column.into(), at);
         Expression::Instance(InstanceQuery {
             index: Some(self.meta.query_instance_index(column, at)),
             column_index: column.index,
@@ -2717,6 +3061,28 @@ impl<'a, F: Field> VirtualCells<'a, F> {
         }
     }
 
+    /// Query an Any column at a relative position, naming the queried cell.
+    ///
+    /// The name identifies the cell in [`ConstraintSystem::unsafe_rotations`] and in
+    /// [`MockProver`](crate::dev::MockProver) failures.
+    pub fn query_named<C: Into<Column<Any>>, S: AsRef<str>>(
+        &mut self,
+        name: S,
+        column: C,
+        at: Rotation,
+    ) -> Expression<F> {
+        let column = column.into();
+        let expr = self.query_any(column, at);
+        if let Some(cell) = self
+            .queried_cells
+            .iter_mut()
+            .find(|cell| cell.column == column && cell.rotation == at)
+        {
+            cell.name = name.as_ref().to_string();
+        }
+        expr
+    }
+
     /// Query a challenge
     pub fn query_challenge(&mut self, challenge: Challenge) -> Expression<F> {
         Expression::Challenge(challenge)
@@ -2725,7 +3091,7 @@ impl<'a, F: Field> VirtualCells<'a, F> {
 
 #[cfg(test)]
 mod tests {
-    use super::Expression;
+    use super::{Column, Expression};
     use halo2curves::bn256::Fr;
 
     #[test]
@@ -2747,6 +3113,76 @@ mod tests {
         assert_eq!(happened, expected);
     }
 
+    #[test]
+    fn unsafe_rotations() {
+        use super::{Any, ConstraintSystem};
+        use crate::poly::Rotation;
+
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let a = meta.advice_column();
+        let q = meta.selector();
+        meta.create_gate("far", |meta| {
+            let q = meta.query_selector(q);
+            let cur = meta.query_named("cur", a, Rotation::cur());
+            let far = meta.query_named("far", a, Rotation(-100));
+            vec![q * (cur - far)]
+        });
+
+        let max_rotation = meta.max_safe_rotation();
+        assert_eq!(max_rotation, meta.blinding_factors() as i32 + 1);
+
+        let unsafe_rotations = meta.unsafe_rotations();
+        assert_eq!(unsafe_rotations.len(), 1);
+        let (gate, cell) = unsafe_rotations[0];
+        assert_eq!(gate, "far");
+        assert_eq!(cell.name(), "far");
+        assert_eq!(cell.column(), Column::<Any>::from(a));
+        assert_eq!(cell.rotation().0, -100);
+
+        // Rotations within the window are safe.
+        meta.create_gate("near", |meta| {
+            let q = meta.query_selector(q);
+            let next = meta.query_advice(a, Rotation(max_rotation));
+            vec![q * next]
+        });
+        assert_eq!(meta.unsafe_rotations().len(), 1);
+    }
+
+    #[test]
+    fn gate_queries_are_deduplicated() {
+        use super::ConstraintSystem;
+        use crate::poly::Rotation;
+
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let q = meta.selector();
+        meta.create_gate("square", |meta| {
+            let b_next = meta.query_advice(b, Rotation::next());
+            vec![
+                q.expr() * (a.cur() * a.cur() - b.cur()),
+                meta.query_selector(q) * (b_next - a.cur()),
+            ]
+        });
+
+        let gate = &meta.gates()[0];
+        assert_eq!(gate.queried_selectors(), &[q]);
+        let queried: Vec<_> = gate
+            .queried_cells()
+            .iter()
+            .map(|cell| (cell.column(), cell.rotation()))
+            .collect();
+        assert_eq!(
+            queried,
+            vec![
+                (b.into(), Rotation::next()),
+                (a.into(), Rotation::cur()),
+                (b.into(), Rotation::cur()),
+            ]
+        );
+        assert_eq!(meta.advice_queries().len(), 3);
+    }
+
     #[test]
     fn iter_product() {
         let exprs: Vec<Expression<Fr>> = vec![
@@ -22,13 +22,34 @@ pub enum Error {
     Opening,
     /// Transcript error
     Transcript(io::Error),
-    /// `k` is too small for the given circuit.
+    /// `k` is too small for the given circuit; see [`ConstraintSystem::minimum_k`].
+    ///
+    /// [`ConstraintSystem::minimum_k`]: crate::plonk::ConstraintSystem::minimum_k
     NotEnoughRowsAvailable {
         /// The current value of `k` being used.
         current_k: u32,
     },
     /// Instance provided exceeds number of available rows
+    ///
+    /// No longer returned by the prover or verifier, which report
+    /// [`Error::InstanceColumnTooLong`] instead.
     InstanceTooLarge,
+    /// The number of instance columns provided for a proof does not match the circuit.
+    InstanceColumnCount {
+        /// The number of instance columns of the circuit.
+        expected: usize,
+        /// The number of instance columns provided.
+        actual: usize,
+    },
+    /// More values were provided for an instance column than it has usable rows.
+    InstanceColumnTooLong {
+        /// The index of the instance column.
+        column: usize,
+        /// The number of values provided for the column.
+        len: usize,
+        /// The number of usable rows of the column.
+        max_len: usize,
+    },
     /// Circuit synthesis requires global constants, but circuit configuration did not
     /// call [`ConstraintSystem::enable_constant`] on fixed columns with sufficient space.
     ///
@@ -39,6 +60,70 @@ pub enum Error {
     ColumnNotInPermutation(Column<Any>),
     /// An error relating to a lookup table.
     TableError(TableError),
+    /// A cell was assigned an unknown value while creating keys or a proof, for
+    /// example because a witness that should have been provided is missing.
+    UnknownValue {
+        /// The region in which the cell was assigned, if any.
+        region: Option<String>,
+        /// The column of the cell.
+        column: Column<Any>,
+        /// The annotation of the cell.
+        annotation: String,
+        /// The row of the cell.
+        row: usize,
+    },
+    /// While creating a proof, the circuit enabled a selector or assigned a fixed cell
+    /// that does not match the proving key. This means that synthesis took a different
+    /// path than during keygen, for example by skipping a region depending on whether a
+    /// witness value is known.
+    ///
+    /// Only selectors and fixed cells are compared against the proving key, so a
+    /// divergence is detected at the first of them that differs. Regions that enable
+    /// no selectors and assign no fixed cells are not checked, and the reported region
+    /// may come after the one where the layout first diverged.
+    SynthesisDivergence {
+        /// The region that was being synthesized when the mismatching cell was
+        /// assigned, if any.
+        region: Option<String>,
+        /// The annotation of the selector or fixed cell that does not match.
+        annotation: String,
+        /// The row of the selector or fixed cell that does not match.
+        row: usize,
+    },
+    /// A [`Proof`](crate::plonk::Proof) was not created for the verifying key it is
+    /// checked against.
+    ProofMismatch(&'static str),
+    /// A [`Proof`](crate::plonk::Proof) was created for a different version of the
+    /// circuit than its verifying key, or a verifying key being read was created for
+    /// a different version of the circuit; see [`Circuit::version`].
+    ///
+    /// [`Circuit::version`]: crate::plonk::Circuit::version
+    CircuitVersionMismatch {
+        /// The version of the circuit of the verifying key, or of the circuit a
+        /// verifying key is read for.
+        expected: u32,
+        /// The version of the circuit the proof or verifying key was created for.
+        actual: u32,
+    },
+    /// The commitment to an advice column does not match the commitment it was
+    /// precommitted to; see [`PrecommittedAdvice`](crate::plonk::PrecommittedAdvice).
+    PrecommittedAdviceMismatch(Column<Any>),
+    /// Precommitted advice cannot be used as given: a column is blinded by the prover or
+    /// is not an advice column of the circuit, or the precommitted columns are not
+    /// given for each proof.
+    InvalidPrecommittedAdvice(&'static str),
+    /// The circuit panicked while synthesizing the witness for a proof. This is only
+    /// returned with the `catch-witness-panics` feature, which otherwise lets the panic
+    /// unwind through the prover.
+    WitnessPanic {
+        /// The region being assigned when the panic occurred, if any.
+        region: Option<String>,
+        /// The column, row and annotation of the cell whose value was being computed,
+        /// if the panic occurred in a witness closure.
+        cell: Option<(Column<Any>, usize, String)>,
+        /// The message of the panic.
+        message: String,
+    },
 }
 
 impl From<io::Error> for Error {
@@ -53,6 +138,84 @@ impl Error {
     pub(crate) fn not_enough_rows_available(current_k: u32) -> Self {
         Error::NotEnoughRowsAvailable { current_k }
     }
+
+    /// Returns the stage of proof verification that failed with this error, or `None`
+    /// if this is not an error that verification returns.
+    ///
+    /// Failing lookup, permutation and gate checks are indistinguishable here; see
+    /// [`VerificationFailure`].
+    pub fn verification_failure(&self) -> Option<VerificationFailure> {
+        match self {
+            Error::InvalidInstances
+            | Error::InstanceColumnCount { .. }
+            | Error::InstanceColumnTooLong { .. } => Some(VerificationFailure::Instances),
+            Error::ProofMismatch(_) | Error::CircuitVersionMismatch { .. } => {
+                Some(VerificationFailure::VerifyingKey)
+            }
+            Error::PrecommittedAdviceMismatch(_) | Error::InvalidPrecommittedAdvice(_) => {
+                Some(VerificationFailure::PrecommittedAdvice)
+            }
+            Error::Transcript(_) => Some(VerificationFailure::Transcript),
+            Error::Opening => Some(VerificationFailure::Opening),
+            Error::ConstraintSystemFailure => Some(VerificationFailure::FinalCheck),
+            _ => None,
+        }
+    }
+}
+
+/// The stage of proof verification that failed; see [`Error::verification_failure`].
+///
+/// Verification stops at the first check that fails. This only distinguishes the stages
+/// that the verifier performs separately, and does not identify the argument that
+/// failed: the gates, lookups, shuffles and the permutation argument are not checked
+/// individually, but aggregated into the vanishing argument, which holds only if the
+/// final check succeeds. An unsatisfied constraint of any of them is therefore reported
+/// as [`VerificationFailure::FinalCheck`]; use [`MockProver`](crate::dev::MockProver)
+/// with the same witness to find which one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VerificationFailure {
+    /// The instances do not match the instance columns of the circuit.
+    Instances,
+    /// The proof was not created for the verifying key, or for this version of the
+    /// circuit.
+    VerifyingKey,
+    /// The advice commitments of the proof do not match the precommitted ones, or the
+    /// precommitted advice was not given for each proof; see
+    /// [`PreparedProof::check_precommitted_advice`].
+    ///
+    /// [`PreparedProof::check_precommitted_advice`]: crate::plonk::PreparedProof::check_precommitted_advice
+    PrecommittedAdvice,
+    /// The proof could not be read from the transcript, for example because it is
+    /// truncated or contains an encoding that is not a valid point or scalar.
+    Transcript,
+    /// The multi-opening proof could not be read or is malformed.
+    Opening,
+    /// The final multiscalar multiplication check failed: the proof was read
+    /// successfully, but it does not show that the circuit is satisfied by the
+    /// instances.
+    ///
+    /// An [`AccumulatorStrategy`] defers this check, and reports it by returning
+    /// `false` from its `finalize` method instead.
+    ///
+    /// [`AccumulatorStrategy`]: crate::poly::kzg::strategy::AccumulatorStrategy
+    FinalCheck,
+}
+
+impl fmt::Display for VerificationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationFailure::Instances => write!(f, "instances do not match the circuit"),
+            VerificationFailure::VerifyingKey => {
+                write!(f, "proof does not match the verifying key")
+            }
+            VerificationFailure::PrecommittedAdvice => {
+                write!(f, "advice does not match its precommitted commitments")
+            }
+            VerificationFailure::Transcript => write!(f, "proof could not be read"),
+            VerificationFailure::Opening => write!(f, "multi-opening proof was invalid"),
+            VerificationFailure::FinalCheck => write!(f, "final check failed"),
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -69,6 +232,18 @@ impl fmt::Display for Error {
                 "k = {current_k} is too small for the given circuit. Try using a larger value of k",
             ),
             Error::InstanceTooLarge => write!(f, "Instance vectors are larger than the circuit"),
+            Error::InstanceColumnCount { expected, actual } => write!(
+                f,
+                "{actual} instance columns were provided, but the circuit has {expected}",
+            ),
+            Error::InstanceColumnTooLong {
+                column,
+                len,
+                max_len,
+            } => write!(
+                f,
+                "{len} values were provided for instance column {column}, which has {max_len} usable rows",
+            ),
             Error::NotEnoughColumnsForConstants => {
                 write!(
                     f,
@@ -79,7 +254,63 @@ impl fmt::Display for Error {
                 f,
                 "Column {column:?} must be included in the permutation. Help: try applying `meta.enable_equalty` on the column",
             ),
-            Error::TableError(error) => write!(f, "{error}")
+            Error::TableError(error) => write!(f, "{error}"),
+            Error::UnknownValue {
+                region,
+                column,
+                annotation,
+                row,
+            } => write!(
+                f,
+                "Cell `{annotation}` in {column:?} at row {row} {} was assigned an unknown value. Help: check that all witnesses are provided",
+                match region {
+                    Some(region) => format!("in region `{region}`"),
+                    None => "outside of any region".to_string(),
+                },
+            ),
+            Error::SynthesisDivergence {
+                region,
+                annotation,
+                row,
+            } => write!(
+                f,
+                "Synthesis diverged from keygen: the selector or fixed cell `{annotation}` at row {row} {} does not match the proving key. Help: the circuit must enable the same selectors and assign the same fixed cells whether or not witness values are known",
+                match region {
+                    Some(region) => format!("in region `{region}`"),
+                    None => "outside of any region".to_string(),
+                },
+            ),
+            Error::ProofMismatch(reason) => {
+                write!(f, "Proof does not match the verifying key: {reason}")
+            }
+            Error::CircuitVersionMismatch { expected, actual } => write!(
+                f,
+                "Created for version {actual} of the circuit, but version {expected} was expected",
+            ),
+            Error::PrecommittedAdviceMismatch(column) => write!(
+                f,
+                "The commitment to {column:?} does not match its precommitted commitment",
+            ),
+            Error::InvalidPrecommittedAdvice(reason) => {
+                write!(f, "Invalid precommitted advice: {reason}")
+            }
+            Error::WitnessPanic {
+                region,
+                cell,
+                message,
+            } => write!(
+                f,
+                "Witness synthesis panicked {}{}: {message}",
+                match cell {
+                    Some((column, row, annotation)) =>
+                        format!("assigning `{annotation}` in {column:?} at row {row} "),
+                    None => String::new(),
+                },
+                match region {
+                    Some(region) => format!("in region `{region}`"),
+                    None => "outside of any region".to_string(),
+                },
+            ),
         }
     }
 }
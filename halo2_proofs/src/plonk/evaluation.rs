@@ -19,8 +19,12 @@ use group::ff::{Field, PrimeField, WithSmallOrderMulGroup};
 use maybe_rayon::iter::IndexedParallelIterator;
 use maybe_rayon::iter::IntoParallelRefIterator;
 use maybe_rayon::iter::ParallelIterator;
+use std::borrow::Borrow;
 
-use super::{shuffle, ConstraintSystem, Expression};
+use super::{
+    shuffle, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeY, ConstraintSystem,
+    Expression,
+};
 
 #[cfg(feature = "mv-lookup")]
 use ff::BatchInvert;
@@ -69,12 +73,12 @@ impl Default for ValueSource {
 impl ValueSource {
     /// Get the value for this source
     #[allow(clippy::too_many_arguments)]
-    pub fn get<F: Field, B: Basis>(
+    pub fn get<F: Field, B: Basis, P: Borrow<Polynomial<F, B>>>(
         &self,
         rotations: &[usize],
         constants: &[F],
         intermediates: &[F],
-        fixed_values: &[Polynomial<F, B>],
+        fixed_values: &[P],
         advice_values: &[Polynomial<F, B>],
         instance_values: &[Polynomial<F, B>],
         challenges: &[F],
@@ -88,7 +92,7 @@ impl ValueSource {
             ValueSource::Constant(idx) => constants[*idx],
             ValueSource::Intermediate(idx) => intermediates[*idx],
             ValueSource::Fixed(column_index, rotation) => {
-                fixed_values[*column_index][rotations[*rotation]]
+                fixed_values[*column_index].borrow()[rotations[*rotation]]
             }
             ValueSource::Advice(column_index, rotation) => {
                 advice_values[*column_index][rotations[*rotation]]
@@ -130,12 +134,12 @@ pub enum Calculation {
 impl Calculation {
     /// Get the resulting value of this calculation
     #[allow(clippy::too_many_arguments)]
-    pub fn evaluate<F: Field, B: Basis>(
+    pub fn evaluate<F: Field, B: Basis, P: Borrow<Polynomial<F, B>>>(
         &self,
         rotations: &[usize],
         constants: &[F],
         intermediates: &[F],
-        fixed_values: &[Polynomial<F, B>],
+        fixed_values: &[P],
         advice_values: &[Polynomial<F, B>],
         instance_values: &[Polynomial<F, B>],
         challenges: &[F],
@@ -366,7 +370,16 @@ impl<C: CurveAffine> Evaluator<C> {
         ev
     }
 
+    /// Returns the number of chunks of [`Self::evaluate_h`] for `num_circuits`
+    /// circuits: the extension of the advice and instance columns to the extended
+    /// domain, and the custom gates, permutation, lookups and shuffles of each circuit.
+    pub(in crate::plonk) fn h_chunks(&self, num_circuits: usize) -> usize {
+        1 + 4 * num_circuits
+    }
+
     /// Evaluate h poly
+    ///
+    /// `chunk_done` is called after each of the chunks counted by [`Self::h_chunks`].
     #[allow(clippy::too_many_arguments)]
     pub(in crate::plonk) fn evaluate_h(
         &self,
@@ -374,14 +387,16 @@ impl<C: CurveAffine> Evaluator<C> {
         advice_polys: &[&[Polynomial<C::ScalarExt, Coeff>]],
         instance_polys: &[&[Polynomial<C::ScalarExt, Coeff>]],
         challenges: &[C::ScalarExt],
-        y: C::ScalarExt,
-        beta: C::ScalarExt,
-        gamma: C::ScalarExt,
-        theta: C::ScalarExt,
+        y: ChallengeY<C>,
+        beta: ChallengeBeta<C>,
+        gamma: ChallengeGamma<C>,
+        theta: ChallengeTheta<C>,
         lookups: &[Vec<lookup::prover::Committed<C>>],
         shuffles: &[Vec<shuffle::prover::Committed<C>>],
         permutations: &[permutation::prover::Committed<C>],
+        chunk_done: &mut dyn FnMut(),
     ) -> Polynomial<C::ScalarExt, ExtendedLagrangeCoeff> {
+        let (y, beta, gamma, theta) = (*y, *beta, *gamma, *theta);
         let start = instant::Instant::now();
         let domain = &pk.vk.domain;
         let size = domain.extended_len();
@@ -420,6 +435,7 @@ impl<C: CurveAffine> Evaluator<C> {
             })
             .collect();
         log::trace!(" - Instance cosets: {:?}", start.elapsed());
+        chunk_done();
 
         let mut values = domain.empty_extended();
 
@@ -435,7 +451,10 @@ impl<C: CurveAffine> Evaluator<C> {
             .zip(permutations.iter())
         {
             // Custom gates
-
+            //
+            // Every column is held in its own coset polynomial, so the order of the
+            // columns in the constraint system does not affect locality here: each
+            // thread reads a contiguous range of rows from every column it queries.
             multicore::scope(|scope| {
                 let chunk_size = (size + num_threads - 1) / num_threads;
                 for (thread_idx, values) in values.chunks_mut(chunk_size).enumerate() {
@@ -464,6 +483,7 @@ impl<C: CurveAffine> Evaluator<C> {
                 }
             });
             log::trace!(" - Custom gates: {:?}", start.elapsed());
+            chunk_done();
 
             // Permutations
             let start = instant::Instant::now();
@@ -471,7 +491,7 @@ impl<C: CurveAffine> Evaluator<C> {
             if !sets.is_empty() {
                 let blinding_factors = pk.vk.cs.blinding_factors();
                 let last_rotation = Rotation(-((blinding_factors + 1) as i32));
-                let chunk_len = pk.vk.cs.degree() - 2;
+                let chunk_len = p.chunk_len(pk.vk.cs.degree());
                 let delta_start = beta * &C::Scalar::ZETA;
 
                 let first_set = sets.first().unwrap();
@@ -523,7 +543,7 @@ impl<C: CurveAffine> Evaluator<C> {
                                 .iter()
                                 .map(|&column| match column.column_type() {
                                     Any::Advice(_) => &advice[column.index()],
-                                    Any::Fixed => &fixed[column.index()],
+                                    Any::Fixed => &*fixed[column.index()],
                                     Any::Instance => &instance[column.index()],
                                 })
                                 .zip(cosets.iter())
@@ -534,7 +554,7 @@ impl<C: CurveAffine> Evaluator<C> {
                             let mut right = set.permutation_product_coset[idx];
                             for values in columns.iter().map(|&column| match column.column_type() {
                                 Any::Advice(_) => &advice[column.index()],
-                                Any::Fixed => &fixed[column.index()],
+                                Any::Fixed => &*fixed[column.index()],
                                 Any::Instance => &instance[column.index()],
                             }) {
                                 right *= values[idx] + current_delta + gamma;
@@ -548,6 +568,7 @@ impl<C: CurveAffine> Evaluator<C> {
                 });
             }
             log::trace!(" - Permutations: {:?}", start.elapsed());
+            chunk_done();
 
             let start = instant::Instant::now();
             // For lookups, compute inputs_inv_sum = ∑ 1 / (f_i(X) + α)
@@ -825,6 +846,7 @@ impl<C: CurveAffine> Evaluator<C> {
                 });
             }
             log::trace!(" - Lookups constraints: {:?}", start.elapsed());
+            chunk_done();
 
             // Shuffle constraints
             let start = instant::Instant::now();
@@ -889,6 +911,7 @@ impl<C: CurveAffine> Evaluator<C> {
                 });
             }
             log::trace!(" - Shuffle constraints: {:?}", start.elapsed());
+            chunk_done();
         }
         values
     }
@@ -1070,10 +1093,10 @@ impl<C: CurveAffine> GraphEvaluator<C> {
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub fn evaluate<B: Basis>(
+    pub fn evaluate<B: Basis, P: Borrow<Polynomial<C::ScalarExt, B>>>(
         &self,
         data: &mut EvaluationData<C>,
-        fixed: &[Polynomial<C::ScalarExt, B>],
+        fixed: &[P],
         advice: &[Polynomial<C::ScalarExt, B>],
         instance: &[Polynomial<C::ScalarExt, B>],
         challenges: &[C::ScalarExt],
@@ -1119,11 +1142,11 @@ impl<C: CurveAffine> GraphEvaluator<C> {
 }
 
 /// Simple evaluation of an expression
-pub fn evaluate<F: Field, B: Basis>(
+pub fn evaluate<F: Field, B: Basis, P: Borrow<Polynomial<F, B>> + Sync>(
     expression: &Expression<F>,
     size: usize,
     rot_scale: i32,
-    fixed: &[Polynomial<F, B>],
+    fixed: &[P],
     advice: &[Polynomial<F, B>],
     instance: &[Polynomial<F, B>],
     challenges: &[F],
@@ -1137,7 +1160,7 @@ pub fn evaluate<F: Field, B: Basis>(
                 &|scalar| scalar,
                 &|_| panic!("virtual selectors are removed during optimization"),
                 &|query| {
-                    fixed[query.column_index]
+                    fixed[query.column_index].borrow()
                         [get_rotation_idx(idx, query.rotation.0, rot_scale, isize)]
                 },
                 &|query| {
@@ -1,9 +1,12 @@
 #![allow(clippy::int_plus_one)]
 
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::ops::Range;
+use std::sync::Arc;
 
-use ff::{Field, FromUniformBytes};
-use group::Curve;
+use ff::{Field, FromUniformBytes, PrimeField};
 
 use super::{
     circuit::{
@@ -14,12 +17,12 @@ use super::{
     permutation, Assigned, Challenge, Error, LagrangeCoeff, Polynomial, ProvingKey, VerifyingKey,
 };
 use crate::{
-    arithmetic::{parallelize, CurveAffine},
+    arithmetic::{batch_normalize, parallelize, CurveAffine},
     circuit::Value,
     poly::{
         batch_invert_assigned,
         commitment::{Blind, Params},
-        EvaluationDomain,
+        Coeff, EvaluationDomain, ExtendedLagrangeCoeff,
     },
 };
 
@@ -44,6 +47,8 @@ where
     #[cfg(feature = "mv-lookup")]
     let cs = cs.chunk_lookups();
 
+    cs.warn_unsafe_rotations();
+
     let degree = cs.degree();
 
     log::debug!("Creating domain with degree {}", degree);
@@ -62,20 +67,22 @@ struct Assembly<F: Field> {
     selectors: Vec<Vec<bool>>,
     // A range of available rows for assignment and copies.
     usable_rows: Range<usize>,
+    // The region currently being assigned, for error reporting.
+    current_region: Option<String>,
     _marker: std::marker::PhantomData<F>,
 }
 
 impl<F: Field> Assignment<F> for Assembly<F> {
-    fn enter_region<NR, N>(&mut self, _: N)
+    fn enter_region<NR, N>(&mut self, name: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        // Do nothing; we don't care about regions in this context.
+        self.current_region = Some(name().into());
     }
 
     fn exit_region(&mut self) {
-        // Do nothing; we don't care about regions in this context.
+        self.current_region = None;
     }
 
     fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
@@ -120,7 +127,7 @@ impl<F: Field> Assignment<F> for Assembly<F> {
 
     fn assign_fixed<V, VR, A, AR>(
         &mut self,
-        _: A,
+        annotation: A,
         column: Column<Fixed>,
         row: usize,
         to: V,
@@ -135,11 +142,20 @@ impl<F: Field> Assignment<F> for Assembly<F> {
             return Err(Error::not_enough_rows_available(self.k));
         }
 
+        let value = to()
+            .into_field()
+            .assign()
+            .map_err(|_| Error::UnknownValue {
+                region: self.current_region.clone(),
+                column: column.into(),
+                annotation: annotation().into(),
+                row,
+            })?;
         *self
             .fixed
             .get_mut(column.index())
             .and_then(|v| v.get_mut(row))
-            .ok_or(Error::BoundsFailure)? = to().into_field().assign()?;
+            .ok_or(Error::BoundsFailure)? = value;
 
         Ok(())
     }
@@ -208,7 +224,12 @@ impl<F: Field> Assignment<F> for Assembly<F> {
 }
 
 /// Generate a `VerifyingKey` from an instance of `Circuit`.
-/// By default, selector compression is turned **off**.
+/// By default, selector compression is turned **on**; see [`keygen_vk_custom`].
+///
+/// Key generation draws no randomness: the fixed and permutation polynomials are
+/// committed to with the default blinding factor, so the key depends only on `params`
+/// and the circuit. Bit-identical keys can therefore be reproduced from the same
+/// `params`, for example ones set up from a seeded RNG.
 pub fn keygen_vk<'params, C, P, ConcreteCircuit>(
     params: &P,
     circuit: &ConcreteCircuit,
@@ -222,19 +243,47 @@ where
     keygen_vk_custom(params, circuit, true)
 }
 
-/// Generate a `VerifyingKey` from an instance of `Circuit`.
+/// The fixed-column assignments, selector positions and copy constraints of a
+/// circuit, captured by synthesizing it once with [`keygen_assignments`].
 ///
-/// The selector compression optimization is turned on only if `compress_selectors` is `true`.
-pub fn keygen_vk_custom<'params, C, P, ConcreteCircuit>(
+/// [`keygen_vk`] and [`keygen_pk`] each synthesize the circuit from scratch. For large
+/// circuits, the circuit can instead be synthesized once, and both keys built from
+/// the result with [`keygen_vk_from_assignments`] and [`keygen_pk_from_assignments`].
+/// The resulting [`ProvingKey`] holds the fixed columns and selectors, so that
+/// [`create_proof`](super::create_proof) only needs to collect the witness.
+#[derive(Clone, Debug)]
+pub struct KeygenAssignments<F: Field> {
+    domain: EvaluationDomain<F>,
+    cs: ConstraintSystem<F>,
+    fixed: Vec<Polynomial<F, LagrangeCoeff>>,
+    permutation: permutation::keygen::Assembly,
+    selectors: Vec<Vec<bool>>,
+    circuit_version: u32,
+}
+
+impl<F: Field> KeygenAssignments<F> {
+    /// Returns the values of the fixed columns, excluding those that selectors are
+    /// converted into.
+    pub fn fixed_values(&self) -> &[Polynomial<F, LagrangeCoeff>] {
+        &self.fixed
+    }
+
+    /// Returns, for each selector, the rows on which it is enabled.
+    pub fn selectors(&self) -> &[Vec<bool>] {
+        &self.selectors
+    }
+}
+
+/// Synthesizes `circuit` to capture its fixed-column assignments, selector positions
+/// and copy constraints; see [`KeygenAssignments`].
+pub fn keygen_assignments<'params, C, P, ConcreteCircuit>(
     params: &P,
     circuit: &ConcreteCircuit,
-    compress_selectors: bool,
-) -> Result<VerifyingKey<C>, Error>
+) -> Result<KeygenAssignments<C::Scalar>, Error>
 where
     C: CurveAffine,
     P: Params<'params, C>,
     ConcreteCircuit: Circuit<C::Scalar>,
-    C::Scalar: FromUniformBytes<64>,
 {
     let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(
         params.k(),
@@ -242,7 +291,7 @@ where
         circuit.params(),
     );
 
-    if (params.n() as usize) < cs.minimum_rows() {
+    if params.k() < cs.minimum_k(0) {
         return Err(Error::not_enough_rows_available(params.k()));
     }
 
@@ -252,6 +301,7 @@ where
         permutation: permutation::keygen::Assembly::new(params.n() as usize, &cs.permutation),
         selectors: vec![vec![false; params.n() as usize]; cs.num_selectors],
         usable_rows: 0..params.n() as usize - (cs.blinding_factors() + 1),
+        current_region: None,
         _marker: std::marker::PhantomData,
     };
 
@@ -263,13 +313,72 @@ where
         cs.constants.clone(),
     )?;
 
-    let mut fixed = batch_invert_assigned(assembly.fixed);
+    Ok(KeygenAssignments {
+        domain,
+        cs,
+        fixed: batch_invert_assigned(&assembly.fixed),
+        permutation: assembly.permutation,
+        selectors: assembly.selectors,
+        circuit_version: ConcreteCircuit::version(),
+    })
+}
+
+/// Generate a `VerifyingKey` from an instance of `Circuit`.
+///
+/// The selector compression optimization is turned on only if `compress_selectors` is `true`.
+/// It combines simple selectors that are never enabled on the same row into shared fixed
+/// columns, replacing each selector in the gates with a polynomial in the shared column
+/// that is non-zero only where the selector is enabled. This reduces the number of fixed
+/// commitments, at the cost of possibly increasing the degree of the gates up to the
+/// maximum degree of the constraint system. Without it, every selector is converted into
+/// its own fixed column.
+pub fn keygen_vk_custom<'params, C, P, ConcreteCircuit>(
+    params: &P,
+    circuit: &ConcreteCircuit,
+    compress_selectors: bool,
+) -> Result<VerifyingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+    C::Scalar: FromUniformBytes<64>,
+{
+    let assignments = keygen_assignments(params, circuit)?;
+    Ok(keygen_vk_from_assignments(
+        params,
+        &assignments,
+        compress_selectors,
+    ))
+}
+
+/// Generate a `VerifyingKey` from the [`KeygenAssignments`] of a circuit.
+///
+/// See [`keygen_vk_custom`] for the meaning of `compress_selectors`.
+///
+/// # Panics
+///
+/// Panics if `params` has a different size than the one `assignments` were created
+/// with.
+pub fn keygen_vk_from_assignments<'params, C, P>(
+    params: &P,
+    assignments: &KeygenAssignments<C::Scalar>,
+    compress_selectors: bool,
+) -> VerifyingKey<C>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+    C::Scalar: FromUniformBytes<64>,
+{
+    assert_eq!(params.k(), assignments.domain.k());
+
+    let domain = assignments.domain.clone();
+    let cs = assignments.cs.clone();
+    let mut fixed = assignments.fixed.clone();
     let (cs, selector_polys) = if compress_selectors {
-        cs.compress_selectors(assembly.selectors.clone(), true)
+        cs.compress_selectors(assignments.selectors.clone(), true)
     } else {
         // After this, the ConstraintSystem should not have any selectors: `verify` does not need them, and `keygen_pk` regenerates `cs` from scratch anyways.
-        let selectors = std::mem::take(&mut assembly.selectors);
-        cs.directly_convert_selectors_to_fixed(selectors, true)
+        cs.directly_convert_selectors_to_fixed(assignments.selectors.clone(), true)
     };
     fixed.extend(
         selector_polys
@@ -277,26 +386,128 @@ where
             .map(|poly| domain.lagrange_from_vec(poly)),
     );
 
-    let permutation_vk = assembly
+    let permutation_vk = assignments
         .permutation
+        .clone()
         .build_vk(params, &domain, &cs.permutation);
 
-    let fixed_commitments = fixed
-        .iter()
-        .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
-        .collect();
+    let fixed_commitments = batch_normalize(
+        &fixed
+            .iter()
+            .map(|poly| params.commit_lagrange(poly, Blind::default()))
+            .collect::<Vec<_>>(),
+    );
+
+    // The raw selectors are only kept when they are compressed.
+    let selectors = if compress_selectors {
+        assignments.selectors.clone()
+    } else {
+        vec![]
+    };
 
-    Ok(VerifyingKey::from_parts(
+    VerifyingKey::from_parts(
         domain,
         fixed_commitments,
         permutation_vk,
         cs,
-        assembly.selectors,
+        selectors,
         compress_selectors,
-    ))
+        assignments.circuit_version,
+    )
+}
+
+/// A fixed column held by a [`FixedColumnPool`], in each of the representations
+/// stored in a [`ProvingKey`].
+#[derive(Debug)]
+struct SharedFixedColumn<F: Field> {
+    values: Arc<Polynomial<F, LagrangeCoeff>>,
+    poly: Arc<Polynomial<F, Coeff>>,
+    coset: Arc<Polynomial<F, ExtendedLagrangeCoeff>>,
+}
+
+/// A pool of fixed columns that can be shared between the proving keys of several
+/// circuits.
+///
+/// Applications that prove several related circuits often load the same large fixed
+/// tables into each of them. Passing the same pool to [`keygen_pk_with_pool`] (or
+/// [`keygen_pk_from_assignments_with_pool`]) for each circuit stores every distinct
+/// fixed column once: proving keys whose fixed columns have identical values, over
+/// evaluation domains of the same size, hold references to the same polynomials.
+///
+/// The pool only needs to live while the proving keys are generated; once it is
+/// dropped, each shared polynomial is freed with the last proving key that uses it.
+#[derive(Debug)]
+pub struct FixedColumnPool<F: Field> {
+    columns: Vec<SharedFixedColumn<F>>,
+    // Indices into `columns`, by extended domain size and fingerprint of the values.
+    index: HashMap<(u32, u64), Vec<usize>>,
+}
+
+impl<F: Field> Default for FixedColumnPool<F> {
+    fn default() -> Self {
+        FixedColumnPool {
+            columns: vec![],
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<F: PrimeField> FixedColumnPool<F> {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct fixed columns in the pool.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns `true` if the pool holds no fixed columns.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Returns the pooled copy of the fixed column with the given `values`, adding it
+    /// to the pool if necessary.
+    fn get_or_insert(
+        &mut self,
+        domain: &EvaluationDomain<F>,
+        values: Polynomial<F, LagrangeCoeff>,
+    ) -> &SharedFixedColumn<F> {
+        let mut hasher = DefaultHasher::new();
+        for value in values.iter() {
+            value.to_repr().as_ref().hash(&mut hasher);
+        }
+        let key = (domain.extended_k(), hasher.finish());
+
+        let candidates = self.index.entry(key).or_default();
+        // Fingerprints may collide, so the values are compared in full.
+        let found = candidates
+            .iter()
+            .copied()
+            .find(|&i| self.columns[i].values[..] == values[..]);
+        let i = match found {
+            Some(i) => i,
+            None => {
+                let poly = domain.lagrange_to_coeff(values.clone());
+                let coset = domain.coeff_to_extended(&poly);
+                self.columns.push(SharedFixedColumn {
+                    values: Arc::new(values),
+                    poly: Arc::new(poly),
+                    coset: Arc::new(coset),
+                });
+                candidates.push(self.columns.len() - 1);
+                self.columns.len() - 1
+            }
+        };
+        &self.columns[i]
+    }
 }
 
 /// Generate a `ProvingKey` from a `VerifyingKey` and an instance of `Circuit`.
+///
+/// Like [`keygen_vk`], this is deterministic in `params`, `vk` and the circuit.
 pub fn keygen_pk<'params, C, P, ConcreteCircuit>(
     params: &P,
     vk: VerifyingKey<C>,
@@ -307,40 +518,79 @@ where
     P: Params<'params, C>,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let mut cs = ConstraintSystem::default();
-    #[cfg(feature = "circuit-params")]
-    let config = ConcreteCircuit::configure_with_params(&mut cs, circuit.params());
-    #[cfg(not(feature = "circuit-params"))]
-    let config = ConcreteCircuit::configure(&mut cs);
-    #[cfg(feature = "mv-lookup")]
-    let cs = cs.chunk_lookups();
+    let assignments = keygen_assignments(params, circuit)?;
+    Ok(keygen_pk_from_assignments(params, vk, assignments))
+}
 
-    if (params.n() as usize) < cs.minimum_rows() {
-        return Err(Error::not_enough_rows_available(params.k()));
-    }
+/// Generate a `ProvingKey` from a `VerifyingKey` and an instance of `Circuit`,
+/// sharing its fixed columns with the other proving keys generated with `pool`.
+pub fn keygen_pk_with_pool<'params, C, P, ConcreteCircuit>(
+    params: &P,
+    vk: VerifyingKey<C>,
+    circuit: &ConcreteCircuit,
+    pool: &mut FixedColumnPool<C::Scalar>,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let assignments = keygen_assignments(params, circuit)?;
+    Ok(keygen_pk_from_assignments_with_pool(
+        params,
+        vk,
+        assignments,
+        pool,
+    ))
+}
 
-    let mut assembly: Assembly<C::Scalar> = Assembly {
-        k: params.k(),
-        fixed: vec![vk.domain.empty_lagrange_assigned(); cs.num_fixed_columns],
-        permutation: permutation::keygen::Assembly::new(params.n() as usize, &cs.permutation),
-        selectors: vec![vec![false; params.n() as usize]; cs.num_selectors],
-        usable_rows: 0..params.n() as usize - (cs.blinding_factors() + 1),
-        _marker: std::marker::PhantomData,
-    };
+/// Generate a `ProvingKey` from a `VerifyingKey` and the [`KeygenAssignments`] of a
+/// circuit, without synthesizing the circuit again.
+///
+/// # Panics
+///
+/// Panics if `params` has a different size than the one `assignments` were created
+/// with.
+pub fn keygen_pk_from_assignments<'params, C, P>(
+    params: &P,
+    vk: VerifyingKey<C>,
+    assignments: KeygenAssignments<C::Scalar>,
+) -> ProvingKey<C>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+{
+    keygen_pk_from_assignments_with_pool(params, vk, assignments, &mut FixedColumnPool::new())
+}
 
-    // Synthesize the circuit to obtain URS
-    ConcreteCircuit::FloorPlanner::synthesize(
-        &mut assembly,
-        circuit,
-        config,
-        cs.constants.clone(),
-    )?;
+/// Generate a `ProvingKey` from a `VerifyingKey` and the [`KeygenAssignments`] of a
+/// circuit, sharing its fixed columns with the other proving keys generated with
+/// `pool`.
+///
+/// # Panics
+///
+/// Panics if `params` has a different size than the one `assignments` were created
+/// with.
+pub fn keygen_pk_from_assignments_with_pool<'params, C, P>(
+    params: &P,
+    vk: VerifyingKey<C>,
+    mut assignments: KeygenAssignments<C::Scalar>,
+    pool: &mut FixedColumnPool<C::Scalar>,
+) -> ProvingKey<C>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+{
+    assert_eq!(params.k(), assignments.domain.k());
 
-    let mut fixed = batch_invert_assigned(assembly.fixed);
+    let mut fixed = std::mem::take(&mut assignments.fixed);
+    let selectors = std::mem::take(&mut assignments.selectors);
     let (cs, selector_polys) = if vk.compress_selectors {
-        cs.compress_selectors(assembly.selectors, true)
+        assignments.cs.compress_selectors(selectors, true)
     } else {
-        cs.directly_convert_selectors_to_fixed(assembly.selectors, true)
+        assignments
+            .cs
+            .directly_convert_selectors_to_fixed(selectors, true)
     };
     fixed.extend(
         selector_polys
@@ -348,17 +598,17 @@ where
             .map(|poly| vk.domain.lagrange_from_vec(poly)),
     );
 
-    let fixed_polys: Vec<_> = fixed
-        .iter()
-        .map(|poly| vk.domain.lagrange_to_coeff(poly.clone()))
-        .collect();
-
-    let fixed_cosets = fixed_polys
-        .iter()
-        .map(|poly| vk.domain.coeff_to_extended(poly))
-        .collect();
+    let mut fixed_values = Vec::with_capacity(fixed.len());
+    let mut fixed_polys = Vec::with_capacity(fixed.len());
+    let mut fixed_cosets = Vec::with_capacity(fixed.len());
+    for values in fixed {
+        let column = pool.get_or_insert(&vk.domain, values);
+        fixed_values.push(column.values.clone());
+        fixed_polys.push(column.poly.clone());
+        fixed_cosets.push(column.coset.clone());
+    }
 
-    let permutation_pk = assembly
+    let permutation_pk = assignments
         .permutation
         .build_pk(params, &vk.domain, &cs.permutation);
 
@@ -398,15 +648,248 @@ where
     // Compute the optimized evaluation data structure
     let ev = Evaluator::new(&vk.cs);
 
-    Ok(ProvingKey {
+    ProvingKey {
         vk,
         l0,
         l_last,
         l_active_row,
-        fixed_values: fixed,
+        fixed_values,
         fixed_polys,
         fixed_cosets,
         permutation: permutation_pk,
         ev,
-    })
+    }
+}
+
+#[test]
+fn test_keygen_vk_compress_selectors() {
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::Expression,
+        poly::{kzg::commitment::ParamsKZG, Rotation},
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::OsRng;
+
+    const NUM_SELECTORS: usize = 4;
+
+    // A circuit with selectors that are enabled on disjoint rows.
+    #[derive(Clone, Copy)]
+    struct MyCircuit;
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = (Column<Advice>, [Selector; NUM_SELECTORS]);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            *self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            // Leave room in the gates for the combined selector expressions.
+            meta.set_minimum_degree(NUM_SELECTORS + 1);
+
+            let a = meta.advice_column();
+            let selectors = [(); NUM_SELECTORS].map(|_| meta.selector());
+            for (i, selector) in selectors.iter().enumerate() {
+                meta.create_gate("a is i", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let q = meta.query_selector(*selector);
+                    vec![q * (a - Expression::Constant(Fr::from(i as u64)))]
+                });
+            }
+            (a, selectors)
+        }
+
+        fn synthesize(
+            &self,
+            (a, selectors): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "disjoint selectors",
+                |mut region| {
+                    for (i, selector) in selectors.iter().enumerate() {
+                        selector.enable(&mut region, i)?;
+                        region.assign_advice(|| "a", a, i, || Value::known(Fr::from(i as u64)))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+
+    let vk = keygen_vk_custom(&params, &MyCircuit, false).expect("keygen_vk should not fail");
+    assert_eq!(vk.fixed_commitments().len(), NUM_SELECTORS);
+
+    let vk = keygen_vk_custom(&params, &MyCircuit, true).expect("keygen_vk should not fail");
+    assert!(vk.fixed_commitments().len() < NUM_SELECTORS);
+}
+
+#[test]
+fn test_vk_constraint_system() {
+    use crate::{plonk::test_utils::AdviceIsFixed, poly::kzg::commitment::ParamsKZG};
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::OsRng;
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let vk = keygen_vk(&params, &AdviceIsFixed(3)).expect("keygen_vk should not fail");
+    let (a, ..) = <AdviceIsFixed as Circuit<Fr>>::configure(&mut ConstraintSystem::default());
+
+    // The statement can be read back from the key alone.
+    let cs = vk.cs();
+    assert_eq!(cs.gates().len(), 1);
+    let gate = &cs.gates()[0];
+    assert_eq!(gate.name(), "a is f");
+    assert_eq!(gate.polynomials().len(), 1);
+    assert_eq!(gate.polynomials()[0].degree(), 2);
+    assert_eq!(cs.lookups().len(), 1);
+    assert_eq!(cs.lookups()[0].table_expressions().len(), 1);
+    assert!(cs.shuffles().is_empty());
+    assert_eq!(cs.permutation().get_columns(), vec![Column::<Any>::from(a)]);
+    // The selector has been turned into a fixed column.
+    assert_eq!(cs.num_selectors(), 0);
+    assert_eq!(vk.fixed_commitments().len(), cs.num_fixed_columns());
+}
+
+#[test]
+fn test_keygen_deterministic() {
+    use crate::{plonk::test_utils::AdviceIsFixed, poly::kzg::commitment::ParamsKZG, SerdeFormat};
+    use halo2curves::bn256::Bn256;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    let circuit = AdviceIsFixed(3);
+    let keys = || {
+        let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, ChaCha20Rng::seed_from_u64(0));
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+        let pk = keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail");
+        (
+            pk.get_vk().to_bytes(SerdeFormat::RawBytes),
+            pk.to_bytes(SerdeFormat::RawBytes),
+        )
+    };
+
+    assert_eq!(keys(), keys());
+
+    // Synthesizing once and reusing the assignments produces the same keys.
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, ChaCha20Rng::seed_from_u64(0));
+    let assignments = keygen_assignments(&params, &circuit).expect("synthesis should not fail");
+    assert_eq!(assignments.selectors().len(), 1);
+    let vk = keygen_vk_from_assignments(&params, &assignments, true);
+    let pk = keygen_pk_from_assignments(&params, vk, assignments);
+    assert_eq!(
+        keys(),
+        (
+            pk.get_vk().to_bytes(SerdeFormat::RawBytes),
+            pk.to_bytes(SerdeFormat::RawBytes),
+        )
+    );
+}
+
+#[test]
+fn test_keygen_fixed_column_pool() {
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        poly::{kzg::commitment::ParamsKZG, Rotation},
+        SerdeFormat,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::OsRng;
+
+    /// A circuit with a fixed table shared between all instances, and a fixed column
+    /// holding a per-circuit constant.
+    #[derive(Clone, Copy)]
+    struct MyCircuit(u64);
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = (Column<Advice>, Column<Fixed>, Column<Fixed>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            *self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let table = meta.fixed_column();
+            let constant = meta.fixed_column();
+            meta.create_gate("a is table + constant", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let table = meta.query_fixed(table, Rotation::cur());
+                let constant = meta.query_fixed(constant, Rotation::cur());
+                vec![a - (table + constant)]
+            });
+            (a, table, constant)
+        }
+
+        fn synthesize(
+            &self,
+            (_, table, constant): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "fixed",
+                |mut region| {
+                    for row in 0..8 {
+                        region.assign_fixed(
+                            || "table",
+                            table,
+                            row,
+                            || Value::known(Fr::from(row as u64)),
+                        )?;
+                        region.assign_fixed(
+                            || "constant",
+                            constant,
+                            row,
+                            || Value::known(Fr::from(self.0)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let mut pool = FixedColumnPool::new();
+    let pks: Vec<_> = [MyCircuit(1), MyCircuit(2)]
+        .iter()
+        .map(|circuit| {
+            let vk = keygen_vk(&params, circuit).expect("keygen_vk should not fail");
+            keygen_pk_with_pool(&params, vk, circuit, &mut pool).expect("keygen_pk should not fail")
+        })
+        .collect();
+
+    // The table is stored once, and each constant column separately.
+    assert!(Arc::ptr_eq(
+        &pks[0].fixed_values[0],
+        &pks[1].fixed_values[0]
+    ));
+    assert!(Arc::ptr_eq(&pks[0].fixed_polys[0], &pks[1].fixed_polys[0]));
+    assert!(Arc::ptr_eq(
+        &pks[0].fixed_cosets[0],
+        &pks[1].fixed_cosets[0]
+    ));
+    assert!(!Arc::ptr_eq(
+        &pks[0].fixed_values[1],
+        &pks[1].fixed_values[1]
+    ));
+    assert_eq!(pool.len(), 3);
+
+    // Sharing columns does not change the proving keys.
+    for (pk, circuit) in pks.iter().zip([MyCircuit(1), MyCircuit(2)]) {
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+        let unshared = keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail");
+        assert_eq!(
+            pk.to_bytes(SerdeFormat::RawBytes),
+            unshared.to_bytes(SerdeFormat::RawBytes)
+        );
+    }
 }
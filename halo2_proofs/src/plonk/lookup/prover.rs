@@ -30,6 +30,7 @@ use std::{
     collections::BTreeMap,
     iter,
     ops::{Mul, MulAssign},
+    sync::Arc,
 };
 
 #[derive(Debug)]
@@ -130,7 +131,7 @@ impl<F: WithSmallOrderMulGroup<3>> Argument<F> {
         domain: &EvaluationDomain<C::Scalar>,
         theta: ChallengeTheta<C>,
         advice_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
-        fixed_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        fixed_values: &'a [Arc<Polynomial<C::Scalar, LagrangeCoeff>>],
         instance_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
         challenges: &'a [C::Scalar],
         mut rng: R,
@@ -180,7 +181,7 @@ impl<F: WithSmallOrderMulGroup<3>> Argument<F> {
         // Closure to construct commitment to vector of values
         let mut commit_values = |values: &Polynomial<C::Scalar, LagrangeCoeff>| {
             let poly = pk.vk.domain.lagrange_to_coeff(values.clone());
-            let blind = Blind(C::Scalar::random(&mut rng));
+            let blind = Blind(pk.vk.cs.blinding_factor(&mut rng));
             let commitment = params.commit_lagrange(values, blind).to_affine();
             (poly, blind, commitment)
         };
@@ -341,7 +342,7 @@ impl<C: CurveAffine> Permuted<C> {
             assert_eq!(z[u], C::Scalar::ONE);
         }
 
-        let product_blind = Blind(C::Scalar::random(rng));
+        let product_blind = Blind(pk.vk.cs.blinding_factor(rng));
         let product_commitment = params.commit_lagrange(&z, product_blind).to_affine();
         let z = pk.vk.domain.lagrange_to_coeff(z);
 
@@ -405,30 +406,35 @@ impl<C: CurveAffine> Evaluated<C> {
             // Open lookup product commitments at x
             .chain(Some(ProverQuery {
                 point: *x,
+                poly_index: 0,
                 poly: &self.constructed.product_poly,
                 blind: self.constructed.product_blind,
             }))
             // Open lookup input commitments at x
             .chain(Some(ProverQuery {
                 point: *x,
+                poly_index: 1,
                 poly: &self.constructed.permuted_input_poly,
                 blind: self.constructed.permuted_input_blind,
             }))
             // Open lookup table commitments at x
             .chain(Some(ProverQuery {
                 point: *x,
+                poly_index: 2,
                 poly: &self.constructed.permuted_table_poly,
                 blind: self.constructed.permuted_table_blind,
             }))
             // Open lookup input commitments at x_inv
             .chain(Some(ProverQuery {
                 point: x_inv,
+                poly_index: 1,
                 poly: &self.constructed.permuted_input_poly,
                 blind: self.constructed.permuted_input_blind,
             }))
             // Open lookup product commitments at x_next
             .chain(Some(ProverQuery {
                 point: x_next,
+                poly_index: 0,
                 poly: &self.constructed.product_poly,
                 blind: self.constructed.product_blind,
             }))
@@ -542,7 +548,12 @@ fn permute_expression_pair_par<'params, C: CurveAffine, P: Params<'params, C>, R
 
     // didn't want to bother with Sync rng or anything so just do this part sequentially
     let blinding: Vec<(C::Scalar, C::Scalar)> = (usable_rows..params.n() as usize)
-        .map(|_| (C::Scalar::random(&mut rng), C::Scalar::random(&mut rng)))
+        .map(|_| {
+            (
+                pk.vk.cs.blinding_factor(&mut rng),
+                pk.vk.cs.blinding_factor(&mut rng),
+            )
+        })
         .collect();
     let (permuted_input_expression, permuted_table_coeffs): (Vec<_>, Vec<_>) = input_unique_ranges
         .into_par_iter()
@@ -630,8 +641,9 @@ fn permute_expression_pair_seq<'params, C: CurveAffine, P: Params<'params, C>, R
     assert!(repeated_input_rows.is_empty());
 
     permuted_input_expression
-        .extend((0..(blinding_factors + 1)).map(|_| C::Scalar::random(&mut rng)));
-    permuted_table_coeffs.extend((0..(blinding_factors + 1)).map(|_| C::Scalar::random(&mut rng)));
+        .extend((0..(blinding_factors + 1)).map(|_| pk.vk.cs.blinding_factor(&mut rng)));
+    permuted_table_coeffs
+        .extend((0..(blinding_factors + 1)).map(|_| pk.vk.cs.blinding_factor(&mut rng)));
     assert_eq!(permuted_input_expression.len(), params.n() as usize);
     assert_eq!(permuted_table_coeffs.len(), params.n() as usize);
 
@@ -180,30 +180,35 @@ impl<C: CurveAffine> Evaluated<C> {
             // Open lookup product commitment at x
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.product_commitment,
+                0,
                 *x,
                 self.product_eval,
             )))
             // Open lookup input commitments at x
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.permuted.permuted_input_commitment,
+                1,
                 *x,
                 self.permuted_input_eval,
             )))
             // Open lookup table commitments at x
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.permuted.permuted_table_commitment,
+                2,
                 *x,
                 self.permuted_table_eval,
             )))
             // Open lookup input commitments at \omega^{-1} x
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.permuted.permuted_input_commitment,
+                1,
                 x_inv,
                 self.permuted_input_inv_eval,
             )))
             // Open lookup product commitment at \omega x
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.product_commitment,
+                0,
                 x_next,
                 self.product_next_eval,
             )))
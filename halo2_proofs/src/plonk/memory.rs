@@ -0,0 +1,168 @@
+use std::mem::size_of;
+
+use ff::Field;
+
+use super::{Assigned, ProvingKey};
+use crate::arithmetic::CurveAffine;
+use crate::poly::{EvaluationDomain, LagrangeCoeff, Polynomial};
+
+/// An estimate of the memory used to create a proof, in bytes, broken down by the
+/// parts of the prover that hold it.
+///
+/// The estimate counts the polynomials held by the prover and ignores smaller
+/// allocations. Returned by [`ProvingKey::memory_requirements`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// The fixed and permutation polynomials held by the proving key.
+    pub proving_key: usize,
+    /// The unreduced advice values that the circuits are synthesized into. These are
+    /// the buffers kept by a [`ProverScratch`], which the prover reuses for each
+    /// circuit, so they are counted once however many circuits are proven.
+    pub synthesis: usize,
+    /// The advice and instance polynomials, in the Lagrange, coefficient and
+    /// extended forms.
+    pub witness: usize,
+    /// The polynomials of the lookup arguments.
+    pub lookups: usize,
+    /// The grand product polynomials of the permutation argument.
+    pub permutation: usize,
+    /// The grand product polynomials of the shuffle arguments.
+    pub shuffles: usize,
+    /// The quotient polynomial, over the extended domain.
+    pub quotient: usize,
+}
+
+impl MemoryEstimate {
+    /// Returns an upper bound on the peak memory use of the prover, assuming every
+    /// part is held at once.
+    pub fn peak(&self) -> usize {
+        self.proving_key
+            + self.synthesis
+            + self.witness
+            + self.lookups
+            + self.permutation
+            + self.shuffles
+            + self.quotient
+    }
+}
+
+/// Buffers that can be reused by [`create_proof_with_scratch`] across proofs,
+/// instead of being allocated for each of them.
+///
+/// Only the advice buffers that the circuits are synthesized into are held; the
+/// polynomials the prover derives from them are allocated for each proof.
+///
+/// The buffers are sized for the proving key they were created for, and are
+/// reallocated if used with a proving key of a different size.
+///
+/// [`create_proof_with_scratch`]: super::create_proof_with_scratch
+#[derive(Debug)]
+pub struct ProverScratch<F: Field> {
+    advice: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
+}
+
+impl<F: Field> Default for ProverScratch<F> {
+    fn default() -> Self {
+        ProverScratch { advice: vec![] }
+    }
+}
+
+impl<F: Field> ProverScratch<F> {
+    /// Returns `num_columns` zeroed advice buffers over `domain`, reusing the ones
+    /// held by `self` where possible.
+    pub(crate) fn take_advice(
+        &mut self,
+        domain: &EvaluationDomain<F>,
+        num_columns: usize,
+    ) -> Vec<Polynomial<Assigned<F>, LagrangeCoeff>> {
+        let n = 1usize << domain.k();
+        let mut advice = std::mem::take(&mut self.advice);
+        advice.retain(|poly| poly.len() == n);
+        advice.truncate(num_columns);
+        for poly in advice.iter_mut() {
+            poly.fill(F::ZERO.into());
+        }
+        advice.resize_with(num_columns, || domain.empty_lagrange_assigned());
+        advice
+    }
+
+    /// Returns advice buffers taken with [`Self::take_advice`] to `self`.
+    pub(crate) fn recycle_advice(&mut self, advice: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>) {
+        self.advice = advice;
+    }
+
+    /// Gets the number of bytes held by the buffers of `self`.
+    pub(super) fn memory_size(&self) -> usize {
+        self.advice.iter().map(|poly| poly.len()).sum::<usize>() * size_of::<Assigned<F>>()
+    }
+}
+
+impl<C: CurveAffine> ProvingKey<C> {
+    /// Estimates the memory needed to create a proof for `num_circuits` circuits
+    /// with this proving key.
+    ///
+    /// This can be used to decide whether a proof can be created before starting
+    /// it. See [`MemoryEstimate`] for what is counted.
+    pub fn memory_requirements(&self, num_circuits: usize) -> MemoryEstimate {
+        let cs = &self.vk.cs;
+        let scalar = size_of::<C::Scalar>();
+        let n = 1usize << self.vk.domain.k();
+        let extended_n = self.vk.domain.extended_len();
+        // A polynomial in the Lagrange and coefficient forms, and over the extended
+        // domain while the quotient is computed.
+        let committed = scalar * (2 * n + extended_n);
+
+        let fixed = self
+            .fixed_values
+            .iter()
+            .map(|poly| poly.len())
+            .sum::<usize>()
+            + self
+                .fixed_polys
+                .iter()
+                .map(|poly| poly.len())
+                .sum::<usize>()
+            + self
+                .fixed_cosets
+                .iter()
+                .map(|poly| poly.len())
+                .sum::<usize>();
+        let proving_key = scalar
+            * (self.l0.len() + self.l_last.len() + self.l_active_row.len() + fixed)
+            + self.permutation.memory_size();
+
+        let synthesis = cs.num_advice_columns * n * size_of::<Assigned<C::Scalar>>();
+        let witness = (cs.num_advice_columns + cs.num_instance_columns) * committed;
+
+        // The compressed input and table, and the committed permuted columns and grand
+        // product, or multiplicities and grand sum.
+        #[cfg(not(feature = "mv-lookup"))]
+        let lookups = cs.lookups.len() * (2 * scalar * n + 3 * committed);
+        #[cfg(feature = "mv-lookup")]
+        let lookups = cs.lookups.len() * (2 * scalar * n + 2 * committed);
+
+        let permutation_sets = cs.permutation.chunks(self.vk.cs_degree);
+        let permutation = permutation_sets * committed;
+
+        let shuffles = cs.shuffles.len() * committed;
+
+        let quotient = 2 * scalar * extended_n;
+
+        MemoryEstimate {
+            proving_key,
+            synthesis,
+            witness: num_circuits * witness,
+            lookups: num_circuits * lookups,
+            permutation: num_circuits * permutation,
+            shuffles: num_circuits * shuffles,
+            quotient,
+        }
+    }
+
+    /// Returns a [`ProverScratch`] with its buffers allocated for this proving key.
+    pub fn prover_scratch(&self) -> ProverScratch<C::Scalar> {
+        ProverScratch {
+            advice: vec![self.vk.domain.empty_lagrange_assigned(); self.vk.cs.num_advice_columns],
+        }
+    }
+}
@@ -23,6 +23,7 @@ use rustc_hash::FxHashMap as HashMap;
 use std::{
     iter,
     ops::{Mul, MulAssign},
+    sync::Arc,
 };
 
 use maybe_rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
@@ -86,7 +87,7 @@ impl<F: WithSmallOrderMulGroup<3>> Argument<F> {
         domain: &EvaluationDomain<C::Scalar>,
         theta: ChallengeTheta<C>,
         advice_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
-        fixed_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        fixed_values: &'a [Arc<Polynomial<C::Scalar, LagrangeCoeff>>],
         instance_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
         challenges: &'a [C::Scalar],
     ) -> Result<Prepared<C>, Error>
@@ -452,16 +453,19 @@ impl<C: CurveAffine> Evaluated<C> {
         iter::empty()
             .chain(Some(ProverQuery {
                 point: *x,
+                poly_index: 0,
                 poly: &self.constructed.phi_poly,
                 blind: Blind(C::Scalar::ZERO),
             }))
             .chain(Some(ProverQuery {
                 point: x_next,
+                poly_index: 0,
                 poly: &self.constructed.phi_poly,
                 blind: Blind(C::Scalar::ZERO),
             }))
             .chain(Some(ProverQuery {
                 point: *x,
+                poly_index: 1,
                 poly: &self.constructed.m_poly,
                 blind: Blind(C::Scalar::ZERO),
             }))
@@ -172,16 +172,19 @@ impl<C: CurveAffine> Evaluated<C> {
         iter::empty()
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.phi_commitment,
+                0,
                 *x,
                 self.phi_eval,
             )))
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.phi_commitment,
+                0,
                 x_next,
                 self.phi_next_eval,
             )))
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.prepared.m_commitment,
+                1,
                 *x,
                 self.m_eval,
             )))
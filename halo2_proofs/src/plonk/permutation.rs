@@ -17,18 +17,38 @@ pub(crate) mod verifier;
 
 pub use keygen::Assembly;
 
+use std::fmt;
 use std::io;
 
 /// A permutation argument.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Argument {
     /// A sequence of columns involved in the argument.
     pub(super) columns: Vec<Column<Any>>,
+    /// The maximum number of columns per product polynomial, if limited below what the
+    /// circuit degree allows.
+    max_chunk_len: Option<usize>,
+}
+
+// The chunk length is only shown when it is set, so that the pinned representation
+// of circuits that do not set it is unchanged.
+impl fmt::Debug for Argument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Argument");
+        debug.field("columns", &self.columns);
+        if let Some(max_chunk_len) = self.max_chunk_len {
+            debug.field("max_chunk_len", &max_chunk_len);
+        }
+        debug.finish()
+    }
 }
 
 impl Argument {
     pub(crate) fn new() -> Self {
-        Argument { columns: vec![] }
+        Argument {
+            columns: vec![],
+            max_chunk_len: None,
+        }
     }
 
     /// Returns the minimum circuit degree required by the permutation argument.
@@ -69,6 +89,38 @@ impl Argument {
         3
     }
 
+    pub(crate) fn set_max_chunk_len(&mut self, max_chunk_len: usize) {
+        assert!(
+            max_chunk_len > 0,
+            "a permutation product polynomial must cover at least one column"
+        );
+        self.max_chunk_len = Some(max_chunk_len);
+    }
+
+    /// Returns the number of columns covered by each product polynomial z(X) of the
+    /// argument, in a circuit of degree `cs_degree`.
+    ///
+    /// Each product polynomial is multiplied by one factor per column in its constraint,
+    /// so at most `cs_degree - 2` columns fit in one; fewer are used if a smaller chunk
+    /// length was set with [`ConstraintSystem::set_permutation_chunk_len`].
+    ///
+    /// [`ConstraintSystem::set_permutation_chunk_len`]: super::ConstraintSystem::set_permutation_chunk_len
+    pub fn chunk_len(&self, cs_degree: usize) -> usize {
+        let max_chunk_len = cs_degree - 2;
+        self.max_chunk_len
+            .map_or(max_chunk_len, |len| std::cmp::min(len, max_chunk_len))
+    }
+
+    /// Returns the number of product polynomials z(X) of the argument, in a circuit of
+    /// degree `cs_degree`.
+    ///
+    /// Consecutive product polynomials are linked by constraining the first value of
+    /// each to the last value of the previous one.
+    pub fn chunks(&self, cs_degree: usize) -> usize {
+        let chunk_len = self.chunk_len(cs_degree);
+        (self.columns.len() + chunk_len - 1) / chunk_len
+    }
+
     pub(crate) fn add_column(&mut self, column: Column<Any>) {
         if !self.columns.contains(&column) {
             self.columns.push(column);
@@ -169,4 +221,16 @@ impl<C: CurveAffine> ProvingKey<C> {
             + polynomial_slice_byte_length(&self.polys)
             + polynomial_slice_byte_length(&self.cosets)
     }
+
+    /// Gets the number of bytes held by the polynomials of `self`.
+    pub(super) fn memory_size(&self) -> usize {
+        let len = self
+            .permutations
+            .iter()
+            .map(|poly| poly.len())
+            .sum::<usize>()
+            + self.polys.iter().map(|poly| poly.len()).sum::<usize>()
+            + self.cosets.iter().map(|poly| poly.len()).sum::<usize>();
+        len * std::mem::size_of::<C::Scalar>()
+    }
 }
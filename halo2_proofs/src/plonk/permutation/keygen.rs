@@ -1,9 +1,8 @@
 use ff::{Field, PrimeField};
-use group::Curve;
 
 use super::{Argument, ProvingKey, VerifyingKey};
 use crate::{
-    arithmetic::{parallelize, CurveAffine},
+    arithmetic::{batch_normalize, parallelize, CurveAffine},
     plonk::{Any, Column, Error},
     poly::{
         commitment::{Blind, Params},
@@ -445,15 +444,12 @@ pub(crate) fn build_vk<'params, C: CurveAffine, P: Params<'params, C>>(
     }
 
     // Pre-compute commitments for the URS.
-    let mut commitments = Vec::with_capacity(p.columns.len());
-    for permutation in &permutations {
-        // Compute commitment to permutation polynomial
-        commitments.push(
-            params
-                .commit_lagrange(permutation, Blind::default())
-                .to_affine(),
-        );
-    }
+    let commitments = batch_normalize(
+        &permutations
+            .iter()
+            .map(|permutation| params.commit_lagrange(permutation, Blind::default()))
+            .collect::<Vec<_>>(),
+    );
 
     VerifyingKey { commitments }
 }
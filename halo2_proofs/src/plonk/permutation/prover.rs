@@ -70,7 +70,7 @@ impl Argument {
         // will never underflow because of the requirement of at least a degree
         // 3 circuit for the permutation argument.
         assert!(pk.vk.cs_degree >= 3);
-        let chunk_len = pk.vk.cs_degree - 2;
+        let chunk_len = self.chunk_len(pk.vk.cs_degree);
         let blinding_factors = pk.vk.cs.blinding_factors();
 
         // Each column gets its own delta power.
@@ -166,7 +166,7 @@ impl Argument {
             // Set new last_z
             last_z = z[params.n() as usize - (blinding_factors + 1)];
 
-            let blind = Blind(C::Scalar::random(&mut rng));
+            let blind = Blind(pk.vk.cs.blinding_factor(&mut rng));
 
             let permutation_product_commitment_projective = params.commit_lagrange(&z, blind);
             let permutation_product_blind = blind;
@@ -212,11 +212,15 @@ impl<C: CurveAffine> super::ProvingKey<C> {
         &self,
         x: ChallengeX<C>,
     ) -> impl Iterator<Item = ProverQuery<'_, C>> + Clone {
-        self.polys.iter().map(move |poly| ProverQuery {
-            point: *x,
-            poly,
-            blind: Blind::default(),
-        })
+        self.polys
+            .iter()
+            .enumerate()
+            .map(move |(poly_index, poly)| ProverQuery {
+                point: *x,
+                poly_index,
+                poly,
+                blind: Blind::default(),
+            })
     }
 
     pub(in crate::plonk) fn evaluate<E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
@@ -294,20 +298,28 @@ impl<C: CurveAffine> Evaluated<C> {
             .rotate_omega(*x, Rotation(-((blinding_factors + 1) as i32)));
 
         iter::empty()
-            .chain(self.constructed.sets.iter().flat_map(move |set| {
-                iter::empty()
-                    // Open permutation product commitments at x and \omega x
-                    .chain(Some(ProverQuery {
-                        point: *x,
-                        poly: &set.permutation_product_poly,
-                        blind: set.permutation_product_blind,
-                    }))
-                    .chain(Some(ProverQuery {
-                        point: x_next,
-                        poly: &set.permutation_product_poly,
-                        blind: set.permutation_product_blind,
-                    }))
-            }))
+            .chain(
+                self.constructed
+                    .sets
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(poly_index, set)| {
+                        iter::empty()
+                            // Open permutation product commitments at x and \omega x
+                            .chain(Some(ProverQuery {
+                                point: *x,
+                                poly_index,
+                                poly: &set.permutation_product_poly,
+                                blind: set.permutation_product_blind,
+                            }))
+                            .chain(Some(ProverQuery {
+                                point: x_next,
+                                poly_index,
+                                poly: &set.permutation_product_poly,
+                                blind: set.permutation_product_blind,
+                            }))
+                    }),
+            )
             // Open it at \omega^{last} x for all but the last set. This rotation is only
             // sensical for the first row, but we only use this rotation in a constraint
             // that is gated on l_0.
@@ -315,11 +327,13 @@ impl<C: CurveAffine> Evaluated<C> {
                 self.constructed
                     .sets
                     .iter()
+                    .enumerate()
                     .rev()
                     .skip(1)
-                    .flat_map(move |set| {
+                    .flat_map(move |(poly_index, set)| {
                         Some(ProverQuery {
                             point: x_last,
+                            poly_index,
                             poly: &set.permutation_product_poly,
                             blind: set.permutation_product_blind,
                         })
@@ -39,7 +39,7 @@ impl Argument {
         vk: &plonk::VerifyingKey<C>,
         transcript: &mut T,
     ) -> Result<Committed<C>, Error> {
-        let chunk_len = vk.cs_degree - 2;
+        let chunk_len = self.chunk_len(vk.cs_degree);
 
         let permutation_product_commitments = self
             .columns
@@ -115,7 +115,7 @@ impl<C: CurveAffine> Evaluated<C> {
         gamma: ChallengeGamma<C>,
         x: ChallengeX<C>,
     ) -> impl Iterator<Item = C::Scalar> + 'a {
-        let chunk_len = vk.cs_degree - 2;
+        let chunk_len = p.chunk_len(vk.cs_degree);
         iter::empty()
             // Enforce only for the first set.
             // l_0(X) * (1 - z_0(X)) = 0
@@ -213,29 +213,44 @@ impl<C: CurveAffine> Evaluated<C> {
             .rotate_omega(*x, Rotation(-((blinding_factors + 1) as i32)));
 
         iter::empty()
-            .chain(self.sets.iter().flat_map(move |set| {
-                iter::empty()
-                    // Open permutation product commitments at x and \omega^{-1} x
-                    // Open permutation product commitments at x and \omega x
-                    .chain(Some(VerifierQuery::new_commitment(
-                        &set.permutation_product_commitment,
-                        *x,
-                        set.permutation_product_eval,
-                    )))
-                    .chain(Some(VerifierQuery::new_commitment(
-                        &set.permutation_product_commitment,
-                        x_next,
-                        set.permutation_product_next_eval,
-                    )))
-            }))
+            .chain(
+                self.sets
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(poly_index, set)| {
+                        iter::empty()
+                            // Open permutation product commitments at x and \omega^{-1} x
+                            // Open permutation product commitments at x and \omega x
+                            .chain(Some(VerifierQuery::new_commitment(
+                                &set.permutation_product_commitment,
+                                poly_index,
+                                *x,
+                                set.permutation_product_eval,
+                            )))
+                            .chain(Some(VerifierQuery::new_commitment(
+                                &set.permutation_product_commitment,
+                                poly_index,
+                                x_next,
+                                set.permutation_product_next_eval,
+                            )))
+                    }),
+            )
             // Open it at \omega^{last} x for all but the last set
-            .chain(self.sets.iter().rev().skip(1).flat_map(move |set| {
-                Some(VerifierQuery::new_commitment(
-                    &set.permutation_product_commitment,
-                    x_last,
-                    set.permutation_product_last_eval.unwrap(),
-                ))
-            }))
+            .chain(
+                self.sets
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .skip(1)
+                    .flat_map(move |(poly_index, set)| {
+                        Some(VerifierQuery::new_commitment(
+                            &set.permutation_product_commitment,
+                            poly_index,
+                            x_last,
+                            set.permutation_product_last_eval.unwrap(),
+                        ))
+                    }),
+            )
     }
 }
 
@@ -249,6 +264,9 @@ impl<C: CurveAffine> CommonEvaluated<C> {
         vkey.commitments
             .iter()
             .zip(self.permutation_evals.iter())
-            .map(move |(commitment, &eval)| VerifierQuery::new_commitment(commitment, *x, eval))
+            .enumerate()
+            .map(move |(poly_index, (commitment, &eval))| {
+                VerifierQuery::new_commitment(commitment, poly_index, *x, eval)
+            })
     }
 }
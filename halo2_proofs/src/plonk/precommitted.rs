@@ -0,0 +1,105 @@
+use ff::Field;
+use group::Curve;
+
+use super::{Advice, Column, ConstraintSystem, Error, ProvingKey};
+use crate::arithmetic::CurveAffine;
+use crate::poly::commitment::{Blind, Params};
+
+/// The commitment to an advice column, created before the proof that uses it.
+///
+/// [`create_proof_with_precommitted_advice`] commits to the column with the blind
+/// given here instead of a random one, and fails if the result is not this
+/// commitment. Proofs that share a precommitted column, or another proof system
+/// that consumes the same commitment, are thereby bound to the same values; the
+/// verifier checks the commitments with [`PreparedProof::check_precommitted_advice`].
+///
+/// The column must be an unblinded advice column (see
+/// [`ConstraintSystem::unblinded_advice_column`]), or the circuit must not hide its
+/// witness, so that the rows reserved for blinding hold fixed values and the
+/// commitment only depends on the values assigned by the circuit and on the blind.
+///
+/// [`create_proof_with_precommitted_advice`]: super::create_proof_with_precommitted_advice
+/// [`PreparedProof::check_precommitted_advice`]: super::PreparedProof::check_precommitted_advice
+/// [`ConstraintSystem::unblinded_advice_column`]: super::ConstraintSystem::unblinded_advice_column
+#[derive(Clone, Copy, Debug)]
+pub struct PrecommittedAdvice<C: CurveAffine> {
+    column: Column<Advice>,
+    commitment: C,
+    blind: Blind<C::Scalar>,
+}
+
+impl<C: CurveAffine> PrecommittedAdvice<C> {
+    /// Describes the commitment `commitment` to `column`, created with `blind`.
+    pub fn new(column: Column<Advice>, commitment: C, blind: Blind<C::Scalar>) -> Self {
+        PrecommittedAdvice {
+            column,
+            commitment,
+            blind,
+        }
+    }
+
+    /// Commits to `values`, assigned from the first row of `column`, as the prover
+    /// commits to the column with `pk`.
+    ///
+    /// Rows after `values` are committed to as unassigned. Returns
+    /// [`Error::InvalidPrecommittedAdvice`] if `column` is blinded by the prover or is
+    /// not in the circuit, and [`Error::NotEnoughRowsAvailable`] if `values` is longer
+    /// than the usable rows of the circuit.
+    pub fn commit<'params, P: Params<'params, C>>(
+        params: &P,
+        pk: &ProvingKey<C>,
+        column: Column<Advice>,
+        values: &[C::Scalar],
+        blind: Blind<C::Scalar>,
+    ) -> Result<Self, Error> {
+        let cs = &pk.vk.cs;
+        check_column(cs, column)?;
+
+        let unusable_rows_start = params.n() as usize - (cs.blinding_factors() + 1);
+        if values.len() > unusable_rows_start {
+            return Err(Error::not_enough_rows_available(params.k()));
+        }
+
+        // This matches the prover: unassigned rows are zero, and the rows reserved for
+        // blinding hold the default blind in unblinded columns.
+        let mut poly = pk.vk.domain.empty_lagrange();
+        poly[..values.len()].copy_from_slice(values);
+        for cell in &mut poly[unusable_rows_start..] {
+            *cell = Blind::<C::Scalar>::default().0;
+        }
+
+        let commitment = params.commit_lagrange(&poly, blind).to_affine();
+        Ok(Self::new(column, commitment, blind))
+    }
+
+    /// Returns the column of the commitment.
+    pub fn column(&self) -> Column<Advice> {
+        self.column
+    }
+
+    /// Returns the commitment.
+    pub fn commitment(&self) -> C {
+        self.commitment
+    }
+
+    /// Returns the blind of the commitment.
+    pub fn blind(&self) -> Blind<C::Scalar> {
+        self.blind
+    }
+}
+
+/// Checks that `column` is an advice column of `cs` that the prover does not blind.
+pub(crate) fn check_column<F: Field>(
+    cs: &ConstraintSystem<F>,
+    column: Column<Advice>,
+) -> Result<(), Error> {
+    if column.index() >= cs.num_advice_columns {
+        return Err(Error::InvalidPrecommittedAdvice(
+            "the column is not in the circuit",
+        ));
+    }
+    if cs.is_hiding() && !cs.unblinded_advice_columns.contains(&column.index()) {
+        return Err(Error::InvalidPrecommittedAdvice("the column is blinded"));
+    }
+    Ok(())
+}
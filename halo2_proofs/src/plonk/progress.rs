@@ -0,0 +1,44 @@
+/// A stage of proof creation, as reported to a [`ProgressSink`].
+///
+/// Stages are reported in the order below, but they may interleave: a sink must not
+/// assume that a stage is complete once another stage starts. Currently only
+/// [`ProofStage::Lookups`] does so. Its product commitments depend on the challenges
+/// drawn for the permutation argument, so it reports `0.5` once its first pass is
+/// done, and reports `1.0` only after [`ProofStage::Permutations`] has completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProofStage {
+    /// Synthesizing the circuits and committing to their advice columns.
+    Advice,
+    /// Committing to the lookup arguments.
+    Lookups,
+    /// Committing to the permutation argument.
+    Permutations,
+    /// Committing to the shuffle arguments.
+    Shuffles,
+    /// Computing and committing to the quotient polynomial.
+    Quotient,
+    /// Evaluating the committed polynomials at the challenge point.
+    Evaluations,
+    /// Creating the multiopening proof. This stage is only reported when it starts
+    /// and when it completes.
+    Multiopen,
+}
+
+/// Receives the progress of proof creation from
+/// [`create_proof_with_progress`](crate::plonk::create_proof_with_progress) or
+/// [`create_proof_with_scratch_and_progress`](crate::plonk::create_proof_with_scratch_and_progress).
+///
+/// Each stage is reported with a fraction of `0.0` when it starts and `1.0` when it
+/// completes, and with fractions in between where the prover loops over the circuits,
+/// over the lookup passes, or over the chunks of the quotient polynomial. Any
+/// `FnMut(ProofStage, f64)` closure is a sink.
+pub trait ProgressSink {
+    /// Reports that `fraction` of `stage` is complete.
+    fn report(&mut self, stage: ProofStage, fraction: f64);
+}
+
+impl<F: FnMut(ProofStage, f64)> ProgressSink for F {
+    fn report(&mut self, stage: ProofStage, fraction: f64) {
+        self(stage, fraction)
+    }
+}
@@ -0,0 +1,554 @@
+use std::io;
+
+use ff::{FromUniformBytes, PrimeField, WithSmallOrderMulGroup};
+use rand_core::{OsRng, RngCore};
+
+use super::{create_proof, Circuit, Error, ProvingKey, VerifyingKey};
+use crate::{
+    arithmetic::CurveAffine,
+    poly::commitment::{CommitmentScheme, Prover, Verifier},
+    transcript::{EncodedChallenge, TranscriptWriterBuffer},
+};
+
+/// The version of the [`Proof`] serialization format.
+const VERSION: u8 = 2;
+
+/// A proof together with the parameters of the circuit it was created for.
+///
+/// [`create_proof`](super::create_proof) writes a proof as raw transcript bytes,
+/// which carry no indication of the circuit they belong to. A `Proof` frames those
+/// bytes with a version byte, the `k` of the circuit, the digest of its verifying
+/// key ([`VerifyingKey::transcript_repr`]) and its [`Circuit::version`], so that a
+/// proof can be matched against a verifying key with [`Proof::check`] before
+/// attempting verification.
+///
+/// The serialized form is
+///
+/// ```text
+/// version (1 byte) || k (4 bytes) || digest (scalar repr)
+///     || circuit version (4 bytes) || transcript length (4 bytes) || transcript
+/// ```
+///
+/// All integers are little-endian, as in the serialized verifying key.
+///
+/// [`Circuit::version`]: super::Circuit::version
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof<C: CurveAffine> {
+    k: u32,
+    circuit_digest: C::Scalar,
+    circuit_version: u32,
+    transcript: Vec<u8>,
+}
+
+impl<C: CurveAffine> Proof<C> {
+    /// Wraps the `transcript` of a proof created with the proving key for `vk`.
+    pub fn new(vk: &VerifyingKey<C>, transcript: Vec<u8>) -> Self {
+        Proof {
+            k: vk.get_domain().k(),
+            circuit_digest: vk.transcript_repr(),
+            circuit_version: vk.circuit_version(),
+            transcript,
+        }
+    }
+
+    /// Creates a proof for `circuits` with the proving key `pk`, written to a
+    /// transcript of type `T`; see [`create_proof`].
+    ///
+    /// The blinding factors are drawn from `rng`, so a seeded RNG always gives the
+    /// same proof for the same inputs, which makes failures in tests reproducible.
+    /// Provers outside of tests should use a cryptographically secure RNG, such as
+    /// the one used by [`Proof::create_with_os_rng`].
+    pub fn create<'params, Scheme, P, E, T, ConcreteCircuit, R>(
+        params: &'params Scheme::ParamsProver,
+        pk: &ProvingKey<C>,
+        circuits: &[ConcreteCircuit],
+        instances: &[&[&[C::Scalar]]],
+        rng: R,
+    ) -> Result<Self, Error>
+    where
+        Scheme: CommitmentScheme<Curve = C, Scalar = C::Scalar>,
+        P: Prover<'params, Scheme>,
+        E: EncodedChallenge<C>,
+        T: TranscriptWriterBuffer<Vec<u8>, C, E>,
+        ConcreteCircuit: Circuit<C::Scalar>,
+        R: RngCore + Send + Sync,
+        C::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+        Scheme::ParamsProver: Send + Sync,
+    {
+        let mut transcript = T::init(vec![]);
+        create_proof::<Scheme, P, E, R, T, ConcreteCircuit>(
+            params,
+            pk,
+            circuits,
+            instances,
+            rng,
+            &mut transcript,
+        )?;
+        Ok(Proof::new(pk.get_vk(), transcript.finalize()?))
+    }
+
+    /// Creates a proof as [`Proof::create`] does, drawing the blinding factors from
+    /// the operating system's RNG.
+    pub fn create_with_os_rng<'params, Scheme, P, E, T, ConcreteCircuit>(
+        params: &'params Scheme::ParamsProver,
+        pk: &ProvingKey<C>,
+        circuits: &[ConcreteCircuit],
+        instances: &[&[&[C::Scalar]]],
+    ) -> Result<Self, Error>
+    where
+        Scheme: CommitmentScheme<Curve = C, Scalar = C::Scalar>,
+        P: Prover<'params, Scheme>,
+        E: EncodedChallenge<C>,
+        T: TranscriptWriterBuffer<Vec<u8>, C, E>,
+        ConcreteCircuit: Circuit<C::Scalar>,
+        C::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+        Scheme::ParamsProver: Send + Sync,
+    {
+        Self::create::<Scheme, P, E, T, ConcreteCircuit, _>(params, pk, circuits, instances, OsRng)
+    }
+
+    /// Returns the `k` of the circuit the proof was created for.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Returns the digest of the verifying key the proof was created for.
+    pub fn circuit_digest(&self) -> C::Scalar {
+        self.circuit_digest
+    }
+
+    /// Returns the version of the circuit the proof was created for.
+    pub fn circuit_version(&self) -> u32 {
+        self.circuit_version
+    }
+
+    /// Returns the transcript bytes, to be read by the verifier.
+    pub fn transcript(&self) -> &[u8] {
+        &self.transcript
+    }
+
+    /// Returns the transcript bytes, consuming the proof.
+    pub fn into_transcript(self) -> Vec<u8> {
+        self.transcript
+    }
+
+    /// Returns the number of bytes in the serialization of `self`.
+    pub fn size(&self) -> usize {
+        Self::header_size() + self.transcript.len()
+    }
+
+    fn header_size() -> usize {
+        let scalar_len = <C::Scalar as PrimeField>::Repr::default().as_ref().len();
+        1 + 4 + scalar_len + 4 + 4
+    }
+
+    /// Returns the size of the serialization of a proof for a single instance of the
+    /// circuit described by `vk`, created with the multiopen verifier `V`, up to the
+    /// multiopening argument.
+    ///
+    /// The size of the multiopening argument that follows depends on the commitment
+    /// scheme and on the queries of the circuit, so a valid proof is at least, and
+    /// usually slightly more than, this size.
+    pub fn expected_size<'params, Scheme, V>(vk: &VerifyingKey<C>) -> usize
+    where
+        Scheme: CommitmentScheme<Curve = C>,
+        V: Verifier<'params, Scheme>,
+    {
+        Self::header_size() + crate::dev::plonk_proof_stats::<Scheme, V>(vk).plonk_bytes()
+    }
+
+    /// Checks that the proof was created for `vk` with the multiopen verifier `V`,
+    /// and is not too short to contain a proof for a single instance of the circuit.
+    ///
+    /// A proof for another version of the circuit is rejected with
+    /// [`Error::CircuitVersionMismatch`]. This does not verify the proof.
+    pub fn check<'params, Scheme, V>(&self, vk: &VerifyingKey<C>) -> Result<(), Error>
+    where
+        Scheme: CommitmentScheme<Curve = C>,
+        V: Verifier<'params, Scheme>,
+    {
+        if self.circuit_version != vk.circuit_version() {
+            return Err(Error::CircuitVersionMismatch {
+                expected: vk.circuit_version(),
+                actual: self.circuit_version,
+            });
+        }
+        if self.k != vk.get_domain().k() {
+            return Err(Error::ProofMismatch("different k"));
+        }
+        if self.circuit_digest != vk.transcript_repr() {
+            return Err(Error::ProofMismatch("different circuit digest"));
+        }
+        if self.size() < Self::expected_size::<Scheme, V>(vk) {
+            return Err(Error::ProofMismatch("proof is too short"));
+        }
+        Ok(())
+    }
+
+    /// Serializes the proof.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.size());
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.k.to_le_bytes());
+        bytes.extend_from_slice(self.circuit_digest.to_repr().as_ref());
+        bytes.extend_from_slice(&self.circuit_version.to_le_bytes());
+        bytes.extend_from_slice(&(self.transcript.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.transcript);
+        bytes
+    }
+
+    /// Deserializes a proof serialized with [`Proof::to_bytes`].
+    pub fn from_bytes(mut bytes: &[u8]) -> io::Result<Self> {
+        let mut version = [0u8; 1];
+        io::Read::read_exact(&mut bytes, &mut version)?;
+        if version[0] != VERSION {
+            return Err(invalid_data("unsupported proof version"));
+        }
+
+        let mut k = [0u8; 4];
+        io::Read::read_exact(&mut bytes, &mut k)?;
+        let k = u32::from_le_bytes(k);
+
+        let mut digest = <C::Scalar as PrimeField>::Repr::default();
+        io::Read::read_exact(&mut bytes, digest.as_mut())?;
+        let circuit_digest = Option::from(C::Scalar::from_repr(digest))
+            .ok_or_else(|| invalid_data("invalid circuit digest"))?;
+
+        let mut circuit_version = [0u8; 4];
+        io::Read::read_exact(&mut bytes, &mut circuit_version)?;
+        let circuit_version = u32::from_le_bytes(circuit_version);
+
+        let mut len = [0u8; 4];
+        io::Read::read_exact(&mut bytes, &mut len)?;
+        if bytes.len() != u32::from_le_bytes(len) as usize {
+            return Err(invalid_data("transcript length does not match"));
+        }
+
+        Ok(Proof {
+            k,
+            circuit_digest,
+            circuit_version,
+            transcript: bytes.to_vec(),
+        })
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::OsRng;
+
+    use super::Proof;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+            ConstraintSystem, Error, VerifyingKey,
+        },
+        poly::{
+            commitment::Params,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+            VerificationStrategy,
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+        SerdeFormat,
+    };
+
+    #[derive(Clone, Default)]
+    struct MyCircuit(Value<Fr>);
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            meta.enable_equality(a);
+            a
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copy",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", config, 0, || self.0)?;
+                    a.copy_advice(|| "a copy", &mut region, config, 1)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn proof_round_trip() {
+        const K: u32 = 4;
+
+        let params: ParamsKZG<Bn256> = ParamsKZG::setup(K, OsRng);
+        let circuit = MyCircuit(Value::known(Fr::ONE));
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = Proof::new(&vk, transcript.finalize().unwrap());
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), proof.size());
+        assert_eq!(&bytes[1..5], &K.to_le_bytes());
+        let proof = Proof::<G1Affine>::from_bytes(&bytes).unwrap();
+        proof
+            .check::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>>(&vk)
+            .unwrap();
+        assert!(
+            proof.size() > Proof::expected_size::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>>(&vk)
+        );
+
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof.transcript());
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            &params,
+            &vk,
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+            params.n(),
+        )
+        .is_ok());
+
+        // Truncated and unknown encodings are rejected.
+        assert!(Proof::<G1Affine>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut unknown = bytes.clone();
+        unknown[0] = 0;
+        assert!(Proof::<G1Affine>::from_bytes(&unknown).is_err());
+
+        // A proof for a different circuit is rejected.
+        let mut other = bytes;
+        other[5] ^= 1;
+        let other = Proof::<G1Affine>::from_bytes(&other).unwrap();
+        assert!(matches!(
+            other.check::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>>(&vk),
+            Err(Error::ProofMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn proof_create_with_seeded_rng() {
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        const K: u32 = 4;
+
+        let params: ParamsKZG<Bn256> = ParamsKZG::setup(K, OsRng);
+        let circuit = MyCircuit(Value::known(Fr::ONE));
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+
+        let create = |seed: u64| {
+            Proof::create::<
+                KZGCommitmentScheme<_>,
+                ProverSHPLONK<_>,
+                _,
+                Blake2bWrite<_, _, Challenge255<_>>,
+                _,
+                _,
+            >(
+                &params,
+                &pk,
+                &[circuit.clone()],
+                &[&[]],
+                ChaCha20Rng::seed_from_u64(seed),
+            )
+            .unwrap()
+        };
+
+        // The same seed gives the same proof, and a different seed a different one.
+        let proof = create(0);
+        assert_eq!(create(0), proof);
+        assert_ne!(create(1), proof);
+
+        let proof = Proof::create_with_os_rng::<
+            KZGCommitmentScheme<_>,
+            ProverSHPLONK<_>,
+            _,
+            Blake2bWrite<_, _, Challenge255<_>>,
+            _,
+        >(&params, &pk, &[circuit], &[&[]])
+        .unwrap();
+        proof
+            .check::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>>(&vk)
+            .unwrap();
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof.transcript());
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            &params,
+            &vk,
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+            params.n(),
+        )
+        .is_ok());
+    }
+
+    // `MyCircuit` at version 1.
+    #[derive(Clone, Default)]
+    struct MyCircuitV1(MyCircuit);
+
+    impl Circuit<Fr> for MyCircuitV1 {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            MyCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            self.0.synthesize(config, layouter)
+        }
+
+        fn version() -> u32 {
+            1
+        }
+    }
+
+    #[test]
+    fn circuit_version() {
+        const K: u32 = 4;
+
+        let params: ParamsKZG<Bn256> = ParamsKZG::setup(K, OsRng);
+        let vk0 = keygen_vk(&params, &MyCircuit::default()).unwrap();
+        let vk1 = keygen_vk(&params, &MyCircuitV1::default()).unwrap();
+        assert_eq!(vk0.circuit_version(), 0);
+        assert_eq!(vk1.circuit_version(), 1);
+        assert_ne!(vk0.transcript_repr(), vk1.transcript_repr());
+
+        // The version is serialized with the proof, and a proof for another version is
+        // rejected before its digest is compared.
+        let proof = Proof::new(&vk0, vec![0; 10]);
+        let bytes = proof.to_bytes();
+        let proof = Proof::<G1Affine>::from_bytes(&bytes).unwrap();
+        assert_eq!(proof.circuit_version(), 0);
+        assert!(matches!(
+            proof.check::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>>(&vk1),
+            Err(Error::CircuitVersionMismatch {
+                expected: 1,
+                actual: 0
+            })
+        ));
+        let proof = Proof::new(&vk1, vec![0; 10]);
+        let bytes1 = proof.to_bytes();
+        assert_eq!(bytes1.len(), proof.size());
+        let proof = Proof::<G1Affine>::from_bytes(&bytes1).unwrap();
+        assert_eq!(proof.circuit_version(), 1);
+
+        // Both proofs share one format, and the circuit version and transcript
+        // length are little-endian like the other integers in the header.
+        let digest_end = 5 + 32;
+        assert_eq!(bytes[0], 2);
+        assert_eq!(bytes1[0], 2);
+        assert_eq!(bytes1.len(), bytes.len());
+        assert_eq!(&bytes[digest_end..digest_end + 4], &0u32.to_le_bytes());
+        assert_eq!(&bytes1[digest_end..digest_end + 4], &1u32.to_le_bytes());
+        assert_eq!(
+            &bytes1[digest_end + 4..digest_end + 8],
+            &10u32.to_le_bytes()
+        );
+
+        // Likewise for verifying keys, which are only read for the version of the
+        // circuit they were created for.
+        assert_eq!(vk0.to_bytes(SerdeFormat::RawBytes)[0], 0x03);
+        let bytes = vk1.to_bytes(SerdeFormat::RawBytes);
+        assert_eq!(bytes[0], 0x04);
+        assert_eq!(&bytes[3..7], &1u32.to_le_bytes());
+        let err = VerifyingKey::<G1Affine>::from_bytes::<MyCircuit>(
+            &bytes,
+            SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            (),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.get_ref().and_then(|e| e.downcast_ref::<Error>()),
+            Some(Error::CircuitVersionMismatch {
+                expected: 0,
+                actual: 1
+            })
+        ));
+        let vk = VerifyingKey::<G1Affine>::from_bytes::<MyCircuitV1>(
+            &bytes,
+            SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            (),
+        )
+        .unwrap();
+        assert_eq!(vk.circuit_version(), 1);
+        assert_eq!(vk.transcript_repr(), vk1.transcript_repr());
+    }
+
+    #[test]
+    fn key_bytes_length() {
+        let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+        let pk0 = keygen_pk(
+            &params,
+            keygen_vk(&params, &MyCircuit::default()).unwrap(),
+            &MyCircuit::default(),
+        )
+        .unwrap();
+        let pk1 = keygen_pk(
+            &params,
+            keygen_vk(&params, &MyCircuitV1::default()).unwrap(),
+            &MyCircuitV1::default(),
+        )
+        .unwrap();
+
+        for pk in [&pk0, &pk1] {
+            for format in [SerdeFormat::Processed, SerdeFormat::RawBytes] {
+                let vk = pk.get_vk();
+                assert_eq!(vk.to_bytes(format).len(), vk.bytes_length(format));
+                assert_eq!(pk.to_bytes(format).len(), pk.bytes_length(format));
+            }
+        }
+        // Keys of unversioned circuits have no circuit version.
+        assert_eq!(
+            pk1.get_vk().bytes_length(SerdeFormat::RawBytes),
+            pk0.get_vk().bytes_length(SerdeFormat::RawBytes) + 4
+        );
+    }
+}
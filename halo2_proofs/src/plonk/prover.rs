@@ -1,13 +1,12 @@
 use ff::{Field, FromUniformBytes, WithSmallOrderMulGroup};
-use group::Curve;
 use instant::Instant;
 use rand_core::RngCore;
 use rustc_hash::FxBuildHasher;
 use rustc_hash::FxHashMap as HashMap;
 use rustc_hash::FxHashSet as HashSet;
 use std::collections::BTreeSet;
-use std::iter;
 use std::ops::RangeTo;
+use std::sync::Arc;
 
 use super::{
     circuit::{
@@ -15,8 +14,11 @@ use super::{
         Advice, Any, Assignment, Challenge, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner,
         Instance, Selector,
     },
-    permutation, shuffle, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
-    ChallengeY, Error, ProvingKey,
+    permutation,
+    precommitted::check_column,
+    shuffle, validate_instances, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta,
+    ChallengeX, ChallengeY, Error, PrecommittedAdvice, ProgressSink, ProofStage, ProverScratch,
+    ProvingKey,
 };
 #[cfg(feature = "mv-lookup")]
 use maybe_rayon::iter::{IndexedParallelIterator, ParallelIterator};
@@ -30,24 +32,28 @@ use super::mv_lookup as lookup;
 use maybe_rayon::iter::{IntoParallelIterator, IntoParallelRefIterator};
 
 use crate::{
-    arithmetic::{eval_polynomial, CurveAffine},
+    arithmetic::{batch_normalize, ct, eval_polynomial, CurveAffine},
     circuit::Value,
     plonk::Assigned,
     poly::{
         commitment::{Blind, CommitmentScheme, Params, Prover},
-        Basis, Coeff, LagrangeCoeff, Polynomial, ProverQuery,
+        Basis, Coeff, LagrangeCoeff, Polynomial, ProverQuery, QueryList,
     },
 };
 use crate::{
     poly::batch_invert_assigned,
     transcript::{EncodedChallenge, TranscriptWrite},
 };
-use group::prime::PrimeCurveAffine;
 
 /// This creates a proof for the provided `circuit` when given the public
 /// parameters `params` and the proving key [`ProvingKey`] that was
 /// generated previously for the same circuit. The provided `instances`
 /// are zero-padded internally.
+///
+/// Returns [`Error::SynthesisDivergence`] if the circuits enable selectors or assign
+/// fixed cells that differ from those recorded in `pk`, which happens when synthesis
+/// lays out the circuit differently than it did during keygen. Differences in the
+/// layout of advice cells alone are not detected.
 pub fn create_proof<
     'params,
     Scheme: CommitmentScheme,
@@ -61,8 +67,224 @@ pub fn create_proof<
     pk: &ProvingKey<Scheme::Curve>,
     circuits: &[ConcreteCircuit],
     instances: &[&[&[Scheme::Scalar]]],
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    Scheme::ParamsProver: Send + Sync,
+{
+    create_proof_with_scratch::<Scheme, P, E, R, T, ConcreteCircuit>(
+        params,
+        pk,
+        circuits,
+        instances,
+        rng,
+        transcript,
+        &mut ProverScratch::default(),
+    )
+}
+
+/// Creates a proof like [`create_proof`], synthesizing the circuits into the advice
+/// buffers held by `scratch` instead of allocating them.
+///
+/// Passing the same [`ProverScratch`] to successive calls, for example one obtained
+/// from [`ProvingKey::prover_scratch`], avoids allocating these buffers for every
+/// proof. The polynomials derived from them, such as the committed advice columns
+/// and the permutation, lookup and quotient polynomials, are still allocated for
+/// each proof.
+#[allow(clippy::too_many_arguments)]
+pub fn create_proof_with_scratch<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore + Send + Sync,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    rng: R,
+    transcript: &mut T,
+    scratch: &mut ProverScratch<Scheme::Scalar>,
+) -> Result<(), Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    Scheme::ParamsProver: Send + Sync,
+{
+    create_proof_with_scratch_and_progress::<Scheme, P, E, R, T, ConcreteCircuit, _>(
+        params,
+        pk,
+        circuits,
+        instances,
+        rng,
+        transcript,
+        scratch,
+        &mut |_: ProofStage, _: f64| {},
+    )
+}
+
+/// Creates a proof like [`create_proof`], reporting its progress to `progress`.
+///
+/// The progress of each [`ProofStage`] is reported as it is reached, which can drive
+/// a progress bar or a watchdog in an application that creates long proofs.
+pub fn create_proof_with_progress<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore + Send + Sync,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+    S: ProgressSink,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    rng: R,
+    transcript: &mut T,
+    progress: &mut S,
+) -> Result<(), Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    Scheme::ParamsProver: Send + Sync,
+{
+    create_proof_with_scratch_and_progress::<Scheme, P, E, R, T, ConcreteCircuit, S>(
+        params,
+        pk,
+        circuits,
+        instances,
+        rng,
+        transcript,
+        &mut ProverScratch::default(),
+        progress,
+    )
+}
+
+/// Creates a proof like [`create_proof_with_scratch`], reporting its progress to
+/// `progress` like [`create_proof_with_progress`].
+#[allow(clippy::too_many_arguments)]
+pub fn create_proof_with_scratch_and_progress<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore + Send + Sync,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+    S: ProgressSink,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    rng: R,
+    transcript: &mut T,
+    scratch: &mut ProverScratch<Scheme::Scalar>,
+    progress: &mut S,
+) -> Result<(), Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    Scheme::ParamsProver: Send + Sync,
+{
+    create_proof_inner::<Scheme, P, E, R, T, ConcreteCircuit, S>(
+        params,
+        pk,
+        circuits,
+        instances,
+        &[],
+        rng,
+        transcript,
+        scratch,
+        progress,
+    )
+}
+
+/// Creates a proof like [`create_proof`], with some advice columns committed to as
+/// given by `precommitted`, which holds the precommitted columns of each circuit.
+///
+/// Each precommitted column is committed to with the blind of its
+/// [`PrecommittedAdvice`], and [`Error::PrecommittedAdviceMismatch`] is returned if
+/// the commitment to the values assigned by the circuit differs from the
+/// precommitted one. The proof then contains the precommitted commitments, which
+/// the verifier checks with
+/// [`PreparedProof::check_precommitted_advice`](super::PreparedProof::check_precommitted_advice).
+///
+/// Returns [`Error::InvalidPrecommittedAdvice`] if `precommitted` does not have one
+/// entry per circuit, or if a precommitted column is blinded or is not in the circuit.
+#[allow(clippy::too_many_arguments)]
+pub fn create_proof_with_precommitted_advice<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore + Send + Sync,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    precommitted: &[&[PrecommittedAdvice<Scheme::Curve>]],
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    Scheme::ParamsProver: Send + Sync,
+{
+    if precommitted.len() != circuits.len() {
+        return Err(Error::InvalidPrecommittedAdvice(
+            "precommitted advice must be given for each circuit",
+        ));
+    }
+    for precommitted in precommitted
+        .iter()
+        .flat_map(|precommitted| precommitted.iter())
+    {
+        check_column(&pk.vk.cs, precommitted.column())?;
+    }
+
+    create_proof_inner::<Scheme, P, E, R, T, ConcreteCircuit, _>(
+        params,
+        pk,
+        circuits,
+        instances,
+        precommitted,
+        rng,
+        transcript,
+        &mut ProverScratch::default(),
+        &mut |_: ProofStage, _: f64| {},
+    )
+}
+
+// Creates a proof. `precommitted` is either empty, or holds the precommitted advice
+// columns of each circuit.
+#[allow(clippy::too_many_arguments)]
+fn create_proof_inner<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore + Send + Sync,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+    S: ProgressSink,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    precommitted: &[&[PrecommittedAdvice<Scheme::Curve>]],
     mut rng: R,
     transcript: &mut T,
+    scratch: &mut ProverScratch<Scheme::Scalar>,
+    progress: &mut S,
 ) -> Result<(), Error>
 where
     Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
@@ -83,9 +305,7 @@ where
     }
 
     for instance in instances.iter() {
-        if instance.len() != pk.vk.cs.num_instance_columns {
-            return Err(Error::InvalidInstances);
-        }
+        validate_instances(&pk.vk, instance)?;
     }
 
     let start = Instant::now();
@@ -99,6 +319,8 @@ where
     let config = ConcreteCircuit::configure_with_params(&mut meta, circuits[0].params());
     #[cfg(not(feature = "circuit-params"))]
     let config = ConcreteCircuit::configure(&mut meta);
+    // Fixed columns created by the circuit, before any selectors were converted.
+    let num_fixed_columns = meta.num_fixed_columns;
 
     // Selector optimizations cannot be applied here; use the ConstraintSystem
     // from the verification key.
@@ -118,9 +340,6 @@ where
                 .map(|values| {
                     let mut poly = domain.empty_lagrange();
                     assert_eq!(poly.len(), params.n() as usize);
-                    if values.len() > (poly.len() - (meta.blinding_factors() + 1)) {
-                        return Err(Error::InstanceTooLarge);
-                    }
                     for (poly, value) in poly.iter_mut().zip(values.iter()) {
                         if !P::QUERY_INSTANCE {
                             transcript.common_scalar(*value)?;
@@ -136,13 +355,8 @@ where
                     .iter()
                     .map(|poly| params.commit_lagrange(poly, Blind::default()))
                     .collect();
-                let mut instance_commitments =
-                    vec![Scheme::Curve::identity(); instance_commitments_projective.len()];
-                <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
-                    &instance_commitments_projective,
-                    &mut instance_commitments,
-                );
-                let instance_commitments = instance_commitments;
+                let instance_commitments =
+                    batch_normalize::<Scheme::Curve>(&instance_commitments_projective);
                 drop(instance_commitments_projective);
 
                 for commitment in &instance_commitments {
@@ -180,28 +394,92 @@ where
         challenges: &'a HashMap<usize, F>,
         instances: &'a [&'a [F]],
         usable_rows: RangeTo<usize>,
+        // Fixed column and selector values from the proving key, used to detect a
+        // layout that differs from the one produced during keygen.
+        fixed_values: &'a [Arc<Polynomial<F, LagrangeCoeff>>],
+        num_fixed_columns: usize,
+        selectors: Option<&'a [Vec<bool>]>,
+        current_region: Option<String>,
         _marker: std::marker::PhantomData<F>,
     }
 
+    impl<'a, F: Field> WitnessCollection<'a, F> {
+        /// Returns whether `selector` was enabled at `row` during keygen, or `None`
+        /// if this cannot be determined.
+        fn selector_enabled(&self, selector: &Selector, row: usize) -> Option<bool> {
+            match self.selectors {
+                // Selectors were compressed; the verifying key retains their values.
+                Some(selectors) => selectors.get(selector.index())?.get(row).copied(),
+                // Each selector was converted to its own fixed column, following the
+                // fixed columns created by the circuit.
+                None => self
+                    .fixed_values
+                    .get(self.num_fixed_columns + selector.index())?
+                    .get(row)
+                    .map(|value| *value == F::ONE),
+            }
+        }
+
+        fn unknown_value(&self, annotation: String, column: Column<Any>, row: usize) -> Error {
+            Error::UnknownValue {
+                region: self.current_region.clone(),
+                column,
+                annotation,
+                row,
+            }
+        }
+
+        fn divergence(&self, annotation: String, row: usize) -> Error {
+            Error::SynthesisDivergence {
+                region: self.current_region.clone(),
+                annotation,
+                row,
+            }
+        }
+
+        fn witness_panic(
+            &self,
+            cell: Option<(Column<Any>, usize, String)>,
+            message: String,
+        ) -> Error {
+            Error::WitnessPanic {
+                region: self.current_region.clone(),
+                cell,
+                message,
+            }
+        }
+    }
+
     impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
-        fn enter_region<NR, N>(&mut self, _: N)
+        fn enter_region<NR, N>(&mut self, name: N)
         where
             NR: Into<String>,
             N: FnOnce() -> NR,
         {
-            // Do nothing; we don't care about regions in this context.
+            self.current_region = Some(name().into());
         }
 
         fn exit_region(&mut self) {
-            // Do nothing; we don't care about regions in this context.
+            self.current_region = None;
         }
 
-        fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+        fn enable_selector<A, AR>(
+            &mut self,
+            annotation: A,
+            selector: &Selector,
+            row: usize,
+        ) -> Result<(), Error>
         where
             A: FnOnce() -> AR,
             AR: Into<String>,
         {
-            // We only care about advice columns here
+            if !self.usable_rows.contains(&row) {
+                return Err(Error::not_enough_rows_available(self.k));
+            }
+
+            if self.selector_enabled(selector, row) == Some(false) {
+                return Err(self.divergence(annotation().into(), row));
+            }
 
             Ok(())
         }
@@ -228,7 +506,7 @@ where
 
         fn assign_advice<V, VR, A, AR>(
             &mut self,
-            _: A,
+            annotation: A,
             column: Column<Advice>,
             row: usize,
             to: V,
@@ -248,21 +526,32 @@ where
                 return Err(Error::not_enough_rows_available(self.k));
             }
 
+            let value = match catch_panic(to) {
+                Ok(value) => value,
+                Err(message) => {
+                    let cell = (column.into(), row, annotation().into());
+                    return Err(self.witness_panic(Some(cell), message));
+                }
+            };
+            let value = value
+                .into_field()
+                .assign()
+                .map_err(|_| self.unknown_value(annotation().into(), column.into(), row))?;
             *self
                 .advice
                 .get_mut(column.index())
                 .and_then(|v| v.get_mut(row))
-                .ok_or(Error::BoundsFailure)? = to().into_field().assign()?;
+                .ok_or(Error::BoundsFailure)? = value;
 
             Ok(())
         }
 
         fn assign_fixed<V, VR, A, AR>(
             &mut self,
-            _: A,
-            _: Column<Fixed>,
-            _: usize,
-            _: V,
+            annotation: A,
+            column: Column<Fixed>,
+            row: usize,
+            to: V,
         ) -> Result<(), Error>
         where
             V: FnOnce() -> Value<VR>,
@@ -270,7 +559,29 @@ where
             A: FnOnce() -> AR,
             AR: Into<String>,
         {
-            // We only care about advice columns here
+            if !self.usable_rows.contains(&row) {
+                return Err(Error::not_enough_rows_available(self.k));
+            }
+
+            let expected = self
+                .fixed_values
+                .get(column.index())
+                .and_then(|column| column.get(row))
+                .ok_or(Error::BoundsFailure)?;
+            let value = match catch_panic(to) {
+                Ok(value) => value,
+                Err(message) => {
+                    let cell = (column.into(), row, annotation().into());
+                    return Err(self.witness_panic(Some(cell), message));
+                }
+            };
+            if value
+                .into_field()
+                .error_if_known_and(|value| value.evaluate() != *expected)
+                .is_err()
+            {
+                return Err(self.divergence(annotation().into(), row));
+            }
 
             Ok(())
         }
@@ -318,6 +629,7 @@ where
     }
 
     let start = Instant::now();
+    progress.report(ProofStage::Advice, 0.0);
     let (advice, challenges) = {
         let mut advice = vec![
             AdviceSingle::<Scheme::Curve, LagrangeCoeff> {
@@ -331,6 +643,8 @@ where
             HashMap::<usize, Scheme::Scalar>::with_capacity_and_hasher(meta.num_challenges, s);
 
         let unusable_rows_start = params.n() as usize - (meta.blinding_factors() + 1);
+        let advice_steps = (pk.vk.cs.phases().count() * circuits.len()) as f64;
+        let mut advice_step = 0;
         for current_phase in pk.vk.cs.phases() {
             let _start = Instant::now();
             let column_indices = meta
@@ -346,14 +660,31 @@ where
                 })
                 .collect::<BTreeSet<_>>();
 
-            for ((circuit, advice), instances) in
-                circuits.iter().zip(advice.iter_mut()).zip(instances)
+            for (circuit_index, ((circuit, advice), instances)) in circuits
+                .iter()
+                .zip(advice.iter_mut())
+                .zip(instances)
+                .enumerate()
             {
+                // The precommitted columns of this phase, by their position in
+                // `column_indices`.
+                let precommitted_columns: Vec<_> = precommitted
+                    .get(circuit_index)
+                    .into_iter()
+                    .flat_map(|precommitted| precommitted.iter())
+                    .filter_map(|precommitted| {
+                        column_indices
+                            .iter()
+                            .position(|index| *index == precommitted.column().index())
+                            .map(|position| (position, precommitted))
+                    })
+                    .collect();
+
                 let _start = Instant::now();
                 let mut witness = WitnessCollection {
                     k: params.k(),
                     current_phase,
-                    advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
+                    advice: scratch.take_advice(domain, meta.num_advice_columns),
                     unblinded_advice: HashSet::from_iter(meta.unblinded_advice_columns.clone()),
                     instances,
                     challenges: &challenges,
@@ -362,73 +693,103 @@ where
                     // number of blinding factors and an extra row for use in the
                     // permutation argument.
                     usable_rows: ..unusable_rows_start,
+                    fixed_values: &pk.fixed_values,
+                    num_fixed_columns,
+                    selectors: pk.vk.compress_selectors.then_some(&pk.vk.selectors[..]),
+                    current_region: None,
                     _marker: std::marker::PhantomData,
                 };
 
                 let _start = Instant::now();
                 // Synthesize the circuit to obtain the witness and other information.
-                ConcreteCircuit::FloorPlanner::synthesize(
-                    &mut witness,
-                    circuit,
-                    config.clone(),
-                    meta.constants.clone(),
-                )?;
-
-                let _start = Instant::now();
-                let mut advice_values = batch_invert_assigned::<Scheme::Scalar>(
-                    witness
-                        .advice
-                        .into_iter()
-                        .enumerate()
-                        .filter_map(|(column_index, advice)| {
-                            if column_indices.contains(&column_index) {
-                                Some(advice)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect(),
-                );
-
-                let _start = Instant::now();
-                // Add blinding factors to advice columns
-                for (column_index, advice_values) in column_indices.iter().zip(&mut advice_values) {
-                    if !witness.unblinded_advice.contains(column_index) {
-                        for cell in &mut advice_values[unusable_rows_start..] {
-                            *cell = Scheme::Scalar::random(&mut rng);
-                        }
-                    } else {
+                catch_panic(|| {
+                    ConcreteCircuit::FloorPlanner::synthesize(
+                        &mut witness,
+                        circuit,
+                        config.clone(),
+                        meta.constants.clone(),
+                    )
+                })
+                .unwrap_or_else(|message| Err(witness.witness_panic(None, message)))?;
+
+                // The witness and blinding factors are secret until they are committed
+                // to, so everything up to the commitments runs in a secret scope.
+                let (advice_values, blinds, advice_commitments) = ct::secret(|| {
+                    let _start = Instant::now();
+                    let mut advice_values = batch_invert_assigned::<Scheme::Scalar, _>(
+                        &witness
+                            .advice
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(column_index, advice)| {
+                                if column_indices.contains(&column_index) {
+                                    Some(advice)
+                                } else {
+                                    None
+                                }
+                            })
+                            .collect::<Vec<_>>(),
+                    );
+
+                    // Every column draws its blinding values, and the unblinded ones are
+                    // swapped for the default blind without branching on the values.
+                    let is_blinded = |column_index: &usize| {
+                        ct::Choice::from(
+                            (meta.is_hiding() && !witness.unblinded_advice.contains(column_index))
+                                as u8,
+                        )
+                    };
+                    let mut blinding_value = |blinded: ct::Choice| {
+                        let random = Scheme::Scalar::random(&mut rng);
+                        ct::conditional_select(&Blind::default().0, &random, blinded)
+                    };
+
+                    let _start = Instant::now();
+                    // Add blinding factors to advice columns, unless proofs are not hiding
+                    for (column_index, advice_values) in
+                        column_indices.iter().zip(&mut advice_values)
+                    {
+                        let blinded = is_blinded(column_index);
                         for cell in &mut advice_values[unusable_rows_start..] {
-                            *cell = Blind::default().0;
+                            *cell = blinding_value(blinded);
                         }
                     }
-                }
 
-                let _start = Instant::now();
-                // Compute commitments to advice column polynomials
-                let blinds: Vec<_> = column_indices
-                    .iter()
-                    .map(|i| {
-                        if witness.unblinded_advice.contains(i) {
-                            Blind::default()
-                        } else {
-                            Blind(Scheme::Scalar::random(&mut rng))
-                        }
-                    })
-                    .collect();
-                let advice_commitments_projective: Vec<_> = advice_values
-                    .iter()
-                    .zip(blinds.iter())
-                    .map(|(poly, blind)| params.commit_lagrange(poly, *blind))
-                    .collect();
-                let mut advice_commitments =
-                    vec![Scheme::Curve::identity(); advice_commitments_projective.len()];
-                <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
-                    &advice_commitments_projective,
-                    &mut advice_commitments,
-                );
-                let advice_commitments = advice_commitments;
-                drop(advice_commitments_projective);
+                    let _start = Instant::now();
+                    // Compute commitments to advice column polynomials
+                    let mut blinds: Vec<_> = column_indices
+                        .iter()
+                        .map(|i| Blind(blinding_value(is_blinded(i))))
+                        .collect();
+                    for (position, precommitted) in &precommitted_columns {
+                        blinds[*position] = precommitted.blind();
+                    }
+                    // Multi-scalar multiplication is variable-time, and the crate has no
+                    // constant-time alternative, so committing to the witness is an
+                    // audited exception to the secret scope.
+                    let advice_commitments_projective: Vec<_> = ct::vartime_exception(
+                        "advice commitments use the variable-time MSM",
+                        || {
+                            advice_values
+                                .iter()
+                                .zip(blinds.iter())
+                                .map(|(poly, blind)| params.commit_lagrange(poly, *blind))
+                                .collect()
+                        },
+                    );
+                    let advice_commitments =
+                        batch_normalize::<Scheme::Curve>(&advice_commitments_projective);
+
+                    (advice_values, blinds, advice_commitments)
+                });
+                scratch.recycle_advice(witness.advice);
+                for (position, precommitted) in &precommitted_columns {
+                    if advice_commitments[*position] != precommitted.commitment() {
+                        return Err(Error::PrecommittedAdviceMismatch(
+                            precommitted.column().into(),
+                        ));
+                    }
+                }
 
                 let _start = Instant::now();
                 for commitment in &advice_commitments {
@@ -440,6 +801,9 @@ where
                     advice.advice_polys[*column_index] = advice_values;
                     advice.advice_blinds[*column_index] = blind;
                 }
+
+                advice_step += 1;
+                progress.report(ProofStage::Advice, advice_step as f64 / advice_steps);
             }
 
             for (index, phase) in meta.challenge_phase.iter().enumerate() {
@@ -466,6 +830,7 @@ where
     log::trace!("Theta challenge: {:?}", start.elapsed());
 
     let start = Instant::now();
+    progress.report(ProofStage::Lookups, 0.0);
     #[cfg(feature = "mv-lookup")]
     let lookups: Vec<Vec<lookup::prover::Prepared<Scheme::Curve>>> = instance
         .par_iter()
@@ -528,6 +893,7 @@ where
                 .collect()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    progress.report(ProofStage::Lookups, 0.5);
     log::trace!("Lookup preparation: {:?}", start.elapsed());
 
     // Sample beta challenge
@@ -542,6 +908,7 @@ where
 
     // Commit to permutations.
     let start = Instant::now();
+    progress.report(ProofStage::Permutations, 0.0);
     let permutations: Vec<permutation::prover::Committed<Scheme::Curve>> = instance
         .iter()
         .zip(advice.iter())
@@ -560,6 +927,7 @@ where
             )
         })
         .collect::<Result<Vec<_>, _>>()?;
+    progress.report(ProofStage::Permutations, 1.0);
     log::trace!("Permutation commitment: {:?}", start.elapsed());
 
     // preallocate the lookups
@@ -608,9 +976,11 @@ where
         }
     }
 
+    progress.report(ProofStage::Lookups, 1.0);
     log::trace!("Lookup commitment: {:?}", start.elapsed());
 
     let start = Instant::now();
+    progress.report(ProofStage::Shuffles, 0.0);
     let shuffles: Vec<Vec<shuffle::prover::Committed<Scheme::Curve>>> = instance
         .iter()
         .zip(advice.iter())
@@ -638,11 +1008,24 @@ where
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    progress.report(ProofStage::Shuffles, 1.0);
     log::trace!("Shuffle commitment: {:?}", start.elapsed());
 
     let start = Instant::now();
+    // The quotient is reported in chunks: the conversion of the advice columns of
+    // each circuit, the chunks of the evaluation of h(X), and its commitment.
+    let quotient_chunks = circuits.len() + pk.ev.h_chunks(circuits.len()) + 1;
+    let mut quotient_chunks_done = 0;
+    let mut quotient_chunk_done = |progress: &mut S| {
+        quotient_chunks_done += 1;
+        progress.report(
+            ProofStage::Quotient,
+            quotient_chunks_done as f64 / quotient_chunks as f64,
+        );
+    };
+    progress.report(ProofStage::Quotient, 0.0);
     // Commit to the vanishing argument's random polynomial for blinding h(x_3)
-    let vanishing = vanishing::Argument::commit(params, domain, &mut rng, transcript)?;
+    let vanishing = vanishing::Argument::commit(params, &pk.vk, &mut rng, transcript)?;
     log::trace!("Vanishing commitment: {:?}", start.elapsed());
 
     // Obtain challenge for keeping all separate gates linearly independent
@@ -659,13 +1042,15 @@ where
                  advice_polys,
                  advice_blinds,
              }| {
-                AdviceSingle {
+                let advice = AdviceSingle {
                     advice_polys: advice_polys
                         .into_iter()
                         .map(|poly| domain.lagrange_to_coeff(poly))
                         .collect::<Vec<_>>(),
                     advice_blinds,
-                }
+                };
+                quotient_chunk_done(progress);
+                advice
             },
         )
         .collect();
@@ -684,19 +1069,21 @@ where
             .map(|i| i.instance_polys.as_slice())
             .collect::<Vec<_>>(),
         &challenges,
-        *y,
-        *beta,
-        *gamma,
-        *theta,
+        y,
+        beta,
+        gamma,
+        theta,
         &lookups,
         &shuffles,
         &permutations,
+        &mut || quotient_chunk_done(progress),
     );
     log::trace!("H(X) evaluation: {:?}", start.elapsed());
 
     // Construct the vanishing argument's h(X) commitments
     let start = Instant::now();
-    let vanishing = vanishing.construct(params, domain, h_poly, &mut rng, transcript)?;
+    let vanishing = vanishing.construct(params, &pk.vk, h_poly, &mut rng, transcript)?;
+    quotient_chunk_done(progress);
     log::trace!("Vanishing construction: {:?}", start.elapsed());
 
     let start = Instant::now();
@@ -705,6 +1092,7 @@ where
     log::trace!("X challenge: {:?}", start.elapsed());
 
     let start = Instant::now();
+    progress.report(ProofStage::Evaluations, 0.0);
     if P::QUERY_INSTANCE {
         // Compute and hash instance evals for each circuit instance
         for instance in instance.iter() {
@@ -817,58 +1205,70 @@ where
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    progress.report(ProofStage::Evaluations, 1.0);
     log::trace!("Shuffle evaluation: {:?}", start.elapsed());
 
     let start = Instant::now();
-    let instances = instance
+    // Each group of queries numbers its polynomials from 0, and the list offsets
+    // them so that every polynomial has its own index.
+    let mut queries = QueryList::new();
+    for ((((instance, advice), permutation), lookups), shuffles) in instance
         .iter()
         .zip(advice.iter())
         .zip(permutations.iter())
         .zip(lookups.iter())
         .zip(shuffles.iter())
-        .flat_map(|((((instance, advice), permutation), lookups), shuffles)| {
-            iter::empty()
-                .chain(
-                    P::QUERY_INSTANCE
-                        .then_some(pk.vk.cs.instance_queries.iter().map(move |&(column, at)| {
-                            ProverQuery {
-                                point: domain.rotate_omega(*x, at),
-                                poly: &instance.instance_polys[column.index()],
-                                blind: Blind::default(),
-                            }
-                        }))
-                        .into_iter()
-                        .flatten(),
-                )
-                .chain(
-                    pk.vk
-                        .cs
-                        .advice_queries
-                        .iter()
-                        .map(move |&(column, at)| ProverQuery {
-                            point: domain.rotate_omega(*x, at),
-                            poly: &advice.advice_polys[column.index()],
-                            blind: advice.advice_blinds[column.index()],
-                        }),
-                )
-                .chain(permutation.open(pk, x))
-                .chain(lookups.iter().flat_map(move |p| p.open(pk, x)))
-                .chain(shuffles.iter().flat_map(move |p| p.open(pk, x)))
-        })
-        .chain(
+    {
+        if P::QUERY_INSTANCE {
+            queries.push(
+                pk.vk
+                    .cs
+                    .instance_queries
+                    .iter()
+                    .map(move |&(column, at)| ProverQuery {
+                        point: domain.rotate_omega(*x, at),
+                        poly_index: column.index(),
+                        poly: &instance.instance_polys[column.index()],
+                        blind: Blind::default(),
+                    }),
+            );
+        }
+        queries.push(
             pk.vk
                 .cs
-                .fixed_queries
+                .advice_queries
                 .iter()
-                .map(|&(column, at)| ProverQuery {
+                .map(move |&(column, at)| ProverQuery {
                     point: domain.rotate_omega(*x, at),
-                    poly: &pk.fixed_polys[column.index()],
-                    blind: Blind::default(),
+                    poly_index: column.index(),
+                    poly: &advice.advice_polys[column.index()],
+                    blind: advice.advice_blinds[column.index()],
                 }),
-        )
-        .chain(pk.permutation.open(x))
-        // We query the h(X) polynomial at x
-        .chain(vanishing.open(x));
+        );
+        queries.push(permutation.open(pk, x));
+        for lookup in lookups.iter() {
+            queries.push(lookup.open(pk, x));
+        }
+        for shuffle in shuffles.iter() {
+            queries.push(shuffle.open(pk, x));
+        }
+    }
+    queries.push(
+        pk.vk
+            .cs
+            .fixed_queries
+            .iter()
+            .map(|&(column, at)| ProverQuery {
+                point: domain.rotate_omega(*x, at),
+                poly_index: column.index(),
+                poly: &pk.fixed_polys[column.index()],
+                blind: Blind::default(),
+            }),
+    );
+    queries.push(pk.permutation.open(x));
+    // We query the h(X) polynomial at x
+    queries.push(vanishing.open(x));
+    let queries = queries.into_vec();
     log::trace!("Open queries: {:?}", start.elapsed());
 
     #[cfg(feature = "counter")]
@@ -883,10 +1283,34 @@ where
         *FFT_COUNTER.lock().unwrap() = BTreeMap::new();
     }
 
+    progress.report(ProofStage::Multiopen, 0.0);
     let prover = P::new(params);
     prover
-        .create_proof(rng, transcript, instances)
-        .map_err(|_| Error::ConstraintSystemFailure)
+        .create_proof(rng, transcript, queries)
+        .map_err(|_| Error::ConstraintSystemFailure)?;
+    progress.report(ProofStage::Multiopen, 1.0);
+    Ok(())
+}
+
+/// Runs `f`, which executes circuit code while creating a proof.
+///
+/// With the `catch-witness-panics` feature, a panic in `f` is caught and its message
+/// returned, so that a failing circuit fails its proof instead of the process. The
+/// panic hook still runs. Otherwise the panic unwinds as usual.
+fn catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    #[cfg(feature = "catch-witness-panics")]
+    {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|payload| {
+            payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic payload".to_string())
+        })
+    }
+
+    #[cfg(not(feature = "catch-witness-panics"))]
+    Ok(f())
 }
 
 #[test]
@@ -954,3 +1378,353 @@ fn test_create_proof() {
     )
     .expect("proof generation should not fail");
 }
+
+#[cfg(feature = "ct-audit")]
+#[test]
+fn test_create_proof_ct_audit() {
+    use crate::{
+        plonk::test_utils::{keygen, prove, verify, AdviceIsFixed},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use halo2curves::bn256::Bn256;
+    use rand_core::OsRng;
+
+    // A full proof, with lookups and a permutation, runs under the audit.
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &AdviceIsFixed(1));
+    let proof = prove(&params, &pk, AdviceIsFixed(1), &[], OsRng);
+    assert!(verify(&params, pk.get_vk(), &[], &proof).is_ok());
+}
+
+#[test]
+fn test_create_proof_with_scratch() {
+    use crate::{
+        plonk::test_utils::{keygen, verify, AdviceIsFixed},
+        poly::kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverSHPLONK,
+        },
+        transcript::{Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::Bn256;
+    use rand_core::OsRng;
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &AdviceIsFixed::default());
+
+    let estimate = pk.memory_requirements(1);
+    assert!(estimate.proving_key > 0);
+    assert!(estimate.synthesis > 0);
+    assert!(estimate.witness > 0);
+    assert!(estimate.lookups > 0);
+    assert_eq!(pk.memory_requirements(2).witness, 2 * estimate.witness);
+    assert!(estimate.peak() > estimate.proving_key + estimate.witness);
+
+    // The same scratch buffers are reused for each proof.
+    let mut scratch = pk.prover_scratch();
+    for value in [1, 2] {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof_with_scratch::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[AdviceIsFixed(value)],
+            &[&[]],
+            OsRng,
+            &mut transcript,
+            &mut scratch,
+        )
+        .expect("proof generation should not fail");
+        let proof = transcript
+            .finalize()
+            .expect("finalizing the transcript should not fail");
+        assert!(verify(&params, pk.get_vk(), &[], &proof).is_ok());
+    }
+    assert_eq!(scratch.memory_size(), estimate.synthesis);
+
+    // The scratch buffers are also reused for each circuit of a proof, so the
+    // estimate counts them once.
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof_with_scratch::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[AdviceIsFixed(1), AdviceIsFixed(2)],
+        &[&[], &[]],
+        OsRng,
+        &mut transcript,
+        &mut scratch,
+    )
+    .expect("proof generation should not fail");
+    assert_eq!(scratch.memory_size(), pk.memory_requirements(2).synthesis);
+}
+
+#[test]
+fn test_create_proof_with_progress() {
+    use crate::{
+        plonk::test_utils::{keygen, AdviceIsFixed},
+        poly::kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverSHPLONK,
+        },
+        transcript::{Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::Bn256;
+    use rand_core::OsRng;
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &AdviceIsFixed(1));
+
+    let mut reports = vec![];
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof_with_progress::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _, _>(
+        &params,
+        &pk,
+        &[AdviceIsFixed(1), AdviceIsFixed(2)],
+        &[&[], &[]],
+        OsRng,
+        &mut transcript,
+        &mut |stage: ProofStage, fraction: f64| reports.push((stage, fraction)),
+    )
+    .expect("proof generation should not fail");
+
+    assert_eq!(reports.first(), Some(&(ProofStage::Advice, 0.0)));
+    assert_eq!(reports.last(), Some(&(ProofStage::Multiopen, 1.0)));
+    for stage in [
+        ProofStage::Advice,
+        ProofStage::Lookups,
+        ProofStage::Permutations,
+        ProofStage::Shuffles,
+        ProofStage::Quotient,
+        ProofStage::Evaluations,
+        ProofStage::Multiopen,
+    ] {
+        let fractions: Vec<_> = reports
+            .iter()
+            .filter(|(reported, _)| *reported == stage)
+            .map(|(_, fraction)| *fraction)
+            .collect();
+        // The prover loops over the circuits while synthesizing them and while
+        // computing the quotient, so both stages report progress in between.
+        if matches!(stage, ProofStage::Advice | ProofStage::Quotient) {
+            assert!(fractions
+                .iter()
+                .any(|fraction| 0.0 < *fraction && *fraction < 1.0));
+        }
+        assert_eq!(fractions.first(), Some(&0.0));
+        assert_eq!(fractions.last(), Some(&1.0));
+        assert!(fractions.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    // The lookups are committed to in two passes around the permutation argument.
+    let position = |report: (ProofStage, f64)| {
+        reports
+            .iter()
+            .position(|reported| *reported == report)
+            .expect("stage should be reported")
+    };
+    assert!(position((ProofStage::Lookups, 0.5)) < position((ProofStage::Permutations, 0.0)));
+    assert!(position((ProofStage::Permutations, 1.0)) < position((ProofStage::Lookups, 1.0)));
+}
+
+#[test]
+fn test_create_proof_synthesis_divergence() {
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{keygen_pk, keygen_vk_custom, test_utils::try_prove},
+        poly::{kzg::commitment::ParamsKZG, Rotation},
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::OsRng;
+
+    // A circuit that only lays out a region when its witness is known, and so is
+    // laid out differently during keygen and proving.
+    #[derive(Clone, Copy)]
+    struct MyCircuit {
+        witnessed: bool,
+    }
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = (Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self { witnessed: false }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.selector();
+            meta.create_gate("a is zero", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let q = meta.query_selector(q);
+                vec![q * a]
+            });
+            (a, q)
+        }
+
+        fn synthesize(
+            &self,
+            (a, q): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            if !self.witnessed {
+                return Ok(());
+            }
+            layouter.assign_region(
+                || "conditional",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", a, 0, || Value::known(Fr::ZERO))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(3, OsRng);
+    let circuit = MyCircuit { witnessed: true };
+
+    for compress_selectors in [true, false] {
+        let vk = keygen_vk_custom(&params, &circuit.without_witnesses(), compress_selectors)
+            .expect("keygen_vk should not fail");
+        let pk = keygen_pk(&params, vk, &circuit.without_witnesses())
+            .expect("keygen_pk should not fail");
+
+        let proof = try_prove(&params, &pk, &[circuit], &[&[]], OsRng);
+        assert!(matches!(
+            proof.unwrap_err(),
+            Error::SynthesisDivergence { region: Some(region), row: 0, .. } if region == "conditional"
+        ));
+    }
+}
+
+#[cfg(feature = "catch-witness-panics")]
+#[test]
+fn test_create_proof_witness_panic() {
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::test_utils::{keygen, try_prove},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::OsRng;
+
+    // A circuit whose witness closure panics, or whose region panics outside of any
+    // witness closure.
+    #[derive(Clone, Copy, Default)]
+    struct MyCircuit {
+        in_witness: bool,
+        in_region: bool,
+    }
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            advice: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "region",
+                |mut region| {
+                    let cell = region.assign_advice(
+                        || "a",
+                        advice,
+                        1,
+                        || {
+                            assert!(!self.in_witness, "bad witness");
+                            Value::known(Fr::ONE)
+                        },
+                    )?;
+                    // Only panics once the value is known, when the region is assigned
+                    // rather than measured.
+                    cell.value().map(|_| assert!(!self.in_region, "bad region"));
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(3, OsRng);
+    let pk = keygen(&params, &MyCircuit::default());
+    let prove = |circuit: MyCircuit| try_prove(&params, &pk, &[circuit], &[&[]], OsRng);
+
+    let error = prove(MyCircuit {
+        in_witness: true,
+        in_region: false,
+    })
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::WitnessPanic {
+            region: Some(ref region),
+            cell: Some((_, 1, ref annotation)),
+            ref message,
+        } if region == "region" && annotation == "a" && message == "bad witness"
+    ));
+
+    let error = prove(MyCircuit {
+        in_witness: false,
+        in_region: true,
+    })
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::WitnessPanic {
+            region: Some(ref region),
+            cell: None,
+            ref message,
+        } if region == "region" && message == "bad region"
+    ));
+
+    // The prover still works after recovering from panics.
+    assert!(prove(MyCircuit::default()).is_ok());
+}
+
+#[test]
+fn test_create_proof_thread_count() {
+    use crate::{
+        multicore::with_num_threads,
+        plonk::test_utils::{keygen, prove, AdviceIsFixed},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use halo2curves::bn256::Bn256;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(6, ChaCha20Rng::seed_from_u64(0));
+    let pk = keygen(&params, &AdviceIsFixed(3));
+
+    // A proof created from a given RNG must not depend on the number of threads.
+    let proofs: Vec<_> = [1, 2, 3, crate::multicore::current_num_threads().max(4)]
+        .into_iter()
+        .map(|num_threads| {
+            with_num_threads(num_threads, || {
+                prove(
+                    &params,
+                    &pk,
+                    AdviceIsFixed(3),
+                    &[],
+                    ChaCha20Rng::seed_from_u64(1),
+                )
+            })
+        })
+        .collect();
+    for proof in &proofs[1..] {
+        assert_eq!(proof, &proofs[0]);
+    }
+}
@@ -17,6 +17,7 @@ use rand_core::RngCore;
 use std::{
     iter,
     ops::{Mul, MulAssign},
+    sync::Arc,
 };
 
 #[derive(Debug)]
@@ -48,7 +49,7 @@ impl<F: WithSmallOrderMulGroup<3>> Argument<F> {
         domain: &EvaluationDomain<C::Scalar>,
         theta: ChallengeTheta<C>,
         advice_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
-        fixed_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        fixed_values: &'a [Arc<Polynomial<C::Scalar, LagrangeCoeff>>],
         instance_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
         challenges: &'a [C::Scalar],
     ) -> Compressed<C>
@@ -110,7 +111,7 @@ impl<F: WithSmallOrderMulGroup<3>> Argument<F> {
         theta: ChallengeTheta<C>,
         gamma: ChallengeGamma<C>,
         advice_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
-        fixed_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        fixed_values: &'a [Arc<Polynomial<C::Scalar, LagrangeCoeff>>],
         instance_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
         challenges: &'a [C::Scalar],
         mut rng: R,
@@ -186,7 +187,7 @@ impl<F: WithSmallOrderMulGroup<3>> Argument<F> {
             assert_eq!(z[u], C::Scalar::ONE);
         }
 
-        let product_blind = Blind(C::Scalar::random(rng));
+        let product_blind = Blind(pk.vk.cs.blinding_factor(rng));
         let product_commitment = params.commit_lagrange(&z, product_blind).to_affine();
         let z = pk.vk.domain.lagrange_to_coeff(z);
 
@@ -237,12 +238,14 @@ impl<C: CurveAffine> Evaluated<C> {
             // Open shuffle product commitments at x
             .chain(Some(ProverQuery {
                 point: *x,
+                poly_index: 0,
                 poly: &self.constructed.product_poly,
                 blind: self.constructed.product_blind,
             }))
             // Open shuffle product commitments at x_next
             .chain(Some(ProverQuery {
                 point: x_next,
+                poly_index: 0,
                 poly: &self.constructed.product_poly,
                 blind: self.constructed.product_blind,
             }))
@@ -125,12 +125,14 @@ impl<C: CurveAffine> Evaluated<C> {
             // Open shuffle product commitment at x
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.product_commitment,
+                0,
                 *x,
                 self.product_eval,
             )))
             // Open shuffle product commitment at \omega x
             .chain(Some(VerifierQuery::new_commitment(
                 &self.committed.product_commitment,
+                0,
                 x_next,
                 self.product_next_eval,
             )))
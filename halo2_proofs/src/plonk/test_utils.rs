@@ -0,0 +1,150 @@
+//! Circuits and helpers shared by the tests of the `plonk` module.
+//!
+//! The helpers prove with SHPLONK over BN254.
+
+use ff::PrimeField;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::RngCore;
+
+use super::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column, ConstraintSystem,
+    Error, Fixed, ProvingKey, Selector, TableColumn, VerifyingKey,
+};
+use crate::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    poly::{
+        commitment::Params,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+        Rotation,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+
+/// Constrains an advice cell to equal a fixed cell and looks it up in a table of
+/// `0..4`, then copies it to the next row.
+///
+/// Circuits with different values have the same shape, and differ only in a fixed
+/// value. The value must be in the table for proofs to verify.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct AdviceIsFixed(pub(crate) u64);
+
+impl<F: PrimeField> Circuit<F> for AdviceIsFixed {
+    type Config = (Column<Advice>, Column<Fixed>, Selector, TableColumn);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        *self
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let a = meta.advice_column();
+        let f = meta.fixed_column();
+        let q = meta.selector();
+        let table = meta.lookup_table_column();
+        meta.enable_equality(a);
+        meta.create_gate("a is f", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let f = meta.query_fixed(f, Rotation::cur());
+            let q = meta.query_selector(q);
+            vec![q * (a - f)]
+        });
+        meta.lookup("a in table", |meta| {
+            vec![(meta.query_advice(a, Rotation::cur()), table)]
+        });
+        (a, f, q, table)
+    }
+
+    fn synthesize(
+        &self,
+        (a, f, q, table): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_table(
+            || "table",
+            |mut t| {
+                for row in 0..4 {
+                    t.assign_cell(|| "table", table, row, || Value::known(F::from(row as u64)))?;
+                }
+                Ok(())
+            },
+        )?;
+        layouter.assign_region(
+            || "a is f",
+            |mut region| {
+                q.enable(&mut region, 0)?;
+                region.assign_fixed(|| "f", f, 0, || Value::known(F::from(self.0)))?;
+                let cell = region.assign_advice(|| "a", a, 0, || Value::known(F::from(self.0)))?;
+                cell.copy_advice(|| "a copy", &mut region, a, 1)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Generates the proving key for `circuit`.
+pub(crate) fn keygen<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+) -> ProvingKey<G1Affine> {
+    let vk = keygen_vk(params, circuit).expect("keygen_vk should not fail");
+    keygen_pk(params, vk, circuit).expect("keygen_pk should not fail")
+}
+
+/// Creates a proof of `circuits`, with the instances of each circuit in `instances`.
+pub(crate) fn try_prove<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: &[C],
+    instances: &[&[&[Fr]]],
+    rng: impl RngCore + Send + Sync,
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+        params,
+        pk,
+        circuits,
+        instances,
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript
+        .finalize()
+        .expect("finalizing the transcript should not fail"))
+}
+
+/// Creates a proof of `circuit` for `instances`, one slice per instance column.
+pub(crate) fn prove<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    rng: impl RngCore + Send + Sync,
+) -> Vec<u8> {
+    try_prove(params, pk, &[circuit], &[instances], rng).expect("proof generation should not fail")
+}
+
+/// Verifies a proof created by [`prove`].
+pub(crate) fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[&[Fr]],
+    proof: &[u8],
+) -> Result<(), Error> {
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        &[instances],
+        &mut transcript,
+        params.n(),
+    )
+}
@@ -1,18 +1,17 @@
 use ff::Field;
 use group::Curve;
 use maybe_rayon::iter::IndexedParallelIterator;
+use maybe_rayon::iter::IntoParallelIterator;
 use maybe_rayon::iter::IntoParallelRefIterator;
 use maybe_rayon::iter::ParallelIterator;
 use rand_chacha::ChaCha20Rng;
 use rand_core::{RngCore, SeedableRng};
-use rustc_hash::FxHashMap as HashMap;
 use std::iter;
 
 use super::Argument;
 use crate::{
-    arithmetic::{eval_polynomial, parallelize, CurveAffine},
-    multicore::current_num_threads,
-    plonk::{ChallengeX, Error},
+    arithmetic::{batch_normalize, eval_polynomial, CurveAffine},
+    plonk::{ChallengeX, Error, VerifyingKey},
     poly::{
         commitment::{Blind, ParamsProver},
         Coeff, EvaluationDomain, ExtendedLagrangeCoeff, Polynomial, ProverQuery,
@@ -20,8 +19,12 @@ use crate::{
     transcript::{EncodedChallenge, TranscriptWrite},
 };
 
+/// The number of coefficients of the random polynomial sampled from each seed.
+const RANDOM_CHUNK_SIZE: usize = 1 << 12;
+
 pub(in crate::plonk) struct Committed<C: CurveAffine> {
-    random_poly: Polynomial<C::Scalar, Coeff>,
+    // The random polynomial is omitted if proofs do not hide the witness.
+    random_poly: Option<Polynomial<C::Scalar, Coeff>>,
     random_blind: Blind<C::Scalar>,
 }
 
@@ -46,35 +49,34 @@ impl<C: CurveAffine> Argument<C> {
         T: TranscriptWrite<C, E>,
     >(
         params: &P,
-        domain: &EvaluationDomain<C::Scalar>,
+        vk: &VerifyingKey<C>,
         mut rng: R,
         transcript: &mut T,
     ) -> Result<Committed<C>, Error> {
+        if !vk.cs.is_hiding() {
+            return Ok(Committed {
+                random_poly: None,
+                random_blind: Blind(C::Scalar::ZERO),
+            });
+        }
+
         // Sample a random polynomial of degree n - 1
+        let domain = &vk.domain;
         let n = 1usize << domain.k() as usize;
         let mut rand_vec = vec![C::Scalar::ZERO; n];
 
-        let num_threads = current_num_threads();
-        let chunk_size = n / num_threads;
-        let thread_seeds = (0..)
-            .step_by(chunk_size + 1)
-            .take(n % num_threads)
-            .chain(
-                (chunk_size != 0)
-                    .then(|| ((n % num_threads) * (chunk_size + 1)..).step_by(chunk_size))
-                    .into_iter()
-                    .flatten(),
-            )
-            .take(num_threads)
-            .zip(iter::repeat_with(|| {
+        // Seed each chunk of a fixed size, rather than each thread, so that the
+        // polynomial does not depend on the number of threads.
+        let chunks: Vec<_> = rand_vec
+            .chunks_mut(RANDOM_CHUNK_SIZE)
+            .map(|chunk| {
                 let mut seed = [0u8; 32];
                 rng.fill_bytes(&mut seed);
-                ChaCha20Rng::from_seed(seed)
-            }))
-            .collect::<HashMap<_, _>>();
-
-        parallelize(&mut rand_vec, |chunk, offset| {
-            let mut rng = thread_seeds[&offset].clone();
+                (chunk, seed)
+            })
+            .collect();
+        chunks.into_par_iter().for_each(|(chunk, seed)| {
+            let mut rng = ChaCha20Rng::from_seed(seed);
             chunk
                 .iter_mut()
                 .for_each(|v| *v = C::Scalar::random(&mut rng));
@@ -90,7 +92,7 @@ impl<C: CurveAffine> Argument<C> {
         transcript.write_point(c)?;
 
         Ok(Committed {
-            random_poly,
+            random_poly: Some(random_poly),
             random_blind,
         })
     }
@@ -106,11 +108,13 @@ impl<C: CurveAffine> Committed<C> {
     >(
         self,
         params: &P,
-        domain: &EvaluationDomain<C::Scalar>,
+        vk: &VerifyingKey<C>,
         h_poly: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
         mut rng: R,
         transcript: &mut T,
     ) -> Result<Constructed<C>, Error> {
+        let domain = &vk.domain;
+
         // Divide by t(X) = X^{params.n} - 1.
         let h_poly = domain.divide_by_vanishing_poly(h_poly);
 
@@ -123,9 +127,12 @@ impl<C: CurveAffine> Committed<C> {
             .map(|v| domain.coeff_from_vec(v.to_vec()))
             .collect::<Vec<_>>();
         drop(h_poly);
+        debug_assert_eq!(h_pieces.len(), domain.get_quotient_poly_degree());
+        // Each piece is hidden by its own blinding factor; the evaluation of the
+        // recombined h(X) is masked by the random polynomial in the opening.
         let h_blinds: Vec<_> = h_pieces
             .iter()
-            .map(|_| Blind(C::Scalar::random(&mut rng)))
+            .map(|_| Blind(vk.cs.blinding_factor(&mut rng)))
             .collect();
 
         // Compute commitments to each h(X) piece
@@ -134,9 +141,7 @@ impl<C: CurveAffine> Committed<C> {
             .zip(h_blinds.par_iter())
             .map(|(h_piece, blind)| params.commit(h_piece, *blind))
             .collect();
-        let mut h_commitments = vec![C::identity(); h_commitments_projective.len()];
-        C::Curve::batch_normalize(&h_commitments_projective, &mut h_commitments);
-        let h_commitments = h_commitments;
+        let h_commitments = batch_normalize::<C>(&h_commitments_projective);
 
         // Hash each h(X) piece
         for c in h_commitments.iter() {
@@ -171,8 +176,10 @@ impl<C: CurveAffine> Constructed<C> {
             .rev()
             .fold(Blind(C::Scalar::ZERO), |acc, eval| acc * Blind(xn) + *eval);
 
-        let random_eval = eval_polynomial(&self.committed.random_poly, *x);
-        transcript.write_scalar(random_eval)?;
+        if let Some(random_poly) = &self.committed.random_poly {
+            let random_eval = eval_polynomial(random_poly, *x);
+            transcript.write_scalar(random_eval)?;
+        }
 
         Ok(Evaluated {
             h_poly,
@@ -190,13 +197,20 @@ impl<C: CurveAffine> Evaluated<C> {
         iter::empty()
             .chain(Some(ProverQuery {
                 point: *x,
+                poly_index: 0,
                 poly: &self.h_poly,
                 blind: self.h_blind,
             }))
-            .chain(Some(ProverQuery {
-                point: *x,
-                poly: &self.committed.random_poly,
-                blind: self.committed.random_blind,
-            }))
+            .chain(
+                self.committed
+                    .random_poly
+                    .as_ref()
+                    .map(|random_poly| ProverQuery {
+                        point: *x,
+                        poly_index: 1,
+                        poly: random_poly,
+                        blind: self.committed.random_blind,
+                    }),
+            )
     }
 }
@@ -16,25 +16,24 @@ use super::super::{ChallengeX, ChallengeY};
 use super::Argument;
 
 pub struct Committed<C: CurveAffine> {
-    random_poly_commitment: C,
+    // The random polynomial is omitted if proofs do not hide the witness.
+    random_poly_commitment: Option<C>,
 }
 
 pub struct Constructed<C: CurveAffine> {
     h_commitments: Vec<C>,
-    random_poly_commitment: C,
+    random_poly_commitment: Option<C>,
 }
 
 pub struct PartiallyEvaluated<C: CurveAffine> {
     h_commitments: Vec<C>,
-    random_poly_commitment: C,
-    random_eval: C::Scalar,
+    random_poly: Option<(C, C::Scalar)>,
 }
 
 pub struct Evaluated<C: CurveAffine, M: MSM<C>> {
     h_commitment: M,
-    random_poly_commitment: C,
     expected_h_eval: C::Scalar,
-    random_eval: C::Scalar,
+    random_poly: Option<(C, C::Scalar)>,
 }
 
 impl<C: CurveAffine> Argument<C> {
@@ -42,9 +41,14 @@ impl<C: CurveAffine> Argument<C> {
         E: EncodedChallenge<C>,
         T: TranscriptRead<C, E>,
     >(
+        vk: &VerifyingKey<C>,
         transcript: &mut T,
     ) -> Result<Committed<C>, Error> {
-        let random_poly_commitment = transcript.read_point()?;
+        let random_poly_commitment = if vk.cs.is_hiding() {
+            Some(transcript.read_point()?)
+        } else {
+            None
+        };
 
         Ok(Committed {
             random_poly_commitment,
@@ -76,12 +80,14 @@ impl<C: CurveAffine> Constructed<C> {
         self,
         transcript: &mut T,
     ) -> Result<PartiallyEvaluated<C>, Error> {
-        let random_eval = transcript.read_scalar()?;
+        let random_poly = match self.random_poly_commitment {
+            Some(commitment) => Some((commitment, transcript.read_scalar()?)),
+            None => None,
+        };
 
         Ok(PartiallyEvaluated {
             h_commitments: self.h_commitments,
-            random_poly_commitment: self.random_poly_commitment,
-            random_eval,
+            random_poly,
         })
     }
 }
@@ -112,8 +118,7 @@ impl<C: CurveAffine> PartiallyEvaluated<C> {
         Evaluated {
             expected_h_eval,
             h_commitment,
-            random_poly_commitment: self.random_poly_commitment,
-            random_eval: self.random_eval,
+            random_poly: self.random_poly,
         }
     }
 }
@@ -126,13 +131,14 @@ impl<C: CurveAffine, M: MSM<C>> Evaluated<C, M> {
         iter::empty()
             .chain(Some(VerifierQuery::new_msm(
                 &self.h_commitment,
+                0,
                 *x,
                 self.expected_h_eval,
             )))
-            .chain(Some(VerifierQuery::new_commitment(
-                &self.random_poly_commitment,
-                *x,
-                self.random_eval,
-            )))
+            .chain(
+                self.random_poly.as_ref().map(|(commitment, eval)| {
+                    VerifierQuery::new_commitment(commitment, 1, *x, *eval)
+                }),
+            )
     }
 }
@@ -1,26 +1,79 @@
 use ff::{Field, FromUniformBytes, WithSmallOrderMulGroup};
-use group::Curve;
-use std::iter;
+use std::fmt;
 
+#[cfg(not(feature = "mv-lookup"))]
+use super::lookup::verifier::Evaluated as LookupEvaluated;
+#[cfg(feature = "mv-lookup")]
+use super::mv_lookup::verifier::Evaluated as LookupEvaluated;
 use super::{
-    vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, ChallengeY, Error,
-    VerifyingKey,
+    permutation, shuffle, vanishing, Advice, ChallengeBeta, ChallengeGamma, ChallengeTheta,
+    ChallengeX, ChallengeY, Column, Error, VerifyingKey,
 };
-use crate::arithmetic::compute_inner_product;
-use crate::poly::commitment::{CommitmentScheme, Verifier};
+use crate::arithmetic::{batch_normalize, compute_inner_product, CurveAffine};
+use crate::poly::commitment::{CommitmentScheme, Verifier, MSM};
 use crate::poly::VerificationStrategy;
 use crate::poly::{
     commitment::{Blind, Params},
-    VerifierQuery,
+    QueryList, VerifierQuery,
 };
-use crate::transcript::{read_n_scalars, EncodedChallenge, TranscriptRead};
+use crate::transcript::{read_n_scalars, EncodedChallenge, TranscriptRead, TranscriptReadBuffer};
 
 #[cfg(feature = "batch")]
 mod batch;
 #[cfg(feature = "batch")]
 pub use batch::BatchVerifier;
+#[cfg(test)]
+mod tests;
+
+/// Checks that `instances`, the instance values for a single proof, fit the circuit
+/// described by `vk`.
+///
+/// There must be one slice of values per instance column of the circuit, and each may
+/// hold at most as many values as the column has usable rows; shorter slices are
+/// zero-padded by the prover and verifier. This is checked by [`create_proof`] and
+/// [`verify_proof`] as well, but calling it first reports a malformed public input
+/// before any proving or verifying work is done.
+///
+/// [`create_proof`]: super::create_proof
+pub fn validate_instances<C: CurveAffine>(
+    vk: &VerifyingKey<C>,
+    instances: &[&[C::Scalar]],
+) -> Result<(), Error> {
+    if instances.len() != vk.cs.num_instance_columns {
+        return Err(Error::InstanceColumnCount {
+            expected: vk.cs.num_instance_columns,
+            actual: instances.len(),
+        });
+    }
+
+    let max_len = (1usize << vk.domain.k()) - (vk.cs.blinding_factors() + 1);
+    for (column, values) in instances.iter().enumerate() {
+        if values.len() > max_len {
+            return Err(Error::InstanceColumnTooLong {
+                column,
+                len: values.len(),
+                max_len,
+            });
+        }
+    }
+
+    Ok(())
+}
 
 /// Returns a boolean indicating whether or not the proof is valid
+///
+/// # Errors
+///
+/// [`Error::verification_failure`] classifies a returned error by the check that
+/// failed: the instances, the verifying key, the transcript, the multi-opening proof or
+/// the final check. It cannot tell a failing lookup, permutation or gate constraint
+/// apart. The verifier does not check these arguments one by one; it aggregates all of
+/// their constraints with a random challenge into the single vanishing argument, so any
+/// of them failing is reported as [`VerificationFailure::FinalCheck`]. Run
+/// [`MockProver`](crate::dev::MockProver) on the same witness to find which constraint
+/// is not satisfied.
+///
+/// [`VerificationFailure::FinalCheck`]: super::VerificationFailure::FinalCheck
 pub fn verify_proof<
     'params,
     Scheme: CommitmentScheme,
@@ -39,34 +92,277 @@ pub fn verify_proof<
 where
     Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
 {
-    // Check that instances matches the expected number of instance columns
-    for instances in instances.iter() {
-        if instances.len() != vk.cs.num_instance_columns {
-            return Err(Error::InvalidInstances);
-        }
-    }
+    prepare_proof::<Scheme, V, E, T>(params, vk, instances, transcript, orig_n)?
+        .verify::<Scheme, V, E, T, Strategy>(params, strategy, transcript)
+}
 
-    let instance_commitments = if V::QUERY_INSTANCE {
-        instances
-            .iter()
-            .map(|instance| {
-                instance
+/// Verifies a proof like [`verify_proof`], reading it directly from the byte slice
+/// `proof`.
+///
+/// The transcript `T` reads each point and scalar straight out of `proof`, so a proof
+/// held in a shared or memory-mapped buffer is verified without first being copied into
+/// an owned buffer.
+///
+/// Bytes after the end of the proof are not read, as with [`verify_proof`], so `proof`
+/// with any bytes appended to it is accepted as well. Callers that use the bytes of a
+/// proof to identify it, for example to reject proofs they have already seen, must
+/// not rely on them being unique.
+pub fn verify_proof_slice<
+    'params,
+    'a,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptReadBuffer<&'a [u8], Scheme::Curve, E>,
+    Strategy: VerificationStrategy<'params, Scheme, V>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: Strategy,
+    instances: &[&[&[Scheme::Scalar]]],
+    proof: &'a [u8],
+    orig_n: u64,
+) -> Result<Strategy::Output, Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+{
+    let mut transcript = T::init(proof);
+    verify_proof::<Scheme, V, E, T, Strategy>(
+        params,
+        vk,
+        strategy,
+        instances,
+        &mut transcript,
+        orig_n,
+    )
+}
+
+/// Verifies a proof like [`verify_proof`], with the instance columns of each proof
+/// given as commitments rather than as values.
+///
+/// This is useful when the verifier already holds commitments to the public inputs,
+/// and saves computing them. The commitments must be the ones [`commit_instances`]
+/// returns for the instance values the proof was created with.
+///
+/// Only verifiers that query instance columns (`V::QUERY_INSTANCE`) can check a
+/// proof against commitments, which means this works with IPA only. The KZG
+/// verifiers evaluate the instance columns from their values instead, and return
+/// [`Error::InvalidInstances`] when given commitments.
+pub fn verify_proof_with_instance_commitments<
+    'params,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E>,
+    Strategy: VerificationStrategy<'params, Scheme, V>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: Strategy,
+    instance_commitments: &[&[Scheme::Curve]],
+    transcript: &mut T,
+    orig_n: u64,
+) -> Result<Strategy::Output, Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+{
+    prepare_proof_with_instance_commitments::<Scheme, V, E, T>(
+        params,
+        vk,
+        instance_commitments,
+        transcript,
+        orig_n,
+    )?
+    .verify::<Scheme, V, E, T, Strategy>(params, strategy, transcript)
+}
+
+/// Commits to `instances`, the instance values for a single proof, returning one
+/// commitment per instance column.
+///
+/// These are the commitments that [`create_proof`] and [`verify_proof`] hash into
+/// the transcript when instance columns are queried, and that
+/// [`verify_proof_with_instance_commitments`] expects.
+///
+/// [`create_proof`]: super::create_proof
+pub fn commit_instances<'params, C: CurveAffine, P: Params<'params, C>>(
+    params: &P,
+    vk: &VerifyingKey<C>,
+    instances: &[&[C::Scalar]],
+) -> Result<Vec<C>, Error> {
+    validate_instances(vk, instances)?;
+
+    let commitments = instances
+        .iter()
+        .map(|values| {
+            let mut poly = vk.domain.empty_lagrange();
+            poly[0..values.len()].copy_from_slice(values);
+            params.commit_lagrange(&poly, Blind::default())
+        })
+        .collect::<Vec<_>>();
+    Ok(batch_normalize::<C>(&commitments))
+}
+
+/// Reads a proof from `transcript` up to its multiopen argument, returning a
+/// [`PreparedProof`] from which verification can be finished.
+///
+/// This derives every challenge and checks that the circuit is satisfied, assuming
+/// the commitments open to the claimed evaluations. The proof is valid only once
+/// [`PreparedProof::verify`] succeeds.
+pub fn prepare_proof<
+    'params,
+    'a,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &'a VerifyingKey<Scheme::Curve>,
+    instances: &[&[&[Scheme::Scalar]]],
+    transcript: &mut T,
+    orig_n: u64,
+) -> Result<
+    PreparedProof<
+        'a,
+        Scheme::Curve,
+        <Scheme::ParamsVerifier as Params<'params, Scheme::Curve>>::MSM,
+    >,
+    Error,
+>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+{
+    prepare_proof_inner::<Scheme, V, E, T>(
+        params,
+        vk,
+        InstanceData::Values(instances),
+        transcript,
+        orig_n,
+    )
+}
+
+/// Reads a proof from `transcript` up to its multiopen argument, like
+/// [`prepare_proof`], but with the instance columns of each proof given as
+/// commitments rather than as values.
+///
+/// See [`verify_proof_with_instance_commitments`] for the requirements on the
+/// commitments and the verifier.
+pub fn prepare_proof_with_instance_commitments<
+    'params,
+    'a,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &'a VerifyingKey<Scheme::Curve>,
+    instance_commitments: &[&[Scheme::Curve]],
+    transcript: &mut T,
+    orig_n: u64,
+) -> Result<
+    PreparedProof<
+        'a,
+        Scheme::Curve,
+        <Scheme::ParamsVerifier as Params<'params, Scheme::Curve>>::MSM,
+    >,
+    Error,
+>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+{
+    prepare_proof_inner::<Scheme, V, E, T>(
+        params,
+        vk,
+        InstanceData::Commitments(instance_commitments),
+        transcript,
+        orig_n,
+    )
+}
+
+/// The instance columns of the proofs being verified.
+enum InstanceData<'i, C: CurveAffine> {
+    /// The values of each instance column.
+    Values(&'i [&'i [&'i [C::Scalar]]]),
+    /// Commitments to each instance column, as returned by [`commit_instances`].
+    Commitments(&'i [&'i [C]]),
+}
+
+fn prepare_proof_inner<
+    'params,
+    'a,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &'a VerifyingKey<Scheme::Curve>,
+    instances: InstanceData<'_, Scheme::Curve>,
+    transcript: &mut T,
+    orig_n: u64,
+) -> Result<
+    PreparedProof<
+        'a,
+        Scheme::Curve,
+        <Scheme::ParamsVerifier as Params<'params, Scheme::Curve>>::MSM,
+    >,
+    Error,
+>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+{
+    let (instances, instance_commitments) = match instances {
+        InstanceData::Values(instances) => {
+            for instances in instances.iter() {
+                validate_instances(vk, instances)?;
+            }
+
+            let instance_commitments = if V::QUERY_INSTANCE {
+                instances
                     .iter()
                     .map(|instance| {
-                        if instance.len() > orig_n as usize - (vk.cs.blinding_factors() + 1) {
-                            return Err(Error::InstanceTooLarge);
-                        }
-                        let mut poly = instance.to_vec();
-                        poly.resize(orig_n as usize, Scheme::Scalar::ZERO);
-                        let poly = vk.domain.lagrange_from_vec(poly);
-
-                        Ok(params.commit_lagrange(&poly, Blind::default()).to_affine())
+                        let commitments = instance
+                            .iter()
+                            .map(|instance| {
+                                let mut poly = instance.to_vec();
+                                poly.resize(orig_n as usize, Scheme::Scalar::ZERO);
+                                let poly = vk.domain.lagrange_from_vec(poly);
+
+                                params.commit_lagrange(&poly, Blind::default())
+                            })
+                            .collect::<Vec<_>>();
+                        batch_normalize::<Scheme::Curve>(&commitments)
                     })
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<Result<Vec<_>, _>>()?
-    } else {
-        vec![vec![]; instances.len()]
+                    .collect()
+            } else {
+                vec![vec![]; instances.len()]
+            };
+
+            (instances, instance_commitments)
+        }
+        InstanceData::Commitments(instance_commitments) => {
+            // Without instance queries the verifier evaluates the instance columns
+            // itself, which needs their values.
+            if !V::QUERY_INSTANCE {
+                return Err(Error::InvalidInstances);
+            }
+            for commitments in instance_commitments.iter() {
+                if commitments.len() != vk.cs.num_instance_columns {
+                    return Err(Error::InstanceColumnCount {
+                        expected: vk.cs.num_instance_columns,
+                        actual: commitments.len(),
+                    });
+                }
+            }
+
+            // The values are only used when instance columns are not queried.
+            let instance_commitments = instance_commitments
+                .iter()
+                .map(|commitments| commitments.to_vec())
+                .collect();
+            (&[][..], instance_commitments)
+        }
     };
 
     let num_proofs = instance_commitments.len();
@@ -186,7 +482,7 @@ where
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let vanishing = vanishing::Argument::read_commitments_before_y(transcript)?;
+    let vanishing = vanishing::Argument::read_commitments_before_y(vk, transcript)?;
 
     // Sample y challenge, which keeps the gates linearly independent.
     let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
@@ -285,15 +581,7 @@ where
         let xn = x.pow([orig_n]);
 
         let blinding_factors = vk.cs.blinding_factors();
-        let l_evals = vk
-            .domain
-            .l_i_range(*x, xn, (-((blinding_factors + 1) as i32))..=0);
-        assert_eq!(l_evals.len(), 2 + blinding_factors);
-        let l_last = l_evals[0];
-        let l_blind: Scheme::Scalar = l_evals[1..(1 + blinding_factors)]
-            .iter()
-            .fold(Scheme::Scalar::ZERO, |acc, eval| acc + eval);
-        let l_0 = l_evals[1 + blinding_factors];
+        let (l_0, l_last, l_blind) = vk.domain.l_0_last_blind(*x, xn, blinding_factors);
 
         // Compute the expected value of h(x)
         let expressions = advice_evals
@@ -394,80 +682,204 @@ where
         vanishing.verify(params, expressions, y, xn)
     };
 
-    let queries = instance_commitments
-        .iter()
-        .zip(instance_evals.iter())
-        .zip(advice_commitments.iter())
-        .zip(advice_evals.iter())
-        .zip(permutations_evaluated.iter())
-        .zip(lookups_evaluated.iter())
-        .zip(shuffles_evaluated.iter())
-        .flat_map(
-            |(
+    Ok(PreparedProof {
+        vk,
+        query_instance: V::QUERY_INSTANCE,
+        x,
+        challenges,
+        instance_commitments,
+        instance_evals,
+        advice_commitments,
+        advice_evals,
+        fixed_evals,
+        permutations_common,
+        permutations_evaluated,
+        lookups_evaluated,
+        shuffles_evaluated,
+        vanishing,
+    })
+}
+
+/// A proof read by [`prepare_proof`], which remains to be checked against its
+/// multiopen argument.
+///
+/// The final multi-scalar multiplication of a proof dominates the cost of verifying
+/// it. Many prepared proofs can share it by finishing verification with the same
+/// batching [`VerificationStrategy`], while callers that only need the parsed proof
+/// can stop before [`PreparedProof::verify`].
+pub struct PreparedProof<'a, C: CurveAffine, M: MSM<C>> {
+    vk: &'a VerifyingKey<C>,
+    query_instance: bool,
+    x: ChallengeX<C>,
+    challenges: Vec<C::Scalar>,
+    instance_commitments: Vec<Vec<C>>,
+    instance_evals: Vec<Vec<C::Scalar>>,
+    advice_commitments: Vec<Vec<C>>,
+    advice_evals: Vec<Vec<C::Scalar>>,
+    fixed_evals: Vec<C::Scalar>,
+    permutations_common: permutation::verifier::CommonEvaluated<C>,
+    permutations_evaluated: Vec<permutation::verifier::Evaluated<C>>,
+    lookups_evaluated: Vec<Vec<LookupEvaluated<C>>>,
+    shuffles_evaluated: Vec<Vec<shuffle::verifier::Evaluated<C>>>,
+    vanishing: vanishing::verifier::Evaluated<C, M>,
+}
+
+impl<C: CurveAffine, M: MSM<C>> fmt::Debug for PreparedProof<'_, C, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedProof")
+            .field("x", &*self.x)
+            .field("challenges", &self.challenges)
+            .field("instance_commitments", &self.instance_commitments)
+            .field("advice_commitments", &self.advice_commitments)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, C: CurveAffine, M: MSM<C>> PreparedProof<'a, C, M>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    /// Returns the commitments to the advice columns, for each proof in the batch.
+    pub fn advice_commitments(&self) -> &[Vec<C>] {
+        &self.advice_commitments
+    }
+
+    /// Checks that the advice columns of each proof in the batch are committed to as
+    /// given by `precommitted`, which holds the precommitted columns of each proof and
+    /// their commitments.
+    ///
+    /// Returns [`Error::PrecommittedAdviceMismatch`] for the first column whose
+    /// commitment differs, and [`Error::InvalidPrecommittedAdvice`] if `precommitted`
+    /// does not have one entry per proof. See
+    /// [`PrecommittedAdvice`](super::PrecommittedAdvice).
+    pub fn check_precommitted_advice(
+        &self,
+        precommitted: &[&[(Column<Advice>, C)]],
+    ) -> Result<(), Error> {
+        if precommitted.len() != self.advice_commitments.len() {
+            return Err(Error::InvalidPrecommittedAdvice(
+                "precommitted advice must be given for each proof",
+            ));
+        }
+
+        for (precommitted, advice_commitments) in
+            precommitted.iter().zip(self.advice_commitments.iter())
+        {
+            for (column, commitment) in precommitted.iter() {
+                if advice_commitments.get(column.index()) != Some(commitment) {
+                    return Err(Error::PrecommittedAdviceMismatch((*column).into()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the commitments to the instance columns, for each proof in the batch.
+    ///
+    /// These are empty if the verifier does not query instance columns.
+    pub fn instance_commitments(&self) -> &[Vec<C>] {
+        &self.instance_commitments
+    }
+
+    /// Returns the values of the challenges used by the circuit.
+    pub fn challenges(&self) -> &[C::Scalar] {
+        &self.challenges
+    }
+
+    /// Finishes verifying the proof by reading its multiopen argument from
+    /// `transcript`, which must be the transcript the proof was prepared from.
+    pub fn verify<
+        'params,
+        Scheme: CommitmentScheme<Curve = C>,
+        V: Verifier<'params, Scheme>,
+        E: EncodedChallenge<C>,
+        T: TranscriptRead<C, E>,
+        Strategy: VerificationStrategy<'params, Scheme, V>,
+    >(
+        self,
+        params: &'params Scheme::ParamsVerifier,
+        strategy: Strategy,
+        transcript: &mut T,
+    ) -> Result<Strategy::Output, Error>
+    where
+        Scheme::ParamsVerifier: Params<'params, C, MSM = M>,
+    {
+        let verifier = V::new(params);
+        strategy.process(|msm| {
+            verifier
+                .verify_proof(transcript, self.queries(), msm)
+                .map_err(|_| Error::Opening)
+        })
+    }
+
+    fn queries(&self) -> Vec<VerifierQuery<'_, C, M>> {
+        let vk = self.vk;
+        let x = self.x;
+
+        // Each group of queries numbers its commitments from 0, and the list offsets
+        // them so that every commitment has its own index.
+        let mut queries = QueryList::new();
+        for (
+            (
                 (
-                    (
-                        (
-                            ((instance_commitments, instance_evals), advice_commitments),
-                            advice_evals,
-                        ),
-                        permutation,
-                    ),
-                    lookups,
+                    (((instance_commitments, instance_evals), advice_commitments), advice_evals),
+                    permutation,
                 ),
-                shuffles,
-            )| {
-                iter::empty()
-                    .chain(
-                        V::QUERY_INSTANCE
-                            .then_some(vk.cs.instance_queries.iter().enumerate().map(
-                                move |(query_index, &(column, at))| {
-                                    VerifierQuery::new_commitment(
-                                        &instance_commitments[column.index()],
-                                        vk.domain.rotate_omega(*x, at),
-                                        instance_evals[query_index],
-                                    )
-                                },
-                            ))
-                            .into_iter()
-                            .flatten(),
-                    )
-                    .chain(vk.cs.advice_queries.iter().enumerate().map(
-                        move |(query_index, &(column, at))| {
-                            VerifierQuery::new_commitment(
-                                &advice_commitments[column.index()],
-                                vk.domain.rotate_omega(*x, at),
-                                advice_evals[query_index],
-                            )
-                        },
-                    ))
-                    .chain(permutation.queries(vk, x))
-                    .chain(lookups.iter().flat_map(move |p| p.queries(vk, x)))
-                    .chain(shuffles.iter().flat_map(move |p| p.queries(vk, x)))
-            },
-        )
-        .chain(
-            vk.cs
-                .fixed_queries
-                .iter()
-                .enumerate()
-                .map(|(query_index, &(column, at))| {
+                lookups,
+            ),
+            shuffles,
+        ) in self
+            .instance_commitments
+            .iter()
+            .zip(self.instance_evals.iter())
+            .zip(self.advice_commitments.iter())
+            .zip(self.advice_evals.iter())
+            .zip(self.permutations_evaluated.iter())
+            .zip(self.lookups_evaluated.iter())
+            .zip(self.shuffles_evaluated.iter())
+        {
+            if self.query_instance {
+                queries.push(vk.cs.instance_queries.iter().enumerate().map(
+                    move |(query_index, &(column, at))| {
+                        VerifierQuery::new_commitment(
+                            &instance_commitments[column.index()],
+                            column.index(),
+                            vk.domain.rotate_omega(*x, at),
+                            instance_evals[query_index],
+                        )
+                    },
+                ));
+            }
+            queries.push(vk.cs.advice_queries.iter().enumerate().map(
+                move |(query_index, &(column, at))| {
                     VerifierQuery::new_commitment(
-                        &vk.fixed_commitments[column.index()],
+                        &advice_commitments[column.index()],
+                        column.index(),
                         vk.domain.rotate_omega(*x, at),
-                        fixed_evals[query_index],
+                        advice_evals[query_index],
                     )
-                }),
-        )
-        .chain(permutations_common.queries(&vk.permutation, x))
-        .chain(vanishing.queries(x));
-
-    // We are now convinced the circuit is satisfied so long as the
-    // polynomial commitments open to the correct values.
-
-    let verifier = V::new(params);
-    strategy.process(|msm| {
-        verifier
-            .verify_proof(transcript, queries, msm)
-            .map_err(|_| Error::Opening)
-    })
+                },
+            ));
+            queries.push(permutation.queries(vk, x));
+            for lookup in lookups.iter() {
+                queries.push(lookup.queries(vk, x));
+            }
+            for shuffle in shuffles.iter() {
+                queries.push(shuffle.queries(vk, x));
+            }
+        }
+        queries.push(vk.cs.fixed_queries.iter().enumerate().map(
+            move |(query_index, &(column, at))| {
+                VerifierQuery::new_commitment(
+                    &vk.fixed_commitments[column.index()],
+                    column.index(),
+                    vk.domain.rotate_omega(*x, at),
+                    self.fixed_evals[query_index],
+                )
+            },
+        ));
+        queries.push(self.permutations_common.queries(&vk.permutation, x));
+        queries.push(self.vanishing.queries(x));
+        queries.into_vec()
+    }
 }
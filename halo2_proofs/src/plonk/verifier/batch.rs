@@ -1,3 +1,5 @@
+use std::io;
+
 use ff::FromUniformBytes;
 use group::ff::Field;
 use halo2curves::CurveAffine;
@@ -17,7 +19,7 @@ use crate::{
             strategy::GuardIPA,
         },
     },
-    transcript::{Blake2bRead, TranscriptReadBuffer},
+    transcript::{Blake2bRead, Challenge255, EncodedChallenge, TranscriptReadBuffer},
 };
 
 /// A proof verification strategy that returns the proof's MSM.
@@ -80,7 +82,8 @@ where
         self.items.push(BatchItem { instances, proof })
     }
 
-    /// Finalizes the batch and checks its validity.
+    /// Finalizes the batch and checks its validity, reading the proofs with a
+    /// [`Blake2bRead`] transcript.
     ///
     /// Returns `false` if *some* proof was invalid. If the caller needs to identify
     /// specific failing proofs, it must re-process the proofs separately.
@@ -89,6 +92,22 @@ where
     /// the internal parallelization requires access to a RNG that is guaranteed to not
     /// clone its internal state when shared between threads.
     pub fn finalize(self, params: &ParamsVerifierIPA<C>, vk: &VerifyingKey<C>) -> bool {
+        self.finalize_with_transcript::<Challenge255<C>, Blake2bRead<_, _, _>>(params, vk)
+    }
+
+    /// Finalizes the batch and checks its validity, reading the proofs with the
+    /// transcript `T`.
+    ///
+    /// See [`BatchVerifier::finalize`].
+    pub fn finalize_with_transcript<E, T>(
+        self,
+        params: &ParamsVerifierIPA<C>,
+        vk: &VerifyingKey<C>,
+    ) -> bool
+    where
+        E: EncodedChallenge<C>,
+        T: TranscriptReadBuffer<io::Cursor<Vec<u8>>, C, E>,
+    {
         fn accumulate_msm<'params, C: CurveAffine>(
             mut acc: MSMIPA<'params, C>,
             msm: MSMIPA<'params, C>,
@@ -115,7 +134,7 @@ where
                 let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
 
                 let strategy = BatchStrategy::new(params);
-                let mut transcript = Blake2bRead::init(&item.proof[..]);
+                let mut transcript = T::init(io::Cursor::new(item.proof));
                 verify_proof(
                     params,
                     vk,
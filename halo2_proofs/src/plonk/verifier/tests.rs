@@ -0,0 +1,970 @@
+//! Tests that create proofs and check how the verifier treats them.
+//!
+//! Most tests prove with SHPLONK over BN254, through the [`keygen`], [`prove`] and
+//! [`verify`] helpers of [`test_utils`](crate::plonk::test_utils).
+
+use ff::PrimeField;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::OsRng;
+
+use super::*;
+use crate::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        create_proof, create_proof_with_precommitted_advice, keygen_pk, keygen_vk,
+        test_utils::{keygen, prove, verify, AdviceIsFixed},
+        Circuit, ConstraintSystem, Expression, Instance, PrecommittedAdvice, ProvingKey,
+        ProvingMode, Selector, TableColumn, VerificationFailure,
+    },
+    poly::{
+        commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy as SingleStrategyIPA,
+        },
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::{AccumulatorStrategy, SingleStrategy},
+        },
+    },
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+};
+
+/// Exposes a witnessed value as an instance.
+#[derive(Clone, Copy, Default)]
+struct InstanceCircuit(u64);
+
+impl<F: PrimeField> Circuit<F> for InstanceCircuit {
+    type Config = (Column<Advice>, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let a = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(a);
+        meta.enable_equality(instance);
+        (a, instance)
+    }
+
+    fn synthesize(
+        &self,
+        (a, instance): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let a = layouter.assign_region(
+            || "a",
+            |mut region| region.assign_advice(|| "a", a, 0, || Value::known(F::from(self.0))),
+        )?;
+        layouter.constrain_instance(a.cell(), instance, 0)
+    }
+}
+
+#[test]
+fn test_verify_proof_bound_to_vk() {
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    // Circuits with the same shape, which differ only in a fixed value.
+    let pk = keygen(&params, &AdviceIsFixed(1));
+    let vk = pk.get_vk();
+    let other_vk = keygen_vk(&params, &AdviceIsFixed(2)).expect("keygen_vk should not fail");
+    assert_ne!(vk.transcript_repr(), other_vk.transcript_repr());
+
+    // The transcripts are used directly, to compare what the prover and verifier read
+    // and write.
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[AdviceIsFixed(1)],
+        &[&[]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let prover_counts = transcript.counts();
+    let proof = transcript.finalize().unwrap();
+
+    let verify = |vk: &VerifyingKey<_>| {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        let valid = verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            &params,
+            vk,
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+            params.n(),
+        )
+        .is_ok();
+        // The verifier follows the prover's protocol whether or not the proof is valid.
+        prover_counts.assert_consistent(&transcript.counts());
+        valid
+    };
+    assert!(verify(vk));
+    assert!(!verify(&other_vk));
+}
+
+#[test]
+fn test_validate_instances() {
+    #[derive(Clone, Copy)]
+    struct MyCircuit;
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = [Column<Instance>; 2];
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            *self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            [meta.instance_column(), meta.instance_column()]
+        }
+
+        fn synthesize(&self, _: Self::Config, _: impl Layouter<Fr>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    const K: u32 = 4;
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(K, OsRng);
+    let vk = keygen_vk(&params, &MyCircuit).expect("keygen_vk should not fail");
+    let max_len = (1 << K) - (vk.cs().blinding_factors() + 1);
+    let values = vec![Fr::ONE; max_len + 1];
+
+    assert!(validate_instances(&vk, &[&values[..max_len], &[]]).is_ok());
+    assert!(matches!(
+        validate_instances(&vk, &[&[]]),
+        Err(Error::InstanceColumnCount {
+            expected: 2,
+            actual: 1
+        })
+    ));
+    assert!(matches!(
+        validate_instances(&vk, &[&[], &values]),
+        Err(Error::InstanceColumnTooLong { column: 1, len, max_len: max })
+            if len == max_len + 1 && max == max_len
+    ));
+}
+
+#[test]
+fn test_verification_failure() {
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &InstanceCircuit::default());
+    let proof = prove(&params, &pk, InstanceCircuit(1), &[&[Fr::ONE]], OsRng);
+
+    let failure = |proof: &[u8], instances: &[&[Fr]]| {
+        verify(&params, pk.get_vk(), instances, proof)
+            .unwrap_err()
+            .verification_failure()
+    };
+
+    assert!(verify(&params, pk.get_vk(), &[&[Fr::ONE]], &proof).is_ok());
+    assert_eq!(failure(&proof, &[]), Some(VerificationFailure::Instances));
+    assert_eq!(
+        failure(&proof, &[&[Fr::from(2)]]),
+        Some(VerificationFailure::FinalCheck)
+    );
+    assert_eq!(
+        failure(&proof[..proof.len() / 2], &[&[Fr::ONE]]),
+        Some(VerificationFailure::Transcript)
+    );
+    // The multi-opening proof is read last.
+    assert_eq!(
+        failure(&proof[..proof.len() - 1], &[&[Fr::ONE]]),
+        Some(VerificationFailure::Opening)
+    );
+    assert_eq!(Error::Synthesis.verification_failure(), None);
+}
+
+#[test]
+fn test_public_lookup_table() {
+    // Proves that a witnessed value is in a public table.
+    #[derive(Clone, Copy, Default)]
+    struct MyCircuit(u64);
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = (Column<Advice>, Selector, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.complex_selector();
+            let table = meta.instance_column();
+            meta.lookup_public("a in table", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let q = meta.query_selector(q);
+                vec![(q * a, table)]
+            });
+            (a, q, table)
+        }
+
+        fn synthesize(
+            &self,
+            (a, q, _): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "a",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", a, 0, || Value::known(Fr::from(self.0)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    // The keys do not depend on the table.
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &MyCircuit::default());
+    let vk = pk.get_vk();
+
+    let epoch_1 = [5, 7, 9].map(Fr::from);
+    let proof = prove(&params, &pk, MyCircuit(7), &[&epoch_1], OsRng);
+    assert!(verify(&params, vk, &[&epoch_1], &proof).is_ok());
+    // The proof is bound to the table it was created with.
+    assert!(verify(&params, vk, &[&[5, 9].map(Fr::from)], &proof).is_err());
+
+    // The table can be updated without running keygen again.
+    let epoch_2 = [7, 11].map(Fr::from);
+    let proof = prove(&params, &pk, MyCircuit(11), &[&epoch_2], OsRng);
+    assert!(verify(&params, vk, &[&epoch_2], &proof).is_ok());
+    assert!(verify(&params, vk, &[&epoch_1], &proof).is_err());
+}
+
+#[test]
+fn test_wide_lookup() {
+    const WIDTH: usize = 6;
+    const TABLE_ROWS: u64 = 8;
+
+    // Looks up a witnessed tuple in a table with `width` columns.
+    fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        width: usize,
+    ) -> (Vec<Column<Advice>>, Selector, Vec<TableColumn>) {
+        let advice: Vec<_> = (0..width).map(|_| meta.advice_column()).collect();
+        let q = meta.complex_selector();
+        let table: Vec<_> = (0..width).map(|_| meta.lookup_table_column()).collect();
+        meta.lookup("tuple in table", |meta| {
+            let q = meta.query_selector(q);
+            advice
+                .iter()
+                .zip(table.iter())
+                .map(|(advice, table)| {
+                    (
+                        q.clone() * meta.query_advice(*advice, Rotation::cur()),
+                        *table,
+                    )
+                })
+                .collect()
+        });
+        (advice, q, table)
+    }
+
+    // The width of the tuples does not affect the degree.
+    let degree = |width| {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        configure(&mut meta, width);
+        meta.degree()
+    };
+    assert_eq!(degree(WIDTH), degree(1));
+
+    #[derive(Clone, Copy, Default)]
+    struct MyCircuit(u64);
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = (Vec<Column<Advice>>, Selector, Vec<TableColumn>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            configure(meta, WIDTH)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, q, table): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            // Row i of the table is (i, 2i, ..., WIDTH * i).
+            layouter.assign_table(
+                || "table",
+                |mut t| {
+                    for row in 0..TABLE_ROWS {
+                        for (j, column) in table.iter().enumerate() {
+                            t.assign_cell(
+                                || "table cell",
+                                *column,
+                                row as usize,
+                                || Value::known(Fr::from(row * (j as u64 + 1))),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "tuple",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    for (j, column) in advice.iter().enumerate() {
+                        region.assign_advice(
+                            || "tuple cell",
+                            *column,
+                            0,
+                            || Value::known(Fr::from(self.0 * (j as u64 + 1))),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(5, OsRng);
+    let pk = keygen(&params, &MyCircuit::default());
+    let proof = prove(&params, &pk, MyCircuit(5), &[], OsRng);
+    assert!(verify(&params, pk.get_vk(), &[], &proof).is_ok());
+}
+
+#[test]
+fn test_high_degree_gate() {
+    const RANGE: u64 = 16;
+
+    // Range-checks a value with a single gate of degree RANGE + 1.
+    #[derive(Clone, Copy, Default)]
+    struct MyCircuit(u64);
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = (Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.selector();
+            meta.create_gate("a < RANGE", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let q = meta.query_selector(q);
+                let range_check = (0..RANGE).fold(Expression::Constant(Fr::ONE), |acc, i| {
+                    acc * (a.clone() - Expression::Constant(Fr::from(i)))
+                });
+                vec![q * range_check]
+            });
+            (a, q)
+        }
+
+        fn synthesize(
+            &self,
+            (a, q): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "a",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", a, 0, || Value::known(Fr::from(self.0)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    // The gate degree exceeds the number of rows.
+    const K: u32 = 3;
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(K, OsRng);
+    let pk = keygen(&params, &MyCircuit::default());
+    let vk = pk.get_vk();
+    assert_eq!(vk.cs().degree(), RANGE as usize + 1);
+    assert_eq!(vk.get_domain().get_quotient_poly_degree(), RANGE as usize);
+
+    let proof = prove(&params, &pk, MyCircuit(RANGE - 1), &[], OsRng);
+    assert!(verify(&params, vk, &[], &proof).is_ok());
+    let proof = prove(&params, &pk, MyCircuit(RANGE), &[], OsRng);
+    assert!(verify(&params, vk, &[], &proof).is_err());
+}
+
+#[test]
+fn test_prepare_proof() {
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &InstanceCircuit::default());
+
+    let values = [1, 2, 3];
+    let proofs = values.map(|value| {
+        prove(
+            &params,
+            &pk,
+            InstanceCircuit(value),
+            &[&[Fr::from(value)]],
+            OsRng,
+        )
+    });
+
+    // Proofs are verified with one final check, given the instances they were
+    // created with.
+    let verify_batch = |instances: [u64; 3]| {
+        let mut transcripts = proofs
+            .iter()
+            .map(|proof| Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]))
+            .collect::<Vec<_>>();
+        let mut strategy = AccumulatorStrategy::new(&params);
+        for (transcript, value) in transcripts.iter_mut().zip(instances) {
+            let proof = prepare_proof::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>, _, _>(
+                &params,
+                pk.get_vk(),
+                &[&[&[Fr::from(value)]]],
+                transcript,
+                params.n(),
+            )
+            .expect("proof should be well-formed");
+            assert_eq!(proof.advice_commitments().len(), 1);
+            assert_eq!(proof.advice_commitments()[0].len(), 1);
+
+            strategy = match proof
+                .verify::<_, VerifierSHPLONK<_>, _, _, _>(&params, strategy, transcript)
+            {
+                Ok(strategy) => strategy,
+                Err(_) => return false,
+            };
+        }
+        VerificationStrategy::<_, VerifierSHPLONK<_>>::finalize(strategy)
+    };
+    assert!(verify_batch(values));
+    assert!(!verify_batch([1, 2, 4]));
+}
+
+#[test]
+fn test_precommitted_advice() {
+    // Witnesses two values in an unblinded column, exposing the first as an instance.
+    #[derive(Clone, Copy, Default)]
+    struct MyCircuit([u64; 2]);
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.unblinded_advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+            (a, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (a, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let first = layouter.assign_region(
+                || "a",
+                |mut region| {
+                    region.assign_advice(|| "a", a, 1, || Value::known(Fr::from(self.0[1])))?;
+                    region.assign_advice(|| "a", a, 0, || Value::known(Fr::from(self.0[0])))
+                },
+            )?;
+            layouter.constrain_instance(first.cell(), instance, 0)
+        }
+    }
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &MyCircuit::default());
+    let (column, _) = MyCircuit::configure(&mut ConstraintSystem::default());
+
+    let values = [3, 5];
+    let precommitted = PrecommittedAdvice::commit(
+        &params,
+        &pk,
+        column,
+        &values.map(Fr::from),
+        Blind(Fr::random(OsRng)),
+    )
+    .unwrap();
+
+    let prove = |values: [u64; 2], precommitted: &[PrecommittedAdvice<_>]| {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof_with_precommitted_advice::<
+            KZGCommitmentScheme<_>,
+            ProverSHPLONK<_>,
+            _,
+            _,
+            _,
+            _,
+        >(
+            &params,
+            &pk,
+            &[MyCircuit(values)],
+            &[&[&[Fr::from(values[0])]]],
+            &[precommitted],
+            OsRng,
+            &mut transcript,
+        )
+        .map(|()| transcript.finalize().unwrap())
+    };
+
+    // The proof must commit to the precommitted values.
+    assert!(matches!(
+        prove([3, 6], &[precommitted]),
+        Err(Error::PrecommittedAdviceMismatch(_))
+    ));
+    let proof = prove(values, &[precommitted]).expect("proof generation should not fail");
+
+    // Only advice columns of the circuit can be precommitted.
+    let missing = Column::new(1, Advice::default());
+    assert!(matches!(
+        PrecommittedAdvice::commit(&params, &pk, missing, &[], Blind::default()),
+        Err(Error::InvalidPrecommittedAdvice(_))
+    ));
+    let unknown = PrecommittedAdvice::new(missing, precommitted.commitment(), Blind::default());
+    assert!(matches!(
+        prove(values, &[precommitted, unknown]),
+        Err(Error::InvalidPrecommittedAdvice(_))
+    ));
+
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let prepared = prepare_proof::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>, _, _>(
+        &params,
+        pk.get_vk(),
+        &[&[&[Fr::from(values[0])]]],
+        &mut transcript,
+        params.n(),
+    )
+    .expect("proof should be well-formed");
+    assert!(prepared
+        .check_precommitted_advice(&[&[(column, precommitted.commitment())]])
+        .is_ok());
+    let error = prepared.check_precommitted_advice(&[]).unwrap_err();
+    assert!(matches!(error, Error::InvalidPrecommittedAdvice(_)));
+    assert_eq!(
+        error.verification_failure(),
+        Some(VerificationFailure::PrecommittedAdvice)
+    );
+    let other =
+        PrecommittedAdvice::commit(&params, &pk, column, &[Fr::from(3)], Blind::default()).unwrap();
+    let error = prepared
+        .check_precommitted_advice(&[&[(column, other.commitment())]])
+        .unwrap_err();
+    assert!(matches!(error, Error::PrecommittedAdviceMismatch(_)));
+    assert_eq!(
+        error.verification_failure(),
+        Some(VerificationFailure::PrecommittedAdvice)
+    );
+    assert!(prepared
+        .verify::<_, VerifierSHPLONK<_>, _, _, _>(
+            &params,
+            SingleStrategy::new(&params),
+            &mut transcript
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_verify_proof_with_instance_commitments() {
+    use halo2curves::pasta::{EqAffine, Fp};
+
+    let params: ParamsIPA<EqAffine> = ParamsIPA::new(4);
+    let vk = keygen_vk(&params, &InstanceCircuit::default()).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk.clone(), &InstanceCircuit::default())
+        .expect("keygen_pk should not fail");
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[InstanceCircuit(7)],
+        &[&[&[Fp::from(7)]]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize().unwrap();
+
+    let verify = |commitments: &[EqAffine]| {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        verify_proof_with_instance_commitments::<_, VerifierIPA<_>, _, _, _>(
+            &params,
+            &vk,
+            SingleStrategyIPA::new(&params),
+            &[commitments],
+            &mut transcript,
+            params.n(),
+        )
+    };
+
+    // The commitments of the instances the proof was created with are accepted.
+    let commitments = commit_instances(&params, &vk, &[&[Fp::from(7)]]).unwrap();
+    assert!(verify(&commitments).is_ok());
+
+    let other = commit_instances(&params, &vk, &[&[Fp::from(8)]]).unwrap();
+    assert!(verify(&other).is_err());
+    assert!(matches!(
+        verify(&[]),
+        Err(Error::InstanceColumnCount {
+            expected: 1,
+            actual: 0
+        })
+    ));
+
+    // Verifiers that evaluate the instance columns themselves need their values.
+    let kzg_params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let kzg_vk =
+        keygen_vk(&kzg_params, &InstanceCircuit::default()).expect("keygen_vk should not fail");
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    assert!(matches!(
+        verify_proof_with_instance_commitments::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            _,
+            _,
+        >(
+            &kzg_params,
+            &kzg_vk,
+            SingleStrategy::new(&kzg_params),
+            &[&[]],
+            &mut transcript,
+            kzg_params.n(),
+        ),
+        Err(Error::InvalidInstances)
+    ));
+}
+
+#[test]
+fn test_non_hiding_proof() {
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    // A gate, a lookup and a copy constraint, so that every argument is exercised.
+    #[derive(Clone, Copy, Default)]
+    struct MyCircuit<const HIDING: bool>;
+
+    impl<const HIDING: bool> Circuit<Fr> for MyCircuit<HIDING> {
+        type Config = (Column<Advice>, Column<Advice>, Selector, TableColumn);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            *self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            if !HIDING {
+                meta.set_proving_mode(ProvingMode::NonHiding);
+            }
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let q = meta.complex_selector();
+            let table = meta.lookup_table_column();
+            meta.enable_equality(a);
+            meta.create_gate("b is a squared", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let q = meta.query_selector(q);
+                vec![q * (a.clone() * a - b)]
+            });
+            meta.lookup("a in table", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let q = meta.query_selector(q);
+                vec![(q * a, table)]
+            });
+            (a, b, q, table)
+        }
+
+        fn synthesize(
+            &self,
+            (a, b, q, table): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "table",
+                |mut t| {
+                    for row in 0..8 {
+                        t.assign_cell(
+                            || "table",
+                            table,
+                            row,
+                            || Value::known(Fr::from(row as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "squares",
+                |mut region| {
+                    let mut first = None;
+                    for row in 0..4 {
+                        let value = Fr::from(row as u64 + 1);
+                        q.enable(&mut region, row)?;
+                        let cell = region.assign_advice(|| "a", a, row, || Value::known(value))?;
+                        region.assign_advice(|| "b", b, row, || Value::known(value.square()))?;
+                        first.get_or_insert(cell);
+                    }
+                    first
+                        .unwrap()
+                        .copy_advice(|| "copy of a", &mut region, a, 4)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(5, OsRng);
+    let hiding_pk = keygen(&params, &MyCircuit::<true>);
+    let pk = keygen(&params, &MyCircuit::<false>);
+    let (hiding_vk, vk) = (hiding_pk.get_vk(), pk.get_vk());
+    assert_eq!(vk.cs().proving_mode(), ProvingMode::NonHiding);
+    assert_eq!(vk.cs().blinding_factors(), 0);
+    assert_ne!(vk.transcript_repr(), hiding_vk.transcript_repr());
+
+    // Without blinding, the proof depends only on the witness.
+    let prove_seeded = |pk: &ProvingKey<_>, circuit, seed| {
+        prove(&params, pk, circuit, &[], ChaCha20Rng::seed_from_u64(seed))
+    };
+    let proof = prove_seeded(&pk, MyCircuit::<false>, 0);
+    assert_eq!(proof, prove_seeded(&pk, MyCircuit::<false>, 1));
+    assert!(verify(&params, vk, &[], &proof).is_ok());
+    assert!(verify(&params, hiding_vk, &[], &proof).is_err());
+
+    // It omits the commitment to the random polynomial and its evaluation.
+    let hiding_proof = prove(&params, &hiding_pk, MyCircuit::<true>, &[], OsRng);
+    assert!(verify(&params, hiding_vk, &[], &hiding_proof).is_ok());
+    assert_eq!(hiding_proof.len() - proof.len(), 2 * 32);
+}
+
+#[test]
+fn test_verify_proof_slice() {
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &InstanceCircuit::default());
+    let proof = prove(&params, &pk, InstanceCircuit(1), &[&[Fr::ONE]], OsRng);
+
+    let verify = |proof: &[u8], instance: Fr| {
+        verify_proof_slice::<_, VerifierSHPLONK<_>, _, Blake2bRead<_, _, Challenge255<_>>, _>(
+            &params,
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[&[instance]]],
+            proof,
+            params.n(),
+        )
+    };
+
+    assert!(verify(&proof, Fr::ONE).is_ok());
+    assert!(verify(&proof, Fr::from(2)).is_err());
+    assert!(verify(&proof[..proof.len() - 1], Fr::ONE).is_err());
+
+    // Trailing bytes are not read, so they do not affect the result.
+    let mut padded = proof.clone();
+    padded.extend_from_slice(&[0xff; 8]);
+    assert!(verify(&padded, Fr::ONE).is_ok());
+}
+
+#[test]
+fn test_evm_calldata() {
+    use crate::transcript::{
+        decode_evm_calldata, encode_evm_calldata, ChallengeEvm, Keccak256Read, Keccak256Write,
+    };
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+    let pk = keygen(&params, &InstanceCircuit::default());
+    let instance = Fr::from(3);
+
+    let mut transcript = Keccak256Write::<_, _, ChallengeEvm<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[InstanceCircuit(3)],
+        &[&[&[instance]]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize().unwrap();
+
+    // The calldata decodes to the instances and proof it was encoded from, and the
+    // decoded proof verifies against the decoded instances.
+    let calldata = encode_evm_calldata(&[&[instance]], &proof).unwrap();
+    let (instances, proof) = decode_evm_calldata::<Fr>(&calldata, &[1]).unwrap();
+    assert_eq!(instances, vec![vec![instance]]);
+    let mut transcript = Keccak256Read::<_, _, ChallengeEvm<_>>::init(&proof[..]);
+    assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+        &params,
+        pk.get_vk(),
+        SingleStrategy::new(&params),
+        &[&[&instances[0][..]]],
+        &mut transcript,
+        params.n(),
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_permutation_chunk_len() {
+    // Copies a value across three columns. A `CHUNK_LEN` of zero keeps the default chunk
+    // length, which covers all three columns at degree 5.
+    #[derive(Clone, Copy)]
+    struct MyCircuit<const CHUNK_LEN: usize> {
+        copied: u64,
+    }
+
+    impl<const CHUNK_LEN: usize> Circuit<Fr> for MyCircuit<CHUNK_LEN> {
+        type Config = [Column<Advice>; 3];
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            *self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            meta.set_minimum_degree(5);
+            if CHUNK_LEN > 0 {
+                meta.set_permutation_chunk_len(CHUNK_LEN);
+            }
+            let columns = [(); 3].map(|_| meta.advice_column());
+            for column in columns {
+                meta.enable_equality(column);
+            }
+            columns
+        }
+
+        fn synthesize(
+            &self,
+            columns: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copies",
+                |mut region| {
+                    let cell = region.assign_advice(
+                        || "value",
+                        columns[0],
+                        0,
+                        || Value::known(Fr::ONE),
+                    )?;
+                    for column in &columns[1..] {
+                        let copy = region.assign_advice(
+                            || "copy",
+                            *column,
+                            0,
+                            || Value::known(Fr::from(self.copied)),
+                        )?;
+                        region.constrain_equal(cell.cell(), copy.cell())?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn prove_copies<const CHUNK_LEN: usize>(
+        params: &ParamsKZG<Bn256>,
+        copied: u64,
+    ) -> (VerifyingKey<G1Affine>, Vec<u8>) {
+        let circuit = MyCircuit::<CHUNK_LEN> { copied };
+        let pk = keygen(params, &circuit);
+        let proof = prove(params, &pk, circuit, &[], OsRng);
+        (pk.get_vk().clone(), proof)
+    }
+
+    let params: ParamsKZG<Bn256> = ParamsKZG::setup(4, OsRng);
+
+    let (vk, proof) = prove_copies::<0>(&params, 1);
+    assert_eq!(vk.cs().permutation().chunks(vk.cs_degree), 1);
+    assert!(verify(&params, &vk, &[], &proof).is_ok());
+
+    let (chunked_vk, chunked_proof) = prove_copies::<1>(&params, 1);
+    assert_eq!(chunked_vk.cs_degree, vk.cs_degree);
+    assert_eq!(
+        chunked_vk.cs().permutation().chunks(chunked_vk.cs_degree),
+        3
+    );
+    assert_ne!(chunked_vk.transcript_repr(), vk.transcript_repr());
+    assert!(chunked_proof.len() > proof.len());
+    assert!(verify(&params, &chunked_vk, &[], &chunked_proof).is_ok());
+
+    // The links between chunks carry the copy constraints across them.
+    let (chunked_vk, chunked_proof) = prove_copies::<1>(&params, 2);
+    assert!(verify(&params, &chunked_vk, &[], &chunked_proof).is_err());
+}
+
+#[test]
+fn test_downsized_params() {
+    use halo2curves::pasta::{EqAffine, Fp};
+
+    const K: u32 = 6;
+    const SMALL_K: u32 = 4;
+
+    // Proofs under parameters downsized from a larger setup verify, and verification
+    // still checks the instances.
+    let mut params: ParamsKZG<Bn256> = ParamsKZG::setup(K, OsRng);
+    params.downsize(SMALL_K);
+    assert_eq!(params.k(), SMALL_K);
+    let pk = keygen(&params, &InstanceCircuit::default());
+    let proof = prove(&params, &pk, InstanceCircuit(15), &[&[Fr::from(15)]], OsRng);
+    assert!(verify(&params, pk.get_vk(), &[&[Fr::from(15)]], &proof).is_ok());
+    assert!(verify(&params, pk.get_vk(), &[&[Fr::from(16)]], &proof).is_err());
+
+    let mut params: ParamsIPA<EqAffine> = ParamsIPA::new(K);
+    params.downsize(SMALL_K);
+    assert_eq!(params.k(), SMALL_K);
+    let vk = keygen_vk(&params, &InstanceCircuit::default()).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk.clone(), &InstanceCircuit::default())
+        .expect("keygen_pk should not fail");
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[InstanceCircuit(15)],
+        &[&[&[Fp::from(15)]]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize().unwrap();
+    for (instance, valid) in [(15, true), (16, false)] {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        let result = verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+            &params,
+            &vk,
+            SingleStrategyIPA::new(&params),
+            &[&[&[Fp::from(instance)]]],
+            &mut transcript,
+            params.n(),
+        );
+        assert_eq!(result.is_ok(), valid);
+    }
+}
@@ -2,14 +2,15 @@
 //! various forms, including computing commitments to them and provably opening
 //! the committed polynomials at arbitrary points.
 
-use crate::arithmetic::parallelize;
+use crate::arithmetic::{batch_invert, parallelize};
 use crate::helpers::SerdePrimeField;
 use crate::plonk::Assigned;
 use crate::SerdeFormat;
-use group::ff::{BatchInvert, Field};
+use group::ff::Field;
 #[cfg(feature = "parallel-poly-read")]
 use maybe_rayon::{iter::ParallelIterator, prelude::ParallelSliceMut};
 
+use std::borrow::Borrow;
 use std::fmt::Debug;
 use std::io;
 use std::marker::PhantomData;
@@ -31,6 +32,7 @@ pub mod kzg;
 mod multiopen_test;
 
 pub use domain::*;
+pub(crate) use query::QueryList;
 pub use query::{ProverQuery, VerifierQuery};
 pub use strategy::{Guard, VerificationStrategy};
 
@@ -218,32 +220,41 @@ impl<F: SerdePrimeField, B> Polynomial<F, B> {
     }
 }
 
-pub(crate) fn batch_invert_assigned<F: Field>(
-    assigned: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
+pub(crate) fn batch_invert_assigned<F: Field, P: Borrow<Polynomial<Assigned<F>, LagrangeCoeff>>>(
+    assigned: &[P],
 ) -> Vec<Polynomial<F, LagrangeCoeff>> {
     let mut assigned_denominators: Vec<_> = assigned
         .iter()
         .map(|f| {
-            f.iter()
+            f.borrow()
+                .iter()
                 .map(|value| value.denominator())
                 .collect::<Vec<_>>()
         })
         .collect();
 
-    assigned_denominators
+    // If the denominator is trivial, we can skip it, reducing the size of the
+    // batch inversion.
+    let mut denominators: Vec<F> = assigned_denominators
+        .iter()
+        .flat_map(|f| f.iter().filter_map(|d| *d))
+        .collect();
+    batch_invert(&mut denominators);
+    for (d, inv) in assigned_denominators
         .iter_mut()
-        .flat_map(|f| {
-            f.iter_mut()
-                // If the denominator is trivial, we can skip it, reducing the
-                // size of the batch inversion.
-                .filter_map(|d| d.as_mut())
-        })
-        .batch_invert();
+        .flat_map(|f| f.iter_mut().filter_map(|d| d.as_mut()))
+        .zip(denominators)
+    {
+        *d = inv;
+    }
 
     assigned
         .iter()
         .zip(assigned_denominators)
-        .map(|(poly, inv_denoms)| poly.invert(inv_denoms.into_iter().map(|d| d.unwrap_or(F::ONE))))
+        .map(|(poly, inv_denoms)| {
+            poly.borrow()
+                .invert(inv_denoms.into_iter().map(|d| d.unwrap_or(F::ONE)))
+        })
         .collect()
 }
 
@@ -346,6 +357,11 @@ impl<'a, F: Field, B: Basis> Sub<F> for &'a Polynomial<F, B> {
 /// Describes the relative rotation of a vector. Negative numbers represent
 /// reverse (leftmost) rotations and positive numbers represent forward (rightmost)
 /// rotations. Zero represents no rotation.
+///
+/// Rotations wrap around the evaluation domain: querying `Rotation::prev()` on the
+/// first row reads the last row, which (like the other rows after the usable ones)
+/// holds blinding factors rather than assigned values. `MockProver` reports gates
+/// enabled where one of their queries leaves the usable rows.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Rotation(pub i32);
 
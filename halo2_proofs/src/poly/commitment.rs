@@ -3,9 +3,12 @@ use super::{
     strategy::Guard,
     Coeff, LagrangeCoeff, Polynomial,
 };
+use crate::arithmetic::parallelize;
 use crate::transcript::{EncodedChallenge, TranscriptRead, TranscriptWrite};
 use crate::{helpers::SerdePrimeField, poly::Error};
-use ff::Field;
+use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
+use ff::{Field, PrimeField};
+use group::GroupEncoding;
 use halo2curves::CurveAffine;
 use rand_core::RngCore;
 
@@ -72,6 +75,80 @@ pub trait Params<'params, C: CurveAffine>: Sized + Clone {
 
     /// Reads params from a buffer.
     fn read<R: io::Read>(reader: &mut R) -> io::Result<Self>;
+
+    /// Returns a stable fingerprint of these parameters.
+    ///
+    /// The fingerprint is a BLAKE2b digest of `k`, the moduli of the fields of the
+    /// curve and every generator of the parameters. It does not depend on how the
+    /// parameters were created or serialized, nor on the number of threads used to
+    /// compute it, so it can be used to key caches of anything derived from the
+    /// parameters.
+    fn fingerprint(&self) -> [u8; 32];
+}
+
+/// Computes [`Params::fingerprint`] from the parts of a set of parameters.
+#[derive(Debug)]
+pub(crate) struct ParamsFingerprint(Blake2bState);
+
+impl ParamsFingerprint {
+    /// The number of generators hashed into each leaf digest. This is fixed so
+    /// that the fingerprint does not depend on the number of threads.
+    const CHUNK_SIZE: usize = 1 << 12;
+
+    /// Starts the fingerprint of parameters of size $2^k$ over the curve `C`.
+    pub(crate) fn new<C: CurveAffine>(k: u32) -> Self {
+        let mut state = Blake2bParams::new()
+            .hash_length(32)
+            .personal(b"Halo2-ParamsHash")
+            .to_state();
+        state.update(&k.to_le_bytes());
+        for modulus in [C::Base::MODULUS, C::ScalarExt::MODULUS] {
+            state.update(&(modulus.len() as u64).to_le_bytes());
+            state.update(modulus.as_bytes());
+        }
+        ParamsFingerprint(state)
+    }
+
+    /// Hashes in a labelled vector of generators.
+    ///
+    /// The generators are hashed in fixed-size chunks in parallel, and the digests
+    /// of the chunks are hashed in order, together with the label and the length.
+    pub(crate) fn generators<G: GroupEncoding + Sync>(
+        mut self,
+        label: &[u8],
+        generators: &[G],
+    ) -> Self {
+        let num_chunks = (generators.len() + Self::CHUNK_SIZE - 1) / Self::CHUNK_SIZE;
+        let mut digests = vec![[0u8; 32]; num_chunks];
+        parallelize(&mut digests, |digests, start| {
+            for (idx, digest) in digests.iter_mut().enumerate() {
+                let offset = (start + idx) * Self::CHUNK_SIZE;
+                let end = std::cmp::min(offset + Self::CHUNK_SIZE, generators.len());
+                let mut state = Blake2bParams::new().hash_length(32).to_state();
+                for generator in &generators[offset..end] {
+                    state.update(generator.to_bytes().as_ref());
+                }
+                digest.copy_from_slice(state.finalize().as_bytes());
+            }
+        });
+
+        self.0.update(&(label.len() as u64).to_le_bytes());
+        self.0.update(label);
+        self.0.update(&(generators.len() as u64).to_le_bytes());
+        for digest in digests.iter() {
+            self.0.update(digest);
+        }
+        self
+    }
+
+    /// Returns the fingerprint.
+    pub(crate) fn finalize(self) -> [u8; 32] {
+        self.0
+            .finalize()
+            .as_bytes()
+            .try_into()
+            .expect("hash length is 32 bytes")
+    }
 }
 
 /// Parameters for circuit sysnthesis and prover parameters.
@@ -645,6 +645,58 @@ impl<F: WithSmallOrderMulGroup<3>> EvaluationDomain<F> {
         self.extended_omega
     }
 
+    /// Get the inverse of the generator of the extended domain's multiplicative
+    /// subgroup.
+    pub fn get_extended_omega_inv(&self) -> F {
+        self.extended_omega_inv
+    }
+
+    /// Get $\zeta$, the shift of the coset over which the extended domain is
+    /// evaluated. The $i$'th point of the extended domain is
+    /// $\zeta \cdot \omega_{ext}^i$, where $\omega_{ext}$ is
+    /// [`Self::get_extended_omega`].
+    pub fn get_g_coset(&self) -> F {
+        self.g_coset
+    }
+
+    /// Get $\zeta^{-1}$, the inverse of [`Self::get_g_coset`].
+    pub fn get_g_coset_inv(&self) -> F {
+        self.g_coset_inv
+    }
+
+    /// Get the number of cosets of the original domain that make up the extended
+    /// domain, which is `extended_len() / n`.
+    pub fn num_cosets(&self) -> usize {
+        1 << (self.extended_k - self.k)
+    }
+
+    /// Get the coset that the extended domain index `index` belongs to.
+    ///
+    /// Since $\omega_{ext}^{m} = \omega$ for `m = num_cosets()`, the point at
+    /// `index = q * m + j` is $\zeta \cdot \omega_{ext}^j \cdot \omega^q$, i.e. the
+    /// $q$'th point of the coset $\zeta \omega_{ext}^j H$. This returns $j$, which is
+    /// also the index of the part produced by [`Self::coeff_to_extended_parts`].
+    pub fn coset_of(&self, index: usize) -> usize {
+        assert!(index < self.extended_len());
+        index & (self.num_cosets() - 1)
+    }
+
+    /// Get the position of the extended domain index `index` within its coset
+    /// (see [`Self::coset_of`]), which is $q$ for `index = q * num_cosets() + j`.
+    pub fn index_in_coset(&self, index: usize) -> usize {
+        assert!(index < self.extended_len());
+        index >> (self.extended_k - self.k)
+    }
+
+    /// Get the extended domain index of the point at position `index` within
+    /// `coset`. This is the inverse of [`Self::coset_of`] and
+    /// [`Self::index_in_coset`].
+    pub fn extended_index(&self, coset: usize, index: usize) -> usize {
+        assert!(coset < self.num_cosets());
+        assert!(index < self.n as usize);
+        (index << (self.extended_k - self.k)) | coset
+    }
+
     /// Multiplies a value by some power of $\omega$, essentially rotating over
     /// the domain.
     pub fn rotate_omega(&self, value: F, rotation: Rotation) -> F {
@@ -713,6 +765,33 @@ impl<F: WithSmallOrderMulGroup<3>> EvaluationDomain<F> {
         results
     }
 
+    /// Evaluates the vanishing polynomial $Z_H(X) = X^n - 1$ of this domain at `x`.
+    pub fn evaluate_vanishing_polynomial(&self, x: F) -> F {
+        x.pow_vartime([self.n]) - F::ONE
+    }
+
+    /// Evaluates, at the point `x` where `xn = x^n`, the Lagrange basis polynomials
+    /// that select the rows the prover reserves in a circuit with `blinding_factors`
+    /// blinding rows.
+    ///
+    /// Returns `(l_0(x), l_last(x), l_blind(x))`, where `l_0` selects the first row,
+    /// `l_last` selects row `n - blinding_factors - 1`, the last row before the
+    /// blinding rows, and `l_blind` is the sum of the `l_i` over the blinding rows.
+    /// These are the conventions used by the prover, so arguments that restrict
+    /// their constraints to the usable rows should multiply them by
+    /// `1 - (l_last(x) + l_blind(x))`.
+    pub fn l_0_last_blind(&self, x: F, xn: F, blinding_factors: usize) -> (F, F, F) {
+        let l_evals = self.l_i_range(x, xn, (-((blinding_factors + 1) as i32))..=0);
+        assert_eq!(l_evals.len(), 2 + blinding_factors);
+        let l_last = l_evals[0];
+        let l_blind = l_evals[1..(1 + blinding_factors)]
+            .iter()
+            .fold(F::ZERO, |acc, eval| acc + eval);
+        let l_0 = l_evals[1 + blinding_factors];
+
+        (l_0, l_last, l_blind)
+    }
+
     /// Gets the quotient polynomial's degree (as a multiple of n)
     pub fn get_quotient_poly_degree(&self) -> usize {
         self.quotient_poly_degree as usize
@@ -822,6 +901,30 @@ fn test_l_i() {
     }
 }
 
+#[test]
+fn test_vanishing_and_l_0_last_blind() {
+    use rand_core::OsRng;
+
+    use halo2curves::pasta::pallas::Scalar;
+    let domain = EvaluationDomain::<Scalar>::new(1, 3);
+
+    // The vanishing polynomial is zero exactly on the domain.
+    for i in 0..8 {
+        let omega_i = domain.rotate_omega(Scalar::ONE, Rotation(i));
+        assert_eq!(domain.evaluate_vanishing_polynomial(omega_i), Scalar::ZERO);
+    }
+    let x = Scalar::random(OsRng);
+    let xn = x.pow([8]);
+    assert_eq!(domain.evaluate_vanishing_polynomial(x), xn - Scalar::ONE);
+
+    // With 3 blinding factors, row 4 is the last row and rows 5 to 7 are blinded.
+    let l = domain.l_i_range(x, xn, 0..8);
+    let (l_0, l_last, l_blind) = domain.l_0_last_blind(x, xn, 3);
+    assert_eq!(l_0, l[0]);
+    assert_eq!(l_last, l[4]);
+    assert_eq!(l_blind, l[5] + l[6] + l[7]);
+}
+
 #[test]
 fn test_coeff_to_extended_part() {
     use halo2curves::pasta::pallas::Scalar;
@@ -843,6 +946,40 @@ fn test_coeff_to_extended_part() {
     assert_eq!(want.values, got.values);
 }
 
+#[test]
+fn test_coset_indices() {
+    use halo2curves::pasta::pallas::Scalar;
+    use rand_core::OsRng;
+
+    let domain = EvaluationDomain::<Scalar>::new(3, 3);
+    assert_eq!(domain.num_cosets() << domain.k(), domain.extended_len());
+    assert_eq!(domain.get_g_coset() * domain.get_g_coset_inv(), Scalar::ONE);
+    assert_eq!(
+        domain.get_extended_omega() * domain.get_extended_omega_inv(),
+        Scalar::ONE
+    );
+
+    let mut poly = domain.empty_coeff();
+    for value in poly.iter_mut() {
+        *value = Scalar::random(OsRng);
+    }
+    let extended = domain.coeff_to_extended(&poly);
+    let parts = domain.coeff_to_extended_parts(&poly);
+
+    for index in 0..domain.extended_len() {
+        let coset = domain.coset_of(index);
+        let index_in_coset = domain.index_in_coset(index);
+        assert_eq!(domain.extended_index(coset, index_in_coset), index);
+
+        let point = domain.get_g_coset() * domain.get_extended_omega().pow([index as u64]);
+        let coset_point = domain.get_g_coset()
+            * domain.get_extended_omega().pow([coset as u64])
+            * domain.get_omega().pow([index_in_coset as u64]);
+        assert_eq!(point, coset_point);
+        assert_eq!(extended[index], parts[coset][index_in_coset]);
+    }
+}
+
 #[test]
 fn bench_coeff_to_extended_parts() {
     use halo2curves::pasta::pallas::Scalar;
@@ -3,11 +3,14 @@
 //!
 //! [halo]: https://eprint.iacr.org/2019/1021
 
-use crate::arithmetic::{best_multiexp_cpu, g_to_lagrange, parallelize, CurveAffine, CurveExt};
+use crate::arithmetic::{best_multiexp, g_to_lagrange, parallelize, Bases, CurveAffine, CurveExt};
 use crate::helpers::CurveRead;
-use crate::poly::commitment::{Blind, CommitmentScheme, Params, ParamsProver, ParamsVerifier};
+use crate::poly::commitment::{
+    Blind, CommitmentScheme, Params, ParamsFingerprint, ParamsProver, ParamsVerifier,
+};
 use crate::poly::ipa::msm::MSMIPA;
 use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
+use crate::transcript::ChallengeScalar;
 
 use group::{Curve, Group};
 use std::marker::PhantomData;
@@ -18,6 +21,22 @@ mod verifier;
 pub use prover::create_proof;
 pub use verifier::verify_proof;
 
+#[derive(Clone, Copy, Debug)]
+struct Xi {}
+/// Challenge for adding the random polynomial with a root at the opening point to the
+/// polynomial being opened.
+type ChallengeXi<F> = ChallengeScalar<F, Xi>;
+
+#[derive(Clone, Copy, Debug)]
+struct Z {}
+/// Challenge for scaling the inner products folded into the generator `U`.
+type ChallengeZ<F> = ChallengeScalar<F, Z>;
+
+#[derive(Clone, Copy, Debug)]
+struct U {}
+/// Challenge for collapsing the vectors of one round of the inner product argument.
+type ChallengeU<F> = ChallengeScalar<F, U>;
+
 use std::io;
 
 /// Public parameters for IPA commitment scheme
@@ -99,7 +118,7 @@ impl<'params, C: CurveAffine> Params<'params, C> for ParamsIPA<C> {
         tmp_bases.extend(self.g_lagrange.iter());
         tmp_bases.push(self.w);
 
-        best_multiexp_cpu::<C>(&tmp_scalars, &tmp_bases)
+        best_multiexp::<C>(&tmp_scalars, &tmp_bases, Bases::Other)
     }
 
     /// Writes params to a buffer.
@@ -140,6 +159,15 @@ impl<'params, C: CurveAffine> Params<'params, C> for ParamsIPA<C> {
             u,
         })
     }
+
+    fn fingerprint(&self) -> [u8; 32] {
+        ParamsFingerprint::new::<C>(self.k)
+            .generators(b"g", &self.g)
+            .generators(b"g_lagrange", &self.g_lagrange)
+            .generators(b"w", &[self.w])
+            .generators(b"u", &[self.u])
+            .finalize()
+    }
 }
 
 impl<'params, C: CurveAffine> ParamsProver<'params, C> for ParamsIPA<C> {
@@ -219,7 +247,7 @@ impl<'params, C: CurveAffine> ParamsProver<'params, C> for ParamsIPA<C> {
         tmp_bases.extend(self.g.iter());
         tmp_bases.push(self.w);
 
-        best_multiexp_cpu::<C>(&tmp_scalars, &tmp_bases)
+        best_multiexp::<C>(&tmp_scalars, &tmp_bases, Bases::Other)
     }
 
     fn get_g(&self) -> &[C] {
@@ -335,7 +363,7 @@ mod test {
         let (proof, ch_prover) = {
             create_proof(&params, rng, &mut transcript, &px, blind, *x).unwrap();
             let ch_prover = transcript.squeeze_challenge();
-            (transcript.finalize(), ch_prover)
+            (transcript.finalize().unwrap(), ch_prover)
         };
 
         // Verify the opening proof
@@ -1,9 +1,9 @@
 use ff::Field;
 use rand_core::RngCore;
 
-use super::ParamsIPA;
+use super::{ChallengeU, ChallengeXi, ChallengeZ, ParamsIPA};
 use crate::arithmetic::{
-    best_multiexp_cpu, compute_inner_product, eval_polynomial, parallelize, CurveAffine,
+    best_multiexp, compute_inner_product, eval_polynomial, parallelize, Bases, CurveAffine,
 };
 
 use crate::poly::commitment::ParamsProver;
@@ -62,18 +62,18 @@ pub fn create_proof<
     // Challenge that will ensure that the prover cannot change P but can only
     // witness a random polynomial commitment that agrees with P at x_3, with high
     // probability.
-    let xi = *transcript.squeeze_challenge_scalar::<()>();
+    let xi: ChallengeXi<_> = transcript.squeeze_challenge_scalar();
 
     // Challenge that ensures that the prover did not interfere with the U term
     // in their commitments.
-    let z = *transcript.squeeze_challenge_scalar::<()>();
+    let z: ChallengeZ<_> = transcript.squeeze_challenge_scalar();
 
     // We'll be opening `P' = P - [v] G_0 + [ξ] S` to ensure it has a root at
     // zero.
-    let mut p_prime_poly = s_poly * xi + p_poly;
+    let mut p_prime_poly = s_poly * *xi + p_poly;
     let v = eval_polynomial(&p_prime_poly, x_3);
     p_prime_poly[0] -= &v;
-    let p_prime_blind = s_poly_blind * Blind(xi) + p_blind;
+    let p_prime_blind = s_poly_blind * Blind(*xi) + p_blind;
 
     // This accumulates the synthetic blinding factor `f` starting
     // with the blinding factor for `P'`.
@@ -106,16 +106,24 @@ pub fn create_proof<
         //
         // TODO: If we modify multiexp to take "extra" bases, we could speed
         // this piece up a bit by combining the multiexps.
-        let l_j = best_multiexp_cpu(&p_prime[half..], &g_prime[0..half]);
-        let r_j = best_multiexp_cpu(&p_prime[0..half], &g_prime[half..]);
+        let l_j = best_multiexp(&p_prime[half..], &g_prime[0..half], Bases::Other);
+        let r_j = best_multiexp(&p_prime[0..half], &g_prime[half..], Bases::Other);
         let value_l_j = compute_inner_product(&p_prime[half..], &b[0..half]);
         let value_r_j = compute_inner_product(&p_prime[0..half], &b[half..]);
         let l_j_randomness = C::Scalar::random(&mut rng);
         let r_j_randomness = C::Scalar::random(&mut rng);
-        let l_j =
-            l_j + &best_multiexp_cpu(&[value_l_j * &z, l_j_randomness], &[params.u, params.w]);
-        let r_j =
-            r_j + &best_multiexp_cpu(&[value_r_j * &z, r_j_randomness], &[params.u, params.w]);
+        let l_j = l_j
+            + &best_multiexp(
+                &[value_l_j * *z, l_j_randomness],
+                &[params.u, params.w],
+                Bases::Other,
+            );
+        let r_j = r_j
+            + &best_multiexp(
+                &[value_r_j * *z, r_j_randomness],
+                &[params.u, params.w],
+                Bases::Other,
+            );
         let l_j = l_j.to_affine();
         let r_j = r_j.to_affine();
 
@@ -123,14 +131,14 @@ pub fn create_proof<
         transcript.write_point(l_j)?;
         transcript.write_point(r_j)?;
 
-        let u_j = *transcript.squeeze_challenge_scalar::<()>();
+        let u_j: ChallengeU<_> = transcript.squeeze_challenge_scalar();
         let u_j_inv = u_j.invert().unwrap(); // TODO, bubble this up
 
         // Collapse `p_prime` and `b`.
         // TODO: parallelize
         for i in 0..half {
             p_prime[i] = p_prime[i] + &(p_prime[i + half] * &u_j_inv);
-            b[i] = b[i] + &(b[i + half] * &u_j);
+            b[i] = b[i] + &(b[i + half] * *u_j);
         }
         p_prime.truncate(half);
         b.truncate(half);
@@ -141,7 +149,7 @@ pub fn create_proof<
 
         // Update randomness (the synthetic blinding factor at the end)
         f += &(l_j_randomness * &u_j_inv);
-        f += &(r_j_randomness * &u_j);
+        f += &(r_j_randomness * *u_j);
     }
 
     // We have fully collapsed `p_prime`, `b`, `G'`
@@ -154,7 +162,7 @@ pub fn create_proof<
     Ok(())
 }
 
-fn parallel_generator_collapse<C: CurveAffine>(g: &mut [C], challenge: C::Scalar) {
+fn parallel_generator_collapse<C: CurveAffine>(g: &mut [C], u_j: ChallengeU<C>) {
     let len = g.len() / 2;
     let (g_lo, g_hi) = g.split_at_mut(len);
 
@@ -162,7 +170,7 @@ fn parallel_generator_collapse<C: CurveAffine>(g: &mut [C], challenge: C::Scalar
         let g_hi = &g_hi[start..];
         let mut tmp = Vec::with_capacity(g_lo.len());
         for (g_lo, g_hi) in g_lo.iter().zip(g_hi.iter()) {
-            tmp.push(g_lo.to_curve() + &(*g_hi * challenge));
+            tmp.push(g_lo.to_curve() + &(*g_hi * *u_j));
         }
         C::Curve::batch_normalize(&tmp, g_lo);
     });
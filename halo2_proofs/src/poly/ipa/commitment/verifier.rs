@@ -1,6 +1,6 @@
 use group::ff::{BatchInvert, Field};
 
-use super::ParamsIPA;
+use super::{ChallengeU, ChallengeXi, ChallengeZ, ParamsIPA};
 use crate::{arithmetic::CurveAffine, poly::ipa::strategy::GuardIPA};
 use crate::{
     poly::{commitment::MSM, ipa::msm::MSMIPA, Error},
@@ -22,10 +22,10 @@ pub fn verify_proof<'params, C: CurveAffine, E: EncodedChallenge<C>, T: Transcri
     // P' = P - [v] G_0 + [ξ] S
     msm.add_constant_term(-v); // add [-v] G_0
     let s_poly_commitment = transcript.read_point().map_err(|_| Error::OpeningError)?;
-    let xi = *transcript.squeeze_challenge_scalar::<()>();
-    msm.append_term(xi, s_poly_commitment.into());
+    let xi: ChallengeXi<_> = transcript.squeeze_challenge_scalar();
+    msm.append_term(*xi, s_poly_commitment.into());
 
-    let z = *transcript.squeeze_challenge_scalar::<()>();
+    let z: ChallengeZ<_> = transcript.squeeze_challenge_scalar();
 
     let mut rounds = vec![];
     for _ in 0..k {
@@ -34,9 +34,9 @@ pub fn verify_proof<'params, C: CurveAffine, E: EncodedChallenge<C>, T: Transcri
         let r = transcript.read_point().map_err(|_| Error::OpeningError)?;
 
         let u_j_packed = transcript.squeeze_challenge();
-        let u_j = *u_j_packed.as_challenge_scalar::<()>();
+        let u_j: ChallengeU<_> = u_j_packed.as_challenge_scalar();
 
-        rounds.push((l, r, u_j, /* to be inverted */ u_j, u_j_packed));
+        rounds.push((l, r, *u_j, /* to be inverted */ *u_j, u_j_packed));
     }
 
     rounds
@@ -75,7 +75,7 @@ pub fn verify_proof<'params, C: CurveAffine, E: EncodedChallenge<C>, T: Transcri
     let f = transcript.read_scalar().map_err(|_| Error::SamplingError)?;
     let b = compute_b(x, &u);
 
-    msm.add_to_u_scalar(neg_c * &b * &z);
+    msm.add_to_u_scalar(neg_c * &b * *z);
     msm.add_to_w_scalar(-f);
 
     let guard = GuardIPA {
@@ -1,4 +1,4 @@
-use crate::arithmetic::{best_multiexp_cpu, CurveAffine};
+use crate::arithmetic::{best_multiexp, Bases, CurveAffine};
 use crate::poly::{commitment::MSM, ipa::commitment::ParamsVerifierIPA};
 use ff::Field;
 use group::Group;
@@ -11,7 +11,8 @@ pub struct MSMIPA<'params, C: CurveAffine> {
     g_scalars: Option<Vec<C::Scalar>>,
     w_scalar: Option<C::Scalar>,
     u_scalar: Option<C::Scalar>,
-    // x-coordinate -> (scalar, y-coordinate)
+    // x-coordinate -> (scalar, y-coordinate). Only used by the verifier, on public
+    // points, so the ordering need not be constant time.
     other: BTreeMap<C::Base, (C::Scalar, C::Base)>,
 }
 
@@ -166,7 +167,7 @@ impl<'a, C: CurveAffine> MSM<C> for MSMIPA<'a, C> {
 
         assert_eq!(scalars.len(), len);
 
-        best_multiexp_cpu(&scalars, &bases)
+        best_multiexp(&scalars, &bases, Bases::Other)
     }
 
     fn bases(&self) -> Vec<C::CurveExt> {
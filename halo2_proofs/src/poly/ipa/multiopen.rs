@@ -4,14 +4,21 @@
 //! [halo]: https://eprint.iacr.org/2019/1021
 
 use super::*;
-use crate::{poly::query::Query, transcript::ChallengeScalar};
+use crate::{
+    poly::{query::Query, Error},
+    transcript::ChallengeScalar,
+};
 use ff::Field;
+use maybe_rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rustc_hash::FxHashMap as HashMap;
 use std::collections::{BTreeMap, BTreeSet};
 
 mod prover;
 mod verifier;
 
 pub use prover::ProverIPA;
+#[cfg(feature = "multiopen-debug")]
+pub use verifier::PointSetEvaluations;
 pub use verifier::VerifierIPA;
 
 #[derive(Clone, Copy, Debug)]
@@ -59,37 +66,55 @@ type IntermediateSets<F, Q> = (
     Vec<Vec<F>>,
 );
 
-fn construct_intermediate_sets<F: Field + Ord, I, Q: Query<F>>(queries: I) -> IntermediateSets<F, Q>
+// Points are ordered with `Ord`, which is not constant time; see `Query::get_point`
+// for why this is safe.
+//
+// Returns `Error::OpeningError` if two queries have the same polynomial index but
+// different commitments.
+fn construct_intermediate_sets<F: Field + Ord, I, Q: Query<F>>(
+    queries: I,
+) -> Result<IntermediateSets<F, Q>, Error>
 where
     I: IntoIterator<Item = Q> + Clone,
 {
+    let queries: Vec<Q> = queries.into_iter().collect();
+
     // Construct sets of unique commitments and corresponding information about
     // their queries.
     let mut commitment_map: Vec<CommitmentData<Q::Eval, Q::Commitment>> = vec![];
+    // Also construct mapping from the index of a queried polynomial to the index
+    // of its commitment in commitment_map.
+    let mut poly_map: HashMap<usize, usize> = HashMap::default();
 
     // Also construct mapping from a unique point to a point_index. This defines
     // an ordering on the points.
     let mut point_index_map = BTreeMap::new();
 
     // Iterate over all of the queries, computing the ordering of the points
-    // while also creating new commitment data.
-    for query in queries.clone() {
-        let num_points = point_index_map.len();
-        let point_idx = point_index_map
-            .entry(query.get_point())
-            .or_insert(num_points);
-
-        if let Some(pos) = commitment_map
-            .iter()
-            .position(|comm| comm.commitment == query.get_commitment())
-        {
-            commitment_map[pos].point_indices.push(*point_idx);
-        } else {
-            let mut tmp = CommitmentData::new(query.get_commitment());
-            tmp.point_indices.push(*point_idx);
-            commitment_map.push(tmp);
-        }
-    }
+    // while also creating new commitment data. Record the commitment and point
+    // index of each query.
+    let query_indices: Vec<(usize, usize)> = queries
+        .iter()
+        .map(|query| {
+            let num_points = point_index_map.len();
+            let point_idx = *point_index_map
+                .entry(query.get_point())
+                .or_insert(num_points);
+
+            let commitment_idx = *poly_map.entry(query.get_poly_index()).or_insert_with(|| {
+                commitment_map.push(CommitmentData::new(query.get_commitment()));
+                commitment_map.len() - 1
+            });
+            // Only the first commitment of each index is opened, so any other would go
+            // unchecked.
+            if commitment_map[commitment_idx].commitment != query.get_commitment() {
+                return Err(Error::OpeningError);
+            }
+            commitment_map[commitment_idx].point_indices.push(point_idx);
+
+            Ok((commitment_idx, point_idx))
+        })
+        .collect::<Result<_, _>>()?;
 
     // Also construct inverse mapping from point_index to the point
     let mut inverse_point_index_map = BTreeMap::new();
@@ -99,64 +124,36 @@ where
 
     // Construct map of unique ordered point_idx_sets to their set_idx
     let mut point_idx_sets = BTreeMap::new();
-    // Also construct mapping from commitment to point_idx_set
-    let mut commitment_set_map = Vec::new();
+    // Also construct mapping from commitment index to point_idx_set
+    let mut commitment_set_map = Vec::with_capacity(commitment_map.len());
 
-    for commitment_data in commitment_map.iter() {
-        let mut point_index_set = BTreeSet::new();
+    for commitment_data in commitment_map.iter_mut() {
         // Note that point_index_set is ordered, unlike point_indices
-        for &point_index in commitment_data.point_indices.iter() {
-            point_index_set.insert(point_index);
-        }
-
-        // Push point_index_set to CommitmentData for the relevant commitment
-        commitment_set_map.push((commitment_data.commitment, point_index_set.clone()));
+        let point_index_set: BTreeSet<usize> =
+            commitment_data.point_indices.iter().cloned().collect();
 
         let num_sets = point_idx_sets.len();
-        point_idx_sets.entry(point_index_set).or_insert(num_sets);
-    }
-
-    // Initialise empty evals vec for each unique commitment
-    for commitment_data in commitment_map.iter_mut() {
-        let len = commitment_data.point_indices.len();
-        commitment_data.evals = vec![Q::Eval::default(); len];
-    }
+        commitment_data.set_index = *point_idx_sets
+            .entry(point_index_set.clone())
+            .or_insert(num_sets);
 
-    // Populate set_index, evals and points for each commitment using point_idx_sets
-    for query in queries {
-        // The index of the point at which the commitment is queried
-        let point_index = point_index_map.get(&query.get_point()).unwrap();
+        // Initialise empty evals vec for each unique commitment
+        commitment_data.evals = vec![Q::Eval::default(); commitment_data.point_indices.len()];
 
-        // The point_index_set at which the commitment was queried
-        let mut point_index_set = BTreeSet::new();
-        for (commitment, point_idx_set) in commitment_set_map.iter() {
-            if query.get_commitment() == *commitment {
-                point_index_set = point_idx_set.clone();
-            }
-        }
-        assert!(!point_index_set.is_empty());
+        commitment_set_map.push(point_index_set.into_iter().collect::<Vec<_>>());
+    }
 
-        // The set_index of the point_index_set
-        let set_index = point_idx_sets.get(&point_index_set).unwrap();
-        for commitment_data in commitment_map.iter_mut() {
-            if query.get_commitment() == commitment_data.commitment {
-                commitment_data.set_index = *set_index;
-            }
-        }
-        let point_index_set: Vec<usize> = point_index_set.iter().cloned().collect();
+    // Evaluate the queries in parallel, as the prover computes each evaluation
+    // from the coefficients of the polynomial.
+    let evals: Vec<Q::Eval> = queries.par_iter().map(|query| query.get_eval()).collect();
 
+    // Populate evals for each commitment using the ordering of its point_index_set
+    for ((commitment_idx, point_idx), eval) in query_indices.into_iter().zip(evals) {
         // The offset of the point_index in the point_index_set
-        let point_index_in_set = point_index_set
-            .iter()
-            .position(|i| i == point_index)
+        let point_index_in_set = commitment_set_map[commitment_idx]
+            .binary_search(&point_idx)
             .unwrap();
-
-        for commitment_data in commitment_map.iter_mut() {
-            if query.get_commitment() == commitment_data.commitment {
-                // Insert the eval using the ordering of the point_index_set
-                commitment_data.evals[point_index_in_set] = query.get_eval();
-            }
-        }
+        commitment_map[commitment_idx].evals[point_index_in_set] = eval;
     }
 
     // Get actual points in each point set
@@ -168,5 +165,5 @@ where
         }
     }
 
-    (commitment_map, point_sets)
+    Ok((commitment_map, point_sets))
 }
@@ -40,7 +40,12 @@ impl<'params, C: CurveAffine> Prover<'params, IPACommitmentScheme<C>> for Prover
         let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar();
         let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar();
 
-        let (poly_map, point_sets) = construct_intermediate_sets(queries);
+        let (poly_map, point_sets) = construct_intermediate_sets(queries).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "queries with the same polynomial index open different polynomials",
+            )
+        })?;
 
         // Collapse openings at same point sets together into single openings using
         // x_1 challenge.
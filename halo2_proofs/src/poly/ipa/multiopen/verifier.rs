@@ -34,8 +34,130 @@ impl<'params, C: CurveAffine> Verifier<'params, IPACommitmentScheme<C>>
         &self,
         transcript: &mut T,
         queries: I,
-        mut msm: MSMIPA<'params, C>,
+        msm: MSMIPA<'params, C>,
     ) -> Result<Self::Guard, Error>
+    where
+        'params: 'com,
+        I: IntoIterator<Item = VerifierQuery<'com, C, MSMIPA<'params, C>>> + Clone,
+    {
+        self.verify_proof_with(transcript, queries, msm, &mut ())
+    }
+}
+
+/// Receives the intermediate values of the multiopen verifier, for debugging.
+trait Recorder<C: CurveAffine> {
+    /// Records that `commitment` is opened at the point set `set_index`. MSMs are
+    /// recorded as `None`.
+    fn commitment(&mut self, _set_index: usize, _commitment: Option<C>) {}
+
+    /// Records the evaluations at the point set `set_index`, compressed with `x_1`,
+    /// their interpolation `r_eval` at `x_3`, and the evaluation `q_eval` of the
+    /// compressed polynomial at `x_3` provided by the prover.
+    fn point_set(
+        &mut self,
+        _set_index: usize,
+        _points: &[C::Scalar],
+        _evals: &[C::Scalar],
+        _r_eval: C::Scalar,
+        _q_eval: C::Scalar,
+    ) {
+    }
+}
+
+impl<C: CurveAffine> Recorder<C> for () {}
+
+/// The values reconstructed by the multiopen verifier for one point set.
+///
+/// For each set of points, the verifier compresses the claimed evaluations of the
+/// commitments opened at these points with powers of `x_1`, and the prover provides
+/// the evaluation `q_eval` at `x_3` of the correspondingly compressed polynomial `q`.
+/// The quotient `(q_eval - r_eval) / \prod (x_3 - point)`, where `r_eval` is the
+/// interpolation of the compressed evaluations at `x_3`, is then checked by the final
+/// opening. If that opening fails, comparing these values with the ones of the prover
+/// shows which set is inconsistent.
+#[cfg(feature = "multiopen-debug")]
+#[derive(Clone, Debug)]
+pub struct PointSetEvaluations<C: CurveAffine> {
+    /// The points of the set.
+    pub points: Vec<C::Scalar>,
+    /// The commitments opened at the points of the set, in query order. Commitments
+    /// given as MSMs are `None`.
+    pub commitments: Vec<Option<C>>,
+    /// The evaluations at each point, compressed with powers of `x_1`.
+    pub evals: Vec<C::Scalar>,
+    /// The interpolation of `evals` at `x_3`, expected from the claimed evaluations.
+    pub r_eval: C::Scalar,
+    /// The evaluation of the compressed polynomial at `x_3`, provided by the prover.
+    pub q_eval: C::Scalar,
+}
+
+#[cfg(feature = "multiopen-debug")]
+impl<C: CurveAffine> Recorder<C> for Vec<PointSetEvaluations<C>> {
+    fn commitment(&mut self, set_index: usize, commitment: Option<C>) {
+        if self.len() <= set_index {
+            self.resize_with(set_index + 1, || PointSetEvaluations {
+                points: vec![],
+                commitments: vec![],
+                evals: vec![],
+                r_eval: C::Scalar::ZERO,
+                q_eval: C::Scalar::ZERO,
+            });
+        }
+        self[set_index].commitments.push(commitment);
+    }
+
+    fn point_set(
+        &mut self,
+        set_index: usize,
+        points: &[C::Scalar],
+        evals: &[C::Scalar],
+        r_eval: C::Scalar,
+        q_eval: C::Scalar,
+    ) {
+        let set = &mut self[set_index];
+        set.points = points.to_vec();
+        set.evals = evals.to_vec();
+        set.r_eval = r_eval;
+        set.q_eval = q_eval;
+    }
+}
+
+impl<'params, C: CurveAffine> VerifierIPA<'params, C> {
+    /// Verifies a multi-open proof like [`Verifier::verify_proof`], and also returns
+    /// the values reconstructed for each point set, to find which set is inconsistent
+    /// when verification fails.
+    ///
+    /// The values are those computed before the failure, if any. The final opening is
+    /// only checked when the returned guard is used.
+    ///
+    /// This is only available for IPA: the KZG verifiers have no verbose mode.
+    #[cfg(feature = "multiopen-debug")]
+    #[allow(clippy::type_complexity)]
+    pub fn verify_proof_verbose<'com, E: EncodedChallenge<C>, T: TranscriptRead<C, E>, I>(
+        &self,
+        transcript: &mut T,
+        queries: I,
+        msm: MSMIPA<'params, C>,
+    ) -> (
+        Result<GuardIPA<'params, C>, Error>,
+        Vec<PointSetEvaluations<C>>,
+    )
+    where
+        'params: 'com,
+        I: IntoIterator<Item = VerifierQuery<'com, C, MSMIPA<'params, C>>> + Clone,
+    {
+        let mut point_sets = vec![];
+        let result = self.verify_proof_with(transcript, queries, msm, &mut point_sets);
+        (result, point_sets)
+    }
+
+    fn verify_proof_with<'com, E: EncodedChallenge<C>, T: TranscriptRead<C, E>, I>(
+        &self,
+        transcript: &mut T,
+        queries: I,
+        mut msm: MSMIPA<'params, C>,
+        recorder: &mut impl Recorder<C>,
+    ) -> Result<GuardIPA<'params, C>, Error>
     where
         'params: 'com,
         I: IntoIterator<Item = VerifierQuery<'com, C, MSMIPA<'params, C>>> + Clone,
@@ -47,7 +169,14 @@ impl<'params, C: CurveAffine> Verifier<'params, IPACommitmentScheme<C>>
         // polynomial terms linearly independent.
         let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar();
 
-        let (commitment_map, point_sets) = construct_intermediate_sets(queries);
+        let (commitment_map, point_sets) = construct_intermediate_sets(queries)?;
+        for commitment_data in commitment_map.iter() {
+            let commitment = match commitment_data.commitment {
+                CommitmentReference::Commitment(c) => Some(*c),
+                CommitmentReference::MSM(_) => None,
+            };
+            recorder.commitment(commitment_data.set_index, commitment);
+        }
 
         // Compress the commitments and expected evaluations at x together.
         // using the challenge x_1
@@ -115,11 +244,13 @@ impl<'params, C: CurveAffine> Verifier<'params, IPACommitmentScheme<C>>
             .iter()
             .zip(q_eval_sets.iter())
             .zip(u.iter())
+            .enumerate()
             .fold(
                 C::Scalar::ZERO,
-                |msm_eval, ((points, evals), proof_eval)| {
+                |msm_eval, (set_index, ((points, evals), proof_eval))| {
                     let r_poly = lagrange_interpolate(points, evals);
                     let r_eval = eval_polynomial(&r_poly, *x_3);
+                    recorder.point_set(set_index, points, evals, r_eval, *proof_eval);
                     let eval = points.iter().fold(*proof_eval - &r_eval, |eval, point| {
                         eval * &(*x_3 - point).invert().unwrap()
                     });
@@ -2,7 +2,7 @@ use super::commitment::{IPACommitmentScheme, ParamsIPA};
 use super::msm::MSMIPA;
 use super::multiopen::VerifierIPA;
 use crate::{
-    arithmetic::best_multiexp_cpu,
+    arithmetic::{best_multiexp, Bases},
     plonk::Error,
     poly::{
         commitment::MSM,
@@ -37,6 +37,10 @@ pub struct Accumulator<C: CurveAffine> {
 /// Define accumulator type as `MSMIPA`
 impl<'params, C: CurveAffine> Guard<IPACommitmentScheme<C>> for GuardIPA<'params, C> {
     type MSMAccumulator = MSMIPA<'params, C>;
+
+    fn into_msm_accumulator(self) -> MSMIPA<'params, C> {
+        self.use_challenges()
+    }
 }
 
 /// IPA specific operations
@@ -67,7 +71,7 @@ impl<'params, C: CurveAffine> GuardIPA<'params, C> {
     pub fn compute_g(&self) -> C {
         let s = compute_s(&self.u, C::Scalar::ONE);
 
-        best_multiexp_cpu(&s, &self.msm.params.g).to_affine()
+        best_multiexp(&s, &self.msm.params.g, Bases::Other).to_affine()
     }
 }
 
@@ -77,6 +81,14 @@ pub struct AccumulatorStrategy<'params, C: CurveAffine> {
     msm: MSMIPA<'params, C>,
 }
 
+impl<'params, C: CurveAffine> AccumulatorStrategy<'params, C> {
+    /// Returns the accumulated checks of the batch without evaluating them, so
+    /// that they can be merged with other checks.
+    pub fn into_msm_accumulator(self) -> MSMIPA<'params, C> {
+        self.msm
+    }
+}
+
 impl<'params, C: CurveAffine>
     VerificationStrategy<'params, IPACommitmentScheme<C>, VerifierIPA<'params, C>>
     for AccumulatorStrategy<'params, C>
@@ -1,14 +1,15 @@
-use crate::arithmetic::{best_multiexp_cpu, g_to_lagrange, parallelize};
+use crate::arithmetic::msm::{FixedBaseTable, PrecomputeConfig};
+use crate::arithmetic::{best_multiexp, g_to_lagrange, parallelize, Bases};
 
-#[cfg(feature = "icicle_gpu")]
-use crate::arithmetic::best_multiexp_gpu;
 #[cfg(feature = "icicle_gpu")]
 use crate::icicle;
 #[cfg(feature = "icicle_gpu")]
 use std::env;
 
 use crate::helpers::SerdeCurveAffine;
-use crate::poly::commitment::{Blind, CommitmentScheme, Params, ParamsProver, ParamsVerifier};
+use crate::poly::commitment::{
+    Blind, CommitmentScheme, Params, ParamsFingerprint, ParamsProver, ParamsVerifier,
+};
 use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
 use crate::SerdeFormat;
 
@@ -33,6 +34,11 @@ pub struct ParamsKZG<E: Engine> {
     pub(crate) g_lagrange: Vec<E::G1Affine>,
     pub(crate) g2: E::G2Affine,
     pub(crate) s_g2: E::G2Affine,
+    pub(crate) g_table: Option<FixedBaseTable<E::G1Affine>>,
+    pub(crate) g_lagrange_table: Option<FixedBaseTable<E::G1Affine>>,
+    // The fingerprint of these parameters, once their bases are loaded on the GPU.
+    #[cfg(feature = "icicle_gpu")]
+    pub(crate) gpu_fingerprint: Option<[u8; 32]>,
 }
 
 /// Umbrella commitment scheme construction for all KZG variants
@@ -67,6 +73,42 @@ where
     E::G1Affine: SerdeCurveAffine,
     E::G1: CurveExt<AffineExt = E::G1Affine>,
 {
+    /// Computes [`Params::fingerprint`], which only needs the bounds of this impl.
+    fn compute_fingerprint(&self) -> [u8; 32] {
+        ParamsFingerprint::new::<E::G1Affine>(self.k)
+            .generators(b"g", &self.g)
+            .generators(b"g_lagrange", &self.g_lagrange)
+            .generators(b"g2", &[self.g2, self.s_g2])
+            .finalize()
+    }
+
+    /// Loads the bases of these parameters on the GPU if `ENABLE_ICICLE_GPU` is set,
+    /// unless they are already loaded.
+    #[cfg(feature = "icicle_gpu")]
+    fn init_gpu(&mut self) {
+        self.gpu_fingerprint = None;
+        if env::var("ENABLE_ICICLE_GPU").is_ok() {
+            let fingerprint = self.compute_fingerprint();
+            icicle::init_gpu::<E::G1Affine>(fingerprint, &self.g, &self.g_lagrange);
+            self.gpu_fingerprint = Some(fingerprint);
+        }
+    }
+
+    /// Describes a prefix of `g`, or of `g_lagrange` if `lagrange` is set, to the MSM
+    /// backend.
+    fn bases(&self, lagrange: bool) -> Bases {
+        #[cfg(feature = "icicle_gpu")]
+        let fingerprint = self.gpu_fingerprint;
+        #[cfg(not(feature = "icicle_gpu"))]
+        let fingerprint = None;
+
+        if lagrange {
+            Bases::GLagrange { fingerprint }
+        } else {
+            Bases::G { fingerprint }
+        }
+    }
+
     /// Initializes parameters for the curve, draws toxic secret from given rng.
     /// MUST NOT be used in production.
     pub fn setup<R: RngCore>(k: u32, rng: R) -> Self {
@@ -125,22 +167,30 @@ where
             g_lagrange
         };
 
-        #[cfg(feature = "icicle_gpu")]
-        if env::var("ENABLE_ICICLE_GPU").is_ok() {
-            icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange);
-        }
-
         let g2 = <E::G2Affine as PrimeCurveAffine>::generator();
         let s_g2 = (g2 * s).into();
 
-        Self {
+        let params = Self {
             k,
             n,
             g,
             g_lagrange,
             g2,
             s_g2,
-        }
+            g_table: None,
+            g_lagrange_table: None,
+            #[cfg(feature = "icicle_gpu")]
+            gpu_fingerprint: None,
+        };
+
+        #[cfg(feature = "icicle_gpu")]
+        let params = {
+            let mut params = params;
+            params.init_gpu();
+            params
+        };
+
+        params
     }
 
     /// Initializes parameters for the curve through existing parameters
@@ -164,19 +214,53 @@ where
             None => g_to_lagrange(g.iter().map(PrimeCurveAffine::to_curve).collect(), k),
         };
 
-        #[cfg(feature = "icicle_gpu")]
-        if env::var("ENABLE_ICICLE_GPU").is_ok() {
-            icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange);
-        }
-
-        Self {
+        let params = Self {
             k,
             n: 1 << k,
             g_lagrange,
             g,
             g2,
             s_g2,
+            g_table: None,
+            g_lagrange_table: None,
+            #[cfg(feature = "icicle_gpu")]
+            gpu_fingerprint: None,
+        };
+
+        #[cfg(feature = "icicle_gpu")]
+        let params = {
+            let mut params = params;
+            params.init_gpu();
+            params
+        };
+
+        params
+    }
+
+    /// Precomputes tables of multiples of `g` and `g_lagrange`, which speed up
+    /// [`ParamsProver::commit`] and [`Params::commit_lagrange`] when the parameters
+    /// are used for many commitments, at the cost of memory.
+    ///
+    /// The memory budget of `config` is shared by the two tables. Returns `false`,
+    /// and keeps no tables, if they do not fit in it.
+    pub fn precompute(&mut self, config: PrecomputeConfig) -> bool {
+        let config = PrecomputeConfig {
+            memory_budget: config.memory_budget.map(|budget| budget / 2),
+            ..config
+        };
+        self.g_table = FixedBaseTable::new(&self.g, config);
+        self.g_lagrange_table = FixedBaseTable::new(&self.g_lagrange, config);
+        if self.g_table.is_none() || self.g_lagrange_table.is_none() {
+            self.clear_precomputed();
+            return false;
         }
+        true
+    }
+
+    /// Drops the tables computed by [`Self::precompute`].
+    pub fn clear_precomputed(&mut self) {
+        self.g_table = None;
+        self.g_lagrange_table = None;
     }
 
     /// Returns gernerator on G2
@@ -279,22 +363,30 @@ where
             }
         };
 
-        #[cfg(feature = "icicle_gpu")]
-        if env::var("ENABLE_ICICLE_GPU").is_ok() {
-            icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange);
-        }
-
         let g2 = E::G2Affine::read(reader, format)?;
         let s_g2 = E::G2Affine::read(reader, format)?;
 
-        Ok(Self {
+        let params = Self {
             k,
             n: n as u64,
             g,
             g_lagrange,
             g2,
             s_g2,
-        })
+            g_table: None,
+            g_lagrange_table: None,
+            #[cfg(feature = "icicle_gpu")]
+            gpu_fingerprint: None,
+        };
+
+        #[cfg(feature = "icicle_gpu")]
+        let params = {
+            let mut params = params;
+            params.init_gpu();
+            params
+        };
+
+        Ok(params)
     }
 }
 
@@ -327,6 +419,20 @@ where
 
         self.g.truncate(self.n as usize);
         self.g_lagrange = g_to_lagrange(self.g.iter().map(|g| g.to_curve()).collect(), k);
+
+        if let Some(g_table) = self.g_table.as_mut() {
+            g_table.truncate(self.n as usize);
+            let config = PrecomputeConfig {
+                window_size: Some(g_table.window_size()),
+                memory_budget: None,
+            };
+            self.g_lagrange_table = FixedBaseTable::new(&self.g_lagrange, config);
+        }
+
+        // MSMs still running over the bases loaded for the larger parameters keep
+        // them alive.
+        #[cfg(feature = "icicle_gpu")]
+        self.init_gpu();
     }
 
     fn empty_msm(&'params self) -> MSMKZG<E> {
@@ -340,15 +446,10 @@ where
         let size = scalars.len();
         assert!(bases.len() >= size);
 
-        #[cfg(feature = "icicle_gpu")]
-        if env::var("ENABLE_ICICLE_GPU").is_ok() && !icicle::should_use_cpu_msm(size) {
-            best_multiexp_gpu::<E::G1Affine>(&scalars, true)
-        } else {
-            best_multiexp_cpu(&scalars, &bases[0..size])
+        match &self.g_lagrange_table {
+            Some(table) => table.multiexp(&scalars),
+            None => best_multiexp(&scalars, &bases[0..size], self.bases(true)),
         }
-
-        #[cfg(not(feature = "icicle_gpu"))]
-        best_multiexp_cpu(&scalars, &bases[0..size])
     }
 
     /// Writes params to a buffer.
@@ -360,6 +461,10 @@ where
     fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
         Self::read_custom(reader, SerdeFormat::RawBytes)
     }
+
+    fn fingerprint(&self) -> [u8; 32] {
+        self.compute_fingerprint()
+    }
 }
 
 impl<'params, E: Engine + Debug> ParamsVerifier<'params, E::G1Affine> for ParamsKZG<E>
@@ -393,15 +498,10 @@ where
         let size = scalars.len();
         assert!(bases.len() >= size);
 
-        #[cfg(feature = "icicle_gpu")]
-        if env::var("ENABLE_ICICLE_GPU").is_ok() && !icicle::should_use_cpu_msm(size) {
-            best_multiexp_gpu::<E::G1Affine>(&scalars, false)
-        } else {
-            best_multiexp_cpu(&scalars, &bases[0..size])
+        match &self.g_table {
+            Some(table) => table.multiexp(&scalars),
+            None => best_multiexp(&scalars, &bases[0..size], self.bases(false)),
         }
-
-        #[cfg(not(feature = "icicle_gpu"))]
-        best_multiexp_cpu(&scalars, &bases[0..size])
     }
 
     fn get_g(&self) -> &[E::G1Affine] {
@@ -441,6 +541,55 @@ mod test {
         assert_eq!(params.commit(&b, alpha), params.commit_lagrange(&a, alpha));
     }
 
+    #[test]
+    fn test_precomputed_commit() {
+        const K: u32 = 5;
+
+        use rand_core::OsRng;
+
+        use crate::arithmetic::msm::PrecomputeConfig;
+        use crate::poly::EvaluationDomain;
+        use halo2curves::bn256::{Bn256, Fr};
+
+        let params = ParamsKZG::<Bn256>::new(K);
+        let domain = EvaluationDomain::new(1, K);
+        let a = domain.lagrange_from_vec((0..1 << K).map(|_| Fr::random(OsRng)).collect());
+        let b = domain.lagrange_to_coeff(a.clone());
+
+        let mut precomputed = params.clone();
+        assert!(precomputed.precompute(PrecomputeConfig::default()));
+        assert_eq!(
+            precomputed.commit(&b, Blind::default()),
+            params.commit(&b, Blind::default())
+        );
+        assert_eq!(
+            precomputed.commit_lagrange(&a, Blind::default()),
+            params.commit_lagrange(&a, Blind::default())
+        );
+
+        // Downsizing keeps the tables consistent with the new bases.
+        let mut small = params.clone();
+        small.downsize(K - 1);
+        precomputed.downsize(K - 1);
+        let domain = EvaluationDomain::new(1, K - 1);
+        let a = domain.lagrange_from_vec((0..1 << (K - 1)).map(|_| Fr::random(OsRng)).collect());
+        let b = domain.lagrange_to_coeff(a.clone());
+        assert_eq!(
+            precomputed.commit(&b, Blind::default()),
+            small.commit(&b, Blind::default())
+        );
+        assert_eq!(
+            precomputed.commit_lagrange(&a, Blind::default()),
+            small.commit_lagrange(&a, Blind::default())
+        );
+
+        let config = PrecomputeConfig {
+            window_size: None,
+            memory_budget: Some(1),
+        };
+        assert!(!precomputed.precompute(config));
+    }
+
     #[test]
     fn test_parameter_serialisation_roundtrip() {
         const K: u32 = 4;
@@ -463,4 +612,28 @@ mod test {
         assert_eq!(params0.g2, params1.g2);
         assert_eq!(params0.s_g2, params1.s_g2);
     }
+
+    #[test]
+    fn test_fingerprint() {
+        // Large enough for the generators to span several chunks.
+        const K: u32 = 13;
+
+        use crate::halo2curves::bn256::Bn256;
+        use rand_core::OsRng;
+
+        let params0 = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let mut data = vec![];
+        params0.write(&mut data).unwrap();
+        let params1: ParamsKZG<Bn256> = Params::read::<_>(&mut &data[..]).unwrap();
+        assert_eq!(params0.fingerprint(), params1.fingerprint());
+
+        // Different toxic waste gives different parameters.
+        let params2 = ParamsKZG::<Bn256>::setup(K, OsRng);
+        assert_ne!(params0.fingerprint(), params2.fingerprint());
+
+        // Downsizing changes the parameters.
+        let mut params3 = params0.clone();
+        params3.downsize(K - 1);
+        assert_ne!(params0.fingerprint(), params3.fingerprint());
+    }
 }
@@ -1,11 +1,15 @@
 mod prover;
 mod verifier;
 
-use crate::{poly::query::Query, transcript::ChallengeScalar};
+use crate::{
+    poly::{query::Query, Error},
+    transcript::ChallengeScalar,
+};
 use ff::Field;
-use maybe_rayon::{iter::ParallelIterator, prelude::IntoParallelIterator};
+use maybe_rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 pub use prover::ProverSHPLONK;
-use std::collections::BTreeSet;
+use rustc_hash::FxHashMap as HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 pub use verifier::VerifierSHPLONK;
 
 #[derive(Clone, Copy, Debug)]
@@ -45,49 +49,50 @@ struct IntermediateSets<F: Field, Q: Query<F>> {
     super_point_set: BTreeSet<F>,
 }
 
+// Points are ordered with `Ord`, which is not constant time; see `Query::get_point`
+// for why this is safe.
+//
+// Returns `Error::OpeningError` if two queries have the same polynomial index but
+// different commitments.
 fn construct_intermediate_sets<F: Field + Ord, I, Q: Query<F, Eval = F>>(
     queries: I,
-) -> IntermediateSets<F, Q>
+) -> Result<IntermediateSets<F, Q>, Error>
 where
     I: IntoIterator<Item = Q> + Clone,
 {
     let queries = queries.into_iter().collect::<Vec<_>>();
 
-    // Find evaluation of a commitment at a rotation
-    let get_eval = |commitment: Q::Commitment, rotation: F| -> F {
-        queries
-            .iter()
-            .find(|query| query.get_commitment() == commitment && query.get_point() == rotation)
-            .unwrap()
-            .get_eval()
-    };
+    // Evaluate the queries in parallel, as the prover computes each evaluation
+    // from the coefficients of the polynomial.
+    let evals: Vec<F> = queries.par_iter().map(|query| query.get_eval()).collect();
 
     // All points that appear in queries
     let mut super_point_set = BTreeSet::new();
 
-    // Collect rotation sets for each commitment
+    // Collect rotation sets for each polynomial, keyed by its index, along with the
+    // evaluation of its first query at each rotation
     // Example elements in the vector:
     // (C_0, {r_5}),
     // (C_1, {r_1, r_2, r_3}),
     // (C_2, {r_2, r_3, r_4}),
     // (C_3, {r_2, r_3, r_4}),
     // ...
-    let mut commitment_rotation_set_map: Vec<(Q::Commitment, BTreeSet<F>)> = vec![];
-    for query in queries.iter() {
+    let mut poly_map: HashMap<usize, usize> = HashMap::default();
+    let mut commitment_rotation_set_map: Vec<(Q::Commitment, BTreeMap<F, F>)> = vec![];
+    for (query, eval) in queries.iter().zip(evals) {
         let rotation = query.get_point();
         super_point_set.insert(rotation);
-        if let Some(commitment_rotation_set) = commitment_rotation_set_map
-            .iter_mut()
-            .find(|(commitment, _)| *commitment == query.get_commitment())
-        {
-            let (_, rotation_set) = commitment_rotation_set;
-            rotation_set.insert(rotation);
-        } else {
-            commitment_rotation_set_map.push((
-                query.get_commitment(),
-                BTreeSet::from_iter(std::iter::once(rotation)),
-            ));
-        };
+        let commitment_index = *poly_map.entry(query.get_poly_index()).or_insert_with(|| {
+            commitment_rotation_set_map.push((query.get_commitment(), BTreeMap::new()));
+            commitment_rotation_set_map.len() - 1
+        });
+        let (commitment, rotation_set) = &mut commitment_rotation_set_map[commitment_index];
+        // Only the first commitment of each index is opened, so any other would go
+        // unchecked.
+        if *commitment != query.get_commitment() {
+            return Err(Error::OpeningError);
+        }
+        rotation_set.entry(rotation).or_insert(eval);
     }
 
     // Flatten rotation sets and collect commitments that opens against each commitment set
@@ -97,46 +102,28 @@ where
     // {r_2, r_3, r_4} : [C_2, C_3],
     // ...
     // NOTE: we want to make the order of the collection of rotation sets independent of the opening points, to ease the verifier computation
-    let mut rotation_set_commitment_map: Vec<(BTreeSet<F>, Vec<Q::Commitment>)> = vec![];
+    let mut rotation_set_index_map: BTreeMap<Vec<F>, usize> = BTreeMap::new();
+    let mut rotation_sets: Vec<RotationSet<F, Q::Commitment>> = vec![];
     for (commitment, rotation_set) in commitment_rotation_set_map.into_iter() {
-        if let Some(rotation_set_commitment) = rotation_set_commitment_map
-            .iter_mut()
-            .find(|(set, _)| set == &rotation_set)
-        {
-            let (_, commitments) = rotation_set_commitment;
-            commitments.push(commitment);
-        } else {
-            rotation_set_commitment_map.push((rotation_set, vec![commitment]));
-        };
+        let (points, evals): (Vec<F>, Vec<F>) = rotation_set.into_iter().unzip();
+        let set_index = *rotation_set_index_map
+            .entry(points.clone())
+            .or_insert_with(|| {
+                rotation_sets.push(RotationSet {
+                    commitments: vec![],
+                    points,
+                });
+                rotation_sets.len() - 1
+            });
+        rotation_sets[set_index]
+            .commitments
+            .push(Commitment((commitment, evals)));
     }
 
-    let rotation_sets = rotation_set_commitment_map
-        .into_par_iter()
-        .map(|(rotations, commitments)| {
-            let rotations_vec = rotations.iter().collect::<Vec<_>>();
-            let commitments: Vec<Commitment<F, Q::Commitment>> = commitments
-                .into_par_iter()
-                .map(|commitment| {
-                    let evals: Vec<F> = rotations_vec
-                        .as_slice()
-                        .into_par_iter()
-                        .map(|&&rotation| get_eval(commitment, rotation))
-                        .collect();
-                    Commitment((commitment, evals))
-                })
-                .collect();
-
-            RotationSet {
-                commitments,
-                points: rotations.into_iter().collect(),
-            }
-        })
-        .collect::<Vec<RotationSet<_, _>>>();
-
-    IntermediateSets {
+    Ok(IntermediateSets {
         rotation_sets,
         super_point_set,
-    }
+    })
 }
 
 #[cfg(test)]
@@ -169,6 +156,15 @@ mod proptests {
         fn get_commitment(&self) -> Self::Commitment {
             self.commitment
         }
+
+        fn get_poly_index(&self) -> usize {
+            self.commitment
+        }
+
+        fn offset_poly_index(mut self, offset: usize) -> Self {
+            self.commitment += offset;
+            self
+        }
     }
 
     prop_compose! {
@@ -229,14 +225,14 @@ mod proptests {
         fn test_intermediate_sets(
             (queries_1, queries_2) in compare_queries(8, 8, 16)
         ) {
-            let IntermediateSets { rotation_sets, .. } = construct_intermediate_sets(queries_1);
+            let IntermediateSets { rotation_sets, .. } = construct_intermediate_sets(queries_1).unwrap();
             let commitment_sets = rotation_sets.iter().map(|data|
                 data.commitments.iter().map(Commitment::get).collect::<Vec<_>>()
             ).collect::<Vec<_>>();
 
             // It shouldn't matter what the point or eval values are; we should get
             // the same exact point set indices and point indices again.
-            let IntermediateSets { rotation_sets: new_rotation_sets, .. } = construct_intermediate_sets(queries_2);
+            let IntermediateSets { rotation_sets: new_rotation_sets, .. } = construct_intermediate_sets(queries_2).unwrap();
             let new_commitment_sets = new_rotation_sets.iter().map(|data|
                 data.commitments.iter().map(Commitment::get).collect::<Vec<_>>()
             ).collect::<Vec<_>>();
@@ -54,9 +54,9 @@ impl<'a, C: CurveAffine> Commitment<C::Scalar, PolynomialPointer<'a, C>> {
 }
 
 impl<'a, C: CurveAffine> CommitmentExtension<'a, C> {
-    fn linearisation_contribution(&self, u: C::Scalar) -> Polynomial<C::Scalar, Coeff> {
+    fn linearisation_contribution(&self, u: ChallengeU<C>) -> Polynomial<C::Scalar, Coeff> {
         let p_x = self.commitment.get().poly;
-        let r_eval = eval_polynomial(&self.low_degree_equivalent.values[..], u);
+        let r_eval = eval_polynomial(&self.low_degree_equivalent.values[..], *u);
         p_x - r_eval
     }
 
@@ -174,7 +174,12 @@ where
             }
         };
 
-        let intermediate_sets = construct_intermediate_sets(queries);
+        let intermediate_sets = construct_intermediate_sets(queries).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "queries with the same polynomial index open different polynomials",
+            )
+        })?;
         let (rotation_sets, super_point_set) = (
             intermediate_sets.rotation_sets,
             intermediate_sets.super_point_set,
@@ -232,7 +237,7 @@ where
                 .commitments
                 .as_slice()
                 .into_par_iter()
-                .map(|commitment| commitment.linearisation_contribution(*u))
+                .map(|commitment| commitment.linearisation_contribution(u))
                 .collect::<Vec<_>>();
 
             // define inner contributor polynomial as
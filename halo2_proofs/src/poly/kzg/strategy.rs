@@ -37,6 +37,10 @@ where
     E::G2Affine: SerdeCurveAffine,
 {
     type MSMAccumulator = DualMSM<'params, E>;
+
+    fn into_msm_accumulator(self) -> DualMSM<'params, E> {
+        self.msm_accumulator
+    }
 }
 
 /// KZG specific operations
@@ -76,6 +80,12 @@ where
     pub fn with(msm_accumulator: DualMSM<'params, E>) -> Self {
         AccumulatorStrategy { msm_accumulator }
     }
+
+    /// Returns the accumulated checks of the batch without evaluating them, so
+    /// that they can be merged with other checks.
+    pub fn into_msm_accumulator(self) -> DualMSM<'params, E> {
+        self.msm_accumulator
+    }
 }
 
 /// A verifier that checks a single proof
@@ -131,7 +141,7 @@ where
         // Guard is updated with new msm contributions
         let guard = f(self.msm_accumulator)?;
         Ok(Self {
-            msm_accumulator: guard.msm_accumulator,
+            msm_accumulator: guard.into_msm_accumulator(),
         })
     }
 
@@ -167,7 +177,7 @@ where
     ) -> Result<Self::Output, Error> {
         // Guard is updated with new msm contributions
         let guard = f(self.msm)?;
-        let msm = guard.msm_accumulator;
+        let msm = guard.into_msm_accumulator();
         if msm.check() {
             Ok(())
         } else {
@@ -7,12 +7,12 @@ mod test {
     use crate::poly::{
         commitment::{CommitmentScheme, Params, Prover, Verifier},
         query::{ProverQuery, VerifierQuery},
-        strategy::VerificationStrategy,
+        strategy::{Guard, VerificationStrategy},
         EvaluationDomain,
     };
     use crate::transcript::{
-        Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, Keccak256Read, Keccak256Write,
-        TranscriptReadBuffer, TranscriptWriterBuffer,
+        Blake2bRead, Blake2bWrite, Challenge255, ChallengeEvm, EncodedChallenge, Keccak256Read,
+        Keccak256Write, TranscriptReadBuffer, TranscriptWriterBuffer,
     };
     use ff::WithSmallOrderMulGroup;
     use group::Curve;
@@ -160,6 +160,278 @@ mod test {
         >(verifier_params, &proof[..], true);
     }
 
+    #[test]
+    fn test_roundtrip_shplonk_evm() {
+        use crate::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+        use crate::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+        use crate::poly::kzg::strategy::AccumulatorStrategy;
+        use halo2curves::bn256::Bn256;
+
+        const K: u32 = 4;
+
+        let params = ParamsKZG::<Bn256>::new(K);
+
+        let proof = create_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<_>,
+            _,
+            Keccak256Write<_, _, ChallengeEvm<_>>,
+        >(&params);
+
+        let verifier_params = params.verifier_params();
+
+        verify::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            Keccak256Read<_, _, ChallengeEvm<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, &proof[..], false);
+
+        verify::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            Keccak256Read<_, _, ChallengeEvm<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, &proof[..], true);
+    }
+
+    #[cfg(feature = "multiopen-debug")]
+    #[test]
+    fn test_verbose_ipa() {
+        use crate::poly::commitment::MSM;
+        use crate::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use crate::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+        use crate::transcript::{Transcript, TranscriptRead};
+        use halo2curves::pasta::EqAffine;
+
+        const K: u32 = 4;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+
+        let proof = create_proof::<
+            IPACommitmentScheme<EqAffine>,
+            ProverIPA<_>,
+            _,
+            Blake2bWrite<_, _, Challenge255<_>>,
+        >(&params);
+
+        let mut runs = vec![];
+        for should_fail in [false, true] {
+            let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+
+            let a = transcript.read_point().unwrap();
+            let b = transcript.read_point().unwrap();
+            let c = transcript.read_point().unwrap();
+
+            let x = transcript.squeeze_challenge().get_scalar();
+            let y = transcript.squeeze_challenge().get_scalar();
+
+            let avx = transcript.read_scalar().unwrap();
+            let bvx = transcript.read_scalar().unwrap();
+            let cvy = transcript.read_scalar().unwrap();
+
+            let queries = [
+                VerifierQuery::new_commitment(&a, 0, x, avx),
+                VerifierQuery::new_commitment(&b, 1, x, if should_fail { avx } else { bvx }),
+                VerifierQuery::new_commitment(&c, 2, y, cvy),
+            ];
+
+            let verifier = VerifierIPA::new(&params);
+            let (guard, point_sets) =
+                verifier.verify_proof_verbose(&mut transcript, queries, params.empty_msm());
+            assert_eq!(guard.unwrap().use_challenges().check(), !should_fail);
+
+            // a and b are opened at x, and c at y.
+            assert_eq!(point_sets.len(), 2);
+            let at_x = point_sets.iter().find(|set| set.points == [x]).unwrap();
+            let at_y = point_sets.iter().find(|set| set.points == [y]).unwrap();
+            assert_eq!(at_x.commitments, [Some(a), Some(b)]);
+            assert_eq!(at_y.commitments, [Some(c)]);
+            assert_eq!(at_y.evals, [cvy]);
+            assert_eq!(at_y.r_eval, cvy);
+
+            runs.push((at_x.clone(), at_y.clone()));
+        }
+
+        // Only the set with the wrong evaluation differs.
+        assert_ne!(runs[0].0.evals, runs[1].0.evals);
+        assert_eq!(runs[0].1.evals, runs[1].1.evals);
+        assert_eq!(runs[0].1.q_eval, runs[1].1.q_eval);
+    }
+
+    #[test]
+    fn test_merge_guards_ipa() {
+        use crate::poly::commitment::MSM;
+        use crate::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use crate::poly::ipa::msm::MSMIPA;
+        use crate::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+        use ff::Field;
+        use halo2curves::pasta::{EqAffine, Fp};
+
+        const K: u32 = 4;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let proofs = [(); 2].map(|_| {
+            create_proof::<
+                IPACommitmentScheme<EqAffine>,
+                ProverIPA<_>,
+                _,
+                Blake2bWrite<_, _, Challenge255<_>>,
+            >(&params)
+        });
+        let verifier_params = params.verifier_params();
+
+        // The checks of both proofs are merged and evaluated once.
+        let check = |should_fail: bool| {
+            let mut msm = MSMIPA::new(verifier_params);
+            for (i, proof) in proofs.iter().enumerate() {
+                let guard = verify_guard::<
+                    IPACommitmentScheme<EqAffine>,
+                    VerifierIPA<_>,
+                    _,
+                    Blake2bRead<_, _, Challenge255<_>>,
+                >(
+                    verifier_params,
+                    &proof[..],
+                    should_fail && i == 1,
+                    MSMIPA::new(verifier_params),
+                );
+                let mut accumulator = guard.into_msm_accumulator();
+                accumulator.scale(Fp::random(OsRng));
+                msm.add_msm(&accumulator);
+            }
+            msm.check()
+        };
+        assert!(check(false));
+        assert!(!check(true));
+    }
+
+    #[test]
+    fn test_merge_guards_shplonk() {
+        use crate::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+        use crate::poly::kzg::msm::DualMSM;
+        use crate::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+        use ff::Field;
+        use halo2curves::bn256::{Bn256, Fr};
+
+        const K: u32 = 4;
+
+        let params = ParamsKZG::<Bn256>::new(K);
+        let proofs = [(); 2].map(|_| {
+            create_proof::<_, ProverSHPLONK<_>, _, Blake2bWrite<_, _, Challenge255<_>>>(&params)
+        });
+        let verifier_params = params.verifier_params();
+
+        // The checks of both proofs are merged and evaluated once.
+        let check = |should_fail: bool| {
+            let mut msm = DualMSM::new(verifier_params);
+            for (i, proof) in proofs.iter().enumerate() {
+                let guard = verify_guard::<
+                    KZGCommitmentScheme<Bn256>,
+                    VerifierSHPLONK<_>,
+                    _,
+                    Blake2bRead<_, _, Challenge255<_>>,
+                >(
+                    verifier_params,
+                    &proof[..],
+                    should_fail && i == 1,
+                    DualMSM::new(verifier_params),
+                );
+                let mut accumulator = guard.into_msm_accumulator();
+                accumulator.scale(Fr::random(OsRng));
+                msm.add_msm(accumulator);
+            }
+            msm.check()
+        };
+        assert!(check(false));
+        assert!(!check(true));
+    }
+
+    #[test]
+    fn test_colliding_poly_indices() {
+        use crate::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use crate::poly::ipa::msm::MSMIPA;
+        use crate::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+        use crate::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+        use crate::poly::kzg::msm::DualMSM;
+        use crate::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+        use halo2curves::{bn256::Bn256, pasta::EqAffine};
+
+        const K: u32 = 4;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let proof = create_proof::<
+            IPACommitmentScheme<EqAffine>,
+            ProverIPA<_>,
+            _,
+            Blake2bWrite<_, _, Challenge255<_>>,
+        >(&params);
+        let verifier_params = params.verifier_params();
+        assert!(verify_colliding_indices::<
+            IPACommitmentScheme<EqAffine>,
+            VerifierIPA<_>,
+            _,
+            Blake2bRead<_, _, Challenge255<_>>,
+        >(
+            verifier_params, &proof[..], MSMIPA::new(verifier_params)
+        ));
+
+        let params = ParamsKZG::<Bn256>::new(K);
+        let proof =
+            create_proof::<_, ProverSHPLONK<_>, _, Blake2bWrite<_, _, Challenge255<_>>>(&params);
+        let verifier_params = params.verifier_params();
+        assert!(verify_colliding_indices::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            Blake2bRead<_, _, Challenge255<_>>,
+        >(
+            verifier_params, &proof[..], DualMSM::new(verifier_params)
+        ));
+    }
+
+    /// Verifies the proof with the query of `c` given the polynomial index of `b`,
+    /// returning whether the verifier rejected it.
+    fn verify_colliding_indices<
+        'a,
+        'params,
+        Scheme: CommitmentScheme,
+        V: Verifier<'params, Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptReadBuffer<&'a [u8], Scheme::Curve, E>,
+    >(
+        params: &'params Scheme::ParamsVerifier,
+        proof: &'a [u8],
+        msm: V::MSMAccumulator,
+    ) -> bool {
+        let verifier = V::new(params);
+
+        let mut transcript = T::init(proof);
+
+        let a = transcript.read_point().unwrap();
+        let b = transcript.read_point().unwrap();
+        let c = transcript.read_point().unwrap();
+
+        let x = transcript.squeeze_challenge();
+        let y = transcript.squeeze_challenge();
+
+        let avx = transcript.read_scalar().unwrap();
+        let bvx = transcript.read_scalar().unwrap();
+        let cvy = transcript.read_scalar().unwrap();
+
+        let queries = [
+            VerifierQuery::new_commitment(&a, 0, x.get_scalar(), avx),
+            VerifierQuery::new_commitment(&b, 1, x.get_scalar(), bvx),
+            VerifierQuery::new_commitment(&c, 1, y.get_scalar(), cvy),
+        ];
+
+        verifier
+            .verify_proof(&mut transcript, queries, msm)
+            .is_err()
+    }
+
     fn verify<
         'a,
         'params,
@@ -189,14 +461,44 @@ mod test {
         let cvy = transcript.read_scalar().unwrap();
 
         let valid_queries = std::iter::empty()
-            .chain(Some(VerifierQuery::new_commitment(&a, x.get_scalar(), avx)))
-            .chain(Some(VerifierQuery::new_commitment(&b, x.get_scalar(), bvx)))
-            .chain(Some(VerifierQuery::new_commitment(&c, y.get_scalar(), cvy)));
+            .chain(Some(VerifierQuery::new_commitment(
+                &a,
+                0,
+                x.get_scalar(),
+                avx,
+            )))
+            .chain(Some(VerifierQuery::new_commitment(
+                &b,
+                1,
+                x.get_scalar(),
+                bvx,
+            )))
+            .chain(Some(VerifierQuery::new_commitment(
+                &c,
+                2,
+                y.get_scalar(),
+                cvy,
+            )));
 
         let invalid_queries = std::iter::empty()
-            .chain(Some(VerifierQuery::new_commitment(&a, x.get_scalar(), avx)))
-            .chain(Some(VerifierQuery::new_commitment(&b, x.get_scalar(), avx)))
-            .chain(Some(VerifierQuery::new_commitment(&c, y.get_scalar(), cvy)));
+            .chain(Some(VerifierQuery::new_commitment(
+                &a,
+                0,
+                x.get_scalar(),
+                avx,
+            )))
+            .chain(Some(VerifierQuery::new_commitment(
+                &b,
+                1,
+                x.get_scalar(),
+                avx,
+            )))
+            .chain(Some(VerifierQuery::new_commitment(
+                &c,
+                2,
+                y.get_scalar(),
+                cvy,
+            )));
 
         let queries = if should_fail {
             invalid_queries.clone()
@@ -218,6 +520,50 @@ mod test {
         }
     }
 
+    fn verify_guard<
+        'a,
+        'params,
+        Scheme: CommitmentScheme,
+        V: Verifier<'params, Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptReadBuffer<&'a [u8], Scheme::Curve, E>,
+    >(
+        params: &'params Scheme::ParamsVerifier,
+        proof: &'a [u8],
+        should_fail: bool,
+        msm: V::MSMAccumulator,
+    ) -> V::Guard {
+        let verifier = V::new(params);
+
+        let mut transcript = T::init(proof);
+
+        let a = transcript.read_point().unwrap();
+        let b = transcript.read_point().unwrap();
+        let c = transcript.read_point().unwrap();
+
+        let x = transcript.squeeze_challenge();
+        let y = transcript.squeeze_challenge();
+
+        let avx = transcript.read_scalar().unwrap();
+        let bvx = transcript.read_scalar().unwrap();
+        let cvy = transcript.read_scalar().unwrap();
+
+        let queries = [
+            VerifierQuery::new_commitment(&a, 0, x.get_scalar(), avx),
+            VerifierQuery::new_commitment(
+                &b,
+                1,
+                x.get_scalar(),
+                if should_fail { avx } else { bvx },
+            ),
+            VerifierQuery::new_commitment(&c, 2, y.get_scalar(), cvy),
+        ];
+
+        verifier
+            .verify_proof(&mut transcript, queries, msm)
+            .unwrap()
+    }
+
     fn create_proof<
         'params,
         Scheme: CommitmentScheme,
@@ -272,16 +618,19 @@ mod test {
         let queries = [
             ProverQuery {
                 point: x.get_scalar(),
+                poly_index: 0,
                 poly: &ax,
                 blind,
             },
             ProverQuery {
                 point: x.get_scalar(),
+                poly_index: 1,
                 poly: &bx,
                 blind,
             },
             ProverQuery {
                 point: y.get_scalar(),
+                poly_index: 2,
                 poly: &cx,
                 blind,
             },
@@ -293,6 +642,6 @@ mod test {
             .create_proof(&mut OsRng, &mut transcript, queries)
             .unwrap();
 
-        transcript.finalize()
+        transcript.finalize().unwrap()
     }
 }
@@ -9,11 +9,65 @@ use halo2curves::CurveAffine;
 
 pub trait Query<F>: Sized + Clone + Send + Sync {
     type Commitment: PartialEq + Copy + Send + Sync;
-    type Eval: Clone + Default + Debug;
-
+    type Eval: Clone + Default + Debug + Send;
+
+    /// Returns the point at which the polynomial is queried.
+    ///
+    /// The multi-opening arguments group queries by point, ordering the points with
+    /// `Ord`, which is not constant time. This does not leak the witness: the points
+    /// are $x \omega^i$ for a challenge $x$ derived from the transcript, so the verifier
+    /// knows them too.
     fn get_point(&self) -> F;
     fn get_eval(&self) -> Self::Eval;
     fn get_commitment(&self) -> Self::Commitment;
+
+    /// Returns the index of the queried polynomial, which identifies it among the
+    /// polynomials being opened: queries of the same polynomial have the same index,
+    /// and queries of different polynomials have different indices.
+    ///
+    /// The multi-opening arguments group queries by this index rather than by the
+    /// identity of the referenced polynomial or commitment.
+    fn get_poly_index(&self) -> usize;
+
+    /// Returns the query with `offset` added to its polynomial index; see
+    /// [`QueryList`].
+    fn offset_poly_index(self, offset: usize) -> Self;
+}
+
+/// The queries of several groups, each of which numbers its polynomials from 0.
+///
+/// The polynomial indices of each group are offset past those of the groups pushed
+/// before it, so that the polynomials of different groups have different indices.
+#[derive(Debug)]
+pub(crate) struct QueryList<Q> {
+    queries: Vec<Q>,
+    num_polys: usize,
+}
+
+impl<Q> QueryList<Q> {
+    pub(crate) fn new() -> Self {
+        QueryList {
+            queries: vec![],
+            num_polys: 0,
+        }
+    }
+
+    /// Appends the queries of a group.
+    pub(crate) fn push<F>(&mut self, group: impl IntoIterator<Item = Q>)
+    where
+        Q: Query<F>,
+    {
+        let offset = self.num_polys;
+        for query in group {
+            let query = query.offset_poly_index(offset);
+            self.num_polys = self.num_polys.max(query.get_poly_index() + 1);
+            self.queries.push(query);
+        }
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<Q> {
+        self.queries
+    }
 }
 
 /// A polynomial query at a point
@@ -21,6 +75,8 @@ pub trait Query<F>: Sized + Clone + Send + Sync {
 pub struct ProverQuery<'com, C: CurveAffine> {
     /// Point at which polynomial is queried
     pub(crate) point: C::Scalar,
+    /// Index of polynomial; see [`Query::get_poly_index`]
+    pub(crate) poly_index: usize,
     /// Coefficients of polynomial
     pub(crate) poly: &'com Polynomial<C::Scalar, Coeff>,
     /// Blinding factor of polynomial
@@ -31,13 +87,20 @@ impl<'com, C> ProverQuery<'com, C>
 where
     C: CurveAffine,
 {
-    /// Create a new prover query based on a polynomial
+    /// Create a new prover query based on a polynomial, which is identified by
+    /// `poly_index` among the polynomials being opened
     pub fn new(
         point: C::Scalar,
+        poly_index: usize,
         poly: &'com Polynomial<C::Scalar, Coeff>,
         blind: Blind<C::Scalar>,
     ) -> Self {
-        ProverQuery { point, poly, blind }
+        ProverQuery {
+            point,
+            poly_index,
+            poly,
+            blind,
+        }
     }
 }
 
@@ -70,22 +133,43 @@ impl<'com, C: CurveAffine> Query<C::Scalar> for ProverQuery<'com, C> {
             blind: self.blind,
         }
     }
+    fn get_poly_index(&self) -> usize {
+        self.poly_index
+    }
+    fn offset_poly_index(mut self, offset: usize) -> Self {
+        self.poly_index += offset;
+        self
+    }
 }
 
 impl<'com, C: CurveAffine, M: MSM<C>> VerifierQuery<'com, C, M> {
-    /// Create a new verifier query based on a commitment
-    pub fn new_commitment(commitment: &'com C, point: C::Scalar, eval: C::Scalar) -> Self {
+    /// Create a new verifier query based on a commitment, which is identified by
+    /// `poly_index` among the commitments being opened
+    pub fn new_commitment(
+        commitment: &'com C,
+        poly_index: usize,
+        point: C::Scalar,
+        eval: C::Scalar,
+    ) -> Self {
         VerifierQuery {
             point,
+            poly_index,
             eval,
             commitment: CommitmentReference::Commitment(commitment),
         }
     }
 
-    /// Create a new verifier query based on a linear combination of commitments
-    pub fn new_msm(msm: &'com M, point: C::Scalar, eval: C::Scalar) -> VerifierQuery<'com, C, M> {
+    /// Create a new verifier query based on a linear combination of commitments,
+    /// which is identified by `poly_index` among the commitments being opened
+    pub fn new_msm(
+        msm: &'com M,
+        poly_index: usize,
+        point: C::Scalar,
+        eval: C::Scalar,
+    ) -> VerifierQuery<'com, C, M> {
         VerifierQuery {
             point,
+            poly_index,
             eval,
             commitment: CommitmentReference::MSM(msm),
         }
@@ -97,6 +181,8 @@ impl<'com, C: CurveAffine, M: MSM<C>> VerifierQuery<'com, C, M> {
 pub struct VerifierQuery<'com, C: CurveAffine, M: MSM<C>> {
     /// Point at which polynomial is queried
     pub(crate) point: C::Scalar,
+    /// Index of polynomial; see [`Query::get_poly_index`]
+    pub(crate) poly_index: usize,
     /// Commitment to polynomial
     pub(crate) commitment: CommitmentReference<'com, C, M>,
     /// Evaluation of polynomial at query point
@@ -111,11 +197,13 @@ where
     /// Create a new verifier query based on a commitment
     pub fn new(
         point: C::Scalar,
+        poly_index: usize,
         commitment: CommitmentReference<'com, C, M>,
         eval: C::Scalar,
     ) -> Self {
         VerifierQuery {
             point,
+            poly_index,
             commitment,
             eval,
         }
@@ -157,4 +245,11 @@ impl<'com, C: CurveAffine, M: MSM<C>> Query<C::Scalar> for VerifierQuery<'com, C
     fn get_commitment(&self) -> Self::Commitment {
         self.commitment
     }
+    fn get_poly_index(&self) -> usize {
+        self.poly_index
+    }
+    fn offset_poly_index(mut self, offset: usize) -> Self {
+        self.poly_index += offset;
+        self
+    }
 }
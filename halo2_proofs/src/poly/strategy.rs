@@ -6,6 +6,15 @@ use crate::plonk::Error;
 pub trait Guard<Scheme: CommitmentScheme> {
     /// Multi scalar engine which is not evaluated yet.
     type MSMAccumulator;
+
+    /// Returns the final check of the verifier as an MSM accumulator, without
+    /// evaluating it.
+    ///
+    /// The accumulators of several proofs, or of other checks, can be merged and
+    /// evaluated once to amortize the cost of verification. Each accumulator must be
+    /// scaled by a fresh random challenge before they are merged, as the
+    /// accumulator strategies do.
+    fn into_msm_accumulator(self) -> Self::MSMAccumulator;
 }
 
 /// Trait representing a strategy for verifying Halo 2 proofs.
@@ -2,12 +2,14 @@
 //! transcripts.
 
 use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
-use group::ff::{FromUniformBytes, PrimeField};
+use group::ff::{Field, FromUniformBytes, PrimeField, WithSmallOrderMulGroup};
 use sha3::{Digest, Keccak256};
 use std::convert::TryInto;
 
+use crate::arithmetic::from_bytes_reduced;
 use halo2curves::{Coordinates, CurveAffine};
 
+use std::collections::VecDeque;
 use std::io::{self, Read, Write};
 use std::marker::PhantomData;
 
@@ -80,6 +82,9 @@ pub trait TranscriptWrite<C: CurveAffine, E: EncodedChallenge<C>>: Transcript<C,
 }
 
 /// Initializes transcript at verifier side.
+///
+/// This is implemented by every transcript reader, so that verifier code can be
+/// generic over the transcript it reads proofs with.
 pub trait TranscriptReadBuffer<R: Read, C: CurveAffine, E: EncodedChallenge<C>>:
     TranscriptRead<C, E>
 {
@@ -88,14 +93,61 @@ pub trait TranscriptReadBuffer<R: Read, C: CurveAffine, E: EncodedChallenge<C>>:
 }
 
 /// Manages beginning and finishing of transcript pipeline.
+///
+/// This is implemented by every transcript writer, so that prover code can be
+/// generic over the transcript it writes proofs with.
 pub trait TranscriptWriterBuffer<W: Write, C: CurveAffine, E: EncodedChallenge<C>>:
     TranscriptWrite<C, E>
 {
     /// Initialize a transcript given an output buffer.
     fn init(writer: W) -> Self;
 
-    /// Conclude the interaction and return the output buffer (writer).
-    fn finalize(self) -> W;
+    /// Conclude the interaction, flushing and returning the output buffer (writer).
+    ///
+    /// Every element is hashed into the transcript state as it is written, so
+    /// elements written after the last challenge was squeezed leave no outstanding
+    /// state: they are part of the proof, and the verifier absorbs them in the same
+    /// order when reading it. The only remaining work is to flush the writer, whose
+    /// errors are returned rather than silently producing a truncated proof.
+    fn finalize(self) -> io::Result<W>;
+}
+
+/// The number of elements absorbed into, and challenges squeezed from, a transcript.
+///
+/// The prover's and verifier's transcripts for a proof must agree on these. If they do
+/// not, one side has skipped or added a step of the protocol, which otherwise only
+/// shows up as a rejected proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TranscriptCounts {
+    /// The number of points absorbed, including those written to or read from the
+    /// proof.
+    pub points: usize,
+    /// The number of scalars absorbed, including those written to or read from the
+    /// proof.
+    pub scalars: usize,
+    /// The number of challenges squeezed.
+    pub challenges: usize,
+}
+
+impl TranscriptCounts {
+    /// Asserts that `verifier`, the counts of the verifier's transcript for a proof,
+    /// match `self`, the counts of the prover's transcript that produced it.
+    ///
+    /// # Panics
+    ///
+    /// Panics with both sets of counts if they differ.
+    pub fn assert_consistent(&self, verifier: &TranscriptCounts) {
+        assert!(
+            self == verifier,
+            "prover and verifier transcripts diverged: the prover absorbed {} points and {} scalars and squeezed {} challenges, the verifier absorbed {} points and {} scalars and squeezed {} challenges",
+            self.points,
+            self.scalars,
+            self.challenges,
+            verifier.points,
+            verifier.scalars,
+            verifier.challenges,
+        );
+    }
 }
 
 /// We will replace BLAKE2b with an algebraic hash function in a later version.
@@ -103,17 +155,33 @@ pub trait TranscriptWriterBuffer<W: Write, C: CurveAffine, E: EncodedChallenge<C
 pub struct Blake2bRead<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
     state: Blake2bState,
     reader: R,
+    counts: TranscriptCounts,
     _marker: PhantomData<(C, E)>,
 }
 
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>> Blake2bRead<R, C, E> {
+    /// Returns the number of elements absorbed and challenges squeezed so far.
+    pub fn counts(&self) -> TranscriptCounts {
+        self.counts
+    }
+}
+
 /// Keccak256 hash function reader for EVM compatibility
 #[derive(Debug, Clone)]
 pub struct Keccak256Read<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
     state: Keccak256,
     reader: R,
+    counts: TranscriptCounts,
     _marker: PhantomData<(C, E)>,
 }
 
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>> Keccak256Read<R, C, E> {
+    /// Returns the number of elements absorbed and challenges squeezed so far.
+    pub fn counts(&self) -> TranscriptCounts {
+        self.counts
+    }
+}
+
 impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, Challenge255<C>>
     for Blake2bRead<R, C, Challenge255<C>>
 where
@@ -127,6 +195,7 @@ where
                 .personal(b"Halo2-Transcript")
                 .to_state(),
             reader,
+            counts: TranscriptCounts::default(),
             _marker: PhantomData,
         }
     }
@@ -144,6 +213,7 @@ where
         Keccak256Read {
             state,
             reader,
+            counts: TranscriptCounts::default(),
             _marker: PhantomData,
         }
     }
@@ -216,6 +286,7 @@ where
     C::Scalar: FromUniformBytes<64>,
 {
     fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.counts.challenges += 1;
         self.state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
         let hasher = self.state.clone();
         let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
@@ -223,6 +294,7 @@ where
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.counts.points += 1;
         self.state.update(&[BLAKE2B_PREFIX_POINT]);
         let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
             io::Error::new(
@@ -237,6 +309,7 @@ where
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.counts.scalars += 1;
         self.state.update(&[BLAKE2B_PREFIX_SCALAR]);
         self.state.update(scalar.to_repr().as_ref());
 
@@ -250,6 +323,7 @@ where
     C::Scalar: FromUniformBytes<64>,
 {
     fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.counts.challenges += 1;
         self.state.update([KECCAK256_PREFIX_CHALLENGE]);
 
         let mut state_lo = self.state.clone();
@@ -267,6 +341,7 @@ where
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.counts.points += 1;
         self.state.update([KECCAK256_PREFIX_POINT]);
         let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
             io::Error::new(
@@ -281,6 +356,7 @@ where
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.counts.scalars += 1;
         self.state.update([KECCAK256_PREFIX_SCALAR]);
         self.state.update(scalar.to_repr().as_ref());
 
@@ -293,17 +369,33 @@ where
 pub struct Blake2bWrite<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
     state: Blake2bState,
     writer: W,
+    counts: TranscriptCounts,
     _marker: PhantomData<(C, E)>,
 }
 
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Blake2bWrite<W, C, E> {
+    /// Returns the number of elements absorbed and challenges squeezed so far.
+    pub fn counts(&self) -> TranscriptCounts {
+        self.counts
+    }
+}
+
 /// Keccak256 hash function writer for EVM compatibility
 #[derive(Debug, Clone)]
 pub struct Keccak256Write<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
     state: Keccak256,
     writer: W,
+    counts: TranscriptCounts,
     _marker: PhantomData<(C, E)>,
 }
 
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Keccak256Write<W, C, E> {
+    /// Returns the number of elements absorbed and challenges squeezed so far.
+    pub fn counts(&self) -> TranscriptCounts {
+        self.counts
+    }
+}
+
 impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, Challenge255<C>>
     for Blake2bWrite<W, C, Challenge255<C>>
 where
@@ -317,13 +409,14 @@ where
                 .personal(b"Halo2-Transcript")
                 .to_state(),
             writer,
+            counts: TranscriptCounts::default(),
             _marker: PhantomData,
         }
     }
 
-    fn finalize(self) -> W {
-        // TODO: handle outstanding scalars? see issue #138
-        self.writer
+    fn finalize(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
     }
 }
 
@@ -339,14 +432,14 @@ where
         Keccak256Write {
             state,
             writer,
+            counts: TranscriptCounts::default(),
             _marker: PhantomData,
         }
     }
 
-    /// Conclude the interaction and return the output buffer (writer).
-    fn finalize(self) -> W {
-        // TODO: handle outstanding scalars? see issue #138
-        self.writer
+    fn finalize(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
     }
 }
 
@@ -390,6 +483,7 @@ where
     C::Scalar: FromUniformBytes<64>,
 {
     fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.counts.challenges += 1;
         self.state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
         let hasher = self.state.clone();
         let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
@@ -397,6 +491,7 @@ where
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.counts.points += 1;
         self.state.update(&[BLAKE2B_PREFIX_POINT]);
         let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
             io::Error::new(
@@ -411,6 +506,7 @@ where
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.counts.scalars += 1;
         self.state.update(&[BLAKE2B_PREFIX_SCALAR]);
         self.state.update(scalar.to_repr().as_ref());
 
@@ -424,6 +520,7 @@ where
     C::Scalar: FromUniformBytes<64>,
 {
     fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.counts.challenges += 1;
         self.state.update([KECCAK256_PREFIX_CHALLENGE]);
 
         let mut state_lo = self.state.clone();
@@ -441,6 +538,7 @@ where
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.counts.points += 1;
         self.state.update([KECCAK256_PREFIX_POINT]);
         let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
             io::Error::new(
@@ -455,6 +553,7 @@ where
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.counts.scalars += 1;
         self.state.update([KECCAK256_PREFIX_SCALAR]);
         self.state.update(scalar.to_repr().as_ref());
 
@@ -462,6 +561,447 @@ where
     }
 }
 
+impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, ChallengeEvm<C>>
+    for Keccak256Read<R, C, ChallengeEvm<C>>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    /// Initialize a transcript given an input buffer.
+    fn init(reader: R) -> Self {
+        Keccak256Read {
+            state: Keccak256::new(),
+            reader,
+            counts: TranscriptCounts::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Read, C: CurveAffine> TranscriptRead<C, ChallengeEvm<C>>
+    for Keccak256Read<R, C, ChallengeEvm<C>>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let x = read_field_be(&mut self.reader)?;
+        let y = read_field_be(&mut self.reader)?;
+        let point: C = Option::from(C::from_xy(x, y)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let scalar = read_field_be(&mut self.reader)?;
+        self.common_scalar(scalar)?;
+
+        Ok(scalar)
+    }
+}
+
+impl<R: Read, C: CurveAffine> Transcript<C, ChallengeEvm<C>>
+    for Keccak256Read<R, C, ChallengeEvm<C>>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    fn squeeze_challenge(&mut self) -> ChallengeEvm<C> {
+        self.counts.challenges += 1;
+        squeeze_challenge_evm(&mut self.state)
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.counts.points += 1;
+        absorb_point_evm(&mut self.state, point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.counts.scalars += 1;
+        self.state.update(field_to_be(&scalar));
+
+        Ok(())
+    }
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, ChallengeEvm<C>>
+    for Keccak256Write<W, C, ChallengeEvm<C>>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    /// Initialize a transcript given an output buffer.
+    fn init(writer: W) -> Self {
+        Keccak256Write {
+            state: Keccak256::new(),
+            writer,
+            counts: TranscriptCounts::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn finalize(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWrite<C, ChallengeEvm<C>>
+    for Keccak256Write<W, C, ChallengeEvm<C>>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "cannot write points at infinity to the transcript",
+            )
+        })?;
+        self.writer.write_all(&field_to_be(coords.x()))?;
+        self.writer.write_all(&field_to_be(coords.y()))
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        self.writer.write_all(&field_to_be(&scalar))
+    }
+}
+
+impl<W: Write, C: CurveAffine> Transcript<C, ChallengeEvm<C>>
+    for Keccak256Write<W, C, ChallengeEvm<C>>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    fn squeeze_challenge(&mut self) -> ChallengeEvm<C> {
+        self.counts.challenges += 1;
+        squeeze_challenge_evm(&mut self.state)
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.counts.points += 1;
+        absorb_point_evm(&mut self.state, point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.counts.scalars += 1;
+        self.state.update(field_to_be(&scalar));
+
+        Ok(())
+    }
+}
+
+/// Encodes `instances`, one slice per instance column, and `proof` as the calldata
+/// of an EVM verifier.
+///
+/// The instances are written column by column as 32-byte big-endian words, followed
+/// by the proof. The proof must have been written by a [`Keccak256Write`] with
+/// [`ChallengeEvm`] challenges, which already writes it in the word layout of an EVM
+/// verifier, with every scalar a 32-byte big-endian word and every point its
+/// `(x, y)` coordinates, so it is appended as is.
+///
+/// Returns an error if the proof is not made of whole words.
+pub fn encode_evm_calldata<F: PrimeField>(instances: &[&[F]], proof: &[u8]) -> io::Result<Vec<u8>> {
+    check_evm_words(proof)?;
+
+    let num_instances = instances.iter().map(|column| column.len()).sum::<usize>();
+    let mut calldata = Vec::with_capacity(32 * num_instances + proof.len());
+    for instance in instances.iter().flat_map(|column| column.iter()) {
+        calldata.extend(field_to_be(instance));
+    }
+    calldata.extend_from_slice(proof);
+    Ok(calldata)
+}
+
+/// Decodes calldata encoded with [`encode_evm_calldata`], whose instance columns
+/// hold `instance_lens[i]` values each.
+///
+/// Returns the instances of each column and the proof, which can be encoded again
+/// with [`encode_evm_calldata`] or read with a [`Keccak256Read`]. Returns an error if
+/// the calldata is shorter than the instances, if an instance is not a canonical
+/// field element, or if the proof is not made of whole words.
+pub fn decode_evm_calldata<F: PrimeField>(
+    mut calldata: &[u8],
+    instance_lens: &[usize],
+) -> io::Result<(Vec<Vec<F>>, Vec<u8>)> {
+    let instances = instance_lens
+        .iter()
+        .map(|len| {
+            (0..*len)
+                .map(|_| read_field_be(&mut calldata))
+                .collect::<io::Result<Vec<_>>>()
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    check_evm_words(calldata)?;
+
+    Ok((instances, calldata.to_vec()))
+}
+
+fn check_evm_words(proof: &[u8]) -> io::Result<()> {
+    if proof.len() % 32 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "proof in calldata is not made of 32-byte words",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the big-endian encoding of `value`, assuming a little-endian
+/// representation.
+fn field_to_be<F: PrimeField>(value: &F) -> Vec<u8> {
+    let mut bytes = value.to_repr().as_ref().to_vec();
+    bytes.reverse();
+    bytes
+}
+
+/// Reads a canonical big-endian encoding of a field element, assuming a
+/// little-endian representation.
+fn read_field_be<R: Read, F: PrimeField>(reader: &mut R) -> io::Result<F> {
+    let mut repr = F::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+    repr.as_mut().reverse();
+    Option::from(F::from_repr(repr)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "invalid field element encoding in proof",
+        )
+    })
+}
+
+/// Absorbs the big-endian affine coordinates of `point`, as an EVM verifier would.
+fn absorb_point_evm<C: CurveAffine>(state: &mut Keccak256, point: C) -> io::Result<()> {
+    let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "cannot write points at infinity to the transcript",
+        )
+    })?;
+    state.update(field_to_be(coords.x()));
+    state.update(field_to_be(coords.y()));
+
+    Ok(())
+}
+
+/// Hashes everything absorbed so far, and restarts the state from the digest.
+fn squeeze_challenge_evm<C: CurveAffine>(state: &mut Keccak256) -> ChallengeEvm<C>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    let digest: [u8; 32] = state.clone().finalize().as_slice().try_into().unwrap();
+    *state = Keccak256::new();
+    state.update(digest);
+
+    ChallengeEvm::new(&digest)
+}
+
+/// An entry of the field-element encoding of a transcript recorded by
+/// [`FieldTranscript`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldTranscriptEntry<F> {
+    /// A base field element absorbed into the transcript.
+    Absorb(F),
+    /// A challenge was squeezed from the transcript. This separates the elements
+    /// absorbed before the challenge from those absorbed after it.
+    Squeeze,
+}
+
+/// A transcript that records everything absorbed by an inner transcript `T` as base
+/// field elements, alongside the byte encoding written or read by `T`.
+///
+/// This allows recursion and accumulation frameworks that consume transcripts of
+/// field elements to replay the proof without knowing the byte encoding of `T`:
+/// - a point is absorbed as its affine coordinates `x` and `y`, with the identity
+///   absorbed as `(0, 0)`;
+/// - a scalar is absorbed as the 128-bit chunks of its canonical encoding, each read
+///   as a little-endian integer, so that it can be absorbed in any base field of at
+///   least 128 bits;
+/// - each challenge squeezed is recorded as [`FieldTranscriptEntry::Squeeze`].
+///
+/// The challenges themselves are still derived by `T`. Wrapping the prover's
+/// transcript records the proof as it is written; wrapping the verifier's transcript
+/// records it from an existing byte proof.
+#[derive(Debug)]
+pub struct FieldTranscript<T, C: CurveAffine> {
+    inner: T,
+    entries: Vec<FieldTranscriptEntry<C::Base>>,
+}
+
+impl<T, C: CurveAffine> FieldTranscript<T, C> {
+    /// Wraps `inner`, recording everything absorbed into it from now on.
+    pub fn new(inner: T) -> Self {
+        FieldTranscript {
+            inner,
+            entries: vec![],
+        }
+    }
+
+    /// Returns the entries recorded so far.
+    pub fn entries(&self) -> &[FieldTranscriptEntry<C::Base>] {
+        &self.entries
+    }
+
+    /// Returns the inner transcript and the recorded entries.
+    pub fn into_parts(self) -> (T, Vec<FieldTranscriptEntry<C::Base>>) {
+        (self.inner, self.entries)
+    }
+
+    fn record_point(&mut self, point: C) {
+        let (x, y) = Option::from(point.coordinates())
+            .map(|coordinates: Coordinates<C>| (*coordinates.x(), *coordinates.y()))
+            .unwrap_or((C::Base::ZERO, C::Base::ZERO));
+        self.entries.push(FieldTranscriptEntry::Absorb(x));
+        self.entries.push(FieldTranscriptEntry::Absorb(y));
+    }
+
+    fn record_scalar(&mut self, scalar: C::Scalar) {
+        let repr = scalar.to_repr();
+        for chunk in repr.as_ref().chunks(16) {
+            let mut bytes = [0u8; 16];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            self.entries
+                .push(FieldTranscriptEntry::Absorb(C::Base::from_u128(
+                    u128::from_le_bytes(bytes),
+                )));
+        }
+    }
+}
+
+impl<T: Transcript<C, E>, C: CurveAffine, E: EncodedChallenge<C>> Transcript<C, E>
+    for FieldTranscript<T, C>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        self.entries.push(FieldTranscriptEntry::Squeeze);
+        self.inner.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.common_point(point)?;
+        self.record_point(point);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(scalar)?;
+        self.record_scalar(scalar);
+        Ok(())
+    }
+}
+
+impl<T: TranscriptRead<C, E>, C: CurveAffine, E: EncodedChallenge<C>> TranscriptRead<C, E>
+    for FieldTranscript<T, C>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let point = self.inner.read_point()?;
+        self.record_point(point);
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let scalar = self.inner.read_scalar()?;
+        self.record_scalar(scalar);
+        Ok(scalar)
+    }
+}
+
+impl<T: TranscriptWrite<C, E>, C: CurveAffine, E: EncodedChallenge<C>> TranscriptWrite<C, E>
+    for FieldTranscript<T, C>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.write_point(point)?;
+        self.record_point(point);
+        Ok(())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.write_scalar(scalar)?;
+        self.record_scalar(scalar);
+        Ok(())
+    }
+}
+
+/// A transcript for tests that returns scripted challenges, wrapping an inner
+/// transcript `T` with challenge encoding `E`.
+///
+/// Every element is absorbed into, and every challenge squeezed from, `T` as usual,
+/// so proofs are written and read in the encoding of `T`. Each challenge squeezed is
+/// replaced by the next value of the script, until the script runs out; after that
+/// the challenges of `T` are returned. This lets tests of the prover and verifier
+/// exercise challenge values that a hash would practically never produce, such as
+/// zero, one or `ZETA`. A prover and a verifier agree on a proof if their transcripts
+/// are given the same script.
+///
+/// The challenges are returned as a [`ScriptedChallenge`], so `T` can use any
+/// encoding.
+#[derive(Debug)]
+pub struct TestTranscript<T, C: CurveAffine, E> {
+    inner: T,
+    script: VecDeque<C::Scalar>,
+    _marker: PhantomData<E>,
+}
+
+impl<T, C: CurveAffine, E> TestTranscript<T, C, E> {
+    /// Wraps `inner`, returning the values of `script` as its next challenges.
+    pub fn new(inner: T, script: impl IntoIterator<Item = C::Scalar>) -> Self {
+        TestTranscript {
+            inner,
+            script: script.into_iter().collect(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of scripted challenges that have not been squeezed yet.
+    pub fn remaining_script(&self) -> usize {
+        self.script.len()
+    }
+
+    /// Returns the inner transcript.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Transcript<C, E>, C: CurveAffine, E: EncodedChallenge<C>>
+    Transcript<C, ScriptedChallenge<C>> for TestTranscript<T, C, E>
+{
+    fn squeeze_challenge(&mut self) -> ScriptedChallenge<C> {
+        let challenge = self.inner.squeeze_challenge().get_scalar();
+        ScriptedChallenge::new(&self.script.pop_front().unwrap_or(challenge))
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(scalar)
+    }
+}
+
+impl<T: TranscriptRead<C, E>, C: CurveAffine, E: EncodedChallenge<C>>
+    TranscriptRead<C, ScriptedChallenge<C>> for TestTranscript<T, C, E>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        self.inner.read_point()
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        self.inner.read_scalar()
+    }
+}
+
+impl<T: TranscriptWrite<C, E>, C: CurveAffine, E: EncodedChallenge<C>>
+    TranscriptWrite<C, ScriptedChallenge<C>> for TestTranscript<T, C, E>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.write_point(point)
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.write_scalar(scalar)
+    }
+}
+
 /// The scalar representation of a verifier challenge.
 ///
 /// The `Type` type can be used to scope the challenge to a specific context, or
@@ -539,6 +1079,123 @@ where
     }
 }
 
+/// A 128-bit challenge, mapped to a scalar using the endomorphism-based encoding from
+/// Algorithm 1 of the [Halo paper](https://eprint.iacr.org/2019/1021).
+///
+/// The challenge is derived from an element of the base field, as squeezed from an
+/// algebraic hash function. Both the truncation to 128 bits and the mapping to a
+/// scalar are cheap to constrain in a circuit over the base field, which makes this
+/// encoding suitable for transcripts that are verified recursively.
+#[derive(Copy, Clone, Debug)]
+pub struct Challenge128<C: CurveAffine>(u128, PhantomData<C>);
+
+impl<C: CurveAffine> std::ops::Deref for Challenge128<C> {
+    type Target = u128;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C: CurveAffine> EncodedChallenge<C> for Challenge128<C>
+where
+    C::Scalar: WithSmallOrderMulGroup<3>,
+{
+    type Input = C::Base;
+
+    /// Takes the low 128 bits of `challenge_input`, whose representation is assumed
+    /// to be little-endian.
+    fn new(challenge_input: &C::Base) -> Self {
+        let repr = challenge_input.to_repr();
+        Challenge128(
+            u128::from_le_bytes(
+                repr.as_ref()[..16]
+                    .try_into()
+                    .expect("base field representation has at least 16 bytes"),
+            ),
+            PhantomData,
+        )
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        let mut acc = (C::Scalar::ZETA + C::Scalar::ONE).double();
+        for i in (0..64).rev() {
+            let should_negate = (self.0 >> ((i << 1) + 1)) & 1 == 1;
+            let should_endo = (self.0 >> (i << 1)) & 1 == 1;
+
+            let q = if should_negate {
+                -C::Scalar::ONE
+            } else {
+                C::Scalar::ONE
+            };
+            let q = if should_endo { q * C::Scalar::ZETA } else { q };
+            acc = acc + q + acc;
+        }
+        acc
+    }
+}
+
+/// A challenge derived as in EVM verifiers: a 256-bit Keccak256 digest, interpreted
+/// as a big-endian `uint256` and reduced modulo the scalar field.
+///
+/// With this encoding, [`Keccak256Read`] and [`Keccak256Write`] hash and write field
+/// elements in big-endian order and points as their uncompressed affine coordinates,
+/// without any prefixes. After every challenge the state restarts from the digest, so
+/// that a Solidity verifier can compute each challenge as
+/// `uint256(keccak256(abi.encodePacked(digest, ...))) % r`.
+#[derive(Copy, Clone, Debug)]
+pub struct ChallengeEvm<C: CurveAffine>([u8; 32], PhantomData<C>);
+
+impl<C: CurveAffine> std::ops::Deref for ChallengeEvm<C> {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C: CurveAffine> EncodedChallenge<C> for ChallengeEvm<C>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    type Input = [u8; 32];
+
+    fn new(challenge_input: &[u8; 32]) -> Self {
+        let mut le_bytes = *challenge_input;
+        le_bytes.reverse();
+        ChallengeEvm(
+            from_bytes_reduced::<C::Scalar>(&le_bytes)
+                .to_repr()
+                .as_ref()
+                .try_into()
+                .expect("Scalar fits into 256 bits"),
+            PhantomData,
+        )
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        let mut repr = <C::Scalar as PrimeField>::Repr::default();
+        repr.as_mut().copy_from_slice(&self.0);
+        C::Scalar::from_repr(repr).unwrap()
+    }
+}
+
+/// A challenge given directly as a scalar, as returned by [`TestTranscript`].
+#[derive(Copy, Clone, Debug)]
+pub struct ScriptedChallenge<C: CurveAffine>(C::Scalar);
+
+impl<C: CurveAffine> EncodedChallenge<C> for ScriptedChallenge<C> {
+    type Input = C::Scalar;
+
+    fn new(challenge_input: &C::Scalar) -> Self {
+        ScriptedChallenge(*challenge_input)
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        self.0
+    }
+}
+
 pub(crate) fn read_n_points<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
     transcript: &mut T,
     n: usize,
@@ -552,3 +1209,262 @@ pub(crate) fn read_n_scalars<C: CurveAffine, E: EncodedChallenge<C>, T: Transcri
 ) -> io::Result<Vec<C::Scalar>> {
     (0..n).map(|_| transcript.read_scalar()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::{prime::PrimeCurveAffine, Curve};
+    use halo2curves::bn256::{Fr, G1Affine};
+    use halo2curves::pasta::{pallas, Fq};
+    use rand_core::OsRng;
+
+    #[test]
+    fn keccak256_evm_encoding() {
+        let point = (G1Affine::generator() * Fr::random(OsRng)).to_affine();
+        let scalar = Fr::random(OsRng);
+
+        let mut transcript = Keccak256Write::<_, G1Affine, ChallengeEvm<_>>::init(vec![]);
+        transcript.write_point(point).unwrap();
+        transcript.write_scalar(scalar).unwrap();
+        let first = transcript.squeeze_challenge_scalar::<()>();
+        let second = transcript.squeeze_challenge_scalar::<()>();
+        let proof = transcript.finalize().unwrap();
+
+        // Points are written uncompressed and, like scalars, in big-endian order.
+        let mut expected = field_to_be(point.coordinates().unwrap().x());
+        expected.extend(field_to_be(point.coordinates().unwrap().y()));
+        expected.extend(field_to_be(&scalar));
+        assert_eq!(proof, expected);
+
+        // The challenges are the digests of the transcript, reduced as big-endian
+        // integers.
+        let digest = Keccak256::digest(&proof);
+        let reduce = |digest: &[u8]| {
+            digest.iter().fold(Fr::ZERO, |acc, byte| {
+                acc * Fr::from(256) + Fr::from(*byte as u64)
+            })
+        };
+        assert_eq!(*first, reduce(&digest));
+        assert_eq!(*second, reduce(&Keccak256::digest(digest)));
+
+        let mut transcript = Keccak256Read::<_, G1Affine, ChallengeEvm<_>>::init(&proof[..]);
+        assert_eq!(transcript.read_point().unwrap(), point);
+        assert_eq!(transcript.read_scalar().unwrap(), scalar);
+        assert_eq!(*transcript.squeeze_challenge_scalar::<()>(), *first);
+    }
+
+    #[test]
+    fn evm_calldata_round_trip() {
+        let column0: Vec<Fr> = (0..2).map(|_| Fr::random(OsRng)).collect();
+        let column1 = vec![Fr::random(OsRng)];
+        let instances: &[&[Fr]] = &[&column0, &[], &column1];
+        let point = (G1Affine::generator() * Fr::random(OsRng)).to_affine();
+
+        let mut transcript = Keccak256Write::<_, G1Affine, ChallengeEvm<_>>::init(vec![]);
+        transcript.write_point(point).unwrap();
+        transcript.write_scalar(column0[0]).unwrap();
+        let proof = transcript.finalize().unwrap();
+
+        let calldata = encode_evm_calldata(instances, &proof).unwrap();
+        assert_eq!(calldata.len(), 32 * (3 + 3));
+        assert_eq!(&calldata[..32], field_to_be(&column0[0]).as_slice());
+        assert_eq!(&calldata[64..96], field_to_be(&column1[0]).as_slice());
+        assert_eq!(&calldata[32 * 3..], proof.as_slice());
+
+        // The column boundaries are restored, and the decoded instances and proof
+        // encode to the same calldata.
+        let (decoded, decoded_proof) = decode_evm_calldata::<Fr>(&calldata, &[2, 0, 1]).unwrap();
+        assert_eq!(decoded, vec![column0.clone(), vec![], column1.clone()]);
+        assert_eq!(decoded_proof, proof);
+        let decoded: Vec<&[Fr]> = decoded.iter().map(|column| &column[..]).collect();
+        assert_eq!(
+            encode_evm_calldata(&decoded, &decoded_proof).unwrap(),
+            calldata
+        );
+
+        // Proofs that are not made of whole words are rejected.
+        assert!(encode_evm_calldata(instances, &proof[..proof.len() - 1]).is_err());
+        // Truncated calldata is rejected.
+        assert!(decode_evm_calldata::<Fr>(&calldata[..64], &[2, 0, 1]).is_err());
+        assert!(decode_evm_calldata::<Fr>(&calldata[..calldata.len() - 1], &[2, 0, 1]).is_err());
+        // Instances are not reduced.
+        let mut non_canonical = calldata.clone();
+        non_canonical[..32].copy_from_slice(&[0xff; 32]);
+        assert!(decode_evm_calldata::<Fr>(&non_canonical, &[2, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn finalize_flushes_writer() {
+        let mut transcript =
+            Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(io::BufWriter::new(vec![]));
+        transcript.write_scalar(Fr::ONE).unwrap();
+        let writer = transcript.finalize().unwrap();
+        assert!(writer.buffer().is_empty());
+        assert_eq!(writer.get_ref().as_slice(), Fr::ONE.to_repr().as_ref());
+
+        // Errors from the writer are not swallowed.
+        struct Unflushable;
+        impl Write for Unflushable {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Err(io::Error::new(io::ErrorKind::Other, "flush failed"))
+            }
+        }
+        let transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(Unflushable);
+        assert!(transcript.finalize().is_err());
+    }
+
+    #[test]
+    fn field_transcript_records_absorptions() {
+        use halo2curves::bn256::Fq;
+
+        let point = (G1Affine::generator() * Fr::random(OsRng)).to_affine();
+        let scalar = Fr::random(OsRng);
+
+        let mut transcript =
+            FieldTranscript::new(Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]));
+        transcript.common_point(G1Affine::identity()).unwrap();
+        transcript.write_point(point).unwrap();
+        let challenge = transcript.squeeze_challenge_scalar::<()>();
+        transcript.write_scalar(scalar).unwrap();
+        let (writer, entries) = transcript.into_parts();
+        let proof = writer.finalize().unwrap();
+
+        let coordinates = point.coordinates().unwrap();
+        let limbs: Vec<_> = scalar
+            .to_repr()
+            .as_ref()
+            .chunks(16)
+            .map(|chunk| Fq::from_u128(u128::from_le_bytes(chunk.try_into().unwrap())))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                FieldTranscriptEntry::Absorb(Fq::ZERO),
+                FieldTranscriptEntry::Absorb(Fq::ZERO),
+                FieldTranscriptEntry::Absorb(*coordinates.x()),
+                FieldTranscriptEntry::Absorb(*coordinates.y()),
+                FieldTranscriptEntry::Squeeze,
+                FieldTranscriptEntry::Absorb(limbs[0]),
+                FieldTranscriptEntry::Absorb(limbs[1]),
+            ]
+        );
+
+        // The verifier records the same entries from the byte proof.
+        let mut transcript = FieldTranscript::new(
+            Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]),
+        );
+        transcript.common_point(G1Affine::identity()).unwrap();
+        assert_eq!(transcript.read_point().unwrap(), point);
+        assert_eq!(*transcript.squeeze_challenge_scalar::<()>(), *challenge);
+        assert_eq!(transcript.read_scalar().unwrap(), scalar);
+        assert_eq!(transcript.entries(), &entries[..]);
+    }
+
+    #[test]
+    fn test_transcript_scripted_challenges() {
+        let scalar = Fq::random(OsRng);
+        let script = [Fq::ZERO, Fq::ONE, Fq::ZETA];
+
+        let mut transcript = TestTranscript::<_, pallas::Affine, _>::new(
+            Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]),
+            script,
+        );
+        transcript.write_scalar(scalar).unwrap();
+        for expected in script {
+            assert_eq!(*transcript.squeeze_challenge_scalar::<()>(), expected);
+        }
+        assert_eq!(transcript.remaining_script(), 0);
+        // Once the script runs out, the challenges are those of the inner transcript.
+        let unscripted = *transcript.squeeze_challenge_scalar::<()>();
+        let proof = transcript.into_inner().finalize().unwrap();
+
+        let mut expected = Blake2bWrite::<_, pallas::Affine, Challenge255<_>>::init(vec![]);
+        expected.write_scalar(scalar).unwrap();
+        for _ in script {
+            expected.squeeze_challenge();
+        }
+        assert_eq!(*expected.squeeze_challenge_scalar::<()>(), unscripted);
+        assert_eq!(proof, expected.finalize().unwrap());
+
+        // A verifier given the same script reads the proof with the same challenges.
+        let mut transcript = TestTranscript::<_, pallas::Affine, _>::new(
+            Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]),
+            script,
+        );
+        assert_eq!(transcript.read_scalar().unwrap(), scalar);
+        for expected in script {
+            assert_eq!(*transcript.squeeze_challenge_scalar::<()>(), expected);
+        }
+        assert_eq!(*transcript.squeeze_challenge_scalar::<()>(), unscripted);
+    }
+
+    #[test]
+    fn transcript_counts() {
+        let point = (G1Affine::generator() * Fr::random(OsRng)).to_affine();
+
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        transcript.common_scalar(Fr::ONE).unwrap();
+        transcript.write_point(point).unwrap();
+        transcript.squeeze_challenge_scalar::<()>();
+        transcript.write_scalar(Fr::ONE).unwrap();
+        let prover = transcript.counts();
+        assert_eq!(
+            prover,
+            TranscriptCounts {
+                points: 1,
+                scalars: 2,
+                challenges: 1,
+            }
+        );
+        let proof = transcript.finalize().unwrap();
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        transcript.common_scalar(Fr::ONE).unwrap();
+        transcript.read_point().unwrap();
+        transcript.squeeze_challenge_scalar::<()>();
+        transcript.read_scalar().unwrap();
+        prover.assert_consistent(&transcript.counts());
+    }
+
+    #[test]
+    #[should_panic(expected = "prover and verifier transcripts diverged")]
+    fn transcript_counts_mismatch() {
+        let mut transcript = Keccak256Write::<_, G1Affine, ChallengeEvm<_>>::init(vec![]);
+        transcript.write_scalar(Fr::ONE).unwrap();
+        transcript.squeeze_challenge_scalar::<()>();
+        let prover = transcript.counts();
+        let proof = transcript.finalize().unwrap();
+
+        // The verifier squeezes a challenge before absorbing the scalar.
+        let mut transcript = Keccak256Read::<_, G1Affine, ChallengeEvm<_>>::init(&proof[..]);
+        transcript.squeeze_challenge_scalar::<()>();
+        transcript.read_scalar().unwrap();
+        transcript.squeeze_challenge_scalar::<()>();
+        prover.assert_consistent(&transcript.counts());
+    }
+
+    #[test]
+    fn challenge128_encoding() {
+        // With all bits unset, each step doubles the accumulator and adds one.
+        let challenge = Challenge128::<pallas::Affine>::new(&pallas::Base::ZERO);
+        assert_eq!(*challenge, 0);
+        let two_pow_64 = Fq::from_u128(1 << 64);
+        assert_eq!(
+            challenge.get_scalar(),
+            two_pow_64 * (Fq::ZETA + Fq::ONE).double() + (two_pow_64 - Fq::ONE)
+        );
+
+        // Only the low 128 bits of the input are used.
+        let input = pallas::Base::from_u128(u128::MAX) + pallas::Base::from_u128(1 << 64).square();
+        let challenge = Challenge128::<pallas::Affine>::new(&input);
+        assert_eq!(*challenge, u128::MAX);
+        assert_ne!(
+            challenge.get_scalar(),
+            Challenge128::<pallas::Affine>::new(&pallas::Base::ZERO).get_scalar()
+        );
+    }
+}
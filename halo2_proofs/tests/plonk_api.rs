@@ -498,7 +498,9 @@ fn plonk_api() {
         };
         assert_eq!(prover.verify(), Ok(()));
 
-        transcript.finalize()
+        transcript
+            .finalize()
+            .expect("finalizing the transcript should not fail")
     }
 
     fn verify_proof<